@@ -0,0 +1,612 @@
+use crate::fit_parser::{SensorPoint, TimerPause};
+use crate::samples::Sample;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Time constants of the exponentially weighted load averages, in days
+const CTL_DAYS: f64 = 42.0;
+const ATL_DAYS: f64 = 7.0;
+
+/// Used when neither max HR nor age is set
+const DEFAULT_MAX_HEART_RATE: i64 = 190;
+const DEFAULT_RESTING_HEART_RATE: i64 = 60;
+
+/// Normalized Power averages power over a rolling window of this many seconds
+const NP_WINDOW_SECONDS: usize = 30;
+
+/// Longer gaps between samples are pauses; power isn't carried across them
+const MAX_POWER_GAP_SECONDS: i64 = 10;
+
+/// Longer gaps between samples don't count towards time in zone
+const MAX_ZONE_GAP_SECONDS: f64 = 60.0;
+
+/// Slower than this counts as standing still, in m/s
+const STATIONARY_SPEED_MPS: f64 = 0.5;
+
+/// Longer gaps between samples are pauses in recording, not movement
+const MAX_MOVING_GAP_SECONDS: f64 = 30.0;
+
+/// Durations of the mean-maximal power curve, in seconds
+pub const POWER_CURVE_DURATIONS: &[usize] = &[
+    1, 2, 3, 5, 10, 15, 20, 30, 45, 60, 90, 120, 180, 240, 300, 360, 480, 600, 720, 900, 1200,
+    1800, 2400, 3600, 5400, 7200, 10800, 14400, 18000, 21600,
+];
+
+/// Standard distances best efforts are searched for, by key and meters
+pub const BEST_EFFORT_DISTANCES: &[(&str, f64)] = &[
+    ("400m", 400.0),
+    ("1k", 1000.0),
+    ("1mi", 1609.344),
+    ("5k", 5000.0),
+    ("10k", 10000.0),
+    ("half_marathon", 21097.5),
+    ("marathon", 42195.0),
+];
+
+/// One dated entry of the athlete profile. Its values apply to workouts from
+/// `effective_date` (`YYYY-MM-DD`) until the next entry.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct AthleteProfile {
+    pub effective_date: String,
+    pub weight_kg: Option<f64>,
+    pub height_cm: Option<f64>,
+    pub sex: Option<String>,
+    pub birth_date: Option<String>,
+    pub max_heart_rate: Option<i64>,
+    pub resting_heart_rate: Option<i64>,
+    pub threshold_heart_rate: Option<i64>,
+    pub ftp_watts: Option<i64>,
+    pub threshold_pace_seconds_per_km: Option<i64>,
+}
+
+impl AthleteProfile {
+    /// The values metrics are computed against on `date`, with the age the
+    /// athlete had that day
+    pub fn settings_on(&self, date: NaiveDate) -> AthleteSettings {
+        let age = self.birth_date.as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            .map(|birth| {
+                let had_birthday = (date.month(), date.day()) >= (birth.month(), birth.day());
+                (date.year() - birth.year() - if had_birthday { 0 } else { 1 }) as i64
+            });
+        AthleteSettings {
+            max_heart_rate: self.max_heart_rate,
+            resting_heart_rate: self.resting_heart_rate,
+            age,
+            ftp_watts: self.ftp_watts,
+            threshold_heart_rate: self.threshold_heart_rate,
+            threshold_pace_seconds_per_km: self.threshold_pace_seconds_per_km,
+        }
+    }
+}
+
+/// The profile entry in effect on `date` (a date or start time): the latest
+/// one from on or before it. There is none before the first entry, so
+/// earlier workouts aren't computed against values set later.
+pub fn profile_on<'a>(profiles: &'a [AthleteProfile], date: &str) -> Option<&'a AthleteProfile> {
+    let day = date.get(..10).unwrap_or(date);
+    profiles.iter()
+        .filter(|p| p.effective_date.as_str() <= day)
+        .max_by(|a, b| a.effective_date.cmp(&b.effective_date))
+}
+
+/// Athlete values the training metrics are computed against
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct AthleteSettings {
+    pub max_heart_rate: Option<i64>,
+    pub resting_heart_rate: Option<i64>,
+    pub age: Option<i64>,
+    /// Functional threshold power, for power-based TSS
+    pub ftp_watts: Option<i64>,
+    /// Lactate threshold heart rate, for LTHR-based zones
+    pub threshold_heart_rate: Option<i64>,
+    /// Running threshold pace in seconds per km, for pace zones
+    pub threshold_pace_seconds_per_km: Option<i64>,
+}
+
+impl AthleteSettings {
+    /// The configured max HR, else 220 - age, else a default
+    pub fn effective_max_heart_rate(&self) -> i64 {
+        self.max_heart_rate
+            .or_else(|| self.age.map(|age| 220 - age))
+            .unwrap_or(DEFAULT_MAX_HEART_RATE)
+    }
+
+    pub fn effective_resting_heart_rate(&self) -> i64 {
+        self.resting_heart_rate.unwrap_or(DEFAULT_RESTING_HEART_RATE)
+    }
+}
+
+/// What heart rate zone percentages are relative to
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HrZoneMethod {
+    /// Percent of max heart rate
+    #[default]
+    MaxHeartRate,
+    /// Percent of heart rate reserve above resting (Karvonen)
+    HeartRateReserve,
+    /// Percent of lactate threshold heart rate
+    LactateThreshold,
+}
+
+/// A training zone from `min` up to `max`, in percent of the zone
+/// method's reference value
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Zone {
+    pub name: String,
+    pub min: f64,
+    pub max: f64,
+    pub color: String,
+}
+
+impl Zone {
+    fn new(name: &str, min: f64, max: f64, color: &str) -> Self {
+        Zone { name: name.to_string(), min, max, color: color.to_string() }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HrZoneSettings {
+    pub method: HrZoneMethod,
+    pub zones: Vec<Zone>,
+}
+
+impl Default for HrZoneSettings {
+    fn default() -> Self {
+        HrZoneSettings {
+            method: HrZoneMethod::MaxHeartRate,
+            zones: vec![
+                Zone::new("Zone 1 (Recovery)", 50.0, 60.0, "#6b7280"),
+                Zone::new("Zone 2 (Aerobic)", 60.0, 70.0, "#3b82f6"),
+                Zone::new("Zone 3 (Tempo)", 70.0, 80.0, "#22c55e"),
+                Zone::new("Zone 4 (Threshold)", 80.0, 90.0, "#f97316"),
+                Zone::new("Zone 5 (Max)", 90.0, 100.0, "#ef4444"),
+            ],
+        }
+    }
+}
+
+impl HrZoneSettings {
+    /// Zone limits in bpm. `None` for LTHR zones while no LTHR is set.
+    pub fn boundaries(&self, athlete: &AthleteSettings) -> Option<Vec<(f64, f64)>> {
+        let max_hr = athlete.effective_max_heart_rate() as f64;
+        let resting_hr = athlete.effective_resting_heart_rate() as f64;
+        // bpm = base + percent of range
+        let (base, range) = match self.method {
+            HrZoneMethod::MaxHeartRate => (0.0, max_hr),
+            HrZoneMethod::HeartRateReserve => (resting_hr, max_hr - resting_hr),
+            HrZoneMethod::LactateThreshold => (0.0, athlete.threshold_heart_rate.filter(|hr| *hr > 0)? as f64),
+        };
+        let to_bpm = |percent: f64| base + percent / 100.0 * range;
+        Some(self.zones.iter().map(|z| (to_bpm(z.min), to_bpm(z.max))).collect())
+    }
+}
+
+/// Coggan's power levels in percent of FTP
+pub fn default_power_zones() -> Vec<Zone> {
+    vec![
+        Zone::new("Z1 Active Recovery", 0.0, 55.0, "#6b7280"),
+        Zone::new("Z2 Endurance", 55.0, 75.0, "#3b82f6"),
+        Zone::new("Z3 Tempo", 75.0, 90.0, "#22c55e"),
+        Zone::new("Z4 Threshold", 90.0, 105.0, "#eab308"),
+        Zone::new("Z5 VO2max", 105.0, 120.0, "#f97316"),
+        Zone::new("Z6 Anaerobic", 120.0, 150.0, "#ef4444"),
+        Zone::new("Z7 Neuromuscular", 150.0, 250.0, "#a855f7"),
+    ]
+}
+
+/// Running pace zones in percent of threshold speed, so faster paces sit
+/// in higher zones
+pub fn default_pace_zones() -> Vec<Zone> {
+    vec![
+        Zone::new("Z1 Recovery", 0.0, 78.0, "#6b7280"),
+        Zone::new("Z2 Endurance", 78.0, 88.0, "#3b82f6"),
+        Zone::new("Z3 Tempo", 88.0, 95.0, "#22c55e"),
+        Zone::new("Z4 Threshold", 95.0, 102.0, "#eab308"),
+        Zone::new("Z5 VO2max", 102.0, 111.0, "#f97316"),
+        Zone::new("Z6 Anaerobic", 111.0, 150.0, "#ef4444"),
+    ]
+}
+
+fn percent_limits(zones: &[Zone], reference: f64) -> Vec<(f64, f64)> {
+    zones.iter()
+        .map(|z| (z.min / 100.0 * reference, z.max / 100.0 * reference))
+        .collect()
+}
+
+/// Power zone limits in watts. `None` while no FTP is set.
+pub fn power_zone_limits(zones: &[Zone], athlete: &AthleteSettings) -> Option<Vec<(f64, f64)>> {
+    let ftp = athlete.ftp_watts.filter(|f| *f > 0)?;
+    Some(percent_limits(zones, ftp as f64))
+}
+
+/// Pace zone limits as speeds in m/s. `None` while no threshold pace is set.
+pub fn pace_zone_limits(zones: &[Zone], athlete: &AthleteSettings) -> Option<Vec<(f64, f64)>> {
+    let pace = athlete.threshold_pace_seconds_per_km.filter(|p| *p > 0)?;
+    Some(percent_limits(zones, 1000.0 / pace as f64))
+}
+
+/// Inclusive range of `YYYY-MM-DD` dates; open ends are unbounded
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DateRange {
+    pub start: Option<String>,
+    pub end: Option<String>,
+}
+
+/// Calendar period totals are grouped by
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Period {
+    Week,
+    Month,
+}
+
+impl Period {
+    /// The period `day` falls in: its ISO week (`YYYY-Www`), which starts on
+    /// Monday and belongs to the year of its Thursday, or its month (`YYYY-MM`)
+    pub fn of(self, day: NaiveDate) -> String {
+        match self {
+            Period::Week => {
+                let week = day.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Period::Month => day.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TrainingLoadDay {
+    pub date: String,
+    pub tss: f64,
+    /// Chronic training load (fitness), 42-day exponential average of TSS
+    pub ctl: f64,
+    /// Acute training load (fatigue), 7-day exponential average of TSS
+    pub atl: f64,
+    /// Training stress balance (form), CTL - ATL
+    pub tsb: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PowerMetrics {
+    pub normalized_power: f64,
+    /// Normalized Power over average power
+    pub variability_index: Option<f64>,
+    pub work_kj: f64,
+}
+
+/// Best average power a workout held for `duration_seconds`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MeanMaxPower {
+    pub duration_seconds: i64,
+    pub watts: f64,
+}
+
+/// Fastest stretch of a workout covering one of the best effort distances.
+/// Offsets are seconds since the workout's first sample.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BestEffort {
+    pub distance: String,
+    pub distance_meters: f64,
+    pub elapsed_seconds: f64,
+    pub start_offset_s: f64,
+    pub end_offset_s: f64,
+}
+
+/// The TSS used for a workout and where it came from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TssEstimate {
+    pub tss: Option<f64>,
+    pub intensity_factor: Option<f64>,
+    /// `"power"` or `"heart_rate"`
+    pub source: Option<&'static str>,
+}
+
+fn parse_time(ts: Option<&str>) -> Option<DateTime<Utc>> {
+    ts.and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Power resampled to one value per second. Each sample holds until the
+/// next one; samples without power count as zero (coasting).
+pub fn power_series(sensor_data: &[SensorPoint]) -> Vec<f64> {
+    if !sensor_data.iter().any(|s| s.power.is_some()) {
+        return Vec::new();
+    }
+
+    let points: Vec<(DateTime<Utc>, f64)> = sensor_data.iter()
+        .filter_map(|s| Some((parse_time(s.timestamp.as_deref())?, s.power.unwrap_or(0) as f64)))
+        .collect();
+
+    let mut series = Vec::new();
+    for (i, (time, power)) in points.iter().enumerate() {
+        let gap = points.get(i + 1).map(|(next, _)| (*next - *time).num_seconds()).unwrap_or(1);
+        let seconds = if gap > MAX_POWER_GAP_SECONDS { 1 } else { gap.max(0) };
+        series.extend(std::iter::repeat(*power).take(seconds as usize));
+    }
+    series
+}
+
+/// Normalized Power (fourth-power mean of the 30 s rolling average),
+/// Variability Index and total work. `None` without at least 30 s of power.
+pub fn power_metrics(sensor_data: &[SensorPoint]) -> Option<PowerMetrics> {
+    let series = power_series(sensor_data);
+    if series.len() < NP_WINDOW_SECONDS {
+        return None;
+    }
+
+    let total: f64 = series.iter().sum();
+    if total <= 0.0 {
+        return None;
+    }
+
+    let mut window_sum: f64 = series[..NP_WINDOW_SECONDS].iter().sum();
+    let mut fourth_powers = (window_sum / NP_WINDOW_SECONDS as f64).powi(4);
+    for i in NP_WINDOW_SECONDS..series.len() {
+        window_sum += series[i] - series[i - NP_WINDOW_SECONDS];
+        fourth_powers += (window_sum / NP_WINDOW_SECONDS as f64).powi(4);
+    }
+    let windows = (series.len() - NP_WINDOW_SECONDS + 1) as f64;
+    let normalized_power = (fourth_powers / windows).powf(0.25);
+    let average_power = total / series.len() as f64;
+
+    Some(PowerMetrics {
+        normalized_power,
+        variability_index: Some(normalized_power / average_power),
+        work_kj: total / 1000.0,
+    })
+}
+
+/// The workout's mean-maximal power for each curve duration it lasted
+pub fn mean_max_power(sensor_data: &[SensorPoint]) -> Vec<MeanMaxPower> {
+    let series = power_series(sensor_data);
+    let mut prefix = Vec::with_capacity(series.len() + 1);
+    prefix.push(0.0);
+    for power in &series {
+        prefix.push(prefix[prefix.len() - 1] + power);
+    }
+
+    POWER_CURVE_DURATIONS.iter()
+        .take_while(|&&duration| duration <= series.len())
+        .map(|&duration| {
+            let best = (duration..=series.len())
+                .map(|end| prefix[end] - prefix[end - duration])
+                .fold(0.0, f64::max);
+            MeanMaxPower { duration_seconds: duration as i64, watts: best / duration as f64 }
+        })
+        .filter(|point| point.watts > 0.0)
+        .collect()
+}
+
+/// Fastest time over each standard distance found anywhere in the workout,
+/// from the cumulative distance of its samples. The start of an effort is
+/// interpolated between the two samples around it.
+pub fn best_efforts(samples: &[Sample]) -> Vec<BestEffort> {
+    let mut points: Vec<(f64, f64)> = Vec::new();
+    for sample in samples {
+        if let (Some(elapsed), Some(distance)) = (sample.elapsed_s, sample.distance) {
+            // Skip the occasional reading that goes backwards
+            if points.last().map_or(true, |&(_, last)| distance >= last) {
+                points.push((elapsed, distance));
+            }
+        }
+    }
+    let Some(&(_, first_distance)) = points.first() else {
+        return Vec::new();
+    };
+
+    let mut efforts = Vec::new();
+    for &(key, target) in BEST_EFFORT_DISTANCES {
+        let mut best: Option<(f64, f64, f64)> = None;
+        let mut i = 0;
+        for &(end_time, end_distance) in &points {
+            let start_distance = end_distance - target;
+            if start_distance < first_distance {
+                continue;
+            }
+            while points[i + 1].1 <= start_distance {
+                i += 1;
+            }
+            let (t0, d0) = points[i];
+            let (t1, d1) = points[i + 1];
+            let start_time = t0 + (t1 - t0) * (start_distance - d0) / (d1 - d0);
+            let elapsed = end_time - start_time;
+            if best.map_or(true, |(fastest, _, _)| elapsed < fastest) {
+                best = Some((elapsed, start_time, end_time));
+            }
+        }
+
+        if let Some((elapsed_seconds, start_offset_s, end_offset_s)) = best {
+            efforts.push(BestEffort {
+                distance: key.to_string(),
+                distance_meters: target,
+                elapsed_seconds,
+                start_offset_s,
+                end_offset_s,
+            });
+        }
+    }
+    efforts
+}
+
+/// Time spent moving: each sample counts for the time since the previous one
+/// unless it falls in a timer pause, follows a gap in recording or was
+/// recorded below walking speed. Speed is taken from the distance covered
+/// when a sample has none; samples with neither are skipped. Returns `None`
+/// without any speed or distance data.
+pub fn moving_seconds(samples: &[Sample], pauses: &[TimerPause]) -> Option<f64> {
+    let parse = |ts: &str| DateTime::parse_from_rfc3339(ts).ok().map(|t| t.with_timezone(&Utc));
+    let pauses: Vec<(DateTime<Utc>, DateTime<Utc>)> = pauses.iter()
+        .filter_map(|p| Some((parse(&p.start)?, parse(&p.end)?)))
+        .collect();
+
+    let mut moving = 0.0;
+    let mut has_speed = false;
+    let mut previous: Option<(f64, Option<f64>)> = None;
+    for sample in samples {
+        let Some(elapsed) = sample.elapsed_s else { continue };
+        if sample.speed.is_none() && sample.distance.is_none() {
+            continue;
+        }
+        let last = previous.replace((elapsed, sample.distance));
+        let Some((last_elapsed, last_distance)) = last else { continue };
+
+        let delta = elapsed - last_elapsed;
+        let speed = sample.speed.or_else(|| match (last_distance, sample.distance) {
+            (Some(d0), Some(d1)) if delta > 0.0 => Some((d1 - d0) / delta),
+            _ => None,
+        });
+        let Some(speed) = speed else { continue };
+        has_speed = true;
+
+        let paused = sample.timestamp.as_deref()
+            .and_then(parse)
+            .is_some_and(|t| pauses.iter().any(|&(start, end)| t > start && t <= end));
+        if delta > 0.0 && delta <= MAX_MOVING_GAP_SECONDS && !paused && speed >= STATIONARY_SPEED_MPS {
+            moving += delta;
+        }
+    }
+    has_speed.then_some(moving)
+}
+
+/// Seconds spent in each zone. Each sample counts for the time since the
+/// previous one; values above the top zone count towards it, values below
+/// the first zone aren't counted.
+pub fn time_in_zones<F>(samples: &[Sample], boundaries: &[(f64, f64)], value: F) -> Vec<f64>
+where
+    F: Fn(&Sample) -> Option<f64>,
+{
+    let mut seconds = vec![0.0; boundaries.len()];
+    let Some(&(_, top)) = boundaries.last() else {
+        return seconds;
+    };
+
+    let mut previous: Option<f64> = None;
+    for sample in samples {
+        let Some(elapsed) = sample.elapsed_s else { continue };
+        let delta = previous.map(|p| elapsed - p);
+        previous = Some(elapsed);
+
+        let (Some(delta), Some(value)) = (delta, value(sample)) else { continue };
+        if !(0.0..=MAX_ZONE_GAP_SECONDS).contains(&delta) {
+            continue;
+        }
+        let zone = boundaries.iter()
+            .position(|&(min, max)| value >= min && value < max)
+            .or_else(|| (value >= top).then_some(boundaries.len() - 1));
+        if let Some(zone) = zone {
+            seconds[zone] += delta;
+        }
+    }
+    seconds
+}
+
+/// Power-based TSS is preferred when the workout has Normalized Power and an
+/// FTP is set; otherwise heart rate is used. `timer_seconds` is the time the
+/// timer ran, so stops don't count as effort; files without it give their
+/// elapsed time.
+pub fn workout_tss(
+    timer_seconds: Option<i64>,
+    avg_heart_rate: Option<i64>,
+    normalized_power: Option<f64>,
+    settings: &AthleteSettings,
+) -> TssEstimate {
+    let ftp = settings.ftp_watts.filter(|f| *f > 0);
+    let duration = timer_seconds.filter(|d| *d > 0);
+    if let (Some(np), Some(ftp), Some(duration)) = (normalized_power, ftp, duration) {
+        let intensity_factor = np / ftp as f64;
+        return TssEstimate {
+            tss: Some(duration as f64 / 3600.0 * intensity_factor * intensity_factor * 100.0),
+            intensity_factor: Some(intensity_factor),
+            source: Some("power"),
+        };
+    }
+
+    match hr_tss(timer_seconds, avg_heart_rate, settings) {
+        Some(tss) => TssEstimate { tss: Some(tss), intensity_factor: None, source: Some("heart_rate") },
+        None => TssEstimate::default(),
+    }
+}
+
+/// Heart-rate based TSS: hours at the workout's share of heart rate reserve,
+/// squared, times 100
+pub fn hr_tss(duration_seconds: Option<i64>, avg_heart_rate: Option<i64>, settings: &AthleteSettings) -> Option<f64> {
+    let duration = duration_seconds.filter(|d| *d > 0)? as f64;
+    let avg_hr = avg_heart_rate.filter(|hr| *hr > 0)? as f64;
+    let max_hr = settings.effective_max_heart_rate() as f64;
+    let resting_hr = settings.effective_resting_heart_rate() as f64;
+    if max_hr <= resting_hr {
+        return None;
+    }
+
+    let intensity = ((avg_hr - resting_hr) / (max_hr - resting_hr)).clamp(0.0, 1.0);
+    Some(duration / 3600.0 * intensity * intensity * 100.0)
+}
+
+/// Continues CTL and ATL day by day from `seed` (the loads of the day before
+/// `start`) through `end`. Days without a workout count as zero TSS.
+pub fn training_load_series(
+    seed: (f64, f64),
+    start: NaiveDate,
+    end: NaiveDate,
+    daily_tss: &HashMap<NaiveDate, f64>,
+) -> Vec<TrainingLoadDay> {
+    let (mut ctl, mut atl) = seed;
+    let mut days = Vec::new();
+
+    for date in start.iter_days().take_while(|d| *d <= end) {
+        let tss = daily_tss.get(&date).copied().unwrap_or(0.0);
+        ctl += (tss - ctl) / CTL_DAYS;
+        atl += (tss - atl) / ATL_DAYS;
+        days.push(TrainingLoadDay {
+            date: date.format("%Y-%m-%d").to_string(),
+            tss,
+            ctl,
+            atl,
+            tsb: ctl - atl,
+        });
+    }
+
+    days
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(effective_date: &str, ftp_watts: i64) -> AthleteProfile {
+        AthleteProfile {
+            effective_date: effective_date.to_string(),
+            ftp_watts: Some(ftp_watts),
+            ..AthleteProfile::default()
+        }
+    }
+
+    #[test]
+    fn periods_use_iso_weeks() {
+        let day = |date| NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+
+        assert_eq!(Period::Week.of(day("2025-06-02")), "2025-W23");
+        assert_eq!(Period::Week.of(day("2025-06-08")), "2025-W23");
+        // Days of a week that spans the new year belong to one year
+        assert_eq!(Period::Week.of(day("2024-12-30")), "2025-W01");
+        assert_eq!(Period::Week.of(day("2021-01-03")), "2020-W53");
+        assert_eq!(Period::Month.of(day("2024-12-30")), "2024-12");
+    }
+
+    #[test]
+    fn profile_in_effect_on_a_date() {
+        let profiles = [profile("2025-03-01", 250), profile("2025-01-01", 230)];
+        let ftp_on = |date| profile_on(&profiles, date).and_then(|p| p.ftp_watts);
+
+        assert_eq!(ftp_on("2024-12-31"), None);
+        assert_eq!(ftp_on("2025-01-01"), Some(230));
+        assert_eq!(ftp_on("2025-01-01T06:30:00+00:00"), Some(230));
+        assert_eq!(ftp_on("2025-02-28T23:59:59+00:00"), Some(230));
+        assert_eq!(ftp_on("2025-03-01"), Some(250));
+        assert_eq!(ftp_on("2026-01-01"), Some(250));
+        assert_eq!(profile_on(&[], "2025-01-01"), None);
+    }
+}
@@ -1,20 +1,47 @@
-use rusqlite::{Connection, Result, params};
+use chrono::{Duration, Local, NaiveDate};
+use rusqlite::{Connection, Result, Transaction, params};
 use serde::{Deserialize, Serialize};
+use crate::developer_fields::{DeveloperField, DeveloperValue};
+use crate::fit_parser::{DeviceInfo, LapData, LengthData};
+use crate::hrv::{analyze_hrv, correct_rr_intervals, RrInterval, DEFAULT_DFA_STEP_SECONDS, DEFAULT_DFA_WINDOW_SECONDS};
+use crate::analytics::{
+    best_efforts, default_pace_zones, default_power_zones, mean_max_power, pace_zone_limits,
+    power_zone_limits, profile_on, time_in_zones, training_load_series, workout_tss, AthleteProfile,
+    AthleteSettings, DateRange, HrZoneSettings, Period, TrainingLoadDay, TssEstimate, Zone,
+};
+use crate::migrations;
+use crate::samples::{sensor_points, Sample};
+use crate::search::{fts_query, HIGHLIGHT_END, HIGHLIGHT_START};
+use crate::sports::{categorize, default_sport_categories, SportCategory, GENERIC_SUB_SPORT};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::HashMap;
+use std::io::{Read, Write};
 use std::path::Path;
 use std::sync::Mutex;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Workout {
     pub id: i64,
-    pub file_hash: String,
+    /// Unset on multisport legs, which share their parent's file
+    pub file_hash: Option<String>,
     pub filename: String,
     pub name: Option<String>,
     pub notes: Option<String>,
     pub tags: Option<String>,
     pub workout_type: Option<String>,
+    pub sub_sport: Option<String>,
+    /// Display category of the sport and sub-sport
+    pub category: Option<String>,
     pub start_time: Option<String>,
     pub end_time: Option<String>,
+    /// Elapsed time from start to finish, pauses included
     pub duration_seconds: Option<i64>,
+    /// Time the recording timer was running
+    pub timer_seconds: Option<i64>,
+    /// Timer time less the stretches spent standing still
+    pub moving_seconds: Option<i64>,
     pub distance_meters: Option<f64>,
     pub total_calories: Option<i64>,
     pub avg_heart_rate: Option<i64>,
@@ -24,11 +51,54 @@ pub struct Workout {
     pub avg_cadence: Option<i64>,
     pub max_cadence: Option<i64>,
     pub avg_speed_mps: Option<f64>,
+    /// Distance over moving time
+    pub avg_moving_speed_mps: Option<f64>,
     pub max_speed_mps: Option<f64>,
     pub elevation_gain_meters: Option<f64>,
     pub elevation_loss_meters: Option<f64>,
+    pub pool_length_meters: Option<f64>,
+    pub parent_id: Option<i64>,
+    pub session_index: Option<i64>,
     pub created_at: String,
     pub updated_at: String,
+    pub tss: Option<f64>,
+    pub normalized_power_watts: Option<f64>,
+    pub intensity_factor: Option<f64>,
+    pub variability_index: Option<f64>,
+    pub work_kj: Option<f64>,
+    /// Whether `tss` was computed from `"power"` or `"heart_rate"`
+    pub tss_source: Option<String>,
+    /// Session averages of the sample channels, over the samples recording each
+    pub avg_temperature_c: Option<f64>,
+    pub avg_vertical_oscillation_mm: Option<f64>,
+    pub avg_ground_contact_time_ms: Option<f64>,
+    /// Left foot share of ground contact time, percent
+    pub avg_ground_contact_balance: Option<f64>,
+    pub avg_stride_length_meters: Option<f64>,
+    pub avg_vertical_ratio: Option<f64>,
+    pub avg_fractional_cadence: Option<f64>,
+    /// Left leg share of power, percent
+    pub avg_left_right_balance: Option<f64>,
+    pub avg_left_torque_effectiveness: Option<f64>,
+    pub avg_right_torque_effectiveness: Option<f64>,
+    pub avg_left_pedal_smoothness: Option<f64>,
+    pub avg_right_pedal_smoothness: Option<f64>,
+    pub avg_respiration_rate: Option<f64>,
+    pub avg_core_temperature_c: Option<f64>,
+    pub avg_spo2: Option<f64>,
+    /// HRV of the workout's corrected R-R intervals, and the heart rates of
+    /// the aerobic and anaerobic thresholds estimated from DFA alpha1
+    pub hrv_rmssd_ms: Option<f64>,
+    pub hrv_sdnn_ms: Option<f64>,
+    pub dfa_aerobic_threshold_hr: Option<f64>,
+    pub dfa_anaerobic_threshold_hr: Option<f64>,
+    /// The device that recorded the file, from FIT `file_id`
+    pub device_manufacturer: Option<String>,
+    pub device_product: Option<String>,
+    pub device_serial_number: Option<i64>,
+    pub file_created_at: Option<String>,
+    /// Child sessions when this is a multisport activity
+    pub legs: Vec<WorkoutSummary>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -37,12 +107,66 @@ pub struct WorkoutSummary {
     pub name: Option<String>,
     pub notes: Option<String>,
     pub workout_type: Option<String>,
+    pub sub_sport: Option<String>,
+    pub category: Option<String>,
     pub start_time: Option<String>,
     pub duration_seconds: Option<i64>,
+    pub moving_seconds: Option<i64>,
     pub distance_meters: Option<f64>,
     pub total_calories: Option<i64>,
     pub avg_heart_rate: Option<i64>,
     pub tags: Option<String>,
+    pub tss: Option<f64>,
+    /// Matched text with the search terms highlighted, when searching
+    pub search_snippet: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Lap {
+    pub id: i64,
+    pub workout_id: i64,
+    pub lap_index: i64,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub duration_seconds: Option<f64>,
+    pub distance_meters: Option<f64>,
+    pub total_calories: Option<i64>,
+    pub avg_heart_rate: Option<i64>,
+    pub max_heart_rate: Option<i64>,
+    pub avg_power_watts: Option<i64>,
+    pub max_power_watts: Option<i64>,
+    pub avg_cadence: Option<i64>,
+    pub max_cadence: Option<i64>,
+    pub avg_speed_mps: Option<f64>,
+    pub max_speed_mps: Option<f64>,
+    pub elevation_gain_meters: Option<f64>,
+    pub elevation_loss_meters: Option<f64>,
+    pub lap_trigger: Option<String>,
+    pub swim_stroke: Option<String>,
+    pub total_strokes: Option<i64>,
+    pub num_lengths: Option<i64>,
+    pub num_active_lengths: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SwimLength {
+    pub length_index: i64,
+    pub start_time: Option<String>,
+    pub duration_seconds: Option<f64>,
+    pub length_type: Option<String>,
+    pub swim_stroke: Option<String>,
+    pub total_strokes: Option<i64>,
+    pub avg_speed_mps: Option<f64>,
+    pub avg_swimming_cadence: Option<i64>,
+}
+
+/// An original activity file kept for reprocessing, decompressed
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub file_hash: String,
+    pub filename: String,
+    pub format: String,
+    pub data: Vec<u8>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -67,137 +191,1623 @@ pub struct StreakInfo {
     pub active_days: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PersonalRecords {
-    pub max_distance_km: f64,
-    pub max_duration_hours: f64,
-    pub max_heart_rate: i64,
-    pub max_speed_kmh: f64,
-    pub max_elevation_gain: f64,
-    pub max_calories: i64,
+/// A workout's value for a record metric
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RecordEntry {
+    pub workout_id: i64,
+    pub workout_name: Option<String>,
+    pub start_time: Option<String>,
+    pub value: f64,
+}
+
+/// The best value of a metric in one sport and the workouts that set the
+/// record in turn (see [`record_histories`])
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PersonalRecord {
+    pub sport: Option<String>,
+    pub metric: String,
+    pub best: RecordEntry,
+    pub history: Vec<RecordEntry>,
+}
+
+/// A record an imported workout broke. `metric` is a record metric or a
+/// best effort distance.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NewRecord {
+    pub workout_id: i64,
+    pub sport: Option<String>,
+    pub metric: String,
+    pub value: f64,
+    pub previous: RecordEntry,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ContributionDay {
     pub date: String,
     pub count: i64,
+    /// Display categories of the day's workouts
     pub workout_types: Vec<String>,
 }
 
+/// Workouts of one display category
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CategoryBreakdown {
+    pub name: String,
+    pub count: i64,
+    pub sports: Vec<SportBreakdown>,
+}
+
+/// Workouts of one sport and sub-sport within a category
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SportBreakdown {
+    pub sport: String,
+    pub sub_sport: Option<String>,
+    pub count: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WeeklySummary {
     pub week: String,
     pub count: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    pub color: Option<String>,
+    /// Number of workouts carrying the tag
+    pub usage_count: i64,
+}
+
+/// Best power held for a duration and the workout that set it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PowerCurvePoint {
+    pub duration_seconds: i64,
+    pub watts: f64,
+    pub workout_id: i64,
+    pub start_time: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SeasonPowerCurve {
+    /// Calendar year
+    pub season: String,
+    pub points: Vec<PowerCurvePoint>,
+}
+
+/// Mean-maximal power curves over the workouts in a date range
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PowerCurves {
+    pub all_time: Vec<PowerCurvePoint>,
+    pub last_90_days: Vec<PowerCurvePoint>,
+    pub seasons: Vec<SeasonPowerCurve>,
+}
+
+/// One workout's time over a best effort distance
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BestEffortEntry {
+    pub workout_id: i64,
+    pub workout_name: Option<String>,
+    pub start_time: Option<String>,
+    pub elapsed_seconds: f64,
+    /// Where the effort lies in the workout, in seconds since its first sample
+    pub start_offset_s: f64,
+    pub end_offset_s: f64,
+}
+
+/// The fastest time over a distance in one sport and the efforts that set
+/// the record in turn (see [`record_histories`])
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BestEffortRecord {
+    pub sport: Option<String>,
+    pub distance: String,
+    pub distance_meters: f64,
+    pub best: BestEffortEntry,
+    pub history: Vec<BestEffortEntry>,
+}
+
+/// Time a workout spent in a zone, with the zone's limits under the
+/// current settings
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ZoneTime {
+    pub zone_index: i64,
+    pub name: String,
+    pub color: String,
+    pub low: f64,
+    pub high: f64,
+    pub seconds: f64,
+}
+
+/// Seconds per zone over the workouts of an ISO week (`YYYY-Www`) or month
+/// (`YYYY-MM`)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ZoneDistribution {
+    pub period: String,
+    pub seconds: Vec<f64>,
+}
+
+/// A workout's HRV summary, for following readiness and thresholds over time
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HrvSummary {
+    pub workout_id: i64,
+    pub start_time: Option<String>,
+    pub workout_type: Option<String>,
+    pub rmssd_ms: Option<f64>,
+    pub sdnn_ms: Option<f64>,
+    pub aerobic_threshold_hr: Option<f64>,
+    pub anaerobic_threshold_hr: Option<f64>,
+}
+
+/// A device across the workouts it recorded or was connected to, for
+/// filtering the workout list
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeviceSummary {
+    pub device_type: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub product_name: Option<String>,
+    pub serial_number: Option<i64>,
+    /// Whether it recorded workouts rather than only being connected
+    pub recorder: bool,
+    pub workout_count: i64,
+    pub last_used: Option<String>,
+}
+
 pub struct Database {
     pub conn: Mutex<Connection>,
 }
 
-impl Database {
-    pub fn new(db_path: &Path) -> Result<Self> {
-        let conn = Connection::open(db_path)?;
-        
-        // Enable WAL mode for better concurrency
-        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")?;
-        
-        // Create tables
+/// Stored workouts starting this close to a new one may be the same activity
+const SAME_ACTIVITY_START_SECONDS: f64 = 120.0;
+/// Share of the shorter of two recordings they must overlap by to be the
+/// same activity
+const SAME_ACTIVITY_MIN_OVERLAP: f64 = 0.5;
+
+/// `workout_zones.zone_type` of heart rate zones
+pub const HEART_RATE_ZONES: &str = "heart_rate";
+/// `workout_zones.zone_type` of power zones
+pub const POWER_ZONES: &str = "power";
+/// `workout_zones.zone_type` of running pace zones
+pub const PACE_ZONES: &str = "pace";
+
+/// Workout columns kept as personal records, by metric name. Higher is
+/// better for all of them.
+const RECORD_METRICS: &[(&str, &str)] = &[
+    ("distance", "distance_meters"),
+    ("duration", "duration_seconds"),
+    ("max_heart_rate", "max_heart_rate"),
+    ("max_speed", "max_speed_mps"),
+    ("elevation_gain", "elevation_gain_meters"),
+    ("calories", "total_calories"),
+    ("max_power", "max_power_watts"),
+    ("normalized_power", "normalized_power_watts"),
+];
+
+/// A workout's tag names as a JSON array, sorted by name
+macro_rules! tags_json_column {
+    () => {
+        "(SELECT json_group_array(name) FROM (
+            SELECT t.name FROM workout_tags wt JOIN tags t ON t.id = wt.tag_id
+            WHERE wt.workout_id = workouts.id ORDER BY t.name
+        ))"
+    };
+}
+
+const WORKOUT_COLUMNS: &str = concat!(
+    "id, file_hash, filename, name, notes, ", tags_json_column!(), ", workout_type, start_time, end_time,
+     duration_seconds, distance_meters, total_calories,
+     avg_heart_rate, max_heart_rate, avg_power_watts, max_power_watts,
+     avg_cadence, max_cadence, avg_speed_mps, max_speed_mps,
+     elevation_gain_meters, elevation_loss_meters, pool_length_meters,
+     parent_id, session_index, created_at, updated_at, tss,
+     normalized_power_watts, intensity_factor, variability_index, work_kj, tss_source,
+     timer_seconds, moving_seconds, avg_moving_speed_mps,
+     avg_temperature_c, avg_vertical_oscillation_mm, avg_ground_contact_time_ms,
+     avg_ground_contact_balance, avg_stride_length_meters, avg_vertical_ratio,
+     avg_fractional_cadence, avg_left_right_balance, avg_left_torque_effectiveness,
+     avg_right_torque_effectiveness, avg_left_pedal_smoothness, avg_right_pedal_smoothness,
+     avg_respiration_rate, avg_core_temperature_c, avg_spo2,
+     hrv_rmssd_ms, hrv_sdnn_ms, dfa_aerobic_threshold_hr, dfa_anaerobic_threshold_hr,
+     device_manufacturer, device_product, device_serial_number, file_created_at,
+     sub_sport, category"
+);
+
+const SUMMARY_COLUMNS: &str = concat!(
+    "id, name, notes, workout_type, start_time, duration_seconds, distance_meters,
+     total_calories, avg_heart_rate, ", tags_json_column!(), ", tss, moving_seconds,
+     sub_sport, category"
+);
+
+fn workout_from_row(row: &rusqlite::Row) -> Result<Workout> {
+    Ok(Workout {
+        id: row.get(0)?,
+        file_hash: row.get(1)?,
+        filename: row.get(2)?,
+        name: row.get(3)?,
+        notes: row.get(4)?,
+        tags: row.get(5)?,
+        workout_type: row.get(6)?,
+        sub_sport: row.get(59)?,
+        category: row.get(60)?,
+        start_time: row.get(7)?,
+        end_time: row.get(8)?,
+        duration_seconds: row.get(9)?,
+        timer_seconds: row.get(33)?,
+        moving_seconds: row.get(34)?,
+        distance_meters: row.get(10)?,
+        total_calories: row.get(11)?,
+        avg_heart_rate: row.get(12)?,
+        max_heart_rate: row.get(13)?,
+        avg_power_watts: row.get(14)?,
+        max_power_watts: row.get(15)?,
+        avg_cadence: row.get(16)?,
+        max_cadence: row.get(17)?,
+        avg_speed_mps: row.get(18)?,
+        avg_moving_speed_mps: row.get(35)?,
+        max_speed_mps: row.get(19)?,
+        elevation_gain_meters: row.get(20)?,
+        elevation_loss_meters: row.get(21)?,
+        pool_length_meters: row.get(22)?,
+        parent_id: row.get(23)?,
+        session_index: row.get(24)?,
+        created_at: row.get(25)?,
+        updated_at: row.get(26)?,
+        tss: row.get(27)?,
+        normalized_power_watts: row.get(28)?,
+        intensity_factor: row.get(29)?,
+        variability_index: row.get(30)?,
+        work_kj: row.get(31)?,
+        tss_source: row.get(32)?,
+        avg_temperature_c: row.get(36)?,
+        avg_vertical_oscillation_mm: row.get(37)?,
+        avg_ground_contact_time_ms: row.get(38)?,
+        avg_ground_contact_balance: row.get(39)?,
+        avg_stride_length_meters: row.get(40)?,
+        avg_vertical_ratio: row.get(41)?,
+        avg_fractional_cadence: row.get(42)?,
+        avg_left_right_balance: row.get(43)?,
+        avg_left_torque_effectiveness: row.get(44)?,
+        avg_right_torque_effectiveness: row.get(45)?,
+        avg_left_pedal_smoothness: row.get(46)?,
+        avg_right_pedal_smoothness: row.get(47)?,
+        avg_respiration_rate: row.get(48)?,
+        avg_core_temperature_c: row.get(49)?,
+        avg_spo2: row.get(50)?,
+        hrv_rmssd_ms: row.get(51)?,
+        hrv_sdnn_ms: row.get(52)?,
+        dfa_aerobic_threshold_hr: row.get(53)?,
+        dfa_anaerobic_threshold_hr: row.get(54)?,
+        device_manufacturer: row.get(55)?,
+        device_product: row.get(56)?,
+        device_serial_number: row.get(57)?,
+        file_created_at: row.get(58)?,
+        legs: Vec::new(),
+    })
+}
+
+fn summary_from_row(row: &rusqlite::Row) -> Result<WorkoutSummary> {
+    Ok(WorkoutSummary {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        notes: row.get(2)?,
+        workout_type: row.get(3)?,
+        sub_sport: row.get(12)?,
+        category: row.get(13)?,
+        start_time: row.get(4)?,
+        duration_seconds: row.get(5)?,
+        moving_seconds: row.get(11)?,
+        distance_meters: row.get(6)?,
+        total_calories: row.get(7)?,
+        avg_heart_rate: row.get(8)?,
+        tags: row.get(9)?,
+        tss: row.get(10)?,
+        search_snippet: None,
+    })
+}
+
+/// Workouts recorded with or connected to a device, given by product or
+/// serial number
+const DEVICE_CONDITION: &str =
+    "id IN (SELECT workout_id FROM workout_devices WHERE product = ? OR CAST(serial_number AS TEXT) = ?)";
+
+/// Column the duration filter applies to: moving time for `"moving"`,
+/// elapsed time otherwise. Workouts without a moving time use elapsed time.
+fn duration_column(basis: Option<&str>) -> &'static str {
+    match basis {
+        Some("moving") => "COALESCE(moving_seconds, duration_seconds)",
+        _ => "duration_seconds",
+    }
+}
+
+/// ORDER BY clause of the workout list for a sort key and `"asc"`/`"desc"`
+/// order. Unknown keys sort by start time, newest first.
+fn workout_order(sort_by: Option<&str>, sort_order: Option<&str>) -> String {
+    let column = match sort_by {
+        Some("duration") => "duration_seconds",
+        Some("moving_time") => "COALESCE(moving_seconds, duration_seconds)",
+        Some("distance") => "distance_meters",
+        _ => "start_time",
+    };
+    let direction = if sort_order == Some("asc") { "ASC" } else { "DESC" };
+    format!("{} {}, start_time DESC", column, direction)
+}
+
+fn query_legs(conn: &Connection, parent_id: i64) -> Result<Vec<WorkoutSummary>> {
+    let sql = format!(
+        "SELECT {} FROM workouts WHERE parent_id = ? ORDER BY session_index",
+        SUMMARY_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params![parent_id], summary_from_row)?;
+
+    let mut legs = Vec::new();
+    for row in rows {
+        legs.push(row?);
+    }
+    Ok(legs)
+}
+
+fn insert_lap_rows(conn: &Connection, workout_id: i64, laps: &[LapData]) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "INSERT INTO laps (
+            workout_id, lap_index, start_time, end_time, duration_seconds, distance_meters,
+            total_calories, avg_heart_rate, max_heart_rate, avg_power_watts, max_power_watts,
+            avg_cadence, max_cadence, avg_speed_mps, max_speed_mps,
+            elevation_gain_meters, elevation_loss_meters, lap_trigger,
+            swim_stroke, total_strokes, num_lengths, num_active_lengths
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+    )?;
+    for (i, lap) in laps.iter().enumerate() {
+        stmt.execute(params![
+            workout_id,
+            i as i64,
+            lap.start_time,
+            lap.end_time,
+            lap.duration_seconds,
+            lap.distance_meters,
+            lap.total_calories,
+            lap.avg_heart_rate,
+            lap.max_heart_rate,
+            lap.avg_power_watts,
+            lap.max_power_watts,
+            lap.avg_cadence,
+            lap.max_cadence,
+            lap.avg_speed_mps,
+            lap.max_speed_mps,
+            lap.elevation_gain_meters,
+            lap.elevation_loss_meters,
+            lap.lap_trigger,
+            lap.swim_stroke,
+            lap.total_strokes,
+            lap.num_lengths,
+            lap.num_active_lengths,
+        ])?;
+    }
+    Ok(())
+}
+
+fn insert_length_rows(conn: &Connection, workout_id: i64, lengths: &[LengthData]) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "INSERT INTO swim_lengths (
+            workout_id, length_index, start_time, duration_seconds, length_type,
+            swim_stroke, total_strokes, avg_speed_mps, avg_swimming_cadence
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+    )?;
+    for (i, length) in lengths.iter().enumerate() {
+        stmt.execute(params![
+            workout_id,
+            i as i64,
+            length.start_time,
+            length.duration_seconds,
+            length.length_type,
+            length.swim_stroke,
+            length.total_strokes,
+            length.avg_speed_mps,
+            length.avg_swimming_cadence,
+        ])?;
+    }
+    Ok(())
+}
+
+fn find_tag_id(conn: &Connection, name: &str) -> Result<Option<i64>> {
+    let mut stmt = conn.prepare("SELECT id FROM tags WHERE name = ?")?;
+    let mut rows = stmt.query(params![name])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(row.get(0)?)),
+        None => Ok(None),
+    }
+}
+
+fn ensure_tag(conn: &Connection, name: &str) -> Result<i64> {
+    conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?)", params![name])?;
+    conn.query_row("SELECT id FROM tags WHERE name = ?", params![name], |row| row.get(0))
+}
+
+pub(crate) fn set_workout_tags(conn: &Connection, workout_id: i64, tags: &[String]) -> Result<()> {
+    conn.execute("DELETE FROM workout_tags WHERE workout_id = ?", params![workout_id])?;
+    for name in tags {
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        let tag_id = ensure_tag(conn, name)?;
         conn.execute(
-            "CREATE TABLE IF NOT EXISTS workouts (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                file_hash TEXT UNIQUE NOT NULL,
-                filename TEXT NOT NULL,
-                name TEXT,
-                notes TEXT,
-                tags TEXT,
-                workout_type TEXT,
-                start_time DATETIME,
-                end_time DATETIME,
-                duration_seconds INTEGER,
-                distance_meters REAL,
-                total_calories INTEGER,
-                avg_heart_rate INTEGER,
-                max_heart_rate INTEGER,
-                avg_power_watts INTEGER,
-                max_power_watts INTEGER,
-                avg_cadence INTEGER,
-                max_cadence INTEGER,
-                avg_speed_mps REAL,
-                max_speed_mps REAL,
-                elevation_gain_meters REAL,
-                elevation_loss_meters REAL,
-                gps_data TEXT,
-                sensor_data TEXT,
-                chart_data TEXT,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-            [],
+            "INSERT OR IGNORE INTO workout_tags (workout_id, tag_id) VALUES (?, ?)",
+            params![workout_id, tag_id],
         )?;
+    }
+    Ok(())
+}
 
-        // Add notes column if it doesn't exist (migration for existing databases)
-        let _ = conn.execute("ALTER TABLE workouts ADD COLUMN notes TEXT", []);
+fn touch_tagged_workouts(conn: &Connection, tag_id: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE workouts SET updated_at = CURRENT_TIMESTAMP
+         WHERE id IN (SELECT workout_id FROM workout_tags WHERE tag_id = ?)",
+        params![tag_id],
+    )?;
+    Ok(())
+}
 
-        // Create index on common query fields
+fn merge_tag_rows(conn: &Connection, source_ids: &[i64], target_id: i64) -> Result<()> {
+    for source_id in source_ids {
+        touch_tagged_workouts(conn, *source_id)?;
         conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_workouts_start_time ON workouts(start_time DESC)",
-            [],
+            "INSERT OR IGNORE INTO workout_tags (workout_id, tag_id)
+             SELECT workout_id, ? FROM workout_tags WHERE tag_id = ?",
+            params![target_id, source_id],
         )?;
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_workouts_type ON workouts(workout_type)",
-            [],
+        conn.execute("DELETE FROM tags WHERE id = ?", params![source_id])?;
+    }
+    Ok(())
+}
+
+const PROFILE_COLUMNS: &str = "effective_date, weight_kg, height_cm, sex, birth_date, max_heart_rate,
+    resting_heart_rate, threshold_heart_rate, ftp_watts, threshold_pace_seconds_per_km";
+
+fn row_to_profile(row: &rusqlite::Row) -> Result<AthleteProfile> {
+    Ok(AthleteProfile {
+        effective_date: row.get(0)?,
+        weight_kg: row.get(1)?,
+        height_cm: row.get(2)?,
+        sex: row.get(3)?,
+        birth_date: row.get(4)?,
+        max_heart_rate: row.get(5)?,
+        resting_heart_rate: row.get(6)?,
+        threshold_heart_rate: row.get(7)?,
+        ftp_watts: row.get(8)?,
+        threshold_pace_seconds_per_km: row.get(9)?,
+    })
+}
+
+/// Every entry of the athlete profile, oldest first
+pub(crate) fn load_athlete_profiles(conn: &Connection) -> Result<Vec<AthleteProfile>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM athlete_profile ORDER BY effective_date",
+        PROFILE_COLUMNS
+    ))?;
+    let rows = stmt.query_map([], row_to_profile)?;
+    rows.collect()
+}
+
+/// The athlete values in effect on the day of `date` (a date or start time),
+/// or today without one
+fn settings_from(profiles: &[AthleteProfile], date: Option<&str>) -> AthleteSettings {
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    let date = date.unwrap_or(&today);
+    let day = date.get(..10).and_then(parse_date).unwrap_or_else(|| Local::now().date_naive());
+    profile_on(profiles, date).map(|p| p.settings_on(day)).unwrap_or_default()
+}
+
+pub(crate) fn athlete_settings_on(conn: &Connection, date: Option<&str>) -> Result<AthleteSettings> {
+    Ok(settings_from(&load_athlete_profiles(conn)?, date))
+}
+
+/// The saved heart rate zones; `None` until they are first saved
+pub(crate) fn load_hr_zones(conn: &Connection) -> Result<Option<HrZoneSettings>> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM settings WHERE key = 'hr_zones'", [], |row| row.get(0))
+        .ok();
+    Ok(value.and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+/// The power or pace zones saved under the settings `key`, else `default`
+fn load_zones(conn: &Connection, key: &str, default: fn() -> Vec<Zone>) -> Result<Vec<Zone>> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM settings WHERE key = ?", params![key], |row| row.get(0))
+        .ok();
+    Ok(value
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_else(default))
+}
+
+/// The sport category mapping saved in settings, else the default one
+fn load_sport_categories(conn: &Connection) -> Result<Vec<SportCategory>> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM settings WHERE key = 'sport_categories'", [], |row| row.get(0))
+        .ok();
+    Ok(value
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_else(default_sport_categories))
+}
+
+/// Recomputes the display category of every workout, e.g. after the
+/// sport category mapping changed
+pub(crate) fn recalculate_categories(conn: &Connection) -> Result<()> {
+    let categories = load_sport_categories(conn)?;
+    let workouts: Vec<(i64, Option<String>, Option<String>)> = {
+        let mut stmt = conn.prepare("SELECT id, workout_type, sub_sport FROM workouts")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<Result<_>>()?
+    };
+
+    let mut stmt = conn.prepare("UPDATE workouts SET category = ? WHERE id = ?")?;
+    for (id, sport, sub_sport) in workouts {
+        stmt.execute(params![categorize(&categories, sport.as_deref(), sub_sport.as_deref()), id])?;
+    }
+    Ok(())
+}
+
+/// Zones with their lower and upper limits in the zone type's unit
+type ZoneLimits = (Vec<Zone>, Vec<(f64, f64)>);
+
+/// The zones of a zone type with their limits, when they can be worked out
+/// from the athlete settings
+fn zone_limits(conn: &Connection, zone_type: &str, athlete: &AthleteSettings) -> Result<Option<ZoneLimits>> {
+    Ok(match zone_type {
+        HEART_RATE_ZONES => {
+            let settings = load_hr_zones(conn)?.unwrap_or_default();
+            settings.boundaries(athlete).map(|limits| (settings.zones, limits))
+        }
+        POWER_ZONES => {
+            let zones = load_zones(conn, "power_zones", default_power_zones)?;
+            power_zone_limits(&zones, athlete).map(|limits| (zones, limits))
+        }
+        PACE_ZONES => {
+            let zones = load_zones(conn, "pace_zones", default_pace_zones)?;
+            pace_zone_limits(&zones, athlete).map(|limits| (zones, limits))
+        }
+        _ => None,
+    })
+}
+
+/// The sample value a zone type is measured by
+fn zone_value(zone_type: &str, sample: &Sample) -> Option<f64> {
+    match zone_type {
+        HEART_RATE_ZONES => sample.heart_rate.map(|hr| hr as f64),
+        POWER_ZONES => sample.power.map(|p| p as f64),
+        // Standing still isn't a running pace
+        PACE_ZONES => sample.speed.filter(|v| *v > 0.0),
+        _ => None,
+    }
+}
+
+/// Replaces the workout's stored time in zones with times computed from its
+/// samples under the current zone settings
+pub(crate) fn store_time_in_zones(conn: &Connection, workout_id: i64, samples: &[Sample]) -> Result<()> {
+    conn.execute("DELETE FROM workout_zones WHERE workout_id = ?", params![workout_id])?;
+    let mut stmt = conn.prepare(
+        "INSERT INTO workout_zones (workout_id, zone_type, zone_index, seconds) VALUES (?, ?, ?, ?)",
+    )?;
+
+    let (workout_type, start_time): (Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT workout_type, start_time FROM workouts WHERE id = ?",
+            params![workout_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap_or((None, None));
+    // Zones follow the profile in effect on the day of the workout
+    let athlete = athlete_settings_on(conn, start_time.as_deref())?;
+
+    for zone_type in [HEART_RATE_ZONES, POWER_ZONES, PACE_ZONES] {
+        // Threshold pace only says something about running
+        if zone_type == PACE_ZONES && workout_type.as_deref() != Some("running") {
+            continue;
+        }
+        let Some((_, limits)) = zone_limits(conn, zone_type, &athlete)? else { continue };
+        let seconds = time_in_zones(samples, &limits, |s| zone_value(zone_type, s));
+        if seconds.iter().any(|s| *s > 0.0) {
+            for (i, zone_seconds) in seconds.iter().enumerate() {
+                stmt.execute(params![workout_id, zone_type, i as i64, zone_seconds])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Recomputes the time in zones of every workout, e.g. after the zones or
+/// the athlete settings they depend on changed
+pub(crate) fn recalculate_zones(conn: &Connection) -> Result<()> {
+    let ids: Vec<i64> = {
+        let mut stmt = conn.prepare("SELECT id FROM workouts WHERE id IN (SELECT DISTINCT workout_id FROM samples)")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect::<Result<_>>()?
+    };
+    for id in ids {
+        store_time_in_zones(conn, id, &load_samples(conn, id, None, None)?)?;
+    }
+    Ok(())
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// Recomputes the stored TSS of every workout against the athlete profile
+/// in effect on its day, e.g. after the profile changed
+pub(crate) fn recalculate_tss(conn: &Connection) -> Result<()> {
+    let profiles = load_athlete_profiles(conn)?;
+    let estimates: Vec<(i64, TssEstimate)> = {
+        let mut stmt = conn.prepare(
+            "SELECT id, start_time, COALESCE(timer_seconds, duration_seconds), avg_heart_rate,
+                    normalized_power_watts
+             FROM workouts",
         )?;
+        let rows = stmt.query_map([], |row| {
+            let settings = settings_from(&profiles, row.get::<_, Option<String>>(1)?.as_deref());
+            Ok((row.get(0)?, workout_tss(row.get(2)?, row.get(3)?, row.get(4)?, &settings)))
+        })?;
+        rows.collect::<Result<_>>()?
+    };
 
-        Ok(Self { conn: Mutex::new(conn) })
+    let mut stmt = conn.prepare(
+        "UPDATE workouts SET tss = ?, intensity_factor = ?, tss_source = ? WHERE id = ?",
+    )?;
+    for (id, estimate) in estimates {
+        stmt.execute(params![estimate.tss, estimate.intensity_factor, estimate.source, id])?;
+    }
+    Ok(())
+}
+
+/// Recomputes everything derived from the athlete profile
+pub(crate) fn recalculate_athlete_metrics(conn: &Connection) -> Result<()> {
+    recalculate_tss(conn)?;
+    refresh_training_load(conn, None)?;
+    recalculate_zones(conn)
+}
+
+/// Rewrites the daily CTL/ATL/TSB series from the day of `from` (any start
+/// time) through today, continuing from the stored loads of the day before.
+/// Without `from` the whole series is rebuilt from the first workout.
+pub(crate) fn refresh_training_load(conn: &Connection, from: Option<&str>) -> Result<()> {
+    let (first, last): (Option<String>, Option<String>) = conn.query_row(
+        "SELECT MIN(DATE(start_time)), MAX(DATE(start_time)) FROM workouts WHERE parent_id IS NULL",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let (Some(first), Some(last)) = (first.as_deref().and_then(parse_date), last.as_deref().and_then(parse_date)) else {
+        conn.execute("DELETE FROM training_load", [])?;
+        return Ok(());
+    };
+
+    let from = match from {
+        Some(time) => conn
+            .query_row("SELECT DATE(?)", params![time], |row| row.get::<_, Option<String>>(0))?
+            .as_deref()
+            .and_then(parse_date),
+        None => None,
+    };
+
+    // Continue from the day before when it is stored, else start over
+    let mut start = first;
+    let mut seed = (0.0, 0.0);
+    if let Some(from) = from.filter(|d| *d > first) {
+        let previous = (from - Duration::days(1)).format("%Y-%m-%d").to_string();
+        let stored = conn.query_row(
+            "SELECT ctl, atl FROM training_load WHERE date = ?",
+            params![previous],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        );
+        if let Ok(loads) = stored {
+            start = from;
+            seed = loads;
+        }
+    }
+    let end = last.max(Local::now().date_naive());
+
+    let mut daily_tss = HashMap::new();
+    {
+        let mut stmt = conn.prepare(
+            "SELECT DATE(start_time), SUM(COALESCE(tss, 0)) FROM workouts
+             WHERE parent_id IS NULL AND DATE(start_time) >= ?
+             GROUP BY DATE(start_time)",
+        )?;
+        let rows = stmt.query_map(params![start.format("%Y-%m-%d").to_string()], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+        })?;
+        for row in rows {
+            let (date, tss) = row?;
+            if let Some(date) = parse_date(&date) {
+                daily_tss.insert(date, tss);
+            }
+        }
+    }
+
+    conn.execute(
+        "DELETE FROM training_load WHERE date >= ? OR date < ?",
+        params![start.format("%Y-%m-%d").to_string(), first.format("%Y-%m-%d").to_string()],
+    )?;
+    let mut stmt = conn.prepare(
+        "INSERT INTO training_load (date, tss, ctl, atl, tsb) VALUES (?, ?, ?, ?, ?)",
+    )?;
+    for day in training_load_series(seed, start, end, &daily_tss) {
+        stmt.execute(params![day.date, day.tss, day.ctl, day.atl, day.tsb])?;
+    }
+    Ok(())
+}
+
+const SAMPLE_COLUMNS: &str = concat!(
+    "timestamp, elapsed_s, lat, lon, altitude, heart_rate, power, cadence, speed, distance, temperature, ",
+    "vertical_oscillation, ground_contact_time, ground_contact_balance, stride_length, vertical_ratio, ",
+    "fractional_cadence, left_right_balance, left_torque_effectiveness, right_torque_effectiveness, ",
+    "left_pedal_smoothness, right_pedal_smoothness, respiration_rate, core_temperature, spo2"
+);
+
+fn sample_from_row(row: &rusqlite::Row) -> Result<Sample> {
+    Ok(Sample {
+        timestamp: row.get(0)?,
+        elapsed_s: row.get(1)?,
+        lat: row.get(2)?,
+        lon: row.get(3)?,
+        altitude: row.get(4)?,
+        heart_rate: row.get(5)?,
+        power: row.get(6)?,
+        cadence: row.get(7)?,
+        speed: row.get(8)?,
+        distance: row.get(9)?,
+        temperature: row.get(10)?,
+        vertical_oscillation: row.get(11)?,
+        ground_contact_time: row.get(12)?,
+        ground_contact_balance: row.get(13)?,
+        stride_length: row.get(14)?,
+        vertical_ratio: row.get(15)?,
+        fractional_cadence: row.get(16)?,
+        left_right_balance: row.get(17)?,
+        left_torque_effectiveness: row.get(18)?,
+        right_torque_effectiveness: row.get(19)?,
+        left_pedal_smoothness: row.get(20)?,
+        right_pedal_smoothness: row.get(21)?,
+        respiration_rate: row.get(22)?,
+        core_temperature: row.get(23)?,
+        spo2: row.get(24)?,
+        developer: Vec::new(),
+    })
+}
+
+pub(crate) fn load_samples(
+    conn: &Connection,
+    workout_id: i64,
+    start_s: Option<f64>,
+    end_s: Option<f64>,
+) -> Result<Vec<Sample>> {
+    let mut sql = format!("SELECT {} FROM samples WHERE workout_id = ?", SAMPLE_COLUMNS);
+    let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(workout_id)];
+    if let Some(start) = start_s {
+        sql.push_str(" AND elapsed_s >= ?");
+        params_vec.push(Box::new(start));
+    }
+    if let Some(end) = end_s {
+        sql.push_str(" AND elapsed_s <= ?");
+        params_vec.push(Box::new(end));
+    }
+    sql.push_str(" ORDER BY sample_index");
+
+    let mut stmt = conn.prepare(&sql)?;
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+    let rows = stmt.query_map(params_refs.as_slice(), sample_from_row)?;
+    rows.collect()
+}
+
+pub(crate) fn insert_sample_rows(conn: &Connection, workout_id: i64, samples: &[Sample]) -> Result<()> {
+    let placeholders = vec!["?"; SAMPLE_COLUMNS.split(',').count() + 2].join(", ");
+    let mut stmt = conn.prepare(&format!(
+        "INSERT INTO samples (workout_id, sample_index, {}) VALUES ({})",
+        SAMPLE_COLUMNS, placeholders
+    ))?;
+    for (i, sample) in samples.iter().enumerate() {
+        stmt.execute(params![
+            workout_id,
+            i as i64,
+            sample.timestamp,
+            sample.elapsed_s,
+            sample.lat,
+            sample.lon,
+            sample.altitude,
+            sample.heart_rate,
+            sample.power,
+            sample.cadence,
+            sample.speed,
+            sample.distance,
+            sample.temperature,
+            sample.vertical_oscillation,
+            sample.ground_contact_time,
+            sample.ground_contact_balance,
+            sample.stride_length,
+            sample.vertical_ratio,
+            sample.fractional_cadence,
+            sample.left_right_balance,
+            sample.left_torque_effectiveness,
+            sample.right_torque_effectiveness,
+            sample.left_pedal_smoothness,
+            sample.right_pedal_smoothness,
+            sample.respiration_rate,
+            sample.core_temperature,
+            sample.spo2,
+        ])?;
+    }
+    Ok(())
+}
+
+/// Workout average columns and the sample channel each one averages
+const CHANNEL_AVERAGES: &[(&str, &str)] = &[
+    ("avg_temperature_c", "temperature"),
+    ("avg_vertical_oscillation_mm", "vertical_oscillation"),
+    ("avg_ground_contact_time_ms", "ground_contact_time"),
+    ("avg_ground_contact_balance", "ground_contact_balance"),
+    ("avg_stride_length_meters", "stride_length"),
+    ("avg_vertical_ratio", "vertical_ratio"),
+    ("avg_fractional_cadence", "fractional_cadence"),
+    ("avg_left_right_balance", "left_right_balance"),
+    ("avg_left_torque_effectiveness", "left_torque_effectiveness"),
+    ("avg_right_torque_effectiveness", "right_torque_effectiveness"),
+    ("avg_left_pedal_smoothness", "left_pedal_smoothness"),
+    ("avg_right_pedal_smoothness", "right_pedal_smoothness"),
+    ("avg_respiration_rate", "respiration_rate"),
+    ("avg_core_temperature_c", "core_temperature"),
+    ("avg_spo2", "spo2"),
+];
+
+/// Sets the workout's session-level channel averages from its stored samples
+pub(crate) fn store_channel_averages(conn: &Connection, workout_id: i64) -> Result<()> {
+    let columns: Vec<&str> = CHANNEL_AVERAGES.iter().map(|(column, _)| *column).collect();
+    let averages: Vec<String> = CHANNEL_AVERAGES.iter().map(|(_, channel)| format!("AVG({})", channel)).collect();
+    conn.execute(
+        &format!(
+            "UPDATE workouts SET ({}) = (SELECT {} FROM samples WHERE workout_id = ?1) WHERE id = ?1",
+            columns.join(", "),
+            averages.join(", ")
+        ),
+        params![workout_id],
+    )?;
+    Ok(())
+}
+
+/// Stores the workout's developer fields and their values in `samples`.
+/// Fields without any value in these samples are left out.
+pub(crate) fn insert_developer_rows(
+    conn: &Connection,
+    workout_id: i64,
+    fields: &[DeveloperField],
+    samples: &[Sample],
+) -> Result<()> {
+    let mut field_stmt = conn.prepare(
+        "INSERT INTO developer_fields (workout_id, field_index, key, name, units, app_id) VALUES (?, ?, ?, ?, ?, ?)",
+    )?;
+    let recorded = fields.iter()
+        .filter(|f| samples.iter().any(|s| s.developer.iter().any(|v| v.key == f.key)));
+    for (i, field) in recorded.enumerate() {
+        field_stmt.execute(params![workout_id, i as i64, field.key, field.name, field.units, field.app_id])?;
+    }
+
+    let mut value_stmt = conn.prepare(
+        "INSERT OR REPLACE INTO developer_samples (workout_id, sample_index, key, value) VALUES (?, ?, ?, ?)",
+    )?;
+    for (i, sample) in samples.iter().enumerate() {
+        for value in &sample.developer {
+            value_stmt.execute(params![workout_id, i as i64, value.key, value.value])?;
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the workout's stored mean-maximal power curve with one computed
+/// from its samples
+pub(crate) fn store_power_curve(conn: &Connection, workout_id: i64, samples: &[Sample]) -> Result<()> {
+    conn.execute("DELETE FROM power_curves WHERE workout_id = ?", params![workout_id])?;
+    let mut stmt = conn.prepare(
+        "INSERT INTO power_curves (workout_id, duration_seconds, watts) VALUES (?, ?, ?)",
+    )?;
+    for point in mean_max_power(&sensor_points(samples)) {
+        stmt.execute(params![workout_id, point.duration_seconds, point.watts])?;
+    }
+    Ok(())
+}
+
+/// Replaces the workout's stored best efforts with those found in its samples
+pub(crate) fn store_best_efforts(conn: &Connection, workout_id: i64, samples: &[Sample]) -> Result<()> {
+    conn.execute("DELETE FROM best_efforts WHERE workout_id = ?", params![workout_id])?;
+    let mut stmt = conn.prepare(
+        "INSERT INTO best_efforts (workout_id, distance, distance_meters, elapsed_seconds, start_offset_s, end_offset_s)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )?;
+    for effort in best_efforts(samples) {
+        stmt.execute(params![
+            workout_id,
+            effort.distance,
+            effort.distance_meters,
+            effort.elapsed_seconds,
+            effort.start_offset_s,
+            effort.end_offset_s,
+        ])?;
+    }
+    Ok(())
+}
+
+/// Inserts a workout row with its samples and the data derived from them,
+/// laps, lengths, R-R intervals, devices and original file, then its legs
+/// under it
+fn insert_workout_rows(
+    conn: &Connection,
+    categories: &[SportCategory],
+    data: &NewWorkout,
+    parent_id: Option<i64>,
+) -> Result<i64> {
+    let workout = &data.workout;
+    conn.execute(
+        "INSERT INTO workouts (
+            file_hash, filename, name, workout_type, start_time, end_time,
+            duration_seconds, distance_meters, total_calories,
+            avg_heart_rate, max_heart_rate, avg_power_watts, max_power_watts,
+            avg_cadence, max_cadence, avg_speed_mps, max_speed_mps,
+            elevation_gain_meters, elevation_loss_meters,
+            pool_length_meters, parent_id, session_index, tss,
+            normalized_power_watts, intensity_factor, variability_index, work_kj, tss_source,
+            timer_seconds, moving_seconds, avg_moving_speed_mps,
+            device_manufacturer, device_product, device_serial_number, file_created_at,
+            sub_sport, category
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            workout.file_hash,
+            workout.filename,
+            workout.name,
+            workout.workout_type,
+            workout.start_time,
+            workout.end_time,
+            workout.duration_seconds,
+            workout.distance_meters,
+            workout.total_calories,
+            workout.avg_heart_rate,
+            workout.max_heart_rate,
+            workout.avg_power_watts,
+            workout.max_power_watts,
+            workout.avg_cadence,
+            workout.max_cadence,
+            workout.avg_speed_mps,
+            workout.max_speed_mps,
+            workout.elevation_gain_meters,
+            workout.elevation_loss_meters,
+            workout.pool_length_meters,
+            parent_id,
+            workout.session_index,
+            workout.tss,
+            workout.normalized_power_watts,
+            workout.intensity_factor,
+            workout.variability_index,
+            workout.work_kj,
+            workout.tss_source,
+            workout.timer_seconds,
+            workout.moving_seconds,
+            workout.avg_moving_speed_mps,
+            workout.device_manufacturer,
+            workout.device_product,
+            workout.device_serial_number,
+            workout.file_created_at,
+            workout.sub_sport,
+            categorize(categories, workout.workout_type.as_deref(), workout.sub_sport.as_deref()),
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    insert_sample_rows(conn, id, &data.samples)?;
+    insert_developer_rows(conn, id, data.developer_fields, &data.samples)?;
+    store_channel_averages(conn, id)?;
+    store_power_curve(conn, id, &data.samples)?;
+    store_best_efforts(conn, id, &data.samples)?;
+    store_time_in_zones(conn, id, &data.samples)?;
+    insert_lap_rows(conn, id, data.laps)?;
+    insert_length_rows(conn, id, data.lengths)?;
+    if !data.rr_intervals_ms.is_empty() {
+        store_rr_interval_rows(conn, id, data.rr_intervals_ms)?;
+    }
+    if !data.devices.is_empty() {
+        store_device_rows(conn, id, data.devices)?;
+    }
+    if let (Some(source), Some(file_hash)) = (&data.source, &workout.file_hash) {
+        store_source_row(conn, file_hash, &workout.filename, source.format, source.data)?;
+    }
+    for leg in &data.legs {
+        insert_workout_rows(conn, categories, leg, Some(id))?;
+    }
+    Ok(id)
+}
+
+/// Replaces the parsed columns of a stored workout and everything stored
+/// alongside it. Name, notes, tags and the parent link are left untouched.
+/// Legs are matched by session index so names and notes given to them are
+/// kept as well; new legs are added and those no longer in the file removed.
+fn update_workout_rows(conn: &Connection, categories: &[SportCategory], id: i64, data: &NewWorkout) -> Result<()> {
+    let workout = &data.workout;
+    conn.execute(
+        "UPDATE workouts SET
+            file_hash = ?, filename = ?, workout_type = ?, start_time = ?, end_time = ?,
+            duration_seconds = ?, distance_meters = ?, total_calories = ?,
+            avg_heart_rate = ?, max_heart_rate = ?, avg_power_watts = ?, max_power_watts = ?,
+            avg_cadence = ?, max_cadence = ?, avg_speed_mps = ?, max_speed_mps = ?,
+            elevation_gain_meters = ?, elevation_loss_meters = ?, pool_length_meters = ?,
+            tss = ?, normalized_power_watts = ?, intensity_factor = ?, variability_index = ?,
+            work_kj = ?, tss_source = ?, timer_seconds = ?, moving_seconds = ?,
+            avg_moving_speed_mps = ?, device_manufacturer = ?, device_product = ?,
+            device_serial_number = ?, file_created_at = ?, sub_sport = ?, category = ?,
+            updated_at = CURRENT_TIMESTAMP
+         WHERE id = ?",
+        params![
+            workout.file_hash,
+            workout.filename,
+            workout.workout_type,
+            workout.start_time,
+            workout.end_time,
+            workout.duration_seconds,
+            workout.distance_meters,
+            workout.total_calories,
+            workout.avg_heart_rate,
+            workout.max_heart_rate,
+            workout.avg_power_watts,
+            workout.max_power_watts,
+            workout.avg_cadence,
+            workout.max_cadence,
+            workout.avg_speed_mps,
+            workout.max_speed_mps,
+            workout.elevation_gain_meters,
+            workout.elevation_loss_meters,
+            workout.pool_length_meters,
+            workout.tss,
+            workout.normalized_power_watts,
+            workout.intensity_factor,
+            workout.variability_index,
+            workout.work_kj,
+            workout.tss_source,
+            workout.timer_seconds,
+            workout.moving_seconds,
+            workout.avg_moving_speed_mps,
+            workout.device_manufacturer,
+            workout.device_product,
+            workout.device_serial_number,
+            workout.file_created_at,
+            workout.sub_sport,
+            categorize(categories, workout.workout_type.as_deref(), workout.sub_sport.as_deref()),
+            id,
+        ],
+    )?;
+    conn.execute("DELETE FROM samples WHERE workout_id = ?", params![id])?;
+    conn.execute("DELETE FROM developer_fields WHERE workout_id = ?", params![id])?;
+    conn.execute("DELETE FROM developer_samples WHERE workout_id = ?", params![id])?;
+    insert_sample_rows(conn, id, &data.samples)?;
+    insert_developer_rows(conn, id, data.developer_fields, &data.samples)?;
+    store_channel_averages(conn, id)?;
+    store_power_curve(conn, id, &data.samples)?;
+    store_best_efforts(conn, id, &data.samples)?;
+    store_time_in_zones(conn, id, &data.samples)?;
+    conn.execute("DELETE FROM laps WHERE workout_id = ?", params![id])?;
+    insert_lap_rows(conn, id, data.laps)?;
+    conn.execute("DELETE FROM swim_lengths WHERE workout_id = ?", params![id])?;
+    insert_length_rows(conn, id, data.lengths)?;
+    store_rr_interval_rows(conn, id, data.rr_intervals_ms)?;
+    store_device_rows(conn, id, data.devices)?;
+
+    let mut stmt = conn.prepare("SELECT session_index, id FROM workouts WHERE parent_id = ?")?;
+    let mut existing_legs = stmt
+        .query_map(params![id], |row| Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, i64>(1)?)))?
+        .collect::<Result<Vec<_>>>()?;
+    for leg in &data.legs {
+        match existing_legs.iter().position(|(index, _)| *index == leg.workout.session_index) {
+            Some(pos) => {
+                let (_, leg_id) = existing_legs.remove(pos);
+                update_workout_rows(conn, categories, leg_id, leg)?;
+            }
+            None => {
+                insert_workout_rows(conn, categories, leg, Some(id))?;
+            }
+        }
+    }
+    for (_, leg_id) in existing_legs {
+        conn.execute("DELETE FROM workouts WHERE id = ?", params![leg_id])?;
+    }
+    Ok(())
+}
+
+/// Replaces the workout's R-R intervals with `rr_ms` after artifact
+/// correction, along with its HRV summary
+fn store_rr_interval_rows(conn: &Connection, workout_id: i64, rr_ms: &[f64]) -> Result<()> {
+    let intervals = correct_rr_intervals(rr_ms);
+    let analysis = analyze_hrv(&intervals, DEFAULT_DFA_WINDOW_SECONDS, DEFAULT_DFA_STEP_SECONDS);
+
+    conn.execute("DELETE FROM rr_intervals WHERE workout_id = ?", params![workout_id])?;
+    let mut stmt = conn.prepare(
+        "INSERT INTO rr_intervals (workout_id, beat_index, elapsed_s, rr_ms, corrected_ms, artifact)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )?;
+    for (i, interval) in intervals.iter().enumerate() {
+        stmt.execute(params![
+            workout_id,
+            i as i64,
+            interval.elapsed_s,
+            interval.rr_ms,
+            interval.corrected_ms,
+            interval.artifact,
+        ])?;
+    }
+    conn.execute(
+        "UPDATE workouts SET hrv_rmssd_ms = ?, hrv_sdnn_ms = ?, dfa_aerobic_threshold_hr = ?,
+         dfa_anaerobic_threshold_hr = ? WHERE id = ?",
+        params![
+            analysis.rmssd_ms,
+            analysis.sdnn_ms,
+            analysis.aerobic_threshold_hr,
+            analysis.anaerobic_threshold_hr,
+            workout_id,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Replaces the devices the workout's own file lists. Those of its other
+/// recordings are kept: their device indexes carry the recording's file
+/// hash, e.g. `hash:creator`.
+fn store_device_rows(conn: &Connection, workout_id: i64, devices: &[DeviceInfo]) -> Result<()> {
+    conn.execute(
+        "DELETE FROM workout_devices WHERE workout_id = ? AND instr(device_index, ':') = 0",
+        params![workout_id],
+    )?;
+    insert_device_rows(conn, workout_id, devices)
+}
+
+fn insert_device_rows(conn: &Connection, workout_id: i64, devices: &[DeviceInfo]) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "INSERT OR REPLACE INTO workout_devices (
+            workout_id, device_index, device_type, manufacturer, product, product_name,
+            serial_number, software_version, battery_status, battery_voltage, source_type
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )?;
+    for device in devices {
+        stmt.execute(params![
+            workout_id,
+            device.device_index,
+            device.device_type,
+            device.manufacturer,
+            device.product,
+            device.product_name,
+            device.serial_number,
+            device.software_version,
+            device.battery_status,
+            device.battery_voltage,
+            device.source_type,
+        ])?;
+    }
+    Ok(())
+}
+
+/// The best stored power for each duration over the workouts matching
+/// `conditions`, grouped by calendar year when `by_season` is set. SQLite
+/// takes the bare workout columns from the row holding the MAX.
+fn query_power_curve(
+    conn: &Connection,
+    conditions: &[String],
+    params_vec: &[Box<dyn rusqlite::ToSql>],
+    by_season: bool,
+) -> Result<Vec<(String, PowerCurvePoint)>> {
+    let group = if by_season { "strftime('%Y', w.start_time)" } else { "''" };
+    let sql = format!(
+        "SELECT {group} AS season, pc.duration_seconds, MAX(pc.watts), w.id, w.start_time
+         FROM power_curves pc JOIN workouts w ON w.id = pc.workout_id
+         WHERE {}
+         GROUP BY season, pc.duration_seconds
+         ORDER BY season, pc.duration_seconds",
+        conditions.join(" AND ")
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+    let rows = stmt.query_map(params_refs.as_slice(), |row| {
+        Ok((
+            row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+            PowerCurvePoint {
+                duration_seconds: row.get(1)?,
+                watts: row.get(2)?,
+                workout_id: row.get(3)?,
+                start_time: row.get(4)?,
+            },
+        ))
+    })?;
+    rows.collect()
+}
+
+fn compress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    GzDecoder::new(data)
+        .read_to_end(&mut out)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Blob, Box::new(e)))?;
+    Ok(out)
+}
+
+/// Keeps an original file compressed, replacing one stored under the same hash
+fn store_source_row(conn: &Connection, file_hash: &str, filename: &str, format: &str, data: &[u8]) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO source_files (file_hash, filename, format, original_size, data)
+         VALUES (?, ?, ?, ?, ?)",
+        params![file_hash, filename, format, data.len() as i64, compress(data)?],
+    )?;
+    Ok(())
+}
+
+/// Whether the file is stored, as a workout or as another recording of one
+fn workout_exists(conn: &Connection, file_hash: &str) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT (SELECT COUNT(*) FROM workouts WHERE file_hash = ?1)
+              + (SELECT COUNT(*) FROM workout_recordings WHERE file_hash = ?1)",
+        params![file_hash],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// A stored workout of the same sport that is the same activity as one
+/// starting at `start_time`: one recorded on another device at the same
+/// time (a watch and a bike computer), or the same recording exported
+/// again. It has to start within two minutes and overlap at least half of
+/// the shorter of the two.
+fn find_same_activity(
+    conn: &Connection,
+    sport: Option<&str>,
+    start_time: &str,
+    duration_seconds: Option<i64>,
+) -> Result<Option<Workout>> {
+    let sql = format!(
+        "SELECT {}, (julianday(start_time) - julianday(?1)) * 86400 AS start_offset FROM workouts
+         WHERE parent_id IS NULL AND workout_type IS ?2
+           AND ABS((julianday(start_time) - julianday(?1)) * 86400) <= ?3
+         ORDER BY ABS(start_offset)",
+        WORKOUT_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params![start_time, sport, SAME_ACTIVITY_START_SECONDS], |row| {
+        Ok((workout_from_row(row)?, row.get::<_, f64>(61)?))
+    })?;
+    for row in rows {
+        let (workout, offset) = row?;
+        let (Some(new), Some(old)) = (duration_seconds, workout.duration_seconds) else {
+            return Ok(Some(workout));
+        };
+        let (new, old) = (new as f64, old as f64);
+        let overlap = (new.min(offset + old) - offset.max(0.0)).max(0.0);
+        if overlap >= SAME_ACTIVITY_MIN_OVERLAP * new.min(old) {
+            return Ok(Some(workout));
+        }
+    }
+    Ok(None)
+}
+
+/// Keeps `source` as another recording of the workout and adds the devices
+/// the workout doesn't list yet, e.g. a heart rate strap both devices used
+/// is listed once
+fn link_recording_rows(
+    conn: &Connection,
+    workout_id: i64,
+    file_hash: &str,
+    filename: &str,
+    devices: &[DeviceInfo],
+    source: &NewSourceFile,
+) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO workout_recordings (file_hash, workout_id, filename) VALUES (?, ?, ?)",
+        params![file_hash, workout_id, filename],
+    )?;
+    store_source_row(conn, file_hash, filename, source.format, source.data)?;
+
+    let mut listed = conn.prepare(
+        "SELECT COUNT(*) FROM workout_devices WHERE workout_id = ? AND product IS ? AND serial_number = ?",
+    )?;
+    let mut added = Vec::new();
+    for device in devices {
+        let count: i64 = listed.query_row(params![workout_id, device.product, device.serial_number], |row| row.get(0))?;
+        if count == 0 {
+            added.push(DeviceInfo {
+                device_index: format!("{}:{}", file_hash, device.device_index),
+                ..device.clone()
+            });
+        }
+    }
+    insert_device_rows(conn, workout_id, &added)
+}
+
+/// How records progressed, from entries arriving oldest first and grouped
+/// by `key`, e.g. the sport: every entry that `beats` all earlier ones of
+/// its group set a new record. Each group comes with its best entry and its
+/// history of records, oldest first, ending with the best.
+fn record_histories<K: PartialEq, E: Clone>(
+    rows: impl Iterator<Item = Result<(K, E)>>,
+    beats: impl Fn(&E, &E) -> bool,
+) -> Result<Vec<(K, E, Vec<E>)>> {
+    let mut histories: Vec<(K, E, Vec<E>)> = Vec::new();
+    for row in rows {
+        let (key, entry) = row?;
+        match histories.last_mut() {
+            Some((group, best, history)) if *group == key => {
+                if beats(&entry, best) {
+                    *best = entry.clone();
+                    history.push(entry);
+                }
+            }
+            _ => histories.push((key, entry.clone(), vec![entry])),
+        }
+    }
+    Ok(histories)
+}
+
+/// Records a workout or its legs broke against every other workout of
+/// the same sport. A sport's first workout doesn't count as a record.
+fn new_records(conn: &Connection, workout_id: i64) -> Result<Vec<NewRecord>> {
+    let mut new_records = Vec::new();
+    let record_from_row = |metric: &str, row: &rusqlite::Row| -> Result<NewRecord> {
+        Ok(NewRecord {
+            workout_id: row.get(0)?,
+            sport: row.get(1)?,
+            metric: metric.to_string(),
+            value: row.get(2)?,
+            previous: RecordEntry {
+                workout_id: row.get(3)?,
+                workout_name: row.get(4)?,
+                start_time: row.get(5)?,
+                value: row.get(6)?,
+            },
+        })
+    };
+
+    for &(metric, column) in RECORD_METRICS {
+        let sql = format!(
+            "SELECT w.id, w.workout_type, w.{column}, o.id, o.name, o.start_time, o.{column}
+             FROM workouts w
+             JOIN workouts o ON o.id = (
+                 SELECT p.id FROM workouts p
+                 WHERE p.workout_type IS w.workout_type AND p.id != w.id AND p.{column} > 0
+                 ORDER BY p.{column} DESC, p.start_time LIMIT 1
+             )
+             WHERE (w.id = ?1 OR w.parent_id = ?1) AND w.{column} > o.{column}"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![workout_id], |row| record_from_row(metric, row))?;
+        for row in rows {
+            new_records.push(row?);
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT w.id, w.workout_type, be.elapsed_seconds, o.id, o.name, o.start_time, ob.elapsed_seconds,
+                be.distance
+         FROM best_efforts be
+         JOIN workouts w ON w.id = be.workout_id
+         JOIN best_efforts ob ON ob.distance = be.distance AND ob.workout_id = (
+             SELECT p.workout_id FROM best_efforts p JOIN workouts pw ON pw.id = p.workout_id
+             WHERE p.distance = be.distance AND pw.workout_type IS w.workout_type AND p.workout_id != w.id
+             ORDER BY p.elapsed_seconds, pw.start_time LIMIT 1
+         )
+         JOIN workouts o ON o.id = ob.workout_id
+         WHERE (w.id = ?1 OR w.parent_id = ?1) AND be.elapsed_seconds < ob.elapsed_seconds
+         ORDER BY be.distance_meters",
+    )?;
+    let rows = stmt.query_map(params![workout_id], |row| {
+        let distance: String = row.get(7)?;
+        record_from_row(&distance, row)
+    })?;
+    for row in rows {
+        new_records.push(row?);
+    }
+
+    Ok(new_records)
+}
+
+/// Where the training load has to be refreshed from after a batch of
+/// imports or reprocesses: the earliest start time any of them touched
+#[derive(Debug, Default)]
+pub struct TrainingLoadRefresh {
+    from: Option<String>,
+}
+
+impl TrainingLoadRefresh {
+    fn include(&mut self, start_time: Option<&str>) {
+        if let Some(start) = start_time {
+            if self.from.as_deref().map_or(true, |from| start < from) {
+                self.from = Some(start.to_string());
+            }
+        }
     }
+}
+
+/// One file imported, or one workout reprocessed, in a transaction of its
+/// own. The training load is left for the end of the batch.
+pub struct Import<'a> {
+    tx: Transaction<'a>,
+    categories: Vec<SportCategory>,
+    refresh: TrainingLoadRefresh,
+}
 
+impl Import<'_> {
     pub fn workout_exists(&self, file_hash: &str) -> Result<bool> {
+        workout_exists(&self.tx, file_hash)
+    }
+
+    pub fn find_same_activity(
+        &self,
+        sport: Option<&str>,
+        start_time: &str,
+        duration_seconds: Option<i64>,
+    ) -> Result<Option<Workout>> {
+        find_same_activity(&self.tx, sport, start_time, duration_seconds)
+    }
+
+    /// Keeps a file of an activity that is already stored as another
+    /// recording of its workout
+    pub fn link_recording(
+        &self,
+        workout_id: i64,
+        file_hash: &str,
+        filename: &str,
+        devices: &[DeviceInfo],
+        source: &NewSourceFile,
+    ) -> Result<()> {
+        link_recording_rows(&self.tx, workout_id, file_hash, filename, devices, source)
+    }
+
+    pub fn athlete_settings(&self, date: Option<&str>) -> Result<AthleteSettings> {
+        athlete_settings_on(&self.tx, date)
+    }
+
+    /// Inserts a workout and its legs with everything stored alongside them
+    pub fn insert_workout(&mut self, workout: &NewWorkout) -> Result<i64> {
+        let id = insert_workout_rows(&self.tx, &self.categories, workout, None)?;
+        self.refresh.include(workout.workout.start_time.as_deref());
+        Ok(id)
+    }
+
+    /// Replaces a stored workout and its legs with a fresh parse of its file
+    pub fn update_workout(&mut self, id: i64, workout: &NewWorkout) -> Result<()> {
+        // Refresh from whichever day is earlier in case the start time moved
+        let old_start: Option<String> = self.tx
+            .query_row("SELECT start_time FROM workouts WHERE id = ?", params![id], |row| row.get(0))
+            .ok()
+            .flatten();
+        update_workout_rows(&self.tx, &self.categories, id, workout)?;
+        self.refresh.include(old_start.as_deref());
+        self.refresh.include(workout.workout.start_time.as_deref());
+        Ok(())
+    }
+
+    pub fn store_source_file(&self, file_hash: &str, filename: &str, format: &str, data: &[u8]) -> Result<()> {
+        store_source_row(&self.tx, file_hash, filename, format, data)
+    }
+
+    pub fn new_records(&self, workout_id: i64) -> Result<Vec<NewRecord>> {
+        new_records(&self.tx, workout_id)
+    }
+}
+
+impl Database {
+    pub fn new(db_path: &Path) -> Result<Self> {
+        let mut conn = Connection::open(db_path)?;
+        
+        // Enable WAL mode for better concurrency
+        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")?;
+
+        // Child tables rely on ON DELETE CASCADE
+        conn.execute_batch("PRAGMA foreign_keys=ON;")?;
+
+        migrations::migrate(&mut conn, db_path)?;
+
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// Runs one import in a transaction, so a failed file leaves nothing
+    /// behind and those before it stored. Where the training load has to be
+    /// refreshed from is added to `refresh`, for `refresh_training_load`
+    /// once the batch is done.
+    pub fn import<T>(&self, refresh: &mut TrainingLoadRefresh, run: impl FnOnce(&mut Import) -> Result<T>) -> Result<T> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let categories = load_sport_categories(&tx)?;
+        let mut import = Import { tx, categories, refresh: TrainingLoadRefresh::default() };
+        let result = run(&mut import)?;
+        import.tx.commit()?;
+        refresh.include(import.refresh.from.as_deref());
+        Ok(result)
+    }
+
+    /// Rewrites the training load from the earliest workout a batch of
+    /// imports or reprocesses touched
+    pub fn refresh_training_load(&self, refresh: TrainingLoadRefresh) -> Result<()> {
+        let Some(from) = refresh.from else { return Ok(()) };
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        refresh_training_load(&tx, Some(&from))?;
+        tx.commit()
+    }
+
+    pub fn get_workout_laps(&self, workout_id: i64) -> Result<Vec<Lap>> {
         let conn = self.conn.lock().unwrap();
-        let count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM workouts WHERE file_hash = ?",
-            params![file_hash],
-            |row| row.get(0),
+        let mut stmt = conn.prepare(
+            "SELECT id, workout_id, lap_index, start_time, end_time, duration_seconds, distance_meters,
+                    total_calories, avg_heart_rate, max_heart_rate, avg_power_watts, max_power_watts,
+                    avg_cadence, max_cadence, avg_speed_mps, max_speed_mps,
+                    elevation_gain_meters, elevation_loss_meters, lap_trigger,
+                    swim_stroke, total_strokes, num_lengths, num_active_lengths
+             FROM laps WHERE workout_id = ? ORDER BY lap_index"
         )?;
-        Ok(count > 0)
+
+        let rows = stmt.query_map(params![workout_id], |row| {
+            Ok(Lap {
+                id: row.get(0)?,
+                workout_id: row.get(1)?,
+                lap_index: row.get(2)?,
+                start_time: row.get(3)?,
+                end_time: row.get(4)?,
+                duration_seconds: row.get(5)?,
+                distance_meters: row.get(6)?,
+                total_calories: row.get(7)?,
+                avg_heart_rate: row.get(8)?,
+                max_heart_rate: row.get(9)?,
+                avg_power_watts: row.get(10)?,
+                max_power_watts: row.get(11)?,
+                avg_cadence: row.get(12)?,
+                max_cadence: row.get(13)?,
+                avg_speed_mps: row.get(14)?,
+                max_speed_mps: row.get(15)?,
+                elevation_gain_meters: row.get(16)?,
+                elevation_loss_meters: row.get(17)?,
+                lap_trigger: row.get(18)?,
+                swim_stroke: row.get(19)?,
+                total_strokes: row.get(20)?,
+                num_lengths: row.get(21)?,
+                num_active_lengths: row.get(22)?,
+            })
+        })?;
+
+        let mut laps = Vec::new();
+        for row in rows {
+            laps.push(row?);
+        }
+        Ok(laps)
     }
 
-    pub fn insert_workout(&self, workout: &InsertWorkout) -> Result<i64> {
+    pub fn get_swim_lengths(&self, workout_id: i64) -> Result<Vec<SwimLength>> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "INSERT INTO workouts (
-                file_hash, filename, name, workout_type, start_time, end_time,
-                duration_seconds, distance_meters, total_calories,
-                avg_heart_rate, max_heart_rate, avg_power_watts, max_power_watts,
-                avg_cadence, max_cadence, avg_speed_mps, max_speed_mps,
-                elevation_gain_meters, elevation_loss_meters,
-                gps_data, sensor_data, chart_data
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-            params![
-                workout.file_hash,
-                workout.filename,
-                workout.name,
-                workout.workout_type,
-                workout.start_time,
-                workout.end_time,
-                workout.duration_seconds,
-                workout.distance_meters,
-                workout.total_calories,
-                workout.avg_heart_rate,
-                workout.max_heart_rate,
-                workout.avg_power_watts,
-                workout.max_power_watts,
-                workout.avg_cadence,
-                workout.max_cadence,
-                workout.avg_speed_mps,
-                workout.max_speed_mps,
-                workout.elevation_gain_meters,
-                workout.elevation_loss_meters,
-                workout.gps_data,
-                workout.sensor_data,
-                workout.chart_data,
-            ],
+        let mut stmt = conn.prepare(
+            "SELECT length_index, start_time, duration_seconds, length_type, swim_stroke,
+                    total_strokes, avg_speed_mps, avg_swimming_cadence
+             FROM swim_lengths WHERE workout_id = ? ORDER BY length_index"
         )?;
-        Ok(conn.last_insert_rowid())
+
+        let rows = stmt.query_map(params![workout_id], |row| {
+            Ok(SwimLength {
+                length_index: row.get(0)?,
+                start_time: row.get(1)?,
+                duration_seconds: row.get(2)?,
+                length_type: row.get(3)?,
+                swim_stroke: row.get(4)?,
+                total_strokes: row.get(5)?,
+                avg_speed_mps: row.get(6)?,
+                avg_swimming_cadence: row.get(7)?,
+            })
+        })?;
+
+        let mut lengths = Vec::new();
+        for row in rows {
+            lengths.push(row?);
+        }
+        Ok(lengths)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -214,10 +1824,17 @@ impl Database {
         max_distance: Option<f64>,
         min_duration: Option<i64>,
         max_duration: Option<i64>,
+        duration_basis: Option<&str>,
+        device: Option<&str>,
+        category: Option<&str>,
+        sub_sport: Option<&str>,
+        sort_by: Option<&str>,
+        sort_order: Option<&str>,
     ) -> Result<Vec<WorkoutSummary>> {
         let conn = self.conn.lock().unwrap();
         
-        let mut conditions = vec!["1=1".to_string()];
+        // Multisport legs are returned through their parent
+        let mut conditions = vec!["parent_id IS NULL".to_string()];
         let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
         
         if let Some(wt) = workout_type {
@@ -226,18 +1843,18 @@ impl Database {
         }
         
         if let Some(t) = tag {
-            conditions.push("tags LIKE ?".to_string());
-            params_vec.push(Box::new(format!("%\"{}%", t)));
+            conditions.push(
+                "id IN (SELECT wt.workout_id FROM workout_tags wt JOIN tags t ON t.id = wt.tag_id WHERE t.name = ?)"
+                    .to_string(),
+            );
+            params_vec.push(Box::new(t.to_string()));
         }
         
-        if let Some(s) = search {
-            if !s.is_empty() {
-                conditions.push("(name LIKE ? OR notes LIKE ? OR tags LIKE ?)".to_string());
-                let pattern = format!("%{}%", s);
-                params_vec.push(Box::new(pattern.clone()));
-                params_vec.push(Box::new(pattern.clone()));
-                params_vec.push(Box::new(pattern));
-            }
+        // Full-text matches are ranked and carry a highlighted snippet. The
+        // query is bound first as the CTE comes first in the statement.
+        let fts = search.and_then(fts_query);
+        if let Some(q) = &fts {
+            params_vec.insert(0, Box::new(q.clone()));
         }
         
         if let Some(ds) = date_start {
@@ -260,41 +1877,75 @@ impl Database {
             params_vec.push(Box::new(max_d));
         }
         
+        let duration = duration_column(duration_basis);
         if let Some(min_dur) = min_duration {
-            conditions.push("duration_seconds >= ?".to_string());
+            conditions.push(format!("{} >= ?", duration));
             params_vec.push(Box::new(min_dur));
         }
         
         if let Some(max_dur) = max_duration {
-            conditions.push("duration_seconds <= ?".to_string());
+            conditions.push(format!("{} <= ?", duration));
             params_vec.push(Box::new(max_dur));
         }
         
+        if let Some(d) = device {
+            conditions.push(DEVICE_CONDITION.to_string());
+            params_vec.push(Box::new(d.to_string()));
+            params_vec.push(Box::new(d.to_string()));
+        }
+        
+        if let Some(c) = category {
+            conditions.push("category = ?".to_string());
+            params_vec.push(Box::new(c.to_string()));
+        }
+        
+        match sub_sport {
+            Some(GENERIC_SUB_SPORT) => conditions.push("sub_sport IS NULL".to_string()),
+            Some(ss) => {
+                conditions.push("sub_sport = ?".to_string());
+                params_vec.push(Box::new(ss.to_string()));
+            }
+            None => {}
+        }
+        
         params_vec.push(Box::new(limit));
         params_vec.push(Box::new(offset));
         
-        let sql = format!(
-            "SELECT id, name, notes, workout_type, start_time, duration_seconds, distance_meters, total_calories, avg_heart_rate, tags 
-             FROM workouts WHERE {} ORDER BY start_time DESC LIMIT ? OFFSET ?",
-            conditions.join(" AND ")
-        );
+        // Search results are ranked by relevance unless a sort is chosen
+        let order = workout_order(sort_by, sort_order);
+        let sql = if fts.is_some() {
+            format!(
+                "WITH matches AS (
+                    SELECT rowid AS workout_id,
+                           bm25(workouts_fts, 10.0, 1.0, 5.0) AS rank,
+                           snippet(workouts_fts, -1, '{}', '{}', '…', 12) AS snippet
+                    FROM workouts_fts WHERE workouts_fts MATCH ?
+                )
+                SELECT {}, matches.snippet FROM workouts
+                JOIN matches ON matches.workout_id = workouts.id
+                WHERE {} ORDER BY {} LIMIT ? OFFSET ?",
+                HIGHLIGHT_START,
+                HIGHLIGHT_END,
+                SUMMARY_COLUMNS,
+                conditions.join(" AND "),
+                if sort_by.is_some() { order } else { "matches.rank, start_time DESC".to_string() }
+            )
+        } else {
+            format!(
+                "SELECT {}, NULL FROM workouts WHERE {} ORDER BY {} LIMIT ? OFFSET ?",
+                SUMMARY_COLUMNS,
+                conditions.join(" AND "),
+                order
+            )
+        };
 
         let mut stmt = conn.prepare(&sql)?;
         let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
         
         let rows = stmt.query_map(params_refs.as_slice(), |row| {
-            Ok(WorkoutSummary {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                notes: row.get(2)?,
-                workout_type: row.get(3)?,
-                start_time: row.get(4)?,
-                duration_seconds: row.get(5)?,
-                distance_meters: row.get(6)?,
-                total_calories: row.get(7)?,
-                avg_heart_rate: row.get(8)?,
-                tags: row.get(9)?,
-            })
+            let mut summary = summary_from_row(row)?;
+            summary.search_snippet = row.get(14)?;
+            Ok(summary)
         })?;
 
         let mut workouts = Vec::new();
@@ -306,117 +1957,272 @@ impl Database {
     }
 
     pub fn get_workout(&self, id: i64) -> Result<Option<Workout>> {
+        let conn = self.conn.lock().unwrap();
+        let sql = format!("SELECT {} FROM workouts WHERE id = ?", WORKOUT_COLUMNS);
+        let mut stmt = conn.prepare(&sql)?;
+        
+        let mut rows = stmt.query(params![id])?;
+        if let Some(row) = rows.next()? {
+            let mut workout = workout_from_row(row)?;
+            workout.legs = query_legs(&conn, workout.id)?;
+            Ok(Some(workout))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Samples of a workout in recording order, optionally limited to an
+    /// elapsed-time range in seconds
+    pub fn get_samples(&self, workout_id: i64, start_s: Option<f64>, end_s: Option<f64>) -> Result<Vec<Sample>> {
+        let conn = self.conn.lock().unwrap();
+        load_samples(&conn, workout_id, start_s, end_s)
+    }
+
+    pub fn get_developer_fields(&self, workout_id: i64) -> Result<Vec<DeveloperField>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT id, file_hash, filename, name, notes, tags, workout_type, start_time, end_time,
-                    duration_seconds, distance_meters, total_calories,
-                    avg_heart_rate, max_heart_rate, avg_power_watts, max_power_watts,
-                    avg_cadence, max_cadence, avg_speed_mps, max_speed_mps,
-                    elevation_gain_meters, elevation_loss_meters, created_at, updated_at
-             FROM workouts WHERE id = ?"
+            "SELECT key, name, units, app_id FROM developer_fields WHERE workout_id = ? ORDER BY field_index",
         )?;
+        let rows = stmt.query_map(params![workout_id], |row| {
+            Ok(DeveloperField {
+                key: row.get(0)?,
+                name: row.get(1)?,
+                units: row.get(2)?,
+                app_id: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Developer values of the samples `get_samples` returns for the same
+    /// range, one list per sample in the same order
+    pub fn get_developer_values(
+        &self,
+        workout_id: i64,
+        start_s: Option<f64>,
+        end_s: Option<f64>,
+    ) -> Result<Vec<Vec<DeveloperValue>>> {
+        let conn = self.conn.lock().unwrap();
+        let mut sql = String::from(
+            "SELECT s.sample_index, d.key, d.value FROM samples s
+             LEFT JOIN developer_samples d ON d.workout_id = s.workout_id AND d.sample_index = s.sample_index
+             WHERE s.workout_id = ?",
+        );
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(workout_id)];
+        if let Some(start) = start_s {
+            sql.push_str(" AND s.elapsed_s >= ?");
+            params_vec.push(Box::new(start));
+        }
+        if let Some(end) = end_s {
+            sql.push_str(" AND s.elapsed_s <= ?");
+            params_vec.push(Box::new(end));
+        }
+        sql.push_str(" ORDER BY s.sample_index");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+        let rows = stmt.query_map(params_refs.as_slice(), |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, Option<f64>>(2)?))
+        })?;
+
+        let mut values: Vec<Vec<DeveloperValue>> = Vec::new();
+        let mut last_index = None;
+        for row in rows {
+            let (index, key, value) = row?;
+            if last_index != Some(index) {
+                values.push(Vec::new());
+                last_index = Some(index);
+            }
+            if let (Some(key), Some(value), Some(current)) = (key, value, values.last_mut()) {
+                current.push(DeveloperValue { key, value });
+            }
+        }
+        Ok(values)
+    }
+
+    pub fn get_rr_intervals(&self, workout_id: i64) -> Result<Vec<RrInterval>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT elapsed_s, rr_ms, corrected_ms, artifact FROM rr_intervals
+             WHERE workout_id = ? ORDER BY beat_index",
+        )?;
+        let rows = stmt.query_map(params![workout_id], |row| {
+            Ok(RrInterval {
+                elapsed_s: row.get(0)?,
+                rr_ms: row.get(1)?,
+                corrected_ms: row.get(2)?,
+                artifact: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// HRV summaries of the workouts in `range` that have R-R intervals,
+    /// oldest first
+    pub fn get_hrv_history(&self, range: &DateRange) -> Result<Vec<HrvSummary>> {
+        let conn = self.conn.lock().unwrap();
+        let mut sql = String::from(
+            "SELECT id, start_time, workout_type, hrv_rmssd_ms, hrv_sdnn_ms, dfa_aerobic_threshold_hr,
+                    dfa_anaerobic_threshold_hr
+             FROM workouts WHERE hrv_rmssd_ms IS NOT NULL",
+        );
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        if let Some(start) = &range.start {
+            sql.push_str(" AND DATE(start_time) >= ?");
+            params_vec.push(Box::new(start.clone()));
+        }
+        if let Some(end) = &range.end {
+            sql.push_str(" AND DATE(start_time) <= ?");
+            params_vec.push(Box::new(end.clone()));
+        }
+        sql.push_str(" ORDER BY start_time");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+        let rows = stmt.query_map(params_refs.as_slice(), |row| {
+            Ok(HrvSummary {
+                workout_id: row.get(0)?,
+                start_time: row.get(1)?,
+                workout_type: row.get(2)?,
+                rmssd_ms: row.get(3)?,
+                sdnn_ms: row.get(4)?,
+                aerobic_threshold_hr: row.get(5)?,
+                anaerobic_threshold_hr: row.get(6)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// The workout's devices, the recording device first, then those only
+    /// its other recordings list
+    pub fn get_workout_devices(&self, workout_id: i64) -> Result<Vec<DeviceInfo>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT device_index, device_type, manufacturer, product, product_name, serial_number,
+                    software_version, battery_status, battery_voltage, source_type
+             FROM workout_devices WHERE workout_id = ?
+             ORDER BY instr(device_index, ':') > 0, device_index NOT LIKE '%creator',
+                      CAST(substr(device_index, instr(device_index, ':') + 1) AS INTEGER)",
+        )?;
+        let rows = stmt.query_map(params![workout_id], |row| {
+            Ok(DeviceInfo {
+                device_index: row.get(0)?,
+                device_type: row.get(1)?,
+                manufacturer: row.get(2)?,
+                product: row.get(3)?,
+                product_name: row.get(4)?,
+                serial_number: row.get(5)?,
+                software_version: row.get(6)?,
+                battery_status: row.get(7)?,
+                battery_voltage: row.get(8)?,
+                source_type: row.get(9)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Every identified device, by product and serial number, with the
+    /// workouts it took part in. Sensors built into the recording device are
+    /// left out. Recording devices come first, then the most recently used.
+    pub fn get_devices(&self) -> Result<Vec<DeviceSummary>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT MAX(d.device_type), MAX(d.manufacturer), d.product, MAX(d.product_name), d.serial_number,
+                    MAX(d.device_index LIKE '%creator'), COUNT(DISTINCT d.workout_id), MAX(w.start_time)
+             FROM workout_devices d JOIN workouts w ON w.id = d.workout_id
+             WHERE (d.product IS NOT NULL OR d.serial_number IS NOT NULL)
+               AND (d.device_index LIKE '%creator' OR COALESCE(d.source_type, '') != 'local')
+             GROUP BY d.product, d.serial_number
+             ORDER BY MAX(d.device_index LIKE '%creator') DESC, MAX(w.start_time) DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(DeviceSummary {
+                device_type: row.get(0)?,
+                manufacturer: row.get(1)?,
+                product: row.get(2)?,
+                product_name: row.get(3)?,
+                serial_number: row.get(4)?,
+                recorder: row.get(5)?,
+                workout_count: row.get(6)?,
+                last_used: row.get(7)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn get_workout_by_date(&self, date: &str) -> Result<Option<Workout>> {
+        let conn = self.conn.lock().unwrap();
+        let sql = format!(
+            "SELECT {} FROM workouts WHERE DATE(start_time) = ? AND parent_id IS NULL ORDER BY start_time ASC LIMIT 1",
+            WORKOUT_COLUMNS
+        );
+        let mut stmt = conn.prepare(&sql)?;
         
-        let mut rows = stmt.query(params![id])?;
+        let mut rows = stmt.query(params![date])?;
         if let Some(row) = rows.next()? {
-            Ok(Some(Workout {
-                id: row.get(0)?,
-                file_hash: row.get(1)?,
-                filename: row.get(2)?,
-                name: row.get(3)?,
-                notes: row.get(4)?,
-                tags: row.get(5)?,
-                workout_type: row.get(6)?,
-                start_time: row.get(7)?,
-                end_time: row.get(8)?,
-                duration_seconds: row.get(9)?,
-                distance_meters: row.get(10)?,
-                total_calories: row.get(11)?,
-                avg_heart_rate: row.get(12)?,
-                max_heart_rate: row.get(13)?,
-                avg_power_watts: row.get(14)?,
-                max_power_watts: row.get(15)?,
-                avg_cadence: row.get(16)?,
-                max_cadence: row.get(17)?,
-                avg_speed_mps: row.get(18)?,
-                max_speed_mps: row.get(19)?,
-                elevation_gain_meters: row.get(20)?,
-                elevation_loss_meters: row.get(21)?,
-                created_at: row.get(22)?,
-                updated_at: row.get(23)?,
-            }))
+            let mut workout = workout_from_row(row)?;
+            workout.legs = query_legs(&conn, workout.id)?;
+            Ok(Some(workout))
         } else {
             Ok(None)
         }
     }
 
-    pub fn get_workout_chart_data(&self, id: i64) -> Result<Option<String>> {
-        let conn = self.conn.lock().unwrap();
-        let result: Option<String> = conn.query_row(
-            "SELECT chart_data FROM workouts WHERE id = ?",
+    pub fn delete_workout(&self, id: i64) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let start_time: Option<String> = tx
+            .query_row("SELECT start_time FROM workouts WHERE id = ?", params![id], |row| row.get(0))
+            .ok()
+            .flatten();
+        tx.execute(
+            "DELETE FROM source_files WHERE file_hash = (SELECT file_hash FROM workouts WHERE id = ?1)
+                OR file_hash IN (SELECT file_hash FROM workout_recordings WHERE workout_id = ?1)",
             params![id],
-            |row| row.get(0),
-        ).ok();
-        Ok(result)
+        )?;
+        let affected = tx.execute("DELETE FROM workouts WHERE id = ?", params![id])?;
+        if affected > 0 {
+            refresh_training_load(&tx, start_time.as_deref())?;
+        }
+        tx.commit()?;
+        Ok(affected > 0)
     }
 
-    pub fn get_workout_gps_data(&self, id: i64) -> Result<Option<String>> {
+    pub fn store_source_file(&self, file_hash: &str, filename: &str, format: &str, data: &[u8]) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        let result: Option<String> = conn.query_row(
-            "SELECT gps_data FROM workouts WHERE id = ?",
-            params![id],
-            |row| row.get(0),
-        ).ok();
-        Ok(result)
+        store_source_row(&conn, file_hash, filename, format, data)
     }
 
-    pub fn get_workout_by_date(&self, date: &str) -> Result<Option<Workout>> {
+    pub fn get_source_file(&self, file_hash: &str) -> Result<Option<SourceFile>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, file_hash, filename, name, notes, tags, workout_type, start_time, end_time,
-                    duration_seconds, distance_meters, total_calories,
-                    avg_heart_rate, max_heart_rate, avg_power_watts, max_power_watts,
-                    avg_cadence, max_cadence, avg_speed_mps, max_speed_mps,
-                    elevation_gain_meters, elevation_loss_meters, created_at, updated_at
-             FROM workouts WHERE DATE(start_time) = ? ORDER BY start_time ASC LIMIT 1"
-        )?;
-        
-        let mut rows = stmt.query(params![date])?;
-        if let Some(row) = rows.next()? {
-            Ok(Some(Workout {
-                id: row.get(0)?,
-                file_hash: row.get(1)?,
-                filename: row.get(2)?,
-                name: row.get(3)?,
-                notes: row.get(4)?,
-                tags: row.get(5)?,
-                workout_type: row.get(6)?,
-                start_time: row.get(7)?,
-                end_time: row.get(8)?,
-                duration_seconds: row.get(9)?,
-                distance_meters: row.get(10)?,
-                total_calories: row.get(11)?,
-                avg_heart_rate: row.get(12)?,
-                max_heart_rate: row.get(13)?,
-                avg_power_watts: row.get(14)?,
-                max_power_watts: row.get(15)?,
-                avg_cadence: row.get(16)?,
-                max_cadence: row.get(17)?,
-                avg_speed_mps: row.get(18)?,
-                max_speed_mps: row.get(19)?,
-                elevation_gain_meters: row.get(20)?,
-                elevation_loss_meters: row.get(21)?,
-                created_at: row.get(22)?,
-                updated_at: row.get(23)?,
-            }))
-        } else {
-            Ok(None)
+        let row = conn.query_row(
+            "SELECT file_hash, filename, format, data FROM source_files WHERE file_hash = ?",
+            params![file_hash],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, Vec<u8>>(3)?)),
+        ).ok();
+
+        match row {
+            Some((file_hash, filename, format, data)) => Ok(Some(SourceFile {
+                file_hash,
+                filename,
+                format,
+                data: decompress(&data)?,
+            })),
+            None => Ok(None),
         }
     }
 
-    pub fn delete_workout(&self, id: i64) -> Result<bool> {
+    /// Top-level workouts whose original file is stored
+    pub fn get_reprocessable_workout_ids(&self) -> Result<Vec<i64>> {
         let conn = self.conn.lock().unwrap();
-        let affected = conn.execute("DELETE FROM workouts WHERE id = ?", params![id])?;
-        Ok(affected > 0)
+        let mut stmt = conn.prepare(
+            "SELECT w.id FROM workouts w
+             JOIN source_files s ON s.file_hash = w.file_hash
+             WHERE w.parent_id IS NULL
+             ORDER BY w.start_time"
+        )?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
     }
 
     pub fn rename_workout(&self, id: i64, name: &str) -> Result<bool> {
@@ -428,12 +2234,18 @@ impl Database {
         Ok(affected > 0)
     }
 
-    pub fn update_tags(&self, id: i64, tags: &str) -> Result<bool> {
-        let conn = self.conn.lock().unwrap();
-        let affected = conn.execute(
-            "UPDATE workouts SET tags = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
-            params![tags, id],
+    /// Replaces the tags of a workout, creating tags that don't exist yet
+    pub fn update_tags(&self, id: i64, tags: &[String]) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let affected = tx.execute(
+            "UPDATE workouts SET updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            params![id],
         )?;
+        if affected > 0 {
+            set_workout_tags(&tx, id, tags)?;
+        }
+        tx.commit()?;
         Ok(affected > 0)
     }
 
@@ -454,7 +2266,8 @@ impl Database {
                 COALESCE(SUM(distance_meters), 0) / 1000.0 as total_distance_km,
                 COALESCE(SUM(duration_seconds), 0) / 3600.0 as total_duration_hours,
                 COALESCE(SUM(total_calories), 0) as total_calories
-             FROM workouts",
+             FROM workouts
+             WHERE parent_id IS NULL",
             [],
             |row| {
                 Ok(Stats {
@@ -480,7 +2293,7 @@ impl Database {
                 COALESCE(SUM(duration_seconds), 0) as duration_seconds,
                 COALESCE(SUM(total_calories), 0) as calories
              FROM workouts
-             WHERE start_time >= date('now', 'start of month')",
+             WHERE start_time >= date('now', 'start of month') AND parent_id IS NULL",
             [],
             |row| {
                 Ok(MonthlyStats {
@@ -502,7 +2315,7 @@ impl Database {
         let mut stmt = conn.prepare(
             "SELECT DISTINCT date(start_time) as workout_date
              FROM workouts
-             WHERE start_time IS NOT NULL
+             WHERE start_time IS NOT NULL AND parent_id IS NULL
              ORDER BY workout_date DESC"
         )?;
         
@@ -539,7 +2352,7 @@ impl Database {
         let active_days: i64 = conn.query_row(
             "SELECT COUNT(DISTINCT date(start_time))
              FROM workouts
-             WHERE start_time >= date('now', '-365 days')",
+             WHERE start_time >= date('now', '-365 days') AND parent_id IS NULL",
             [],
             |row| row.get(0),
         )?;
@@ -550,29 +2363,48 @@ impl Database {
         })
     }
 
-    pub fn get_personal_records(&self) -> Result<PersonalRecords> {
+    /// Records per sport and metric with their progression, limited to one
+    /// sport when given. Multisport legs count towards their own sport.
+    pub fn get_personal_records(&self, sport: Option<&str>) -> Result<Vec<PersonalRecord>> {
         let conn = self.conn.lock().unwrap();
-        let records = conn.query_row(
-            "SELECT 
-                COALESCE(MAX(distance_meters), 0) / 1000.0 as max_distance_km,
-                COALESCE(MAX(duration_seconds), 0) / 3600.0 as max_duration_hours,
-                COALESCE(MAX(max_heart_rate), 0) as max_heart_rate,
-                COALESCE(MAX(max_speed_mps), 0) * 3.6 as max_speed_kmh,
-                COALESCE(MAX(elevation_gain_meters), 0) as max_elevation_gain,
-                COALESCE(MAX(total_calories), 0) as max_calories
-             FROM workouts",
-            [],
-            |row| {
-                Ok(PersonalRecords {
-                    max_distance_km: row.get(0)?,
-                    max_duration_hours: row.get(1)?,
-                    max_heart_rate: row.get(2)?,
-                    max_speed_kmh: row.get(3)?,
-                    max_elevation_gain: row.get(4)?,
-                    max_calories: row.get(5)?,
-                })
-            },
-        )?;
+        let mut records: Vec<PersonalRecord> = Vec::new();
+
+        for &(metric, column) in RECORD_METRICS {
+            let mut sql = format!(
+                "SELECT workout_type, id, name, start_time, {column} FROM workouts WHERE {column} > 0"
+            );
+            let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+            if let Some(sport) = sport {
+                sql.push_str(" AND workout_type = ?");
+                params_vec.push(Box::new(sport.to_string()));
+            }
+            sql.push_str(" ORDER BY workout_type, start_time, id");
+
+            let mut stmt = conn.prepare(&sql)?;
+            let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+            let rows = stmt.query_map(params_refs.as_slice(), |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    RecordEntry {
+                        workout_id: row.get(1)?,
+                        workout_name: row.get(2)?,
+                        start_time: row.get(3)?,
+                        value: row.get(4)?,
+                    },
+                ))
+            })?;
+
+            let histories = record_histories(rows, |entry: &RecordEntry, best| entry.value > best.value)?;
+            records.extend(histories.into_iter().map(|(sport, best, history)| PersonalRecord {
+                sport,
+                metric: metric.to_string(),
+                best,
+                history,
+            }));
+        }
+
+        // Group by sport, keeping the metric order within each
+        records.sort_by(|a, b| a.sport.cmp(&b.sport));
         Ok(records)
     }
 
@@ -581,9 +2413,9 @@ impl Database {
         let mut stmt = conn.prepare(
             "SELECT date(start_time) as workout_date, 
                     COUNT(*) as count,
-                    GROUP_CONCAT(workout_type) as types
+                    GROUP_CONCAT(COALESCE(category, workout_type)) as types
              FROM workouts
-             WHERE start_time >= date('now', ? || ' days')
+             WHERE start_time >= date('now', ? || ' days') AND parent_id IS NULL
              GROUP BY workout_date
              ORDER BY workout_date"
         )?;
@@ -613,7 +2445,7 @@ impl Database {
         let mut stmt = conn.prepare(
             "SELECT strftime('%Y-W%W', start_time) as week, COUNT(*) as count
              FROM workouts
-             WHERE start_time >= date('now', ? || ' days')
+             WHERE start_time >= date('now', ? || ' days') AND parent_id IS NULL
              GROUP BY week
              ORDER BY week"
         )?;
@@ -633,46 +2465,483 @@ impl Database {
         Ok(summary)
     }
 
-    pub fn get_activity_breakdown(&self) -> Result<Vec<(String, i64)>> {
+    /// Workout counts per display category, each split by sport and
+    /// sub-sport. Categories and the sports in them come most frequent first.
+    pub fn get_activity_breakdown(&self) -> Result<Vec<CategoryBreakdown>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT COALESCE(workout_type, 'unknown') as type, COUNT(*) as count
+            "SELECT COALESCE(category, workout_type, 'unknown') AS category,
+                    COALESCE(workout_type, 'unknown') AS sport, sub_sport, COUNT(*) AS count
              FROM workouts
-             GROUP BY workout_type
+             WHERE parent_id IS NULL
+             GROUP BY 1, 2, 3
              ORDER BY count DESC"
         )?;
 
         let rows = stmt.query_map([], |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            Ok((row.get::<_, String>(0)?, SportBreakdown {
+                sport: row.get(1)?,
+                sub_sport: row.get(2)?,
+                count: row.get(3)?,
+            }))
         })?;
 
-        let mut breakdown = Vec::new();
+        let mut breakdown: Vec<CategoryBreakdown> = Vec::new();
         for row in rows {
-            breakdown.push(row?);
+            let (category, sport) = row?;
+            match breakdown.iter_mut().find(|c| c.name == category) {
+                Some(entry) => {
+                    entry.count += sport.count;
+                    entry.sports.push(sport);
+                }
+                None => breakdown.push(CategoryBreakdown { name: category, count: sport.count, sports: vec![sport] }),
+            }
         }
+        breakdown.sort_by_key(|c| std::cmp::Reverse(c.count));
         Ok(breakdown)
     }
 
-    pub fn get_all_tags(&self) -> Result<Vec<String>> {
+    pub fn get_sport_categories(&self) -> Result<Vec<SportCategory>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT DISTINCT tags FROM workouts WHERE tags IS NOT NULL AND tags != '[]'")?;
-        
-        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
-        
-        let mut all_tags = std::collections::HashSet::new();
+        load_sport_categories(&conn)
+    }
+
+    /// Saves the sport category mapping and recomputes every workout's
+    /// category when it changed
+    pub fn update_sport_categories(&self, categories: &[SportCategory]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        if load_sport_categories(&tx)? != categories {
+            let json = serde_json::to_string(categories).unwrap_or_default();
+            tx.execute(
+                "INSERT OR REPLACE INTO settings (key, value) VALUES ('sport_categories', ?)",
+                params![json],
+            )?;
+            recalculate_categories(&tx)?;
+        }
+        tx.commit()
+    }
+
+    pub fn get_all_tags(&self) -> Result<Vec<Tag>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.name, t.color, COUNT(wt.workout_id)
+             FROM tags t
+             LEFT JOIN workout_tags wt ON wt.tag_id = t.id
+             GROUP BY t.id
+             ORDER BY t.name",
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(Tag {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+                usage_count: row.get(3)?,
+            })
+        })?;
+
+        let mut tags = Vec::new();
         for row in rows {
-            if let Ok(tags_json) = row {
-                if let Ok(tags) = serde_json::from_str::<Vec<String>>(&tags_json) {
-                    for tag in tags {
-                        all_tags.insert(tag);
-                    }
+            tags.push(row?);
+        }
+        Ok(tags)
+    }
+
+    pub fn set_tag_color(&self, name: &str, color: Option<&str>) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let affected = conn.execute(
+            "UPDATE tags SET color = ? WHERE name = ?",
+            params![color, name],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Renames a tag on every workout. Renaming onto an existing tag merges
+    /// the two.
+    pub fn rename_tag(&self, old_name: &str, new_name: &str) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let Some(tag_id) = find_tag_id(&tx, old_name)? else {
+            return Ok(false);
+        };
+        match find_tag_id(&tx, new_name)? {
+            Some(target_id) if target_id != tag_id => merge_tag_rows(&tx, &[tag_id], target_id)?,
+            _ => {
+                touch_tagged_workouts(&tx, tag_id)?;
+                tx.execute("UPDATE tags SET name = ? WHERE id = ?", params![new_name, tag_id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Moves every workout tagged with one of `sources` over to `target`,
+    /// creating it if needed, and removes the source tags.
+    pub fn merge_tags(&self, sources: &[String], target: &str) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let target_id = ensure_tag(&tx, target)?;
+        let mut source_ids = Vec::new();
+        for source in sources {
+            if let Some(id) = find_tag_id(&tx, source)? {
+                if id != target_id {
+                    source_ids.push(id);
                 }
             }
         }
-        
-        let mut tags: Vec<String> = all_tags.into_iter().collect();
-        tags.sort();
-        Ok(tags)
+        if source_ids.is_empty() {
+            return Ok(false);
+        }
+        merge_tag_rows(&tx, &source_ids, target_id)?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// The athlete values metrics use for a workout starting at `date`, or
+    /// today's without one
+    pub fn get_athlete_settings(&self, date: Option<&str>) -> Result<AthleteSettings> {
+        let conn = self.conn.lock().unwrap();
+        athlete_settings_on(&conn, date)
+    }
+
+    /// The profile entry in effect on `date`, or today without one
+    pub fn get_athlete_profile(&self, date: Option<&str>) -> Result<Option<AthleteProfile>> {
+        let conn = self.conn.lock().unwrap();
+        let profiles = load_athlete_profiles(&conn)?;
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        Ok(profile_on(&profiles, date.unwrap_or(&today)).cloned())
+    }
+
+    pub fn get_athlete_profile_history(&self) -> Result<Vec<AthleteProfile>> {
+        let conn = self.conn.lock().unwrap();
+        load_athlete_profiles(&conn)
+    }
+
+    /// Saves the profile entry for its effective date, replacing one from the
+    /// same day, and recomputes every workout's TSS, time in zones and the
+    /// training load series
+    /// Saves a profile entry and recomputes the metrics that depend on it.
+    /// Returns whether the entry changed.
+    pub fn update_athlete_profile(&self, profile: &AthleteProfile) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let changed = !load_athlete_profiles(&tx)?.contains(profile);
+        if changed {
+            tx.execute(
+                &format!("INSERT OR REPLACE INTO athlete_profile ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)", PROFILE_COLUMNS),
+                params![
+                    profile.effective_date,
+                    profile.weight_kg,
+                    profile.height_cm,
+                    profile.sex,
+                    profile.birth_date,
+                    profile.max_heart_rate,
+                    profile.resting_heart_rate,
+                    profile.threshold_heart_rate,
+                    profile.ftp_watts,
+                    profile.threshold_pace_seconds_per_km,
+                ],
+            )?;
+            recalculate_athlete_metrics(&tx)?;
+        }
+        tx.commit()?;
+        Ok(changed)
+    }
+
+    pub fn delete_athlete_profile(&self, effective_date: &str) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let deleted = tx.execute("DELETE FROM athlete_profile WHERE effective_date = ?", params![effective_date])?;
+        if deleted > 0 {
+            recalculate_athlete_metrics(&tx)?;
+        }
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+
+    pub fn get_hr_zones(&self) -> Result<Option<HrZoneSettings>> {
+        let conn = self.conn.lock().unwrap();
+        load_hr_zones(&conn)
+    }
+
+    /// Saves the heart rate zones and recomputes every workout's time in
+    /// zones when they changed. Returns whether they did.
+    pub fn update_hr_zones(&self, zones: &HrZoneSettings) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let changed = load_hr_zones(&tx)?.as_ref() != Some(zones);
+        if changed {
+            let json = serde_json::to_string(zones).unwrap_or_default();
+            tx.execute(
+                "INSERT OR REPLACE INTO settings (key, value) VALUES ('hr_zones', ?)",
+                params![json],
+            )?;
+            recalculate_zones(&tx)?;
+        }
+        tx.commit()?;
+        Ok(changed)
+    }
+
+    pub fn get_power_zones(&self) -> Result<Vec<Zone>> {
+        let conn = self.conn.lock().unwrap();
+        load_zones(&conn, "power_zones", default_power_zones)
+    }
+
+    pub fn update_power_zones(&self, zones: &[Zone]) -> Result<bool> {
+        self.save_zones("power_zones", zones, default_power_zones)
+    }
+
+    pub fn get_pace_zones(&self) -> Result<Vec<Zone>> {
+        let conn = self.conn.lock().unwrap();
+        load_zones(&conn, "pace_zones", default_pace_zones)
+    }
+
+    pub fn update_pace_zones(&self, zones: &[Zone]) -> Result<bool> {
+        self.save_zones("pace_zones", zones, default_pace_zones)
+    }
+
+    /// Saves power or pace zones and recomputes every workout's time in
+    /// zones when they changed. Returns whether they did.
+    fn save_zones(&self, key: &str, zones: &[Zone], default: fn() -> Vec<Zone>) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let changed = load_zones(&tx, key, default)? != zones;
+        if changed {
+            let json = serde_json::to_string(zones).unwrap_or_default();
+            tx.execute(
+                "INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)",
+                params![key, json],
+            )?;
+            recalculate_zones(&tx)?;
+        }
+        tx.commit()?;
+        Ok(changed)
+    }
+
+    /// The workout's time in each zone of a zone type. Empty when the zones
+    /// can't be worked out, e.g. LTHR zones without an LTHR.
+    pub fn get_workout_zones(&self, workout_id: i64, zone_type: &str) -> Result<Vec<ZoneTime>> {
+        let conn = self.conn.lock().unwrap();
+        let start_time: Option<String> = conn
+            .query_row("SELECT start_time FROM workouts WHERE id = ?", params![workout_id], |row| row.get(0))
+            .ok()
+            .flatten();
+        let athlete = athlete_settings_on(&conn, start_time.as_deref())?;
+        let Some((zones, limits)) = zone_limits(&conn, zone_type, &athlete)? else {
+            return Ok(Vec::new());
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT zone_index, seconds FROM workout_zones WHERE workout_id = ? AND zone_type = ?",
+        )?;
+        let stored: HashMap<i64, f64> = stmt
+            .query_map(params![workout_id, zone_type], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_>>()?;
+
+        Ok(zones.into_iter().zip(limits).enumerate()
+            .map(|(i, (zone, (low, high)))| ZoneTime {
+                zone_index: i as i64,
+                name: zone.name,
+                color: zone.color,
+                low,
+                high,
+                seconds: stored.get(&(i as i64)).copied().unwrap_or(0.0),
+            })
+            .collect())
+    }
+
+    /// Time in each zone of a zone type summed per week or month
+    pub fn get_zone_distribution(&self, zone_type: &str, period: Period, range: &DateRange) -> Result<Vec<ZoneDistribution>> {
+        let conn = self.conn.lock().unwrap();
+        // Multisport legs are counted through their parent
+        let mut conditions = vec!["z.zone_type = ?".to_string(), "w.parent_id IS NULL".to_string()];
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(zone_type.to_string())];
+        if let Some(start) = &range.start {
+            conditions.push("DATE(w.start_time) >= ?".to_string());
+            params_vec.push(Box::new(start.clone()));
+        }
+        if let Some(end) = &range.end {
+            conditions.push("DATE(w.start_time) <= ?".to_string());
+            params_vec.push(Box::new(end.clone()));
+        }
+
+        // Summed per day, then per period, as SQLite has no ISO weeks
+        let sql = format!(
+            "SELECT DATE(w.start_time) AS day, z.zone_index, SUM(z.seconds)
+             FROM workout_zones z JOIN workouts w ON w.id = z.workout_id
+             WHERE {}
+             GROUP BY day, z.zone_index
+             ORDER BY day, z.zone_index",
+            conditions.join(" AND ")
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+        let rows = stmt.query_map(params_refs.as_slice(), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, f64>(2)?))
+        })?;
+
+        let zone_count = zone_limits(&conn, zone_type, &athlete_settings_on(&conn, None)?)?
+            .map(|(zones, _)| zones.len())
+            .unwrap_or(0);
+        let mut distribution: Vec<ZoneDistribution> = Vec::new();
+        for row in rows {
+            let (day, zone_index, seconds) = row?;
+            let Some(day) = parse_date(&day) else { continue };
+            let period = period.of(day);
+            match distribution.last() {
+                Some(last) if last.period == period => {}
+                _ => distribution.push(ZoneDistribution { period, seconds: vec![0.0; zone_count] }),
+            }
+            let totals = &mut distribution.last_mut().unwrap().seconds;
+            let zone_index = zone_index as usize;
+            if zone_index >= totals.len() {
+                totals.resize(zone_index + 1, 0.0);
+            }
+            totals[zone_index] += seconds;
+        }
+        Ok(distribution)
+    }
+
+    /// Daily TSS, CTL, ATL and TSB from the first workout through today
+    pub fn get_training_load(&self, range: &DateRange) -> Result<Vec<TrainingLoadDay>> {
+        let conn = self.conn.lock().unwrap();
+
+        // Carry the series forward over the days since it was last written
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let last: Option<String> = conn.query_row("SELECT MAX(date) FROM training_load", [], |row| row.get(0))?;
+        if let Some(last) = last.filter(|last| *last < today) {
+            refresh_training_load(&conn, Some(&last))?;
+        }
+
+        let mut sql = "SELECT date, tss, ctl, atl, tsb FROM training_load WHERE 1 = 1".to_string();
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        if let Some(start) = &range.start {
+            sql.push_str(" AND date >= ?");
+            params_vec.push(Box::new(start.clone()));
+        }
+        if let Some(end) = &range.end {
+            sql.push_str(" AND date <= ?");
+            params_vec.push(Box::new(end.clone()));
+        }
+        sql.push_str(" ORDER BY date");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+        let rows = stmt.query_map(params_refs.as_slice(), |row| {
+            Ok(TrainingLoadDay {
+                date: row.get(0)?,
+                tss: row.get(1)?,
+                ctl: row.get(2)?,
+                atl: row.get(3)?,
+                tsb: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// All-time, last-90-days and per-season power curves, limited to one
+    /// sport and a date range when given
+    pub fn get_power_curve(&self, sport: Option<&str>, range: &DateRange) -> Result<PowerCurves> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut conditions = vec!["1 = 1".to_string()];
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        if let Some(sport) = sport {
+            conditions.push("w.workout_type = ?".to_string());
+            params_vec.push(Box::new(sport.to_string()));
+        }
+        if let Some(start) = &range.start {
+            conditions.push("DATE(w.start_time) >= ?".to_string());
+            params_vec.push(Box::new(start.clone()));
+        }
+        if let Some(end) = &range.end {
+            conditions.push("DATE(w.start_time) <= ?".to_string());
+            params_vec.push(Box::new(end.clone()));
+        }
+
+        let points = |(_, point): (String, PowerCurvePoint)| point;
+        let all_time = query_power_curve(&conn, &conditions, &params_vec, false)?
+            .into_iter()
+            .map(points)
+            .collect();
+
+        let mut seasons: Vec<SeasonPowerCurve> = Vec::new();
+        for (season, point) in query_power_curve(&conn, &conditions, &params_vec, true)? {
+            match seasons.last_mut() {
+                Some(curve) if curve.season == season => curve.points.push(point),
+                _ => seasons.push(SeasonPowerCurve { season, points: vec![point] }),
+            }
+        }
+
+        let since = (Local::now() - Duration::days(90)).format("%Y-%m-%d").to_string();
+        conditions.push("DATE(w.start_time) >= ?".to_string());
+        params_vec.push(Box::new(since));
+        let last_90_days = query_power_curve(&conn, &conditions, &params_vec, false)?
+            .into_iter()
+            .map(points)
+            .collect();
+
+        Ok(PowerCurves { all_time, last_90_days, seasons })
+    }
+
+    /// Best efforts per sport and distance with their record progression,
+    /// limited to one sport when given
+    pub fn get_best_efforts(&self, sport: Option<&str>) -> Result<Vec<BestEffortRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut sql = "SELECT w.workout_type, be.distance, be.distance_meters, w.id, w.name, w.start_time,
+                    be.elapsed_seconds, be.start_offset_s, be.end_offset_s
+             FROM best_efforts be JOIN workouts w ON w.id = be.workout_id"
+            .to_string();
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        if let Some(sport) = sport {
+            sql.push_str(" WHERE w.workout_type = ?");
+            params_vec.push(Box::new(sport.to_string()));
+        }
+        sql.push_str(" ORDER BY w.workout_type, be.distance_meters, w.start_time, w.id");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+        let rows = stmt.query_map(params_refs.as_slice(), |row| {
+            Ok((
+                (row.get::<_, Option<String>>(0)?, row.get::<_, String>(1)?, row.get::<_, f64>(2)?),
+                BestEffortEntry {
+                    workout_id: row.get(3)?,
+                    workout_name: row.get(4)?,
+                    start_time: row.get(5)?,
+                    elapsed_seconds: row.get(6)?,
+                    start_offset_s: row.get(7)?,
+                    end_offset_s: row.get(8)?,
+                },
+            ))
+        })?;
+
+        let histories = record_histories(rows, |entry: &BestEffortEntry, best| {
+            entry.elapsed_seconds < best.elapsed_seconds
+        })?;
+        Ok(histories
+            .into_iter()
+            .map(|((sport, distance, distance_meters), best, history)| BestEffortRecord {
+                sport,
+                distance,
+                distance_meters,
+                best,
+                history,
+            })
+            .collect())
+    }
+
+    /// Removes a tag from every workout and forgets it
+    pub fn delete_tag(&self, name: &str) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let Some(tag_id) = find_tag_id(&tx, name)? else {
+            return Ok(false);
+        };
+        touch_tagged_workouts(&tx, tag_id)?;
+        tx.execute("DELETE FROM tags WHERE id = ?", params![tag_id])?;
+        tx.commit()?;
+        Ok(true)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -687,10 +2956,15 @@ impl Database {
         max_distance: Option<f64>,
         min_duration: Option<i64>,
         max_duration: Option<i64>,
+        duration_basis: Option<&str>,
+        device: Option<&str>,
+        category: Option<&str>,
+        sub_sport: Option<&str>,
     ) -> Result<i64> {
         let conn = self.conn.lock().unwrap();
         
-        let mut conditions = vec!["1=1".to_string()];
+        // Multisport legs are returned through their parent
+        let mut conditions = vec!["parent_id IS NULL".to_string()];
         let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
         
         if let Some(wt) = workout_type {
@@ -699,18 +2973,16 @@ impl Database {
         }
         
         if let Some(t) = tag {
-            conditions.push("tags LIKE ?".to_string());
-            params_vec.push(Box::new(format!("%\"{}%", t)));
+            conditions.push(
+                "id IN (SELECT wt.workout_id FROM workout_tags wt JOIN tags t ON t.id = wt.tag_id WHERE t.name = ?)"
+                    .to_string(),
+            );
+            params_vec.push(Box::new(t.to_string()));
         }
         
-        if let Some(s) = search {
-            if !s.is_empty() {
-                conditions.push("(name LIKE ? OR notes LIKE ? OR tags LIKE ?)".to_string());
-                let pattern = format!("%{}%", s);
-                params_vec.push(Box::new(pattern.clone()));
-                params_vec.push(Box::new(pattern.clone()));
-                params_vec.push(Box::new(pattern));
-            }
+        if let Some(q) = search.and_then(fts_query) {
+            conditions.push("id IN (SELECT rowid FROM workouts_fts WHERE workouts_fts MATCH ?)".to_string());
+            params_vec.push(Box::new(q));
         }
         
         if let Some(ds) = date_start {
@@ -733,16 +3005,37 @@ impl Database {
             params_vec.push(Box::new(max_d));
         }
         
+        let duration = duration_column(duration_basis);
         if let Some(min_dur) = min_duration {
-            conditions.push("duration_seconds >= ?".to_string());
+            conditions.push(format!("{} >= ?", duration));
             params_vec.push(Box::new(min_dur));
         }
         
         if let Some(max_dur) = max_duration {
-            conditions.push("duration_seconds <= ?".to_string());
+            conditions.push(format!("{} <= ?", duration));
             params_vec.push(Box::new(max_dur));
         }
         
+        if let Some(d) = device {
+            conditions.push(DEVICE_CONDITION.to_string());
+            params_vec.push(Box::new(d.to_string()));
+            params_vec.push(Box::new(d.to_string()));
+        }
+        
+        if let Some(c) = category {
+            conditions.push("category = ?".to_string());
+            params_vec.push(Box::new(c.to_string()));
+        }
+        
+        match sub_sport {
+            Some(GENERIC_SUB_SPORT) => conditions.push("sub_sport IS NULL".to_string()),
+            Some(ss) => {
+                conditions.push("sub_sport = ?".to_string());
+                params_vec.push(Box::new(ss.to_string()));
+            }
+            None => {}
+        }
+        
         let sql = format!(
             "SELECT COUNT(*) FROM workouts WHERE {}",
             conditions.join(" AND ")
@@ -753,9 +3046,30 @@ impl Database {
     }
 }
 
+/// A workout with its legs and the rows stored alongside it
+pub struct NewWorkout<'a> {
+    pub workout: InsertWorkout,
+    pub samples: Vec<Sample>,
+    pub developer_fields: &'a [DeveloperField],
+    pub laps: &'a [LapData],
+    pub lengths: &'a [LengthData],
+    pub rr_intervals_ms: &'a [f64],
+    pub devices: &'a [DeviceInfo],
+    pub legs: Vec<NewWorkout<'a>>,
+    /// The original file, kept for reprocessing; legs share their parent's
+    pub source: Option<NewSourceFile<'a>>,
+}
+
+/// An original activity file to keep alongside the workout it holds
+pub struct NewSourceFile<'a> {
+    pub format: &'a str,
+    pub data: &'a [u8],
+}
+
 #[derive(Debug)]
 pub struct InsertWorkout {
-    pub file_hash: String,
+    /// Unset on multisport legs, which share their parent's file
+    pub file_hash: Option<String>,
     pub filename: String,
     pub name: Option<String>,
     pub workout_type: Option<String>,
@@ -774,7 +3088,240 @@ pub struct InsertWorkout {
     pub max_speed_mps: Option<f64>,
     pub elevation_gain_meters: Option<f64>,
     pub elevation_loss_meters: Option<f64>,
-    pub gps_data: Option<String>,
-    pub sensor_data: Option<String>,
-    pub chart_data: Option<String>,
+    pub pool_length_meters: Option<f64>,
+    pub session_index: Option<i64>,
+    pub tss: Option<f64>,
+    pub normalized_power_watts: Option<f64>,
+    pub intensity_factor: Option<f64>,
+    pub variability_index: Option<f64>,
+    pub work_kj: Option<f64>,
+    pub tss_source: Option<String>,
+    pub timer_seconds: Option<i64>,
+    pub moving_seconds: Option<i64>,
+    pub avg_moving_speed_mps: Option<f64>,
+    pub device_manufacturer: Option<String>,
+    pub device_product: Option<String>,
+    pub device_serial_number: Option<i64>,
+    pub file_created_at: Option<String>,
+    pub sub_sport: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_database() -> Database {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+        migrations::migrate(&mut conn, Path::new(":memory:")).unwrap();
+        Database { conn: Mutex::new(conn) }
+    }
+
+    fn workout(file_hash: &str, sport: &str, start_time: &str, duration_seconds: i64) -> InsertWorkout {
+        InsertWorkout {
+            file_hash: Some(file_hash.to_string()),
+            filename: format!("{}.fit", file_hash),
+            name: None,
+            workout_type: Some(sport.to_string()),
+            start_time: Some(start_time.to_string()),
+            end_time: None,
+            duration_seconds: Some(duration_seconds),
+            distance_meters: None,
+            total_calories: None,
+            avg_heart_rate: None,
+            max_heart_rate: None,
+            avg_power_watts: None,
+            max_power_watts: None,
+            avg_cadence: None,
+            max_cadence: None,
+            avg_speed_mps: None,
+            max_speed_mps: None,
+            elevation_gain_meters: None,
+            elevation_loss_meters: None,
+            pool_length_meters: None,
+            session_index: None,
+            tss: None,
+            normalized_power_watts: None,
+            intensity_factor: None,
+            variability_index: None,
+            work_kj: None,
+            tss_source: None,
+            timer_seconds: None,
+            moving_seconds: None,
+            avg_moving_speed_mps: None,
+            device_manufacturer: None,
+            device_product: None,
+            device_serial_number: None,
+            file_created_at: None,
+            sub_sport: None,
+        }
+    }
+
+    fn device(device_index: &str, product: &str, serial_number: i64) -> DeviceInfo {
+        DeviceInfo {
+            device_index: device_index.to_string(),
+            product: Some(product.to_string()),
+            serial_number: Some(serial_number),
+            ..DeviceInfo::default()
+        }
+    }
+
+    fn insert(db: &Database, workout: InsertWorkout, devices: &[DeviceInfo]) -> i64 {
+        db.import(&mut TrainingLoadRefresh::default(), |import| {
+            import.insert_workout(&NewWorkout {
+                workout,
+                samples: Vec::new(),
+                developer_fields: &[],
+                laps: &[],
+                lengths: &[],
+                rr_intervals_ms: &[],
+                devices,
+                legs: Vec::new(),
+                source: Some(NewSourceFile { format: "fit", data: b"own" }),
+            })
+        })
+        .unwrap()
+    }
+
+    fn with_legs(workout: InsertWorkout, legs: &[(i64, &str)]) -> NewWorkout<'static> {
+        let start_time = workout.start_time.clone().unwrap();
+        let leg = |&(session_index, sport): &(i64, &str)| NewWorkout {
+            workout: InsertWorkout {
+                file_hash: None,
+                session_index: Some(session_index),
+                ..self::workout("", sport, &start_time, 600)
+            },
+            samples: Vec::new(),
+            developer_fields: &[],
+            laps: &[],
+            lengths: &[],
+            rr_intervals_ms: &[],
+            devices: &[],
+            legs: Vec::new(),
+            source: None,
+        };
+        NewWorkout {
+            workout,
+            samples: Vec::new(),
+            developer_fields: &[],
+            laps: &[],
+            lengths: &[],
+            rr_intervals_ms: &[],
+            devices: &[],
+            legs: legs.iter().map(leg).collect(),
+            source: None,
+        }
+    }
+
+    fn same_activity(db: &Database, sport: &str, start_time: &str, duration_seconds: i64) -> Option<i64> {
+        let conn = db.conn.lock().unwrap();
+        find_same_activity(&conn, Some(sport), start_time, Some(duration_seconds))
+            .unwrap()
+            .map(|w| w.id)
+    }
+
+    #[test]
+    fn overlapping_recordings_are_the_same_activity() {
+        let db = memory_database();
+        let id = insert(&db, workout("edge", "cycling", "2025-06-01T08:00:00+00:00", 3600), &[]);
+
+        // A watch started a minute later and stopped before the bike computer
+        assert_eq!(same_activity(&db, "cycling", "2025-06-01T08:01:00+00:00", 3300), Some(id));
+        // Started first, on either side of the two minutes
+        assert_eq!(same_activity(&db, "cycling", "2025-06-01T07:58:30+00:00", 3700), Some(id));
+        assert_eq!(same_activity(&db, "cycling", "2025-06-01T07:57:00+00:00", 3700), None);
+        // Another sport at the same time isn't the same activity
+        assert_eq!(same_activity(&db, "running", "2025-06-01T08:00:00+00:00", 3600), None);
+    }
+
+    #[test]
+    fn back_to_back_workouts_are_not_the_same_activity() {
+        let db = memory_database();
+        let warm_up = insert(&db, workout("warm-up", "running", "2025-06-01T08:00:00+00:00", 90), &[]);
+
+        // Started as the warm-up ended
+        assert_eq!(same_activity(&db, "running", "2025-06-01T08:01:30+00:00", 1800), None);
+        // Overlapping by less than half of the warm-up
+        assert_eq!(same_activity(&db, "running", "2025-06-01T08:01:00+00:00", 1800), None);
+        assert_eq!(same_activity(&db, "running", "2025-06-01T08:00:30+00:00", 1800), Some(warm_up));
+    }
+
+    #[test]
+    fn other_recordings_add_their_devices_and_file() {
+        let db = memory_database();
+        let edge = [device("creator", "edge_1040", 1), device("0", "hrm_pro", 7)];
+        let id = insert(&db, workout("edge", "cycling", "2025-06-01T08:00:00+00:00", 3600), &edge);
+
+        let watch = [device("creator", "fenix7", 2), device("1", "hrm_pro", 7)];
+        db.import(&mut TrainingLoadRefresh::default(), |import| {
+            let source = NewSourceFile { format: "fit", data: b"watch" };
+            import.link_recording(id, "watch", "watch.fit", &watch, &source)?;
+            assert!(import.workout_exists("watch")?);
+            Ok(())
+        })
+        .unwrap();
+
+        // The strap both used is listed once
+        let indexes = |db: &Database| -> Vec<String> {
+            db.get_workout_devices(id).unwrap().into_iter().map(|d| d.device_index).collect()
+        };
+        assert_eq!(indexes(&db), ["creator", "0", "watch:creator"]);
+        assert!(db.get_devices().unwrap().iter().all(|d| d.recorder != (d.product.as_deref() == Some("hrm_pro"))));
+        assert_eq!(db.get_source_file("watch").unwrap().unwrap().data, b"watch");
+
+        // Reprocessing the workout's own file keeps the other recording's devices
+        store_device_rows(&db.conn.lock().unwrap(), id, &edge[..1]).unwrap();
+        assert_eq!(indexes(&db), ["creator", "watch:creator"]);
+
+        assert!(db.delete_workout(id).unwrap());
+        assert!(db.get_source_file("edge").unwrap().is_none());
+        assert!(db.get_source_file("watch").unwrap().is_none());
+        let conn = db.conn.lock().unwrap();
+        assert!(!workout_exists(&conn, "watch").unwrap());
+    }
+
+    #[test]
+    fn reprocessing_replaces_a_workout_and_matches_its_legs() {
+        let db = memory_database();
+        let tri = || workout("tri", "multisport", "2025-06-01T08:00:00+00:00", 7200);
+        let mut refresh = TrainingLoadRefresh::default();
+        let id = db
+            .import(&mut refresh, |import| import.insert_workout(&with_legs(tri(), &[(0, "swimming"), (1, "cycling")])))
+            .unwrap();
+        let legs = db.get_workout(id).unwrap().unwrap().legs;
+        db.rename_workout(legs[0].id, "Lake swim").unwrap();
+
+        // A failure partway leaves the stored workout as it was
+        let failed: Result<()> = db.import(&mut refresh, |import| {
+            import.update_workout(id, &with_legs(tri(), &[(0, "swimming")]))?;
+            Err(rusqlite::Error::InvalidQuery)
+        });
+        assert!(failed.is_err());
+        assert_eq!(db.get_workout(id).unwrap().unwrap().legs.len(), 2);
+
+        // Legs are matched by session index; the file decides which there are
+        db.import(&mut refresh, |import| import.update_workout(id, &with_legs(tri(), &[(0, "swimming"), (2, "running")])))
+            .unwrap();
+        let legs = db.get_workout(id).unwrap().unwrap().legs;
+        let names: Vec<_> = legs.iter().map(|l| (l.workout_type.as_deref(), l.name.as_deref())).collect();
+        assert_eq!(names, [(Some("swimming"), Some("Lake swim")), (Some("running"), None)]);
+        assert_eq!(refresh.from.as_deref(), Some("2025-06-01T08:00:00+00:00"));
+    }
+
+    #[test]
+    fn saving_settings_reports_whether_they_changed() {
+        let db = memory_database();
+        let profile = AthleteProfile {
+            effective_date: "2025-01-01".to_string(),
+            max_heart_rate: Some(185),
+            ..AthleteProfile::default()
+        };
+        assert!(db.update_athlete_profile(&profile).unwrap());
+        assert!(!db.update_athlete_profile(&profile).unwrap());
+
+        let zones = HrZoneSettings::default();
+        assert!(db.update_hr_zones(&zones).unwrap());
+        assert!(!db.update_hr_zones(&zones).unwrap());
+        assert!(!db.update_power_zones(&default_power_zones()).unwrap());
+    }
 }
@@ -0,0 +1,281 @@
+//! Developer (Connect IQ) fields of FIT files. fitparser decodes the profile
+//! fields only and drops developer data, so the messages that carry it are
+//! read here straight from the file.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const RECORD: u16 = 20;
+const FIELD_DESCRIPTION: u16 = 206;
+const DEVELOPER_DATA_ID: u16 = 207;
+
+/// A developer field of the `record` messages, as its `field_description`
+/// message describes it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeveloperField {
+    /// `<developer_data_index>.<field_definition_number>`, unique within a file
+    pub key: String,
+    pub name: String,
+    pub units: Option<String>,
+    /// Application id of the Connect IQ app that wrote the field, as hex
+    pub app_id: Option<String>,
+}
+
+/// The value of a developer field in one record, scale and offset applied
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DeveloperValue {
+    pub key: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct DeveloperData {
+    /// Fields with at least one value, in order of first appearance
+    pub fields: Vec<DeveloperField>,
+    /// Developer values of every `record` message, in file order
+    pub records: Vec<Vec<DeveloperValue>>,
+}
+
+struct Definition {
+    big_endian: bool,
+    global: u16,
+    /// Field number and size of each profile field
+    fields: Vec<(u8, usize)>,
+    /// Developer data index, field number and size of each developer field
+    developer_fields: Vec<(u8, u8, usize)>,
+}
+
+struct Description {
+    name: String,
+    units: Option<String>,
+    base_type: u8,
+    scale: f64,
+    offset: f64,
+}
+
+fn read_uint(bytes: &[u8], big_endian: bool) -> u64 {
+    let fold = |acc: u64, b: &u8| (acc << 8) | *b as u64;
+    if big_endian {
+        bytes.iter().fold(0, fold)
+    } else {
+        bytes.iter().rev().fold(0, fold)
+    }
+}
+
+fn read_string(bytes: &[u8]) -> Option<String> {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    let s = String::from_utf8_lossy(&bytes[..end]).trim().to_string();
+    (!s.is_empty()).then_some(s)
+}
+
+/// A scalar value of a FIT base type, or None when the field holds the
+/// type's invalid value, is an array or isn't a number
+fn decode_number(bytes: &[u8], base_type: u8, big_endian: bool) -> Option<f64> {
+    let (size, invalid) = match base_type & 0x1F {
+        0 | 2 => (1, 0xFF),
+        1 => (1, 0x7F),
+        3 => (2, 0x7FFF),
+        4 => (2, 0xFFFF),
+        5 => (4, 0x7FFF_FFFF),
+        6 | 8 => (4, 0xFFFF_FFFF),
+        9 | 15 => (8, u64::MAX),
+        10 => (1, 0),
+        11 => (2, 0),
+        12 => (4, 0),
+        16 => (8, 0),
+        14 => (8, 0x7FFF_FFFF_FFFF_FFFF),
+        _ => return None,
+    };
+    if bytes.len() != size {
+        return None;
+    }
+    let raw = read_uint(bytes, big_endian);
+    if raw == invalid {
+        return None;
+    }
+    let value = match base_type & 0x1F {
+        1 => raw as u8 as i8 as f64,
+        3 => raw as u16 as i16 as f64,
+        5 => raw as u32 as i32 as f64,
+        14 => raw as i64 as f64,
+        8 => f32::from_bits(raw as u32) as f64,
+        9 => f64::from_bits(raw),
+        _ => raw as f64,
+    };
+    value.is_finite().then_some(value)
+}
+
+/// Reads the developer field descriptions and the developer values of the
+/// `record` messages. Reading stops at the first malformed message, keeping
+/// what was read up to it; chained FIT files are read one after the other.
+pub fn parse_developer_data(data: &[u8]) -> DeveloperData {
+    let mut out = DeveloperData::default();
+    let mut descriptions: HashMap<(u8, u8), Description> = HashMap::new();
+    let mut app_ids: HashMap<u8, String> = HashMap::new();
+
+    let mut pos = 0;
+    while let Some(end) = read_file(data, pos, &mut descriptions, &mut app_ids, &mut out) {
+        pos = end;
+    }
+    out
+}
+
+/// Reads one FIT file starting at `pos` and returns where the next one
+/// would start
+fn read_file(
+    data: &[u8],
+    pos: usize,
+    descriptions: &mut HashMap<(u8, u8), Description>,
+    app_ids: &mut HashMap<u8, String>,
+    out: &mut DeveloperData,
+) -> Option<usize> {
+    let header_size = *data.get(pos)? as usize;
+    let data_size = read_uint(data.get(pos + 4..pos + 8)?, false) as usize;
+    if data.get(pos + 8..pos + 12)? != b".FIT" {
+        return None;
+    }
+    let end = pos + header_size + data_size;
+    let messages = data.get(pos + header_size..end)?;
+
+    let mut definitions: HashMap<u8, Definition> = HashMap::new();
+    let mut p = 0;
+    while p < messages.len() {
+        let header = messages[p];
+        p += 1;
+        if header & 0x80 == 0 && header & 0x40 != 0 {
+            let body = messages.get(p..p + 5)?;
+            let big_endian = body[1] == 1;
+            let global = read_uint(&body[2..4], big_endian) as u16;
+            let count = body[4] as usize;
+            p += 5;
+            let fields = messages.get(p..p + count * 3)?
+                .chunks(3)
+                .map(|f| (f[0], f[1] as usize))
+                .collect();
+            p += count * 3;
+            let mut developer_fields = Vec::new();
+            if header & 0x20 != 0 {
+                let count = *messages.get(p)? as usize;
+                p += 1;
+                developer_fields = messages.get(p..p + count * 3)?
+                    .chunks(3)
+                    .map(|f| (f[2], f[0], f[1] as usize))
+                    .collect();
+                p += count * 3;
+            }
+            definitions.insert(header & 0x0F, Definition { big_endian, global, fields, developer_fields });
+            continue;
+        }
+
+        // Compressed timestamp headers carry the local type in bits 5-6
+        let local = if header & 0x80 != 0 { (header >> 5) & 0x03 } else { header & 0x0F };
+        let definition = definitions.get(&local)?;
+        let mut fields: HashMap<u8, &[u8]> = HashMap::new();
+        for &(number, size) in &definition.fields {
+            fields.insert(number, messages.get(p..p + size)?);
+            p += size;
+        }
+        let mut developer_values = Vec::new();
+        for &(index, number, size) in &definition.developer_fields {
+            let bytes = messages.get(p..p + size)?;
+            p += size;
+            if definition.global != RECORD {
+                continue;
+            }
+            let Some(description) = descriptions.get(&(index, number)) else { continue };
+            let Some(raw) = decode_number(bytes, description.base_type, definition.big_endian) else { continue };
+            let key = format!("{}.{}", index, number);
+            if !out.fields.iter().any(|f| f.key == key) {
+                out.fields.push(DeveloperField {
+                    key: key.clone(),
+                    name: description.name.clone(),
+                    units: description.units.clone(),
+                    app_id: app_ids.get(&index).cloned(),
+                });
+            }
+            developer_values.push(DeveloperValue { key, value: raw / description.scale - description.offset });
+        }
+
+        let uint = |number: u8| fields.get(&number).map(|b| read_uint(b, definition.big_endian));
+        match definition.global {
+            RECORD => out.records.push(developer_values),
+            FIELD_DESCRIPTION => {
+                let (Some(index), Some(number), Some(base_type)) = (uint(0), uint(1), uint(2)) else { continue };
+                let Some(name) = fields.get(&3).and_then(|b| read_string(b)) else { continue };
+                let scale = uint(6).filter(|s| *s != 0 && *s != 0xFF).unwrap_or(1) as f64;
+                let offset = uint(7).filter(|o| *o != 0x7F).map(|o| o as u8 as i8).unwrap_or(0) as f64;
+                descriptions.insert((index as u8, number as u8), Description {
+                    name,
+                    units: fields.get(&8).and_then(|b| read_string(b)),
+                    base_type: base_type as u8,
+                    scale,
+                    offset,
+                });
+            }
+            DEVELOPER_DATA_ID => {
+                let (Some(index), Some(app_id)) = (uint(3), fields.get(&1)) else { continue };
+                if app_id.iter().any(|b| *b != 0xFF) {
+                    let hex = app_id.iter().map(|b| format!("{:02x}", b)).collect();
+                    app_ids.insert(index as u8, hex);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Skip the file CRC
+    Some(end + 2).filter(|next| *next < data.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The FIT SDK's developer data example: three records with a
+    /// `doughnuts_earned` field
+    const DEVELOPER_DATA: &[u8] = include_bytes!("../tests/fixtures/DeveloperData.fit");
+
+    fn values(data: &DeveloperData) -> Vec<f64> {
+        data.records.iter().flatten().map(|v| v.value).collect()
+    }
+
+    #[test]
+    fn reads_record_developer_fields() {
+        let data = parse_developer_data(DEVELOPER_DATA);
+
+        assert_eq!(data.fields.len(), 1);
+        let field = &data.fields[0];
+        assert_eq!(field.key, "0.0");
+        assert_eq!(field.name, "doughnuts_earned");
+        assert_eq!(field.units.as_deref(), Some("doughnuts"));
+        assert_eq!(field.app_id.as_deref(), Some("0101020305080d1522375990e97962db"));
+        assert_eq!(values(&data), vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn reads_chained_files_one_after_the_other() {
+        let chained = [DEVELOPER_DATA, DEVELOPER_DATA].concat();
+        let data = parse_developer_data(&chained);
+
+        assert_eq!(data.fields.len(), 1);
+        assert_eq!(values(&data), vec![1.0, 2.0, 3.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn keeps_what_was_read_before_a_truncation() {
+        let truncated = [DEVELOPER_DATA, &DEVELOPER_DATA[..DEVELOPER_DATA.len() - 10]].concat();
+        assert_eq!(values(&parse_developer_data(&truncated)), vec![1.0, 2.0, 3.0]);
+
+        assert!(parse_developer_data(&[]).records.is_empty());
+    }
+
+    #[test]
+    fn invalid_values_are_none() {
+        assert_eq!(decode_number(&[0xFF], 2, false), None);
+        assert_eq!(decode_number(&[0xFF, 0x7F], 0x83, false), None);
+        assert_eq!(decode_number(&[0x7F, 0xFF], 0x83, true), None);
+        assert_eq!(decode_number(&[0xFE, 0xFF], 0x83, false), Some(-2.0));
+        // Arrays aren't a single number
+        assert_eq!(decode_number(&[1, 2], 2, false), None);
+    }
+}
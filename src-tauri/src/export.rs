@@ -0,0 +1,673 @@
+use crate::database::{Lap, Workout};
+use crate::fit_parser::{sport_number, sub_sport_number};
+use crate::samples::Sample;
+use chrono::{DateTime, Duration, Utc};
+use std::fs;
+use std::path::Path;
+
+/// Seconds between the Unix epoch and the FIT epoch (1989-12-31T00:00:00Z)
+const FIT_EPOCH_OFFSET: i64 = 631_065_600;
+const FIT_PROFILE_VERSION: u16 = 2132;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Gpx,
+    Tcx,
+    Fit,
+}
+
+impl ExportFormat {
+    pub fn parse(format: &str) -> Result<Self, String> {
+        match format.to_lowercase().as_str() {
+            "gpx" => Ok(ExportFormat::Gpx),
+            "tcx" => Ok(ExportFormat::Tcx),
+            "fit" => Ok(ExportFormat::Fit),
+            other => Err(format!("Unsupported export format: {}", other)),
+        }
+    }
+}
+
+/// A stored sample with its timestamp parsed
+#[derive(Debug, Default, Clone)]
+struct ExportPoint {
+    time: Option<DateTime<Utc>>,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    altitude: Option<f64>,
+    heart_rate: Option<i64>,
+    cadence: Option<i64>,
+    power: Option<i64>,
+    speed: Option<f64>,
+    distance: Option<f64>,
+}
+
+fn parse_time(ts: Option<&str>) -> Option<DateTime<Utc>> {
+    ts.and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn export_points(samples: &[Sample]) -> Vec<ExportPoint> {
+    samples.iter()
+        .map(|s| ExportPoint {
+            time: parse_time(s.timestamp.as_deref()),
+            lat: s.lat,
+            lon: s.lon,
+            altitude: s.altitude,
+            heart_rate: s.heart_rate,
+            cadence: s.cadence,
+            power: s.power,
+            speed: s.speed,
+            distance: s.distance,
+        })
+        .collect()
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn workout_title(workout: &Workout) -> String {
+    workout.name.clone()
+        .or_else(|| workout.workout_type.clone())
+        .unwrap_or_else(|| "Workout".to_string())
+}
+
+fn to_gpx(workout: &Workout, points: &[ExportPoint]) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(concat!(
+        "<gpx version=\"1.1\" creator=\"OpenConnectCompanion\" ",
+        "xmlns=\"http://www.topografix.com/GPX/1/1\" ",
+        "xmlns:gpxtpx=\"http://www.garmin.com/xmlschemas/TrackPointExtension/v2\" ",
+        "xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" ",
+        "xsi:schemaLocation=\"http://www.topografix.com/GPX/1/1 http://www.topografix.com/GPX/1/1/gpx.xsd\">\n",
+    ));
+
+    if let Some(start) = parse_time(workout.start_time.as_deref()) {
+        out.push_str(&format!("  <metadata>\n    <time>{}</time>\n  </metadata>\n", format_time(start)));
+    }
+
+    out.push_str("  <trk>\n");
+    out.push_str(&format!("    <name>{}</name>\n", xml_escape(&workout_title(workout))));
+    if let Some(notes) = &workout.notes {
+        out.push_str(&format!("    <desc>{}</desc>\n", xml_escape(notes)));
+    }
+    if let Some(sport) = &workout.workout_type {
+        out.push_str(&format!("    <type>{}</type>\n", xml_escape(sport)));
+    }
+    out.push_str("    <trkseg>\n");
+
+    for point in points {
+        let (Some(lat), Some(lon)) = (point.lat, point.lon) else {
+            continue;
+        };
+        out.push_str(&format!("      <trkpt lat=\"{:.7}\" lon=\"{:.7}\">\n", lat, lon));
+        if let Some(altitude) = point.altitude {
+            out.push_str(&format!("        <ele>{:.1}</ele>\n", altitude));
+        }
+        if let Some(time) = point.time {
+            out.push_str(&format!("        <time>{}</time>\n", format_time(time)));
+        }
+
+        let mut tpx = String::new();
+        if let Some(hr) = point.heart_rate {
+            tpx.push_str(&format!("<gpxtpx:hr>{}</gpxtpx:hr>", hr));
+        }
+        if let Some(cadence) = point.cadence {
+            tpx.push_str(&format!("<gpxtpx:cad>{}</gpxtpx:cad>", cadence));
+        }
+        if let Some(speed) = point.speed {
+            tpx.push_str(&format!("<gpxtpx:speed>{:.3}</gpxtpx:speed>", speed));
+        }
+        if !tpx.is_empty() || point.power.is_some() {
+            out.push_str("        <extensions>");
+            // Power is not part of TrackPointExtension; <power> is what Strava and others read
+            if let Some(power) = point.power {
+                out.push_str(&format!("<power>{}</power>", power));
+            }
+            if !tpx.is_empty() {
+                out.push_str(&format!("<gpxtpx:TrackPointExtension>{}</gpxtpx:TrackPointExtension>", tpx));
+            }
+            out.push_str("</extensions>\n");
+        }
+        out.push_str("      </trkpt>\n");
+    }
+
+    out.push_str("    </trkseg>\n  </trk>\n</gpx>\n");
+    out
+}
+
+fn tcx_sport(workout_type: Option<&str>) -> &'static str {
+    match workout_type {
+        Some("running") | Some("walking") | Some("hiking") => "Running",
+        Some("cycling") => "Biking",
+        _ => "Other",
+    }
+}
+
+/// Lap totals used by the TCX and FIT writers, taken from a stored lap or,
+/// when the workout has none, from the workout itself.
+#[derive(Debug, Default, Clone)]
+struct ExportLap {
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    duration_seconds: Option<f64>,
+    distance_meters: Option<f64>,
+    total_calories: Option<i64>,
+    avg_heart_rate: Option<i64>,
+    max_heart_rate: Option<i64>,
+    avg_power_watts: Option<i64>,
+    max_power_watts: Option<i64>,
+    avg_cadence: Option<i64>,
+    max_cadence: Option<i64>,
+    avg_speed_mps: Option<f64>,
+    max_speed_mps: Option<f64>,
+    elevation_gain_meters: Option<f64>,
+    elevation_loss_meters: Option<f64>,
+    lap_trigger: Option<String>,
+}
+
+fn workout_totals(workout: &Workout) -> ExportLap {
+    ExportLap {
+        start: parse_time(workout.start_time.as_deref()),
+        end: parse_time(workout.end_time.as_deref()),
+        duration_seconds: workout.duration_seconds.map(|d| d as f64),
+        distance_meters: workout.distance_meters,
+        total_calories: workout.total_calories,
+        avg_heart_rate: workout.avg_heart_rate,
+        max_heart_rate: workout.max_heart_rate,
+        avg_power_watts: workout.avg_power_watts,
+        max_power_watts: workout.max_power_watts,
+        avg_cadence: workout.avg_cadence,
+        max_cadence: workout.max_cadence,
+        avg_speed_mps: workout.avg_speed_mps,
+        max_speed_mps: workout.max_speed_mps,
+        elevation_gain_meters: workout.elevation_gain_meters,
+        elevation_loss_meters: workout.elevation_loss_meters,
+        lap_trigger: Some("session_end".to_string()),
+    }
+}
+
+fn export_laps(workout: &Workout, laps: &[Lap]) -> Vec<ExportLap> {
+    if laps.is_empty() {
+        return vec![workout_totals(workout)];
+    }
+
+    laps.iter()
+        .map(|lap| ExportLap {
+            start: parse_time(lap.start_time.as_deref()),
+            end: parse_time(lap.end_time.as_deref()),
+            duration_seconds: lap.duration_seconds,
+            distance_meters: lap.distance_meters,
+            total_calories: lap.total_calories,
+            avg_heart_rate: lap.avg_heart_rate,
+            max_heart_rate: lap.max_heart_rate,
+            avg_power_watts: lap.avg_power_watts,
+            max_power_watts: lap.max_power_watts,
+            avg_cadence: lap.avg_cadence,
+            max_cadence: lap.max_cadence,
+            avg_speed_mps: lap.avg_speed_mps,
+            max_speed_mps: lap.max_speed_mps,
+            elevation_gain_meters: lap.elevation_gain_meters,
+            elevation_loss_meters: lap.elevation_loss_meters,
+            lap_trigger: lap.lap_trigger.clone(),
+        })
+        .collect()
+}
+
+/// Assigns each point to the lap whose time range contains it. Points before
+/// the first lap go to the first lap and points after the last go to the last.
+fn group_points_by_lap<'a>(laps: &[ExportLap], points: &'a [ExportPoint]) -> Vec<Vec<&'a ExportPoint>> {
+    let mut groups: Vec<Vec<&ExportPoint>> = vec![Vec::new(); laps.len()];
+    for point in points {
+        let index = point.time
+            .map(|t| {
+                laps.iter()
+                    .rposition(|lap| lap.start.map(|s| t >= s).unwrap_or(false))
+                    .unwrap_or(0)
+            })
+            .unwrap_or(laps.len() - 1);
+        groups[index].push(point);
+    }
+    groups
+}
+
+fn tcx_trigger(trigger: Option<&str>) -> &'static str {
+    match trigger {
+        Some("distance") => "Distance",
+        Some("time") => "Time",
+        Some("position_start") | Some("position_lap") | Some("position_waypoint") | Some("position_marked") => "Location",
+        Some("heart_rate") => "HeartRate",
+        _ => "Manual",
+    }
+}
+
+fn to_tcx(workout: &Workout, laps: &[ExportLap], points: &[ExportPoint]) -> String {
+    let sport = tcx_sport(workout.workout_type.as_deref());
+    let is_running = sport == "Running";
+    let start = laps.first()
+        .and_then(|l| l.start)
+        .or_else(|| parse_time(workout.start_time.as_deref()))
+        .or_else(|| points.iter().find_map(|p| p.time))
+        .unwrap_or_else(Utc::now);
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(concat!(
+        "<TrainingCenterDatabase ",
+        "xmlns=\"http://www.garmin.com/xmlschemas/TrainingCenterDatabase/v2\" ",
+        "xmlns:ns3=\"http://www.garmin.com/xmlschemas/ActivityExtension/v2\" ",
+        "xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" ",
+        "xsi:schemaLocation=\"http://www.garmin.com/xmlschemas/TrainingCenterDatabase/v2 http://www.garmin.com/xmlschemas/TrainingCenterDatabasev2.xsd\">\n",
+    ));
+    out.push_str("  <Activities>\n");
+    out.push_str(&format!("    <Activity Sport=\"{}\">\n", sport));
+    out.push_str(&format!("      <Id>{}</Id>\n", format_time(start)));
+
+    for (lap, lap_points) in laps.iter().zip(group_points_by_lap(laps, points)) {
+        let lap_start = lap.start.unwrap_or(start);
+        out.push_str(&format!("      <Lap StartTime=\"{}\">\n", format_time(lap_start)));
+        out.push_str(&format!("        <TotalTimeSeconds>{:.1}</TotalTimeSeconds>\n", lap.duration_seconds.unwrap_or(0.0)));
+        out.push_str(&format!("        <DistanceMeters>{:.1}</DistanceMeters>\n", lap.distance_meters.unwrap_or(0.0)));
+        if let Some(max_speed) = lap.max_speed_mps {
+            out.push_str(&format!("        <MaximumSpeed>{:.3}</MaximumSpeed>\n", max_speed));
+        }
+        out.push_str(&format!("        <Calories>{}</Calories>\n", lap.total_calories.unwrap_or(0)));
+        if let Some(hr) = lap.avg_heart_rate {
+            out.push_str(&format!("        <AverageHeartRateBpm><Value>{}</Value></AverageHeartRateBpm>\n", hr));
+        }
+        if let Some(hr) = lap.max_heart_rate {
+            out.push_str(&format!("        <MaximumHeartRateBpm><Value>{}</Value></MaximumHeartRateBpm>\n", hr));
+        }
+        out.push_str("        <Intensity>Active</Intensity>\n");
+        if let (Some(cadence), false) = (lap.avg_cadence, is_running) {
+            out.push_str(&format!("        <Cadence>{}</Cadence>\n", cadence.min(254)));
+        }
+        out.push_str(&format!("        <TriggerMethod>{}</TriggerMethod>\n", tcx_trigger(lap.lap_trigger.as_deref())));
+
+        out.push_str("        <Track>\n");
+        for point in lap_points {
+            let Some(time) = point.time else {
+                continue;
+            };
+            out.push_str("          <Trackpoint>\n");
+            out.push_str(&format!("            <Time>{}</Time>\n", format_time(time)));
+            if let (Some(lat), Some(lon)) = (point.lat, point.lon) {
+                out.push_str(&format!(
+                    "            <Position><LatitudeDegrees>{:.7}</LatitudeDegrees><LongitudeDegrees>{:.7}</LongitudeDegrees></Position>\n",
+                    lat, lon
+                ));
+            }
+            if let Some(altitude) = point.altitude {
+                out.push_str(&format!("            <AltitudeMeters>{:.1}</AltitudeMeters>\n", altitude));
+            }
+            if let Some(distance) = point.distance {
+                out.push_str(&format!("            <DistanceMeters>{:.1}</DistanceMeters>\n", distance));
+            }
+            if let Some(hr) = point.heart_rate {
+                out.push_str(&format!("            <HeartRateBpm><Value>{}</Value></HeartRateBpm>\n", hr));
+            }
+            if let (Some(cadence), false) = (point.cadence, is_running) {
+                out.push_str(&format!("            <Cadence>{}</Cadence>\n", cadence.min(254)));
+            }
+
+            let mut tpx = String::new();
+            if let Some(speed) = point.speed {
+                tpx.push_str(&format!("<ns3:Speed>{:.3}</ns3:Speed>", speed));
+            }
+            if let (Some(cadence), true) = (point.cadence, is_running) {
+                tpx.push_str(&format!("<ns3:RunCadence>{}</ns3:RunCadence>", cadence.min(254)));
+            }
+            if let Some(power) = point.power {
+                tpx.push_str(&format!("<ns3:Watts>{}</ns3:Watts>", power));
+            }
+            if !tpx.is_empty() {
+                out.push_str(&format!("            <Extensions><ns3:TPX>{}</ns3:TPX></Extensions>\n", tpx));
+            }
+            out.push_str("          </Trackpoint>\n");
+        }
+        out.push_str("        </Track>\n");
+
+        let mut lx = String::new();
+        if let Some(speed) = lap.avg_speed_mps {
+            lx.push_str(&format!("<ns3:AvgSpeed>{:.3}</ns3:AvgSpeed>", speed));
+        }
+        if is_running {
+            if let Some(cadence) = lap.avg_cadence {
+                lx.push_str(&format!("<ns3:AvgRunCadence>{}</ns3:AvgRunCadence>", cadence.min(254)));
+            }
+            if let Some(cadence) = lap.max_cadence {
+                lx.push_str(&format!("<ns3:MaxRunCadence>{}</ns3:MaxRunCadence>", cadence.min(254)));
+            }
+        }
+        if let Some(power) = lap.avg_power_watts {
+            lx.push_str(&format!("<ns3:AvgWatts>{}</ns3:AvgWatts>", power));
+        }
+        if let Some(power) = lap.max_power_watts {
+            lx.push_str(&format!("<ns3:MaxWatts>{}</ns3:MaxWatts>", power));
+        }
+        if !lx.is_empty() {
+            out.push_str(&format!("        <Extensions><ns3:LX>{}</ns3:LX></Extensions>\n", lx));
+        }
+        out.push_str("      </Lap>\n");
+    }
+
+    out.push_str("    </Activity>\n  </Activities>\n</TrainingCenterDatabase>\n");
+    out
+}
+
+// FIT encoding
+
+const FIT_CRC_TABLE: [u16; 16] = [
+    0x0000, 0xCC01, 0xD801, 0x1400, 0xF001, 0x3C00, 0x2800, 0xE401,
+    0xA001, 0x6C00, 0x7800, 0xB401, 0x5000, 0x9C01, 0x8801, 0x4400,
+];
+
+fn fit_crc(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for byte in data {
+        let tmp = FIT_CRC_TABLE[(crc & 0xF) as usize];
+        crc = (crc >> 4) & 0x0FFF;
+        crc = crc ^ tmp ^ FIT_CRC_TABLE[(byte & 0xF) as usize];
+        let tmp = FIT_CRC_TABLE[(crc & 0xF) as usize];
+        crc = (crc >> 4) & 0x0FFF;
+        crc = crc ^ tmp ^ FIT_CRC_TABLE[((byte >> 4) & 0xF) as usize];
+    }
+    crc
+}
+
+/// A field value; None is written as the base type's invalid value
+#[derive(Debug, Clone, Copy)]
+enum FitValue {
+    Enum(Option<u8>),
+    U8(Option<u8>),
+    U16(Option<u16>),
+    U32(Option<u32>),
+    S32(Option<i32>),
+}
+
+impl FitValue {
+    /// (size in bytes, base type number)
+    fn base_type(self) -> (u8, u8) {
+        match self {
+            FitValue::Enum(_) => (1, 0x00),
+            FitValue::U8(_) => (1, 0x02),
+            FitValue::U16(_) => (2, 0x84),
+            FitValue::U32(_) => (4, 0x86),
+            FitValue::S32(_) => (4, 0x85),
+        }
+    }
+
+    fn write(self, buf: &mut Vec<u8>) {
+        match self {
+            FitValue::Enum(v) | FitValue::U8(v) => buf.push(v.unwrap_or(0xFF)),
+            FitValue::U16(v) => buf.extend_from_slice(&v.unwrap_or(0xFFFF).to_le_bytes()),
+            FitValue::U32(v) => buf.extend_from_slice(&v.unwrap_or(0xFFFF_FFFF).to_le_bytes()),
+            FitValue::S32(v) => buf.extend_from_slice(&v.unwrap_or(0x7FFF_FFFF).to_le_bytes()),
+        }
+    }
+}
+
+fn fit_u8(value: Option<i64>) -> FitValue {
+    FitValue::U8(value.map(|v| v.clamp(0, 0xFE) as u8))
+}
+
+fn fit_u16(value: Option<f64>, scale: f64, offset: f64) -> FitValue {
+    FitValue::U16(value.map(|v| ((v + offset) * scale).round().clamp(0.0, 65534.0) as u16))
+}
+
+fn fit_u32(value: Option<f64>, scale: f64) -> FitValue {
+    FitValue::U32(value.map(|v| (v * scale).round().clamp(0.0, 4_294_967_294.0) as u32))
+}
+
+fn fit_time(time: Option<DateTime<Utc>>) -> FitValue {
+    FitValue::U32(time.map(|t| (t.timestamp() - FIT_EPOCH_OFFSET).max(0) as u32))
+}
+
+fn fit_semicircles(degrees: Option<f64>) -> FitValue {
+    FitValue::S32(degrees.map(|d| (d * (2f64.powi(31) / 180.0)).round() as i32))
+}
+
+/// Writes FIT messages, emitting a definition the first time each global
+/// message number is used. Every message of a type must use the same fields.
+struct FitWriter {
+    data: Vec<u8>,
+    defined: Vec<u16>,
+}
+
+impl FitWriter {
+    fn new() -> Self {
+        FitWriter { data: Vec::new(), defined: Vec::new() }
+    }
+
+    fn message(&mut self, global: u16, fields: &[(u8, FitValue)]) {
+        let local = match self.defined.iter().position(|g| *g == global) {
+            Some(local) => local as u8,
+            None => {
+                let local = self.defined.len() as u8;
+                self.defined.push(global);
+                self.data.push(0x40 | local);
+                self.data.push(0); // reserved
+                self.data.push(0); // little-endian
+                self.data.extend_from_slice(&global.to_le_bytes());
+                self.data.push(fields.len() as u8);
+                for (number, value) in fields {
+                    let (size, base_type) = value.base_type();
+                    self.data.extend_from_slice(&[*number, size, base_type]);
+                }
+                local
+            }
+        };
+
+        self.data.push(local);
+        for (_, value) in fields {
+            value.write(&mut self.data);
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        let mut file = Vec::with_capacity(self.data.len() + 16);
+        file.push(14);
+        file.push(0x20); // protocol version 2.0
+        file.extend_from_slice(&FIT_PROFILE_VERSION.to_le_bytes());
+        file.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+        file.extend_from_slice(b".FIT");
+        let header_crc = fit_crc(&file);
+        file.extend_from_slice(&header_crc.to_le_bytes());
+        file.extend_from_slice(&self.data);
+        let crc = fit_crc(&file);
+        file.extend_from_slice(&crc.to_le_bytes());
+        file
+    }
+}
+
+fn fit_sport(workout_type: Option<&str>) -> u8 {
+    workout_type.and_then(sport_number).unwrap_or(0)
+}
+
+fn fit_sub_sport(sub_sport: Option<&str>) -> u8 {
+    sub_sport.and_then(sub_sport_number).unwrap_or(0)
+}
+
+fn fit_lap_trigger(trigger: Option<&str>) -> u8 {
+    match trigger {
+        Some("manual") => 0,
+        Some("time") => 1,
+        Some("distance") => 2,
+        Some("position_start") => 3,
+        Some("position_lap") => 4,
+        Some("position_waypoint") => 5,
+        Some("position_marked") => 6,
+        Some("fitness_equipment") => 8,
+        _ => 7,
+    }
+}
+
+// Global message numbers and enum values from the FIT profile
+const MESG_FILE_ID: u16 = 0;
+const MESG_SESSION: u16 = 18;
+const MESG_LAP: u16 = 19;
+const MESG_RECORD: u16 = 20;
+const MESG_EVENT: u16 = 21;
+const MESG_ACTIVITY: u16 = 34;
+const FIELD_TIMESTAMP: u8 = 253;
+const FIELD_MESSAGE_INDEX: u8 = 254;
+const EVENT_TIMER: u8 = 0;
+const EVENT_SESSION: u8 = 8;
+const EVENT_LAP: u8 = 9;
+const EVENT_ACTIVITY: u8 = 26;
+const EVENT_TYPE_START: u8 = 0;
+const EVENT_TYPE_STOP: u8 = 1;
+const EVENT_TYPE_STOP_ALL: u8 = 4;
+
+/// Speed, heart rate, cadence, power and ascent totals. Lap and session lay
+/// these ten fields out contiguously, starting at avg_speed.
+fn totals_fields(lap: &ExportLap, avg_speed_field: u8) -> Vec<(u8, FitValue)> {
+    [
+        fit_u16(lap.avg_speed_mps, 1000.0, 0.0),
+        fit_u16(lap.max_speed_mps, 1000.0, 0.0),
+        fit_u8(lap.avg_heart_rate),
+        fit_u8(lap.max_heart_rate),
+        fit_u8(lap.avg_cadence),
+        fit_u8(lap.max_cadence),
+        fit_u16(lap.avg_power_watts.map(|p| p as f64), 1.0, 0.0),
+        fit_u16(lap.max_power_watts.map(|p| p as f64), 1.0, 0.0),
+        fit_u16(lap.elevation_gain_meters, 1.0, 0.0),
+        fit_u16(lap.elevation_loss_meters, 1.0, 0.0),
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(i, value)| (avg_speed_field + i as u8, value))
+    .collect()
+}
+
+fn lap_end(lap: &ExportLap) -> Option<DateTime<Utc>> {
+    lap.end.or_else(|| {
+        let start = lap.start?;
+        Some(start + Duration::milliseconds((lap.duration_seconds? * 1000.0) as i64))
+    })
+}
+
+fn to_fit(workout: &Workout, laps: &[ExportLap], points: &[ExportPoint]) -> Vec<u8> {
+    let start = laps.first()
+        .and_then(|l| l.start)
+        .or_else(|| parse_time(workout.start_time.as_deref()))
+        .or_else(|| points.iter().find_map(|p| p.time));
+    let end = laps.last()
+        .and_then(lap_end)
+        .or_else(|| parse_time(workout.end_time.as_deref()))
+        .or_else(|| points.iter().rev().find_map(|p| p.time))
+        .or(start);
+
+    let mut writer = FitWriter::new();
+
+    writer.message(MESG_FILE_ID, &[
+        (0, FitValue::Enum(Some(4))), // type: activity
+        (1, FitValue::U16(Some(255))), // manufacturer: development
+        (2, FitValue::U16(Some(0))),
+        (4, fit_time(start)),
+    ]);
+
+    writer.message(MESG_EVENT, &[
+        (FIELD_TIMESTAMP, fit_time(start)),
+        (0, FitValue::Enum(Some(EVENT_TIMER))),
+        (1, FitValue::Enum(Some(EVENT_TYPE_START))),
+    ]);
+
+    for point in points {
+        if point.time.is_none() {
+            continue;
+        }
+        writer.message(MESG_RECORD, &[
+            (FIELD_TIMESTAMP, fit_time(point.time)),
+            (0, fit_semicircles(point.lat)),
+            (1, fit_semicircles(point.lon)),
+            (2, fit_u16(point.altitude, 5.0, 500.0)),
+            (3, fit_u8(point.heart_rate)),
+            (4, fit_u8(point.cadence)),
+            (5, fit_u32(point.distance, 100.0)),
+            (6, fit_u16(point.speed, 1000.0, 0.0)),
+            (7, fit_u16(point.power.map(|p| p as f64), 1.0, 0.0)),
+        ]);
+    }
+
+    writer.message(MESG_EVENT, &[
+        (FIELD_TIMESTAMP, fit_time(end)),
+        (0, FitValue::Enum(Some(EVENT_TIMER))),
+        (1, FitValue::Enum(Some(EVENT_TYPE_STOP_ALL))),
+    ]);
+
+    for (index, lap) in laps.iter().enumerate() {
+        let mut fields = vec![
+            (FIELD_TIMESTAMP, fit_time(lap_end(lap).or(end))),
+            (FIELD_MESSAGE_INDEX, FitValue::U16(Some(index as u16))),
+            (0, FitValue::Enum(Some(EVENT_LAP))),
+            (1, FitValue::Enum(Some(EVENT_TYPE_STOP))),
+            (2, fit_time(lap.start)),
+            (7, fit_u32(lap.duration_seconds, 1000.0)),
+            (8, fit_u32(lap.duration_seconds, 1000.0)),
+            (9, fit_u32(lap.distance_meters, 100.0)),
+            (11, fit_u16(lap.total_calories.map(|c| c as f64), 1.0, 0.0)),
+            (24, FitValue::Enum(Some(fit_lap_trigger(lap.lap_trigger.as_deref())))),
+        ];
+        fields.extend(totals_fields(lap, 13));
+        writer.message(MESG_LAP, &fields);
+    }
+
+    let session = workout_totals(workout);
+    let mut fields = vec![
+        (FIELD_TIMESTAMP, fit_time(end)),
+        (FIELD_MESSAGE_INDEX, FitValue::U16(Some(0))),
+        (0, FitValue::Enum(Some(EVENT_SESSION))),
+        (1, FitValue::Enum(Some(EVENT_TYPE_STOP))),
+        (2, fit_time(start)),
+        (5, FitValue::Enum(Some(fit_sport(workout.workout_type.as_deref())))),
+        (6, FitValue::Enum(Some(fit_sub_sport(workout.sub_sport.as_deref())))),
+        (7, fit_u32(session.duration_seconds, 1000.0)),
+        (8, fit_u32(session.duration_seconds, 1000.0)),
+        (9, fit_u32(session.distance_meters, 100.0)),
+        (11, fit_u16(session.total_calories.map(|c| c as f64), 1.0, 0.0)),
+        (25, FitValue::U16(Some(0))),
+        (26, FitValue::U16(Some(laps.len() as u16))),
+    ];
+    fields.extend(totals_fields(&session, 14));
+    writer.message(MESG_SESSION, &fields);
+
+    writer.message(MESG_ACTIVITY, &[
+        (FIELD_TIMESTAMP, fit_time(end)),
+        (0, fit_u32(session.duration_seconds, 1000.0)),
+        (1, FitValue::U16(Some(1))),
+        (2, FitValue::Enum(Some(0))), // type: manual
+        (3, FitValue::Enum(Some(EVENT_ACTIVITY))),
+        (4, FitValue::Enum(Some(EVENT_TYPE_STOP))),
+    ]);
+
+    writer.finish()
+}
+
+/// Rebuilds an activity file from the stored workout and writes it to `path`
+pub fn export_workout(
+    workout: &Workout,
+    laps: &[Lap],
+    samples: &[Sample],
+    format: ExportFormat,
+    path: &Path,
+) -> Result<(), String> {
+    let points = export_points(samples);
+    let export_laps = export_laps(workout, laps);
+
+    let bytes = match format {
+        ExportFormat::Gpx => to_gpx(workout, &points).into_bytes(),
+        ExportFormat::Tcx => to_tcx(workout, &export_laps, &points).into_bytes(),
+        ExportFormat::Fit => to_fit(workout, &export_laps, &points),
+    };
+
+    fs::write(path, bytes).map_err(|e| format!("Failed to write file: {}", e))
+}
@@ -1,3 +1,5 @@
+use crate::developer_fields::{parse_developer_data, DeveloperField, DeveloperValue};
+use crate::sports::GENERIC_SUB_SPORT;
 use fitparser::{self, FitDataRecord, Value};
 use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
@@ -14,7 +16,7 @@ pub struct GpsPoint {
     pub altitude: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct SensorPoint {
     pub timestamp: Option<String>,
     pub heart_rate: Option<i64>,
@@ -23,6 +25,42 @@ pub struct SensorPoint {
     pub speed: Option<f64>,
     pub distance: Option<f64>,
     pub altitude: Option<f64>,
+    pub temperature: Option<f64>,
+    /// Running dynamics: vertical oscillation in mm, ground contact time in
+    /// ms, ground contact balance as the left foot's %, stride length in m
+    /// and vertical ratio in %
+    pub vertical_oscillation: Option<f64>,
+    pub ground_contact_time: Option<f64>,
+    pub ground_contact_balance: Option<f64>,
+    pub stride_length: Option<f64>,
+    pub vertical_ratio: Option<f64>,
+    /// Fraction of a cycle on top of `cadence`
+    pub fractional_cadence: Option<f64>,
+    /// Cycling dynamics in %: the left leg's share of power, and torque
+    /// effectiveness and pedal smoothness per leg
+    pub left_right_balance: Option<f64>,
+    pub left_torque_effectiveness: Option<f64>,
+    pub right_torque_effectiveness: Option<f64>,
+    pub left_pedal_smoothness: Option<f64>,
+    pub right_pedal_smoothness: Option<f64>,
+    /// Breaths per minute
+    pub respiration_rate: Option<f64>,
+    /// Degrees C, from a core body temperature sensor
+    pub core_temperature: Option<f64>,
+    /// Oxygen saturation in %
+    pub spo2: Option<f64>,
+    /// Values of the file's developer fields
+    #[serde(default)]
+    pub developer: Vec<DeveloperValue>,
+}
+
+/// Chart series of a developer field
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeveloperSeries {
+    pub key: String,
+    pub name: String,
+    pub units: Option<String>,
+    pub values: Vec<Option<f64>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -33,16 +71,129 @@ pub struct ChartData {
     pub cadence: Vec<Option<i64>>,
     pub speed: Vec<Option<f64>>,
     pub altitude: Vec<Option<f64>>,
+    // The channels below are empty when the file doesn't record them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub temperature: Vec<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vertical_oscillation: Vec<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ground_contact_time: Vec<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ground_contact_balance: Vec<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stride_length: Vec<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vertical_ratio: Vec<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fractional_cadence: Vec<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub left_right_balance: Vec<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub left_torque_effectiveness: Vec<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub right_torque_effectiveness: Vec<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub left_pedal_smoothness: Vec<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub right_pedal_smoothness: Vec<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub respiration_rate: Vec<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub core_temperature: Vec<Option<f64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spo2: Vec<Option<f64>>,
+    pub developer: Vec<DeveloperSeries>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LapData {
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub duration_seconds: Option<f64>,
+    pub distance_meters: Option<f64>,
+    pub total_calories: Option<i64>,
+    pub avg_heart_rate: Option<i64>,
+    pub max_heart_rate: Option<i64>,
+    pub avg_power_watts: Option<i64>,
+    pub max_power_watts: Option<i64>,
+    pub avg_cadence: Option<i64>,
+    pub max_cadence: Option<i64>,
+    pub avg_speed_mps: Option<f64>,
+    pub max_speed_mps: Option<f64>,
+    pub elevation_gain_meters: Option<f64>,
+    pub elevation_loss_meters: Option<f64>,
+    pub lap_trigger: Option<String>,
+    pub swim_stroke: Option<String>,
+    pub total_strokes: Option<i64>,
+    pub num_lengths: Option<i64>,
+    pub num_active_lengths: Option<i64>,
+}
+
+/// A single pool length from a FIT `length` message
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LengthData {
+    pub start_time: Option<String>,
+    pub duration_seconds: Option<f64>,
+    /// "active" for a swum length, "idle" for rest at the wall
+    pub length_type: Option<String>,
+    pub swim_stroke: Option<String>,
+    pub total_strokes: Option<i64>,
+    pub avg_speed_mps: Option<f64>,
+    pub avg_swimming_cadence: Option<i64>,
+}
+
+/// Time the recording timer was stopped, from a FIT timer `stop` event to
+/// the next `start`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimerPause {
+    pub start: String,
+    pub end: String,
+}
+
+/// The device that created a file, from its FIT `file_id` message
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FileCreator {
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<i64>,
+    pub time_created: Option<String>,
+}
+
+/// The recording device or a sensor connected to it, from the last FIT
+/// `device_info` message of its device index
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DeviceInfo {
+    /// `"creator"` for the recording device, the sensor's index otherwise
+    pub device_index: String,
+    /// ANT+, Bluetooth or local device type, e.g. `heart_rate`, `bike_power`
+    pub device_type: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub product_name: Option<String>,
+    pub serial_number: Option<i64>,
+    pub software_version: Option<f64>,
+    pub battery_status: Option<String>,
+    pub battery_voltage: Option<f64>,
+    /// How the sensor was connected: `antplus`, `bluetooth_low_energy`, `local`...
+    pub source_type: Option<String>,
+}
+
+/// Index of the recording device in `device_info` messages
+pub const CREATOR_DEVICE_INDEX: &str = "creator";
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ParsedFitData {
     pub file_hash: String,
     pub filename: String,
     pub workout_type: Option<String>,
+    /// FIT sub-sport (trail, treadmill, indoor_cycling...), unset when generic
+    pub sub_sport: Option<String>,
     pub start_time: Option<String>,
     pub end_time: Option<String>,
+    /// Elapsed time from start to finish, pauses included
     pub duration_seconds: Option<i64>,
+    /// Time the recording timer was running
+    pub timer_seconds: Option<i64>,
     pub distance_meters: Option<f64>,
     pub total_calories: Option<i64>,
     pub avg_heart_rate: Option<i64>,
@@ -55,9 +206,49 @@ pub struct ParsedFitData {
     pub max_speed_mps: Option<f64>,
     pub elevation_gain_meters: Option<f64>,
     pub elevation_loss_meters: Option<f64>,
+    pub pool_length_meters: Option<f64>,
     pub gps_data: Vec<GpsPoint>,
     pub sensor_data: Vec<SensorPoint>,
-    pub chart_data: ChartData,
+    pub laps: Vec<LapData>,
+    pub lengths: Vec<LengthData>,
+    pub pauses: Vec<TimerPause>,
+    /// Developer fields with values in the sensor data
+    pub developer_fields: Vec<DeveloperField>,
+    /// Beat-to-beat intervals in ms as recorded, artifacts included. `hrv`
+    /// messages carry no timestamps, so legs of a multisport activity have
+    /// none and the parent keeps them all.
+    pub rr_intervals_ms: Vec<f64>,
+    /// The device that recorded the file. Empty for GPX and TCX files.
+    pub creator: FileCreator,
+    /// The recording device and its sensors, the recording device first.
+    /// Legs of a multisport activity have none; the parent keeps them.
+    pub devices: Vec<DeviceInfo>,
+    /// Child sessions of a multisport activity (swim, T1, bike, T2, run)
+    pub legs: Vec<ParsedFitData>,
+}
+
+/// Summary fields of a single FIT `session` message
+#[derive(Debug, Default, Clone)]
+struct SessionSummary {
+    sport: Option<String>,
+    sub_sport: Option<String>,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+    duration_seconds: Option<i64>,
+    timer_seconds: Option<i64>,
+    distance_meters: Option<f64>,
+    total_calories: Option<i64>,
+    avg_heart_rate: Option<i64>,
+    max_heart_rate: Option<i64>,
+    avg_power: Option<i64>,
+    max_power: Option<i64>,
+    avg_cadence: Option<i64>,
+    max_cadence: Option<i64>,
+    avg_speed: Option<f64>,
+    max_speed: Option<f64>,
+    elevation_gain: Option<f64>,
+    elevation_loss: Option<f64>,
+    pool_length: Option<f64>,
 }
 
 // FIT timestamp epoch is December 31, 1989, 00:00:00 UTC
@@ -87,10 +278,23 @@ fn value_to_i64(value: &Value) -> Option<i64> {
         Value::UInt32(v) => Some(*v as i64),
         Value::SInt64(v) => Some(*v),
         Value::UInt64(v) => Some(*v as i64),
+        Value::UInt8z(v) => Some(*v as i64),
+        Value::UInt16z(v) => Some(*v as i64),
+        Value::UInt32z(v) => Some(*v as i64),
+        Value::UInt64z(v) => Some(*v as i64),
         _ => None,
     }
 }
 
+/// Enum names as fitparser resolves them, or the raw number of values it
+/// has no name for (products newer than its profile, for one)
+fn value_to_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        _ => value_to_i64(value).map(|n| n.to_string()),
+    }
+}
+
 fn value_to_f64(value: &Value) -> Option<f64> {
     match value {
         Value::Float32(v) => Some(*v as f64),
@@ -107,6 +311,14 @@ fn value_to_f64(value: &Value) -> Option<f64> {
     }
 }
 
+/// The left leg's share of a FIT `left_right_balance` in %. The value is
+/// the right leg's share when its top bit is set; otherwise the side is
+/// unknown and the value is skipped.
+fn left_balance_percent(value: &Value) -> Option<f64> {
+    let raw = value_to_i64(value)?;
+    (raw & 0x80 != 0).then_some(100.0 - (raw & 0x7F) as f64)
+}
+
 fn value_to_timestamp(value: &Value) -> Option<DateTime<Utc>> {
     match value {
         // fitparser often returns timestamps as DateTime<Local> in Timestamp variant
@@ -118,62 +330,392 @@ fn value_to_timestamp(value: &Value) -> Option<DateTime<Utc>> {
     }
 }
 
+/// Names of the FIT `sport` enum, for files fitparser leaves as numbers
+const SPORTS: &[(u8, &str)] = &[
+    (0, "generic"),
+    (1, "running"),
+    (2, "cycling"),
+    (3, "transition"),
+    (4, "fitness_equipment"),
+    (5, "swimming"),
+    (6, "basketball"),
+    (7, "soccer"),
+    (8, "tennis"),
+    (9, "american_football"),
+    (10, "training"),
+    (11, "walking"),
+    (12, "cross_country_skiing"),
+    (13, "alpine_skiing"),
+    (14, "snowboarding"),
+    (15, "rowing"),
+    (16, "mountaineering"),
+    (17, "hiking"),
+    (18, "multisport"),
+    (19, "paddling"),
+    (20, "flying"),
+    (21, "e_biking"),
+    (22, "motorcycling"),
+    (23, "boating"),
+    (24, "driving"),
+    (25, "golf"),
+    (26, "hang_gliding"),
+    (27, "horseback_riding"),
+    (28, "hunting"),
+    (29, "fishing"),
+    (30, "inline_skating"),
+    (31, "rock_climbing"),
+    (32, "sailing"),
+    (33, "ice_skating"),
+    (34, "sky_diving"),
+    (35, "snowshoeing"),
+    (36, "snowmobiling"),
+    (37, "stand_up_paddleboarding"),
+    (38, "surfing"),
+    (39, "wakeboarding"),
+    (40, "water_skiing"),
+    (41, "kayaking"),
+    (42, "rafting"),
+    (43, "windsurfing"),
+    (44, "kitesurfing"),
+    (45, "tactical"),
+    (46, "jumpmaster"),
+    (47, "boxing"),
+    (48, "floor_climbing"),
+    (49, "baseball"),
+    (53, "diving"),
+    (62, "hiit"),
+    (64, "racket"),
+    (65, "wheelchair_push_walk"),
+    (66, "wheelchair_push_run"),
+    (67, "meditation"),
+    (69, "disc_golf"),
+    (71, "cricket"),
+    (72, "rugby"),
+    (73, "hockey"),
+    (74, "lacrosse"),
+    (75, "volleyball"),
+    (76, "water_tubing"),
+    (77, "wakesurfing"),
+    (80, "mixed_martial_arts"),
+    (82, "snorkeling"),
+    (83, "dance"),
+    (84, "jump_rope"),
+    (254, "all"),
+];
+
 fn sport_to_string(sport_num: u8) -> String {
-    match sport_num {
-        0 => "generic".to_string(),
-        1 => "running".to_string(),
-        2 => "cycling".to_string(),
-        3 => "transition".to_string(),
-        4 => "fitness_equipment".to_string(),
-        5 => "swimming".to_string(),
-        6 => "basketball".to_string(),
-        7 => "soccer".to_string(),
-        8 => "tennis".to_string(),
-        9 => "american_football".to_string(),
-        10 => "training".to_string(),
-        11 => "walking".to_string(),
-        12 => "cross_country_skiing".to_string(),
-        13 => "alpine_skiing".to_string(),
-        14 => "snowboarding".to_string(),
-        15 => "rowing".to_string(),
-        16 => "mountaineering".to_string(),
-        17 => "hiking".to_string(),
-        18 => "multisport".to_string(),
-        19 => "paddling".to_string(),
-        20 => "strength_training".to_string(),
-        _ => format!("sport_{}", sport_num),
+    SPORTS.iter()
+        .find(|(n, _)| *n == sport_num)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| format!("sport_{}", sport_num))
+}
+
+/// The FIT number of a sport name
+pub(crate) fn sport_number(name: &str) -> Option<u8> {
+    SPORTS.iter().find(|(_, n)| *n == name).map(|(number, _)| *number)
+}
+
+/// Names of the FIT `sub_sport` enum
+const SUB_SPORTS: &[(u8, &str)] = &[
+    (0, "generic"),
+    (1, "treadmill"),
+    (2, "street"),
+    (3, "trail"),
+    (4, "track"),
+    (5, "spin"),
+    (6, "indoor_cycling"),
+    (7, "road"),
+    (8, "mountain"),
+    (9, "downhill"),
+    (10, "recumbent"),
+    (11, "cyclocross"),
+    (12, "hand_cycling"),
+    (13, "track_cycling"),
+    (14, "indoor_rowing"),
+    (15, "elliptical"),
+    (16, "stair_climbing"),
+    (17, "lap_swimming"),
+    (18, "open_water"),
+    (19, "flexibility_training"),
+    (20, "strength_training"),
+    (21, "warm_up"),
+    (22, "match"),
+    (23, "exercise"),
+    (24, "challenge"),
+    (25, "indoor_skiing"),
+    (26, "cardio_training"),
+    (27, "indoor_walking"),
+    (28, "e_bike_fitness"),
+    (29, "bmx"),
+    (30, "casual_walking"),
+    (31, "speed_walking"),
+    (32, "bike_to_run_transition"),
+    (33, "run_to_bike_transition"),
+    (34, "swim_to_bike_transition"),
+    (35, "atv"),
+    (36, "motocross"),
+    (37, "backcountry"),
+    (38, "resort"),
+    (39, "rc_drone"),
+    (40, "wingsuit"),
+    (41, "whitewater"),
+    (42, "skate_skiing"),
+    (43, "yoga"),
+    (44, "pilates"),
+    (45, "indoor_running"),
+    (46, "gravel_cycling"),
+    (47, "e_bike_mountain"),
+    (48, "commuting"),
+    (49, "mixed_surface"),
+    (50, "navigate"),
+    (51, "track_me"),
+    (52, "map"),
+    (53, "single_gas_diving"),
+    (54, "multi_gas_diving"),
+    (55, "gauge_diving"),
+    (56, "apnea_diving"),
+    (57, "apnea_hunting"),
+    (58, "virtual_activity"),
+    (59, "obstacle"),
+    (62, "breathing"),
+    (65, "sail_race"),
+    (67, "ultra"),
+    (68, "indoor_climbing"),
+    (69, "bouldering"),
+    (70, "hiit"),
+    (73, "amrap"),
+    (74, "emom"),
+    (75, "tabata"),
+    (84, "pickleball"),
+    (85, "padel"),
+    (86, "indoor_wheelchair_walk"),
+    (87, "indoor_wheelchair_run"),
+    (88, "indoor_hand_cycling"),
+    (94, "squash"),
+    (95, "badminton"),
+    (96, "racquetball"),
+    (97, "table_tennis"),
+    (110, "fly_canopy"),
+    (111, "fly_paraglide"),
+    (112, "fly_paramotor"),
+    (113, "fly_pressurized"),
+    (114, "fly_navigate"),
+    (115, "fly_timer"),
+    (116, "fly_altimeter"),
+    (117, "fly_wx"),
+    (118, "fly_vfr"),
+    (119, "fly_ifr"),
+    (254, "all"),
+];
+
+fn sub_sport_to_string(sub_sport_num: u8) -> String {
+    SUB_SPORTS.iter()
+        .find(|(n, _)| *n == sub_sport_num)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| format!("sub_sport_{}", sub_sport_num))
+}
+
+/// The FIT number of a sub-sport name
+pub(crate) fn sub_sport_number(name: &str) -> Option<u8> {
+    SUB_SPORTS.iter().find(|(_, n)| *n == name).map(|(number, _)| *number)
+}
+
+fn lap_trigger_to_string(trigger_num: u8) -> String {
+    match trigger_num {
+        0 => "manual".to_string(),
+        1 => "time".to_string(),
+        2 => "distance".to_string(),
+        3 => "position_start".to_string(),
+        4 => "position_lap".to_string(),
+        5 => "position_waypoint".to_string(),
+        6 => "position_marked".to_string(),
+        7 => "session_end".to_string(),
+        8 => "fitness_equipment".to_string(),
+        _ => format!("trigger_{}", trigger_num),
+    }
+}
+
+fn swim_stroke_to_string(stroke_num: u8) -> String {
+    match stroke_num {
+        0 => "freestyle".to_string(),
+        1 => "backstroke".to_string(),
+        2 => "breaststroke".to_string(),
+        3 => "butterfly".to_string(),
+        4 => "drill".to_string(),
+        5 => "mixed".to_string(),
+        6 => "im".to_string(),
+        _ => format!("stroke_{}", stroke_num),
+    }
+}
+
+fn event_to_string(event_num: u8) -> String {
+    match event_num {
+        0 => "timer".to_string(),
+        _ => format!("event_{}", event_num),
+    }
+}
+
+fn event_type_to_string(type_num: u8) -> String {
+    match type_num {
+        0 => "start".to_string(),
+        1 => "stop".to_string(),
+        4 => "stop_all".to_string(),
+        8 => "stop_disable".to_string(),
+        9 => "stop_disable_all".to_string(),
+        _ => format!("event_type_{}", type_num),
+    }
+}
+
+fn length_type_to_string(type_num: u8) -> String {
+    match type_num {
+        0 => "idle".to_string(),
+        1 => "active".to_string(),
+        _ => format!("length_type_{}", type_num),
+    }
+}
+
+/// The record's sub-sport, unless it is the generic one
+fn get_sub_sport(record: &FitDataRecord) -> Option<String> {
+    get_enum_field(record, "sub_sport", sub_sport_to_string).filter(|s| s != GENERIC_SUB_SPORT)
+}
+
+/// Reads an enum field that fitparser returns either by name or as its raw number
+fn get_enum_field(record: &FitDataRecord, field_name: &str, to_string: fn(u8) -> String) -> Option<String> {
+    get_field_value(record, field_name).and_then(|val| match val {
+        Value::String(s) => Some(s.to_lowercase()),
+        Value::UInt8(n) | Value::Enum(n) => Some(to_string(*n)),
+        _ => None,
+    })
+}
+
+/// The first of `field_names` the record has, as text. fitparser names
+/// subfields after what they depend on, e.g. `garmin_product` for Garmin
+/// devices and `product` for others.
+fn get_text_field(record: &FitDataRecord, field_names: &[&str]) -> Option<String> {
+    field_names.iter().find_map(|name| get_field_value(record, name).and_then(value_to_text))
+}
+
+fn parse_file_id(record: &FitDataRecord) -> FileCreator {
+    FileCreator {
+        manufacturer: get_text_field(record, &["manufacturer"]),
+        product: get_text_field(record, &["garmin_product", "favero_product", "product"]),
+        serial_number: get_field_value(record, "serial_number").and_then(value_to_i64),
+        time_created: get_field_value(record, "time_created")
+            .and_then(value_to_timestamp)
+            .map(|ts| ts.to_rfc3339()),
+    }
+}
+
+fn parse_device_info(record: &FitDataRecord) -> Option<DeviceInfo> {
+    Some(DeviceInfo {
+        device_index: get_text_field(record, &["device_index"])?,
+        device_type: get_text_field(record, &["antplus_device_type", "ble_device_type", "local_device_type", "device_type"]),
+        manufacturer: get_text_field(record, &["manufacturer"]),
+        product: get_text_field(record, &["garmin_product", "favero_product", "product"]),
+        product_name: get_text_field(record, &["product_name"]),
+        serial_number: get_field_value(record, "serial_number").and_then(value_to_i64),
+        software_version: get_field_value(record, "software_version").and_then(value_to_f64),
+        battery_status: get_text_field(record, &["battery_status"]),
+        battery_voltage: get_field_value(record, "battery_voltage").and_then(value_to_f64),
+        source_type: get_text_field(record, &["source_type"]),
+    })
+}
+
+fn parse_length(record: &FitDataRecord) -> LengthData {
+    LengthData {
+        start_time: get_field_value(record, "start_time")
+            .and_then(value_to_timestamp)
+            .map(|ts| ts.to_rfc3339()),
+        duration_seconds: get_field_value(record, "total_elapsed_time")
+            .or_else(|| get_field_value(record, "total_timer_time"))
+            .and_then(value_to_f64),
+        length_type: get_enum_field(record, "length_type", length_type_to_string),
+        swim_stroke: get_enum_field(record, "swim_stroke", swim_stroke_to_string),
+        total_strokes: get_field_value(record, "total_strokes").and_then(value_to_i64),
+        avg_speed_mps: get_field_value(record, "avg_speed").and_then(value_to_f64),
+        avg_swimming_cadence: get_field_value(record, "avg_swimming_cadence").and_then(value_to_i64),
+    }
+}
+
+fn parse_lap(record: &FitDataRecord) -> LapData {
+    let start_time = get_field_value(record, "start_time")
+        .and_then(value_to_timestamp)
+        .map(|ts| ts.to_rfc3339());
+    let end_time = get_field_value(record, "timestamp")
+        .and_then(value_to_timestamp)
+        .map(|ts| ts.to_rfc3339());
+
+    // Prefer elapsed time so laps add up to the session duration
+    let duration_seconds = get_field_value(record, "total_elapsed_time")
+        .or_else(|| get_field_value(record, "total_timer_time"))
+        .and_then(value_to_f64);
+
+    let avg_speed_mps = get_field_value(record, "avg_speed")
+        .or_else(|| get_field_value(record, "enhanced_avg_speed"))
+        .and_then(value_to_f64);
+    let max_speed_mps = get_field_value(record, "max_speed")
+        .or_else(|| get_field_value(record, "enhanced_max_speed"))
+        .and_then(value_to_f64);
+
+    let lap_trigger = get_enum_field(record, "lap_trigger", lap_trigger_to_string);
+
+    // Swimming laps report strokes in total_cycles unless fitparser resolved the subfield
+    let total_strokes = get_field_value(record, "total_strokes")
+        .or_else(|| get_field_value(record, "total_cycles"))
+        .and_then(value_to_i64);
+
+    LapData {
+        start_time,
+        end_time,
+        duration_seconds,
+        distance_meters: get_field_value(record, "total_distance").and_then(value_to_f64),
+        total_calories: get_field_value(record, "total_calories").and_then(value_to_i64),
+        avg_heart_rate: get_field_value(record, "avg_heart_rate").and_then(value_to_i64),
+        max_heart_rate: get_field_value(record, "max_heart_rate").and_then(value_to_i64),
+        avg_power_watts: get_field_value(record, "avg_power").and_then(value_to_i64),
+        max_power_watts: get_field_value(record, "max_power").and_then(value_to_i64),
+        avg_cadence: get_field_value(record, "avg_cadence").and_then(value_to_i64),
+        max_cadence: get_field_value(record, "max_cadence").and_then(value_to_i64),
+        avg_speed_mps,
+        max_speed_mps,
+        elevation_gain_meters: get_field_value(record, "total_ascent").and_then(value_to_f64),
+        elevation_loss_meters: get_field_value(record, "total_descent").and_then(value_to_f64),
+        lap_trigger,
+        swim_stroke: get_enum_field(record, "swim_stroke", swim_stroke_to_string),
+        total_strokes,
+        num_lengths: get_field_value(record, "num_lengths").and_then(value_to_i64),
+        num_active_lengths: get_field_value(record, "num_active_lengths").and_then(value_to_i64),
     }
 }
 
 #[allow(dead_code)]
 pub fn compute_file_hash(file_path: &Path) -> Result<String, String> {
     let data = fs::read(file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    Ok(hash_bytes(&data))
+}
+
+/// SHA-256 of the raw file contents, used for deduplication
+pub fn hash_bytes(data: &[u8]) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(&data);
-    Ok(hex::encode(hasher.finalize()))
+    hasher.update(data);
+    hex::encode(hasher.finalize())
 }
 
-pub fn parse_fit_file(file_path: &Path) -> Result<ParsedFitData, String> {
-    info!("Parsing FIT file: {:?}", file_path);
-    
-    let file_data = fs::read(file_path).map_err(|e| format!("Failed to read file: {}", e))?;
-    let file_hash = {
-        let mut hasher = Sha256::new();
-        hasher.update(&file_data);
-        hex::encode(hasher.finalize())
-    };
+pub fn parse_fit_bytes(file_data: &[u8], filename: &str) -> Result<ParsedFitData, String> {
+    info!("Parsing FIT file: {}", filename);
 
-    let filename = file_path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown")
-        .to_string();
+    let file_hash = hash_bytes(file_data);
+    let filename = filename.to_string();
 
-    let records = fitparser::from_bytes(&file_data)
+    let records = fitparser::from_bytes(file_data)
         .map_err(|e| format!("Failed to parse FIT file: {}", e))?;
 
     info!("Parsed {} records from FIT file", records.len());
 
+    // Developer field values line up with the `record` messages by position
+    let developer = parse_developer_data(file_data);
+    let mut developer_records = developer.records.into_iter();
+
     // Debug: collect all unique record kinds
     let mut record_kinds: std::collections::HashSet<String> = std::collections::HashSet::new();
     for record in &records {
@@ -182,25 +724,20 @@ pub fn parse_fit_file(file_path: &Path) -> Result<ParsedFitData, String> {
     info!("Record kinds found: {:?}", record_kinds);
 
     let mut workout_type: Option<String> = None;
-    let mut start_time: Option<DateTime<Utc>> = None;
-    let mut end_time: Option<DateTime<Utc>> = None;
-    let mut duration_seconds: Option<i64> = None;
-    let mut distance_meters: Option<f64> = None;
-    let mut total_calories: Option<i64> = None;
-    let mut avg_heart_rate: Option<i64> = None;
-    let mut max_heart_rate: Option<i64> = None;
-    let mut avg_power: Option<i64> = None;
-    let mut max_power: Option<i64> = None;
-    let mut avg_cadence: Option<i64> = None;
-    let mut max_cadence: Option<i64> = None;
-    let mut avg_speed: Option<f64> = None;
-    let mut max_speed: Option<f64> = None;
-    let mut elevation_gain: Option<f64> = None;
-    let mut elevation_loss: Option<f64> = None;
+    let mut activity_type: Option<String> = None;
+    let mut sub_sport: Option<String> = None;
+    let mut lap_start_time: Option<DateTime<Utc>> = None;
 
+    let mut sessions: Vec<SessionSummary> = Vec::new();
     let mut gps_data: Vec<GpsPoint> = Vec::new();
     let mut sensor_data: Vec<SensorPoint> = Vec::new();
-    let mut altitudes: Vec<f64> = Vec::new();
+    let mut laps: Vec<LapData> = Vec::new();
+    let mut lengths: Vec<LengthData> = Vec::new();
+    let mut pauses: Vec<TimerPause> = Vec::new();
+    let mut timer_stopped_at: Option<String> = None;
+    let mut rr_intervals_ms: Vec<f64> = Vec::new();
+    let mut creator = FileCreator::default();
+    let mut devices: Vec<DeviceInfo> = Vec::new();
 
     for record in &records {
         let kind = record.kind().to_string();
@@ -209,164 +746,55 @@ pub fn parse_fit_file(file_path: &Path) -> Result<ParsedFitData, String> {
             "sport" => {
                 debug!("Found sport record");
                 // Get sport type - can be string or numeric
-                if let Some(val) = get_field_value(record, "sport") {
-                    match val {
-                        Value::String(s) => {
-                            workout_type = Some(s.to_lowercase());
-                            debug!("Sport (string): {}", s);
-                        }
-                        Value::UInt8(n) => {
-                            workout_type = Some(sport_to_string(*n));
-                            debug!("Sport (uint8): {} -> {}", n, sport_to_string(*n));
-                        }
-                        _ => {
-                            debug!("Sport has unexpected type: {:?}", val);
-                        }
-                    }
-                }
-            }
-            "session" => {
-                debug!("Found session record with {} fields", record.fields().len());
-                
-                // Log all fields for debugging
-                for field in record.fields() {
-                    debug!("  Session field: {} = {:?}", field.name(), field.value());
-                }
-                
-                // Get sport type from session if not already set
                 if workout_type.is_none() {
                     if let Some(val) = get_field_value(record, "sport") {
                         match val {
                             Value::String(s) => {
                                 workout_type = Some(s.to_lowercase());
+                                debug!("Sport (string): {}", s);
                             }
                             Value::UInt8(n) => {
                                 workout_type = Some(sport_to_string(*n));
+                                debug!("Sport (uint8): {} -> {}", n, sport_to_string(*n));
+                            }
+                            _ => {
+                                debug!("Sport has unexpected type: {:?}", val);
                             }
-                            _ => {}
-                        }
-                    }
-                }
-
-                // Start time
-                if let Some(val) = get_field_value(record, "start_time") {
-                    if let Some(ts) = value_to_timestamp(val) {
-                        start_time = Some(ts);
-                        debug!("Start time: {:?}", start_time);
-                    }
-                }
-                
-                // End time (timestamp field in session)
-                if let Some(val) = get_field_value(record, "timestamp") {
-                    if let Some(ts) = value_to_timestamp(val) {
-                        end_time = Some(ts);
-                        debug!("End time: {:?}", end_time);
-                    }
-                }
-                
-                // Duration - fitparser returns this in seconds as f64
-                if let Some(val) = get_field_value(record, "total_elapsed_time") {
-                    if let Some(t) = value_to_f64(val) {
-                        // The value is in seconds (not ms)
-                        duration_seconds = Some(t as i64);
-                        debug!("Duration (elapsed): {} seconds", t);
-                    }
-                }
-                if duration_seconds.is_none() {
-                    if let Some(val) = get_field_value(record, "total_timer_time") {
-                        if let Some(t) = value_to_f64(val) {
-                            duration_seconds = Some(t as i64);
-                            debug!("Duration (timer): {} seconds", t);
                         }
                     }
                 }
-                
-                // Distance - fitparser returns this in meters
-                if let Some(val) = get_field_value(record, "total_distance") {
-                    if let Some(d) = value_to_f64(val) {
-                        // Value is already in meters
-                        distance_meters = Some(d);
-                        debug!("Distance: {} meters", d);
-                    }
-                }
-                
-                // Calories
-                if let Some(val) = get_field_value(record, "total_calories") {
-                    total_calories = value_to_i64(val);
-                    debug!("Calories: {:?}", total_calories);
-                }
-                
-                // Heart rate
-                if let Some(val) = get_field_value(record, "avg_heart_rate") {
-                    avg_heart_rate = value_to_i64(val);
-                }
-                if let Some(val) = get_field_value(record, "max_heart_rate") {
-                    max_heart_rate = value_to_i64(val);
-                }
-                
-                // Power
-                if let Some(val) = get_field_value(record, "avg_power") {
-                    avg_power = value_to_i64(val);
-                }
-                if let Some(val) = get_field_value(record, "max_power") {
-                    max_power = value_to_i64(val);
-                }
-                
-                // Cadence
-                if let Some(val) = get_field_value(record, "avg_cadence") {
-                    avg_cadence = value_to_i64(val);
-                }
-                if let Some(val) = get_field_value(record, "max_cadence") {
-                    max_cadence = value_to_i64(val);
+                if sub_sport.is_none() {
+                    sub_sport = get_sub_sport(record);
                 }
+            }
+            "session" => {
+                debug!("Found session record with {} fields", record.fields().len());
                 
-                // Speed - fitparser returns in m/s
-                if let Some(val) = get_field_value(record, "avg_speed") {
-                    avg_speed = value_to_f64(val);
-                    debug!("Avg speed: {:?} m/s", avg_speed);
-                }
-                if let Some(val) = get_field_value(record, "max_speed") {
-                    max_speed = value_to_f64(val);
-                }
-                // Try enhanced speed if regular not available
-                if avg_speed.is_none() {
-                    if let Some(val) = get_field_value(record, "enhanced_avg_speed") {
-                        avg_speed = value_to_f64(val);
-                    }
-                }
-                if max_speed.is_none() {
-                    if let Some(val) = get_field_value(record, "enhanced_max_speed") {
-                        max_speed = value_to_f64(val);
-                    }
+                // Log all fields for debugging
+                for field in record.fields() {
+                    debug!("  Session field: {} = {:?}", field.name(), field.value());
                 }
                 
-                // Elevation from session (total_ascent/descent)
-                if let Some(val) = get_field_value(record, "total_ascent") {
-                    elevation_gain = value_to_f64(val);
-                    debug!("Elevation gain: {:?}", elevation_gain);
-                }
-                if let Some(val) = get_field_value(record, "total_descent") {
-                    elevation_loss = value_to_f64(val);
-                }
+                sessions.push(parse_session(record));
             }
             "record" => {
                 // Extract timestamp
                 let timestamp = get_field_value(record, "timestamp")
-                    .and_then(|v| value_to_timestamp(v))
+                    .and_then(value_to_timestamp)
                     .map(|ts| ts.to_rfc3339());
 
                 // Extract GPS data - position values are in semicircles
                 let lat = get_field_value(record, "position_lat")
-                    .and_then(|v| value_to_i64(v))
+                    .and_then(value_to_i64)
                     .map(|v| semicircles_to_degrees(v as i32));
                 let lon = get_field_value(record, "position_long")
-                    .and_then(|v| value_to_i64(v))
+                    .and_then(value_to_i64)
                     .map(|v| semicircles_to_degrees(v as i32));
                 
                 // Altitude - already in meters from fitparser
                 let altitude = get_field_value(record, "altitude")
                     .or_else(|| get_field_value(record, "enhanced_altitude"))
-                    .and_then(|v| value_to_f64(v));
+                    .and_then(value_to_f64);
 
                 if let (Some(lat_val), Some(lon_val)) = (lat, lon) {
                     if lat_val.abs() <= 90.0 && lon_val.abs() <= 180.0 {
@@ -379,26 +807,31 @@ pub fn parse_fit_file(file_path: &Path) -> Result<ParsedFitData, String> {
                     }
                 }
 
-                if let Some(alt) = altitude {
-                    altitudes.push(alt);
-                }
-
                 // Extract sensor data
                 let heart_rate = get_field_value(record, "heart_rate")
-                    .and_then(|v| value_to_i64(v));
+                    .and_then(value_to_i64);
                 let power = get_field_value(record, "power")
-                    .and_then(|v| value_to_i64(v));
+                    .and_then(value_to_i64);
                 let cadence = get_field_value(record, "cadence")
-                    .and_then(|v| value_to_i64(v));
+                    .and_then(value_to_i64);
                     
                 // Speed - already in m/s
                 let speed = get_field_value(record, "speed")
                     .or_else(|| get_field_value(record, "enhanced_speed"))
-                    .and_then(|v| value_to_f64(v));
+                    .and_then(value_to_f64);
                     
                 // Distance - already in meters
                 let distance = get_field_value(record, "distance")
-                    .and_then(|v| value_to_f64(v));
+                    .and_then(value_to_f64);
+
+                // Temperature - degrees C
+                let temperature = get_field_value(record, "temperature")
+                    .and_then(value_to_f64);
+
+                // Running dynamics from an HRM or foot pod. Step length
+                // comes in mm.
+                let f64_field = |name: &str| get_field_value(record, name).and_then(value_to_f64);
+                let stride_length = f64_field("step_length").map(|mm| mm / 1000.0);
 
                 sensor_data.push(SensorPoint {
                     timestamp,
@@ -408,85 +841,485 @@ pub fn parse_fit_file(file_path: &Path) -> Result<ParsedFitData, String> {
                     speed,
                     distance,
                     altitude,
+                    temperature,
+                    vertical_oscillation: f64_field("vertical_oscillation"),
+                    ground_contact_time: f64_field("stance_time"),
+                    ground_contact_balance: f64_field("stance_time_balance"),
+                    stride_length,
+                    vertical_ratio: f64_field("vertical_ratio"),
+                    fractional_cadence: f64_field("fractional_cadence"),
+                    left_right_balance: get_field_value(record, "left_right_balance")
+                        .and_then(left_balance_percent),
+                    left_torque_effectiveness: f64_field("left_torque_effectiveness"),
+                    right_torque_effectiveness: f64_field("right_torque_effectiveness"),
+                    left_pedal_smoothness: f64_field("left_pedal_smoothness"),
+                    right_pedal_smoothness: f64_field("right_pedal_smoothness"),
+                    respiration_rate: f64_field("enhanced_respiration_rate")
+                        .or_else(|| f64_field("respiration_rate")),
+                    core_temperature: f64_field("core_temperature"),
+                    spo2: f64_field("saturated_hemoglobin_percent"),
+                    developer: developer_records.next().unwrap_or_default(),
                 });
             }
             "activity" => {
                 debug!("Found activity record");
                 // Activity record can also have sport type
-                if workout_type.is_none() {
-                    if let Some(val) = get_field_value(record, "type") {
-                        match val {
-                            Value::String(s) => {
-                                workout_type = Some(s.to_lowercase());
-                            }
-                            Value::UInt8(n) => {
-                                workout_type = Some(sport_to_string(*n));
-                            }
-                            _ => {}
+                if let Some(val) = get_field_value(record, "type") {
+                    match val {
+                        Value::String(s) => {
+                            activity_type = Some(s.to_lowercase());
                         }
+                        Value::UInt8(n) => {
+                            activity_type = Some(sport_to_string(*n));
+                        }
+                        _ => {}
                     }
                 }
             }
             "lap" => {
                 // Lap records can provide data if session is missing
-                if start_time.is_none() {
+                if lap_start_time.is_none() {
                     if let Some(val) = get_field_value(record, "start_time") {
                         if let Some(ts) = value_to_timestamp(val) {
-                            start_time = Some(ts);
+                            lap_start_time = Some(ts);
                         }
                     }
                 }
+
+                let lap = parse_lap(record);
+                debug!("Lap {}: {:?}s, {:?}m, trigger={:?}", laps.len(), lap.duration_seconds, lap.distance_meters, lap.lap_trigger);
+                laps.push(lap);
+            }
+            "length" => {
+                // Pool swims record every length between walls, including rests
+                lengths.push(parse_length(record));
+            }
+            "file_id" => {
+                creator = parse_file_id(record);
+            }
+            "device_info" => {
+                // Devices are listed at the start and again at the end of an
+                // activity; the last message has the final battery status
+                if let Some(device) = parse_device_info(record) {
+                    match devices.iter_mut().find(|d| d.device_index == device.device_index) {
+                        Some(existing) => *existing = device,
+                        None => devices.push(device),
+                    }
+                }
+            }
+            "hrv" => {
+                // Up to five intervals in seconds, padded with invalid values
+                let times = match get_field_value(record, "time") {
+                    Some(Value::Array(values)) => values.iter().filter_map(value_to_f64).collect(),
+                    Some(value) => value_to_f64(value).into_iter().collect(),
+                    None => Vec::new(),
+                };
+                rr_intervals_ms.extend(times.into_iter().filter(|t: &f64| *t < 65.535).map(|t| t * 1000.0));
+            }
+            "event" => {
+                // Timer stops and starts mark manual pauses and auto-pause
+                if get_enum_field(record, "event", event_to_string).as_deref() != Some("timer") {
+                    continue;
+                }
+                let timestamp = get_field_value(record, "timestamp")
+                    .and_then(value_to_timestamp)
+                    .map(|ts| ts.to_rfc3339());
+                match get_enum_field(record, "event_type", event_type_to_string).as_deref() {
+                    Some("start") => {
+                        if let (Some(start), Some(end)) = (timer_stopped_at.take(), timestamp) {
+                            pauses.push(TimerPause { start, end });
+                        }
+                    }
+                    Some(event_type) if event_type.starts_with("stop") => {
+                        timer_stopped_at = timer_stopped_at.or(timestamp);
+                    }
+                    _ => {}
+                }
             }
             _ => {}
         }
     }
 
+    // A file with several sessions is a multisport activity (triathlon, brick).
+    // Each session becomes a leg with its own summary and sample range, and the
+    // parent carries the combined totals.
+    let mut legs: Vec<ParsedFitData> = Vec::new();
+    let is_multisport = sessions.len() > 1;
+    let mut summary = if is_multisport {
+        info!("Multisport activity with {} sessions", sessions.len());
+        for session in &sessions {
+            legs.push(build_leg(
+                session,
+                file_hash.clone(),
+                &filename,
+                &gps_data,
+                &sensor_data,
+                &laps,
+                &lengths,
+                &pauses,
+                &developer.fields,
+                &creator,
+            ));
+        }
+        let mut combined = combine_sessions(&sessions);
+        combined.sport = Some("multisport".to_string());
+        combined
+    } else {
+        let mut session = sessions.pop().unwrap_or_default();
+        session.sport = workout_type.or(session.sport).or(activity_type);
+        session.sub_sport = sub_sport.or(session.sub_sport);
+        session
+    };
+
+    if summary.start_time.is_none() {
+        summary.start_time = lap_start_time;
+    }
+
+    // Files without device_info still name their creator in file_id
+    if !devices.iter().any(|d| d.device_index == CREATOR_DEVICE_INDEX)
+        && (creator.product.is_some() || creator.serial_number.is_some())
+    {
+        devices.push(DeviceInfo {
+            device_index: CREATOR_DEVICE_INDEX.to_string(),
+            manufacturer: creator.manufacturer.clone(),
+            product: creator.product.clone(),
+            serial_number: creator.serial_number,
+            ..Default::default()
+        });
+    }
+    devices.sort_by_key(|d| d.device_index != CREATOR_DEVICE_INDEX);
+
     // Calculate elevation gain/loss from records if not in session
-    if elevation_gain.is_none() || elevation_loss.is_none() {
+    if summary.elevation_gain.is_none() || summary.elevation_loss.is_none() {
+        let altitudes: Vec<f64> = sensor_data.iter().filter_map(|p| p.altitude).collect();
         let (calc_gain, calc_loss) = calculate_elevation_changes(&altitudes);
-        if elevation_gain.is_none() {
-            elevation_gain = calc_gain;
+        if summary.elevation_gain.is_none() {
+            summary.elevation_gain = calc_gain;
         }
-        if elevation_loss.is_none() {
-            elevation_loss = calc_loss;
+        if summary.elevation_loss.is_none() {
+            summary.elevation_loss = calc_loss;
         }
     }
 
-    // Build chart data
-    let chart_data = build_chart_data(&sensor_data);
-
     info!(
-        "Parsed workout: type={:?}, duration={:?}s, distance={:?}m, calories={:?}, hr={:?}/{:?}, gps_points={}, sensor_points={}",
-        workout_type, duration_seconds, distance_meters, total_calories,
-        avg_heart_rate, max_heart_rate, gps_data.len(), sensor_data.len()
+        "Parsed workout: type={:?}, duration={:?}s, timer={:?}s, pauses={}, distance={:?}m, calories={:?}, hr={:?}/{:?}, gps_points={}, sensor_points={}, laps={}, lengths={}, legs={}",
+        summary.sport, summary.duration_seconds, summary.timer_seconds, pauses.len(), summary.distance_meters, summary.total_calories,
+        summary.avg_heart_rate, summary.max_heart_rate, gps_data.len(), sensor_data.len(), laps.len(), lengths.len(), legs.len()
     );
 
     Ok(ParsedFitData {
         file_hash,
         filename,
-        workout_type,
-        start_time: start_time.map(|t| t.to_rfc3339()),
-        end_time: end_time.map(|t| t.to_rfc3339()),
-        duration_seconds,
-        distance_meters,
-        total_calories,
-        avg_heart_rate,
-        max_heart_rate,
-        avg_power_watts: avg_power,
-        max_power_watts: max_power,
-        avg_cadence,
-        max_cadence,
-        avg_speed_mps: avg_speed,
-        max_speed_mps: max_speed,
+        workout_type: summary.sport,
+        sub_sport: summary.sub_sport,
+        start_time: summary.start_time.map(|t| t.to_rfc3339()),
+        end_time: summary.end_time.map(|t| t.to_rfc3339()),
+        duration_seconds: summary.duration_seconds,
+        timer_seconds: summary.timer_seconds,
+        distance_meters: summary.distance_meters,
+        total_calories: summary.total_calories,
+        avg_heart_rate: summary.avg_heart_rate,
+        max_heart_rate: summary.max_heart_rate,
+        avg_power_watts: summary.avg_power,
+        max_power_watts: summary.max_power,
+        avg_cadence: summary.avg_cadence,
+        max_cadence: summary.max_cadence,
+        avg_speed_mps: summary.avg_speed,
+        max_speed_mps: summary.max_speed,
+        elevation_gain_meters: summary.elevation_gain,
+        elevation_loss_meters: summary.elevation_loss,
+        pool_length_meters: summary.pool_length,
+        gps_data,
+        sensor_data,
+        laps,
+        lengths,
+        pauses,
+        developer_fields: developer.fields,
+        rr_intervals_ms,
+        creator,
+        devices,
+        legs,
+    })
+}
+
+fn parse_session(record: &FitDataRecord) -> SessionSummary {
+    let mut session = SessionSummary::default();
+
+    if let Some(val) = get_field_value(record, "sport") {
+        match val {
+            Value::String(s) => {
+                session.sport = Some(s.to_lowercase());
+            }
+            Value::UInt8(n) => {
+                session.sport = Some(sport_to_string(*n));
+            }
+            _ => {}
+        }
+    }
+    session.sub_sport = get_sub_sport(record);
+
+    // Start time
+    if let Some(val) = get_field_value(record, "start_time") {
+        if let Some(ts) = value_to_timestamp(val) {
+            session.start_time = Some(ts);
+            debug!("Start time: {:?}", session.start_time);
+        }
+    }
+    
+    // End time (timestamp field in session)
+    if let Some(val) = get_field_value(record, "timestamp") {
+        if let Some(ts) = value_to_timestamp(val) {
+            session.end_time = Some(ts);
+            debug!("End time: {:?}", session.end_time);
+        }
+    }
+    
+    // Durations - fitparser returns these in seconds as f64. Elapsed time
+    // includes pauses, timer time only counts while the timer was running.
+    if let Some(val) = get_field_value(record, "total_elapsed_time") {
+        if let Some(t) = value_to_f64(val) {
+            session.duration_seconds = Some(t as i64);
+            debug!("Duration (elapsed): {} seconds", t);
+        }
+    }
+    if let Some(val) = get_field_value(record, "total_timer_time") {
+        if let Some(t) = value_to_f64(val) {
+            session.timer_seconds = Some(t as i64);
+            debug!("Duration (timer): {} seconds", t);
+        }
+    }
+    if session.duration_seconds.is_none() {
+        session.duration_seconds = session.timer_seconds;
+    }
+    
+    // Distance - fitparser returns this in meters
+    if let Some(val) = get_field_value(record, "total_distance") {
+        if let Some(d) = value_to_f64(val) {
+            // Value is already in meters
+            session.distance_meters = Some(d);
+            debug!("Distance: {} meters", d);
+        }
+    }
+    
+    // Calories
+    if let Some(val) = get_field_value(record, "total_calories") {
+        session.total_calories = value_to_i64(val);
+        debug!("Calories: {:?}", session.total_calories);
+    }
+    
+    // Heart rate
+    if let Some(val) = get_field_value(record, "avg_heart_rate") {
+        session.avg_heart_rate = value_to_i64(val);
+    }
+    if let Some(val) = get_field_value(record, "max_heart_rate") {
+        session.max_heart_rate = value_to_i64(val);
+    }
+    
+    // Power
+    if let Some(val) = get_field_value(record, "avg_power") {
+        session.avg_power = value_to_i64(val);
+    }
+    if let Some(val) = get_field_value(record, "max_power") {
+        session.max_power = value_to_i64(val);
+    }
+    
+    // Cadence
+    if let Some(val) = get_field_value(record, "avg_cadence") {
+        session.avg_cadence = value_to_i64(val);
+    }
+    if let Some(val) = get_field_value(record, "max_cadence") {
+        session.max_cadence = value_to_i64(val);
+    }
+    
+    // Speed - fitparser returns in m/s
+    if let Some(val) = get_field_value(record, "avg_speed") {
+        session.avg_speed = value_to_f64(val);
+        debug!("Avg speed: {:?} m/s", session.avg_speed);
+    }
+    if let Some(val) = get_field_value(record, "max_speed") {
+        session.max_speed = value_to_f64(val);
+    }
+    // Try enhanced speed if regular not available
+    if session.avg_speed.is_none() {
+        if let Some(val) = get_field_value(record, "enhanced_avg_speed") {
+            session.avg_speed = value_to_f64(val);
+        }
+    }
+    if session.max_speed.is_none() {
+        if let Some(val) = get_field_value(record, "enhanced_max_speed") {
+            session.max_speed = value_to_f64(val);
+        }
+    }
+    
+    // Elevation from session (total_ascent/descent)
+    if let Some(val) = get_field_value(record, "total_ascent") {
+        session.elevation_gain = value_to_f64(val);
+        debug!("Elevation gain: {:?}", session.elevation_gain);
+    }
+    if let Some(val) = get_field_value(record, "total_descent") {
+        session.elevation_loss = value_to_f64(val);
+    }
+
+    // Pool length - fitparser returns this in meters
+    if let Some(val) = get_field_value(record, "pool_length") {
+        session.pool_length = value_to_f64(val).filter(|l| *l > 0.0);
+        debug!("Pool length: {:?} meters", session.pool_length);
+    }
+
+    session
+}
+
+/// Returns true if an RFC 3339 timestamp falls inside the session's time range.
+/// Laps use a half-open range so a lap starting on a session boundary belongs
+/// to the next session only.
+fn in_session_range(timestamp: Option<&str>, session: &SessionSummary, include_end: bool) -> bool {
+    let (Some(start), Some(end)) = (session.start_time, session.end_time) else {
+        return false;
+    };
+    timestamp
+        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+        .map(|ts| {
+            let ts = ts.with_timezone(&Utc);
+            ts >= start && (ts < end || (include_end && ts == end))
+        })
+        .unwrap_or(false)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_leg(
+    session: &SessionSummary,
+    file_hash: String,
+    filename: &str,
+    gps_data: &[GpsPoint],
+    sensor_data: &[SensorPoint],
+    laps: &[LapData],
+    lengths: &[LengthData],
+    pauses: &[TimerPause],
+    developer_fields: &[DeveloperField],
+    creator: &FileCreator,
+) -> ParsedFitData {
+    let gps_data: Vec<GpsPoint> = gps_data.iter()
+        .filter(|p| in_session_range(p.timestamp.as_deref(), session, true))
+        .cloned()
+        .collect();
+    let sensor_data: Vec<SensorPoint> = sensor_data.iter()
+        .filter(|p| in_session_range(p.timestamp.as_deref(), session, true))
+        .cloned()
+        .collect();
+    let laps: Vec<LapData> = laps.iter()
+        .filter(|l| in_session_range(l.start_time.as_deref(), session, false))
+        .cloned()
+        .collect();
+    let lengths: Vec<LengthData> = lengths.iter()
+        .filter(|l| in_session_range(l.start_time.as_deref(), session, false))
+        .cloned()
+        .collect();
+    let pauses: Vec<TimerPause> = pauses.iter()
+        .filter(|p| in_session_range(Some(&p.start), session, false))
+        .cloned()
+        .collect();
+    let developer_fields: Vec<DeveloperField> = developer_fields.iter()
+        .filter(|f| sensor_data.iter().any(|p| p.developer.iter().any(|v| v.key == f.key)))
+        .cloned()
+        .collect();
+
+    let mut elevation_gain = session.elevation_gain;
+    let mut elevation_loss = session.elevation_loss;
+    if elevation_gain.is_none() || elevation_loss.is_none() {
+        let altitudes: Vec<f64> = sensor_data.iter().filter_map(|p| p.altitude).collect();
+        let (calc_gain, calc_loss) = calculate_elevation_changes(&altitudes);
+        elevation_gain = elevation_gain.or(calc_gain);
+        elevation_loss = elevation_loss.or(calc_loss);
+    }
+
+    debug!(
+        "Leg {:?}: {:?}s, {:?}m, sensor_points={}",
+        session.sport, session.duration_seconds, session.distance_meters, sensor_data.len()
+    );
+
+    ParsedFitData {
+        file_hash,
+        filename: filename.to_string(),
+        workout_type: session.sport.clone(),
+        sub_sport: session.sub_sport.clone(),
+        start_time: session.start_time.map(|t| t.to_rfc3339()),
+        end_time: session.end_time.map(|t| t.to_rfc3339()),
+        duration_seconds: session.duration_seconds,
+        timer_seconds: session.timer_seconds,
+        distance_meters: session.distance_meters,
+        total_calories: session.total_calories,
+        avg_heart_rate: session.avg_heart_rate,
+        max_heart_rate: session.max_heart_rate,
+        avg_power_watts: session.avg_power,
+        max_power_watts: session.max_power,
+        avg_cadence: session.avg_cadence,
+        max_cadence: session.max_cadence,
+        avg_speed_mps: session.avg_speed,
+        max_speed_mps: session.max_speed,
         elevation_gain_meters: elevation_gain,
         elevation_loss_meters: elevation_loss,
+        pool_length_meters: session.pool_length,
         gps_data,
         sensor_data,
-        chart_data,
-    })
+        laps,
+        lengths,
+        pauses,
+        developer_fields,
+        rr_intervals_ms: Vec::new(),
+        creator: creator.clone(),
+        devices: Vec::new(),
+        legs: Vec::new(),
+    }
 }
 
-fn calculate_elevation_changes(altitudes: &[f64]) -> (Option<f64>, Option<f64>) {
+/// Combines the sessions of a multisport file into a single summary.
+/// Totals are summed, maxima are taken across legs and averages are
+/// weighted by each leg's duration. Cadence is left empty because its
+/// unit differs between sports.
+fn combine_sessions(sessions: &[SessionSummary]) -> SessionSummary {
+    fn sum<T: std::iter::Sum<T>>(values: impl Iterator<Item = Option<T>>) -> Option<T> {
+        let present: Vec<T> = values.flatten().collect();
+        if present.is_empty() { None } else { Some(present.into_iter().sum()) }
+    }
+
+    fn weighted_avg(sessions: &[SessionSummary], value: impl Fn(&SessionSummary) -> Option<i64>) -> Option<i64> {
+        let (total, weight) = sessions.iter()
+            .filter_map(|s| Some((value(s)? as f64, s.duration_seconds? as f64)))
+            .fold((0.0, 0.0), |(t, w), (v, d)| (t + v * d, w + d));
+        if weight > 0.0 { Some((total / weight).round() as i64) } else { None }
+    }
+
+    let duration_seconds = sum(sessions.iter().map(|s| s.duration_seconds));
+    let distance_meters = sum(sessions.iter().map(|s| s.distance_meters));
+    let avg_speed = match (distance_meters, duration_seconds) {
+        (Some(d), Some(t)) if t > 0 => Some(d / t as f64),
+        _ => None,
+    };
+
+    SessionSummary {
+        sport: None,
+        sub_sport: None,
+        start_time: sessions.iter().filter_map(|s| s.start_time).min(),
+        end_time: sessions.iter().filter_map(|s| s.end_time).max(),
+        duration_seconds,
+        timer_seconds: sum(sessions.iter().map(|s| s.timer_seconds)),
+        distance_meters,
+        total_calories: sum(sessions.iter().map(|s| s.total_calories)),
+        avg_heart_rate: weighted_avg(sessions, |s| s.avg_heart_rate),
+        max_heart_rate: sessions.iter().filter_map(|s| s.max_heart_rate).max(),
+        avg_power: weighted_avg(sessions, |s| s.avg_power),
+        max_power: sessions.iter().filter_map(|s| s.max_power).max(),
+        avg_cadence: None,
+        max_cadence: None,
+        avg_speed,
+        max_speed: sessions.iter().filter_map(|s| s.max_speed).reduce(f64::max),
+        elevation_gain: sum(sessions.iter().map(|s| s.elevation_gain)),
+        elevation_loss: sum(sessions.iter().map(|s| s.elevation_loss)),
+        pool_length: None,
+    }
+}
+
+pub fn calculate_elevation_changes(altitudes: &[f64]) -> (Option<f64>, Option<f64>) {
     if altitudes.len() < 2 {
         return (None, None);
     }
@@ -507,7 +1340,9 @@ fn calculate_elevation_changes(altitudes: &[f64]) -> (Option<f64>, Option<f64>)
     (Some(gain), Some(loss))
 }
 
-fn build_chart_data(sensor_data: &[SensorPoint]) -> ChartData {
+/// Builds the chart series of the sensor data, with a series for each of
+/// `developer_fields`
+pub fn build_chart_data(sensor_data: &[SensorPoint], developer_fields: &[DeveloperField]) -> ChartData {
     // Downsample if needed (LTTB algorithm simplified)
     let max_points = 1000;
     let step = if sensor_data.len() > max_points {
@@ -516,30 +1351,45 @@ fn build_chart_data(sensor_data: &[SensorPoint]) -> ChartData {
         1
     };
 
-    let mut timestamps = Vec::new();
-    let mut heart_rate = Vec::new();
-    let mut power = Vec::new();
-    let mut cadence = Vec::new();
-    let mut speed = Vec::new();
-    let mut altitude = Vec::new();
-
-    for (i, point) in sensor_data.iter().enumerate() {
-        if i % step == 0 {
-            timestamps.push(point.timestamp.clone().unwrap_or_default());
-            heart_rate.push(point.heart_rate);
-            power.push(point.power);
-            cadence.push(point.cadence);
-            speed.push(point.speed);
-            altitude.push(point.altitude);
-        }
-    }
+    let points: Vec<&SensorPoint> = sensor_data.iter().step_by(step).collect();
+    let series = |value: fn(&SensorPoint) -> Option<f64>| -> Vec<Option<f64>> {
+        points.iter().map(|p| value(p)).collect()
+    };
+    let channel = |value: fn(&SensorPoint) -> Option<f64>| {
+        if points.iter().any(|p| value(p).is_some()) { series(value) } else { Vec::new() }
+    };
 
     ChartData {
-        timestamps,
-        heart_rate,
-        power,
-        cadence,
-        speed,
-        altitude,
+        timestamps: points.iter().map(|p| p.timestamp.clone().unwrap_or_default()).collect(),
+        heart_rate: points.iter().map(|p| p.heart_rate).collect(),
+        power: points.iter().map(|p| p.power).collect(),
+        cadence: points.iter().map(|p| p.cadence).collect(),
+        speed: series(|p| p.speed),
+        altitude: series(|p| p.altitude),
+        temperature: channel(|p| p.temperature),
+        vertical_oscillation: channel(|p| p.vertical_oscillation),
+        ground_contact_time: channel(|p| p.ground_contact_time),
+        ground_contact_balance: channel(|p| p.ground_contact_balance),
+        stride_length: channel(|p| p.stride_length),
+        vertical_ratio: channel(|p| p.vertical_ratio),
+        fractional_cadence: channel(|p| p.fractional_cadence),
+        left_right_balance: channel(|p| p.left_right_balance),
+        left_torque_effectiveness: channel(|p| p.left_torque_effectiveness),
+        right_torque_effectiveness: channel(|p| p.right_torque_effectiveness),
+        left_pedal_smoothness: channel(|p| p.left_pedal_smoothness),
+        right_pedal_smoothness: channel(|p| p.right_pedal_smoothness),
+        respiration_rate: channel(|p| p.respiration_rate),
+        core_temperature: channel(|p| p.core_temperature),
+        spo2: channel(|p| p.spo2),
+        developer: developer_fields.iter()
+            .map(|field| DeveloperSeries {
+                key: field.key.clone(),
+                name: field.name.clone(),
+                units: field.units.clone(),
+                values: points.iter()
+                    .map(|p| p.developer.iter().find(|v| v.key == field.key).map(|v| v.value))
+                    .collect(),
+            })
+            .collect(),
     }
 }
@@ -0,0 +1,136 @@
+use crate::fit_parser::{hash_bytes, FileCreator, ParsedFitData};
+use crate::track::{build_track, normalize_sport, normalize_sub_sport, parse_xml, TrackPoint};
+use chrono::{DateTime, Utc};
+use log::info;
+
+fn child_text<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|c| c.is_element() && c.tag_name().name() == name)
+        .and_then(|c| c.text())
+        .map(|t| t.trim())
+}
+
+/// Extension values are matched by local name so Garmin TrackPointExtension
+/// (gpxtpx:hr, gpxtpx:cad) and the power tags used by other apps all work.
+fn extension_value(node: roxmltree::Node, names: &[&str]) -> Option<f64> {
+    node.children()
+        .find(|c| c.is_element() && c.tag_name().name() == "extensions")?
+        .descendants()
+        .find(|d| d.is_element() && names.contains(&d.tag_name().name()))
+        .and_then(|d| d.text())
+        .and_then(|t| t.trim().parse::<f64>().ok())
+}
+
+fn parse_trackpoint(node: roxmltree::Node) -> TrackPoint {
+    TrackPoint {
+        time: child_text(node, "time")
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc)),
+        lat: node.attribute("lat").and_then(|v| v.parse().ok()),
+        lon: node.attribute("lon").and_then(|v| v.parse().ok()),
+        altitude: child_text(node, "ele").and_then(|v| v.parse().ok()),
+        heart_rate: extension_value(node, &["hr", "heartrate"]).map(|v| v.round() as i64),
+        cadence: extension_value(node, &["cad", "cadence"]).map(|v| v.round() as i64),
+        power: extension_value(node, &["power", "PowerInWatts", "watts"]).map(|v| v.round() as i64),
+        speed: extension_value(node, &["speed"]),
+        distance: None,
+        temperature: extension_value(node, &["atemp", "temp"]),
+    }
+}
+
+pub fn parse_gpx_bytes(file_data: &[u8], filename: &str) -> Result<ParsedFitData, String> {
+    info!("Parsing GPX file: {}", filename);
+
+    let file_hash = hash_bytes(file_data);
+    let filename = filename.to_string();
+
+    let text = String::from_utf8_lossy(file_data);
+    let doc = parse_xml(&text)
+        .map_err(|e| format!("Failed to parse GPX file: {}", e))?;
+
+    let track = doc.descendants()
+        .find(|n| n.is_element() && n.tag_name().name() == "trk")
+        .ok_or_else(|| "GPX file contains no track".to_string())?;
+
+    let workout_type = child_text(track, "type").map(normalize_sport);
+    let sub_sport = child_text(track, "type").and_then(normalize_sub_sport);
+
+    let points: Vec<TrackPoint> = doc.descendants()
+        .filter(|n| n.is_element() && n.tag_name().name() == "trkpt")
+        .map(parse_trackpoint)
+        .collect();
+
+    if points.is_empty() {
+        return Err("GPX file contains no trackpoints".to_string());
+    }
+
+    let track = build_track(&points);
+    let summary = track.summary;
+
+    info!(
+        "Parsed GPX workout: type={:?}, duration={:?}s, distance={:?}m, gps_points={}, sensor_points={}",
+        workout_type, summary.duration_seconds, summary.distance_meters,
+        track.gps_data.len(), track.sensor_data.len()
+    );
+
+    Ok(ParsedFitData {
+        file_hash,
+        filename,
+        workout_type,
+        sub_sport,
+        start_time: summary.start_time,
+        end_time: summary.end_time,
+        duration_seconds: summary.duration_seconds,
+        timer_seconds: None,
+        distance_meters: summary.distance_meters,
+        total_calories: None,
+        avg_heart_rate: summary.avg_heart_rate,
+        max_heart_rate: summary.max_heart_rate,
+        avg_power_watts: summary.avg_power,
+        max_power_watts: summary.max_power,
+        avg_cadence: summary.avg_cadence,
+        max_cadence: summary.max_cadence,
+        avg_speed_mps: summary.avg_speed,
+        max_speed_mps: summary.max_speed,
+        elevation_gain_meters: summary.elevation_gain,
+        elevation_loss_meters: summary.elevation_loss,
+        pool_length_meters: None,
+        gps_data: track.gps_data,
+        sensor_data: track.sensor_data,
+        laps: Vec::new(),
+        lengths: Vec::new(),
+        pauses: Vec::new(),
+        developer_fields: Vec::new(),
+        rr_intervals_ms: Vec::new(),
+        creator: FileCreator::default(),
+        devices: Vec::new(),
+        legs: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RIDE: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+        <gpx version=\"1.1\" xmlns=\"http://www.topografix.com/GPX/1/1\"
+             xmlns:gpxtpx=\"http://www.garmin.com/xmlschemas/TrackPointExtension/v1\">
+          <trk><type>cycling</type><trkseg>
+            <trkpt lat=\"0\" lon=\"0\"><time>2025-06-01T08:00:00Z</time>
+              <extensions><gpxtpx:TrackPointExtension><gpxtpx:hr>130</gpxtpx:hr></gpxtpx:TrackPointExtension></extensions>
+            </trkpt>
+            <trkpt lat=\"0.001\" lon=\"0\"><time>2025-06-01T08:00:10Z</time></trkpt>
+          </trkseg></trk>
+        </gpx>";
+
+    #[test]
+    fn reads_exports_with_a_byte_order_mark() {
+        let data = format!("\u{feff}\r\n  {}", RIDE);
+        let parsed = parse_gpx_bytes(data.as_bytes(), "ride.gpx").unwrap();
+
+        assert_eq!(parsed.workout_type.as_deref(), Some("cycling"));
+        assert_eq!(parsed.duration_seconds, Some(10));
+        assert_eq!(parsed.sensor_data[0].heart_rate, Some(130));
+        assert_eq!(parsed.gps_data.len(), 2);
+    }
+}
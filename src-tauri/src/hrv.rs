@@ -0,0 +1,294 @@
+//! Heart rate variability from the beat-to-beat (R-R) intervals chest straps
+//! record in FIT `hrv` messages
+
+use serde::{Deserialize, Serialize};
+
+/// Intervals outside this range, in ms, can't be heartbeats
+const MIN_RR_MS: f64 = 300.0;
+const MAX_RR_MS: f64 = 2000.0;
+
+/// Beats on each side of an interval that its local median is taken over
+const MEDIAN_HALF_WIDTH: usize = 5;
+
+/// Intervals further than this share from their local median are artifacts
+/// (missed or extra beats)
+const ARTIFACT_THRESHOLD: f64 = 0.2;
+
+/// Box sizes in beats of the short-term DFA scaling exponent
+const DFA_MIN_BOX: usize = 4;
+const DFA_MAX_BOX: usize = 16;
+
+/// Windows with fewer beats give no alpha1
+const MIN_DFA_BEATS: usize = 50;
+
+/// DFA alpha1 is computed over windows of this length, every step
+pub const DEFAULT_DFA_WINDOW_SECONDS: f64 = 120.0;
+pub const DEFAULT_DFA_STEP_SECONDS: f64 = 30.0;
+
+/// DFA alpha1 at the aerobic (VT1) and anaerobic (VT2) thresholds
+const AEROBIC_THRESHOLD_ALPHA1: f64 = 0.75;
+const ANAEROBIC_THRESHOLD_ALPHA1: f64 = 0.5;
+
+/// One beat-to-beat interval and its artifact-corrected value
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RrInterval {
+    /// Seconds since the first beat
+    pub elapsed_s: f64,
+    pub rr_ms: f64,
+    pub corrected_ms: f64,
+    pub artifact: bool,
+}
+
+/// DFA alpha1 over the window ending at `elapsed_s`, with the window's
+/// mean heart rate
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DfaWindow {
+    pub elapsed_s: f64,
+    pub alpha1: f64,
+    pub heart_rate: f64,
+    pub rmssd_ms: Option<f64>,
+}
+
+/// Time-domain HRV of a whole workout and DFA alpha1 over time. Thresholds
+/// are the heart rates where alpha1, fitted linearly against heart rate,
+/// crosses 0.75 (aerobic) and 0.5 (anaerobic); they are only given when
+/// the workout's windows span them.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HrvAnalysis {
+    pub beats: usize,
+    pub artifacts: usize,
+    pub artifact_percent: f64,
+    pub mean_rr_ms: Option<f64>,
+    pub rmssd_ms: Option<f64>,
+    pub sdnn_ms: Option<f64>,
+    pub dfa_alpha1: Vec<DfaWindow>,
+    pub aerobic_threshold_hr: Option<f64>,
+    pub anaerobic_threshold_hr: Option<f64>,
+}
+
+fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    Some(if values.len() % 2 == 0 { (values[mid - 1] + values[mid]) / 2.0 } else { values[mid] })
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Flags intervals outside the plausible range or more than 20% off the
+/// median of the beats around them, and replaces them with that median.
+/// Elapsed time follows the recorded intervals, artifacts included.
+pub fn correct_rr_intervals(rr_ms: &[f64]) -> Vec<RrInterval> {
+    let plausible = |rr: f64| (MIN_RR_MS..=MAX_RR_MS).contains(&rr);
+    let mut elapsed_ms = 0.0;
+    rr_ms.iter()
+        .enumerate()
+        .map(|(i, &rr)| {
+            elapsed_ms += rr;
+            let start = i.saturating_sub(MEDIAN_HALF_WIDTH);
+            let end = (i + MEDIAN_HALF_WIDTH + 1).min(rr_ms.len());
+            let mut neighbours: Vec<f64> = (start..end)
+                .filter(|j| *j != i)
+                .map(|j| rr_ms[j])
+                .filter(|rr| plausible(*rr))
+                .collect();
+            let local = median(&mut neighbours);
+            let artifact = !plausible(rr)
+                || local.is_some_and(|m| (rr - m).abs() > ARTIFACT_THRESHOLD * m);
+            RrInterval {
+                elapsed_s: elapsed_ms / 1000.0,
+                rr_ms: rr,
+                corrected_ms: if artifact { local.unwrap_or(rr.clamp(MIN_RR_MS, MAX_RR_MS)) } else { rr },
+                artifact,
+            }
+        })
+        .collect()
+}
+
+/// Root mean square of successive differences
+pub fn rmssd(rr_ms: &[f64]) -> Option<f64> {
+    let diffs: Vec<f64> = rr_ms.windows(2).map(|w| (w[1] - w[0]).powi(2)).collect();
+    mean(&diffs).map(f64::sqrt)
+}
+
+/// Standard deviation of the intervals
+pub fn sdnn(rr_ms: &[f64]) -> Option<f64> {
+    let avg = mean(rr_ms)?;
+    let variance = rr_ms.iter().map(|rr| (rr - avg).powi(2)).sum::<f64>() / rr_ms.len() as f64;
+    Some(variance.sqrt())
+}
+
+/// Least-squares slope and intercept of `y` against `x`
+fn linear_fit(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    if sxx == 0.0 {
+        return None;
+    }
+    let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let slope = sxy / sxx;
+    Some((slope, mean_y - slope * mean_x))
+}
+
+/// Short-term scaling exponent of detrended fluctuation analysis over box
+/// sizes of 4 to 16 beats
+pub fn dfa_alpha1(rr_ms: &[f64]) -> Option<f64> {
+    if rr_ms.len() < MIN_DFA_BEATS {
+        return None;
+    }
+    let avg = mean(rr_ms)?;
+    let mut profile = Vec::with_capacity(rr_ms.len());
+    let mut sum = 0.0;
+    for rr in rr_ms {
+        sum += rr - avg;
+        profile.push(sum);
+    }
+
+    let mut fluctuations = Vec::new();
+    for n in DFA_MIN_BOX..=DFA_MAX_BOX {
+        let boxes = profile.len() / n;
+        let mut squares = 0.0;
+        for chunk in profile.chunks_exact(n).take(boxes) {
+            let points: Vec<(f64, f64)> = chunk.iter().enumerate().map(|(i, y)| (i as f64, *y)).collect();
+            let (slope, intercept) = linear_fit(&points)?;
+            squares += points.iter().map(|(x, y)| (y - (intercept + slope * x)).powi(2)).sum::<f64>();
+        }
+        let fluctuation = (squares / (boxes * n) as f64).sqrt();
+        if fluctuation > 0.0 {
+            fluctuations.push(((n as f64).ln(), fluctuation.ln()));
+        }
+    }
+    linear_fit(&fluctuations).map(|(slope, _)| slope)
+}
+
+/// Heart rate where the fitted alpha1 crosses `alpha1`, when the windows'
+/// heart rates reach it
+fn threshold_heart_rate(windows: &[DfaWindow], fit: Option<(f64, f64)>, alpha1: f64) -> Option<f64> {
+    let (slope, intercept) = fit.filter(|(slope, _)| *slope < 0.0)?;
+    let hr = (alpha1 - intercept) / slope;
+    let low = windows.iter().map(|w| w.heart_rate).fold(f64::INFINITY, f64::min);
+    let high = windows.iter().map(|w| w.heart_rate).fold(f64::NEG_INFINITY, f64::max);
+    (low..=high).contains(&hr).then_some(hr)
+}
+
+/// HRV metrics of a workout's corrected intervals, with DFA alpha1 over
+/// windows of `window_seconds` every `step_seconds`
+pub fn analyze_hrv(intervals: &[RrInterval], window_seconds: f64, step_seconds: f64) -> HrvAnalysis {
+    let corrected: Vec<f64> = intervals.iter().map(|i| i.corrected_ms).collect();
+    let artifacts = intervals.iter().filter(|i| i.artifact).count();
+
+    let mut dfa = Vec::new();
+    let total = intervals.last().map(|i| i.elapsed_s).unwrap_or(0.0);
+    let mut end = window_seconds;
+    while step_seconds > 0.0 && end <= total {
+        let window: Vec<f64> = intervals.iter()
+            .filter(|i| i.elapsed_s > end - window_seconds && i.elapsed_s <= end)
+            .map(|i| i.corrected_ms)
+            .collect();
+        if let (Some(alpha1), Some(mean_rr)) = (dfa_alpha1(&window), mean(&window)) {
+            dfa.push(DfaWindow {
+                elapsed_s: end,
+                alpha1,
+                heart_rate: 60_000.0 / mean_rr,
+                rmssd_ms: rmssd(&window),
+            });
+        }
+        end += step_seconds;
+    }
+
+    let points: Vec<(f64, f64)> = dfa.iter().map(|w| (w.heart_rate, w.alpha1)).collect();
+    let fit = linear_fit(&points);
+    HrvAnalysis {
+        beats: intervals.len(),
+        artifacts,
+        artifact_percent: if intervals.is_empty() { 0.0 } else { artifacts as f64 * 100.0 / intervals.len() as f64 },
+        mean_rr_ms: mean(&corrected),
+        rmssd_ms: rmssd(&corrected),
+        sdnn_ms: sdnn(&corrected),
+        aerobic_threshold_hr: threshold_heart_rate(&dfa, fit, AEROBIC_THRESHOLD_ALPHA1),
+        anaerobic_threshold_hr: threshold_heart_rate(&dfa, fit, ANAEROBIC_THRESHOLD_ALPHA1),
+        dfa_alpha1: dfa,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seeded standard normal samples (xorshift64 and Box-Muller)
+    fn gaussian_noise(seed: u64, count: usize) -> Vec<f64> {
+        let mut state = seed;
+        let mut uniform = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        (0..count)
+            .map(|_| {
+                let u1 = uniform().max(f64::MIN_POSITIVE);
+                let u2 = uniform();
+                (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn corrects_missed_and_extra_beats() {
+        let mut rr = vec![800.0; 20];
+        rr[5] = 1600.0; // a missed beat
+        rr[12] = 420.0; // an extra beat
+        rr[16] = 2500.0; // longer than any heartbeat
+        rr[18] = 850.0; // within 20% of its neighbours
+
+        let intervals = correct_rr_intervals(&rr);
+        let artifacts: Vec<usize> = intervals.iter().enumerate().filter(|(_, i)| i.artifact).map(|(n, _)| n).collect();
+
+        assert_eq!(artifacts, vec![5, 12, 16]);
+        assert_eq!(intervals[5].corrected_ms, 800.0);
+        assert_eq!(intervals[12].corrected_ms, 800.0);
+        assert_eq!(intervals[18].corrected_ms, 850.0);
+        // Elapsed time keeps the recorded intervals
+        assert_eq!(intervals[5].elapsed_s, 5.6);
+    }
+
+    #[test]
+    fn time_domain_metrics() {
+        let rr = [800.0, 820.0, 780.0, 800.0];
+
+        assert_eq!(rmssd(&rr), Some(800.0f64.sqrt()));
+        assert_eq!(sdnn(&rr), Some(200.0f64.sqrt()));
+        assert_eq!(rmssd(&[800.0]), None);
+    }
+
+    #[test]
+    fn dfa_alpha1_of_uncorrelated_and_brownian_intervals() {
+        let noise = gaussian_noise(0x5eed, 2000);
+        let white: Vec<f64> = noise.iter().map(|n| 800.0 + 20.0 * n).collect();
+        let brownian: Vec<f64> = noise
+            .iter()
+            .scan(800.0, |rr, n| {
+                *rr += 5.0 * n;
+                Some(*rr)
+            })
+            .collect();
+
+        // Short boxes put uncorrelated intervals a little above the
+        // asymptotic 0.5, and Brownian ones a little above 1.5
+        let white_alpha1 = dfa_alpha1(&white).unwrap();
+        assert!((0.45..0.65).contains(&white_alpha1), "white noise alpha1 {white_alpha1}");
+        let brownian_alpha1 = dfa_alpha1(&brownian).unwrap();
+        assert!((1.4..1.7).contains(&brownian_alpha1), "Brownian noise alpha1 {brownian_alpha1}");
+
+        assert_eq!(dfa_alpha1(&white[..MIN_DFA_BEATS - 1]), None);
+    }
+}
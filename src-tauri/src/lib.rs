@@ -1,11 +1,31 @@
+mod analytics;
 mod database;
+mod developer_fields;
+mod export;
 mod fit_parser;
-
-use database::{Database, InsertWorkout, Stats, MonthlyStats, StreakInfo, PersonalRecords, ContributionDay, WeeklySummary, Workout, WorkoutSummary};
-use fit_parser::{parse_fit_file, GpsPoint, ChartData};
+mod gpx_parser;
+mod hrv;
+mod migrations;
+mod samples;
+mod search;
+mod sports;
+mod swim;
+mod tcx_parser;
+mod track;
+
+use analytics::{default_pace_zones, default_power_zones, moving_seconds, power_metrics, workout_tss, AthleteProfile, AthleteSettings, DateRange, HrZoneSettings, Period, TrainingLoadDay, Zone};
+use database::{BestEffortRecord, CategoryBreakdown, Database, DeviceSummary, HrvSummary, Import, InsertWorkout, Lap, NewRecord, NewSourceFile, NewWorkout, PowerCurves, Stats, Tag, MonthlyStats, StreakInfo, PersonalRecord, TrainingLoadRefresh, ContributionDay, WeeklySummary, Workout, WorkoutSummary, ZoneDistribution, ZoneTime};
+use export::ExportFormat;
+use fit_parser::{build_chart_data, parse_fit_bytes, DeviceInfo, GpsPoint, ChartData, ParsedFitData};
+use gpx_parser::parse_gpx_bytes;
+use hrv::{analyze_hrv, HrvAnalysis, DEFAULT_DFA_STEP_SECONDS, DEFAULT_DFA_WINDOW_SECONDS};
+use samples::{gps_points, merge_samples, sensor_points, Sample};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use tauri::{Manager, State};
+use sports::{default_sport_categories, SportCategory};
+use std::path::{Path, PathBuf};
+use swim::{analyze_swim, SwimAnalysis};
+use tcx_parser::parse_tcx_bytes;
+use tauri::{AppHandle, Emitter, Manager, State};
 
 struct AppState {
     db: Database,
@@ -28,9 +48,237 @@ struct UploadResult {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct ActivityBreakdown {
-    name: String,
-    count: i64,
+struct ReprocessSummary {
+    reprocessed: i64,
+    failed: i64,
+    errors: Vec<String>,
+}
+
+fn to_insert_workout(
+    parsed: &ParsedFitData,
+    samples: &[Sample],
+    settings: &AthleteSettings,
+    session_index: Option<i64>,
+) -> InsertWorkout {
+    let power = power_metrics(&parsed.sensor_data);
+    let normalized_power = power.as_ref().map(|p| p.normalized_power);
+    // Stops with the timer paused aren't scored, nor counted as moving.
+    // Moving time can't exceed the time the timer ran; without speed data
+    // the timer time is all we know.
+    let timer_seconds = parsed.timer_seconds.or(parsed.duration_seconds);
+    let estimate = workout_tss(timer_seconds, parsed.avg_heart_rate, normalized_power, settings);
+    let moving_seconds = match moving_seconds(samples, &parsed.pauses) {
+        Some(moving) => Some((moving.round() as i64).min(timer_seconds.unwrap_or(i64::MAX))),
+        None => timer_seconds,
+    };
+    let avg_moving_speed_mps = match (parsed.distance_meters, moving_seconds) {
+        (Some(d), Some(t)) if t > 0 => Some(d / t as f64),
+        _ => None,
+    };
+
+    InsertWorkout {
+        // Legs share their parent's file
+        file_hash: session_index.is_none().then(|| parsed.file_hash.clone()),
+        filename: parsed.filename.clone(),
+        name: None,
+        workout_type: parsed.workout_type.clone(),
+        sub_sport: parsed.sub_sport.clone(),
+        start_time: parsed.start_time.clone(),
+        end_time: parsed.end_time.clone(),
+        duration_seconds: parsed.duration_seconds,
+        timer_seconds: parsed.timer_seconds,
+        moving_seconds,
+        distance_meters: parsed.distance_meters,
+        total_calories: parsed.total_calories,
+        avg_heart_rate: parsed.avg_heart_rate,
+        max_heart_rate: parsed.max_heart_rate,
+        avg_power_watts: parsed.avg_power_watts,
+        max_power_watts: parsed.max_power_watts,
+        avg_cadence: parsed.avg_cadence,
+        max_cadence: parsed.max_cadence,
+        avg_speed_mps: parsed.avg_speed_mps,
+        max_speed_mps: parsed.max_speed_mps,
+        elevation_gain_meters: parsed.elevation_gain_meters,
+        elevation_loss_meters: parsed.elevation_loss_meters,
+        pool_length_meters: parsed.pool_length_meters,
+        session_index,
+        tss: estimate.tss,
+        normalized_power_watts: normalized_power,
+        intensity_factor: estimate.intensity_factor,
+        variability_index: power.as_ref().and_then(|p| p.variability_index),
+        work_kj: power.as_ref().map(|p| p.work_kj),
+        tss_source: estimate.source.map(str::to_string),
+        avg_moving_speed_mps,
+        device_manufacturer: parsed.creator.manufacturer.clone(),
+        device_product: parsed.creator.product.clone(),
+        device_serial_number: parsed.creator.serial_number,
+        file_created_at: parsed.creator.time_created.clone(),
+    }
+}
+
+/// A parsed workout and its multisport legs, numbered by session, ready to
+/// insert or to replace the stored one with. Legs are scored with the
+/// settings of the whole activity.
+fn new_workout<'a>(
+    parsed: &'a ParsedFitData,
+    settings: &AthleteSettings,
+    session_index: Option<i64>,
+) -> NewWorkout<'a> {
+    let samples = merge_samples(&parsed.gps_data, &parsed.sensor_data);
+    NewWorkout {
+        workout: to_insert_workout(parsed, &samples, settings, session_index),
+        samples,
+        developer_fields: &parsed.developer_fields,
+        laps: &parsed.laps,
+        lengths: &parsed.lengths,
+        rr_intervals_ms: &parsed.rr_intervals_ms,
+        devices: &parsed.devices,
+        legs: parsed.legs.iter()
+            .enumerate()
+            .map(|(i, leg)| new_workout(leg, settings, Some(i as i64)))
+            .collect(),
+        source: None,
+    }
+}
+
+/// Extensions of the activity files we can import
+const ACTIVITY_EXTENSIONS: &[&str] = &["fit", "gpx", "tcx"];
+
+/// The file format, taken from the extension, when it is one we can import
+fn activity_format(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .filter(|ext| ACTIVITY_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Parses activity file contents with the parser for the given format
+fn parse_activity_bytes(format: &str, data: &[u8], filename: &str) -> Result<ParsedFitData, String> {
+    match format {
+        "gpx" => parse_gpx_bytes(data, filename),
+        "tcx" => parse_tcx_bytes(data, filename),
+        _ => parse_fit_bytes(data, filename),
+    }
+}
+
+/// Re-parses a workout from its stored original file and replaces it and its
+/// legs in one transaction
+fn reprocess_stored_workout(db: &Database, id: i64, refresh: &mut TrainingLoadRefresh) -> Result<(), String> {
+    let workout = db.get_workout(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Workout not found".to_string())?;
+
+    // Legs share their parent's file, so reprocess the whole activity
+    if let Some(parent_id) = workout.parent_id {
+        return reprocess_stored_workout(db, parent_id, refresh);
+    }
+
+    let source = match workout.file_hash.as_deref() {
+        Some(file_hash) => db.get_source_file(file_hash).map_err(|e| e.to_string())?,
+        None => None,
+    };
+    let source = source.ok_or_else(|| "The original file was not kept for this workout".to_string())?;
+    let parsed = parse_activity_bytes(&source.format, &source.data, &source.filename)?;
+    db.import(refresh, |import| {
+        let settings = import.athlete_settings(parsed.start_time.as_deref())?;
+        import.update_workout(id, &new_workout(&parsed, &settings, None))
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// An activity file read and parsed, ready to import
+struct ActivityFile {
+    format: String,
+    data: Vec<u8>,
+    parsed: ParsedFitData,
+}
+
+/// Reads and parses an activity file, outside of any transaction. Files that
+/// can't be read or parsed give their unsuccessful result.
+fn read_activity_file(file_path: &str) -> Result<ActivityFile, UploadResult> {
+    let path = PathBuf::from(file_path);
+    
+    if !path.exists() {
+        return Err(UploadResult {
+            success: false,
+            message: "File not found".to_string(),
+            workout_id: None,
+            duplicate: false,
+        });
+    }
+
+    let format = activity_format(&path).unwrap_or_else(|| "fit".to_string());
+    let filename = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let data = std::fs::read(&path).map_err(|e| UploadResult {
+        success: false,
+        message: format!("Failed to read file: {}", e),
+        workout_id: None,
+        duplicate: false,
+    })?;
+
+    // Parse the activity file
+    let parsed = parse_activity_bytes(&format, &data, &filename).map_err(|e| UploadResult {
+        success: false,
+        message: format!("Failed to parse file: {}", e),
+        workout_id: None,
+        duplicate: false,
+    })?;
+
+    Ok(ActivityFile { format, data, parsed })
+}
+
+/// Stores a parsed activity file. Files already stored get an unsuccessful
+/// result rather than an error.
+fn import_file(import: &mut Import, file: &ActivityFile, new_records: &mut Vec<NewRecord>) -> rusqlite::Result<UploadResult> {
+    let ActivityFile { format, data: file_data, parsed } = file;
+
+    // Check for duplicate
+    if import.workout_exists(&parsed.file_hash)? {
+        // Workouts imported before originals were kept get theirs filled in
+        import.store_source_file(&parsed.file_hash, &parsed.filename, format, file_data)?;
+        return Ok(UploadResult {
+            success: false,
+            message: "This workout has already been uploaded".to_string(),
+            workout_id: None,
+            duplicate: true,
+        });
+    }
+
+    let source = NewSourceFile { format, data: file_data };
+
+    // The same activity recorded on another device, or exported again, is
+    // kept as another recording of the stored workout
+    if let Some(start_time) = parsed.start_time.as_deref() {
+        let sport = parsed.workout_type.as_deref();
+        if let Some(existing) = import.find_same_activity(sport, start_time, parsed.duration_seconds)? {
+            import.link_recording(existing.id, &parsed.file_hash, &parsed.filename, &parsed.devices, &source)?;
+            let device = existing.device_product.as_deref().unwrap_or("another file");
+            return Ok(UploadResult {
+                success: true,
+                message: format!("Added as another recording of the activity stored from {}", device),
+                workout_id: Some(existing.id),
+                duplicate: true,
+            });
+        }
+    }
+
+    // Insert into database, keeping the original file for reprocessing
+    let settings = import.athlete_settings(parsed.start_time.as_deref())?;
+    let mut workout = new_workout(parsed, &settings, None);
+    workout.source = Some(source);
+    let workout_id = import.insert_workout(&workout)?;
+    new_records.extend(import.new_records(workout_id)?);
+
+    Ok(UploadResult {
+        success: true,
+        message: "Workout uploaded successfully".to_string(),
+        workout_id: Some(workout_id),
+        duplicate: false,
+    })
 }
 
 // Commands
@@ -49,6 +297,12 @@ fn get_workouts(
     max_distance: Option<f64>,
     min_duration: Option<i64>,
     max_duration: Option<i64>,
+    duration_basis: Option<String>,
+    device: Option<String>,
+    category: Option<String>,
+    sub_sport: Option<String>,
+    sort_by: Option<String>,
+    sort_order: Option<String>,
 ) -> Result<WorkoutsResponse, String> {
     let page = page.unwrap_or(1);
     let per_page = per_page.unwrap_or(15);
@@ -66,6 +320,12 @@ fn get_workouts(
         max_distance,
         min_duration,
         max_duration,
+        duration_basis.as_deref(),
+        device.as_deref(),
+        category.as_deref(),
+        sub_sport.as_deref(),
+        sort_by.as_deref(),
+        sort_order.as_deref(),
     ).map_err(|e| e.to_string())?;
 
     let total = state.db.get_total_workout_count(
@@ -78,6 +338,10 @@ fn get_workouts(
         max_distance,
         min_duration,
         max_duration,
+        duration_basis.as_deref(),
+        device.as_deref(),
+        category.as_deref(),
+        sub_sport.as_deref(),
     ).map_err(|e| e.to_string())?;
 
     Ok(WorkoutsResponse {
@@ -93,28 +357,75 @@ fn get_workout(state: State<AppState>, id: i64) -> Result<Option<Workout>, Strin
     state.db.get_workout(id).map_err(|e| e.to_string())
 }
 
+/// Chart series for a workout, optionally limited to an elapsed-time range in
+/// seconds so the frontend can zoom in at full resolution. Developer fields
+/// are limited to the `developer_channels` keys when given.
 #[tauri::command]
-fn get_workout_chart_data(state: State<AppState>, id: i64) -> Result<Option<ChartData>, String> {
-    let chart_json = state.db.get_workout_chart_data(id).map_err(|e| e.to_string())?;
-    match chart_json {
-        Some(json) => {
-            let data: ChartData = serde_json::from_str(&json).map_err(|e| e.to_string())?;
-            Ok(Some(data))
+fn get_workout_chart_data(
+    state: State<AppState>,
+    id: i64,
+    start_seconds: Option<f64>,
+    end_seconds: Option<f64>,
+    developer_channels: Option<Vec<String>>,
+) -> Result<Option<ChartData>, String> {
+    let mut samples = state.db.get_samples(id, start_seconds, end_seconds).map_err(|e| e.to_string())?;
+    if samples.is_empty() {
+        return Ok(None);
+    }
+    let mut fields = state.db.get_developer_fields(id).map_err(|e| e.to_string())?;
+    if let Some(channels) = developer_channels {
+        fields.retain(|f| channels.contains(&f.key));
+    }
+    if !fields.is_empty() {
+        let values = state.db.get_developer_values(id, start_seconds, end_seconds).map_err(|e| e.to_string())?;
+        for (sample, developer) in samples.iter_mut().zip(values) {
+            sample.developer = developer;
         }
-        None => Ok(None)
     }
+    Ok(Some(build_chart_data(&sensor_points(&samples), &fields)))
 }
 
 #[tauri::command]
-fn get_workout_gps_data(state: State<AppState>, id: i64) -> Result<Option<Vec<GpsPoint>>, String> {
-    let gps_json = state.db.get_workout_gps_data(id).map_err(|e| e.to_string())?;
-    match gps_json {
-        Some(json) => {
-            let data: Vec<GpsPoint> = serde_json::from_str(&json).map_err(|e| e.to_string())?;
-            Ok(Some(data))
-        }
-        None => Ok(None)
+fn get_workout_gps_data(
+    state: State<AppState>,
+    id: i64,
+    start_seconds: Option<f64>,
+    end_seconds: Option<f64>,
+) -> Result<Option<Vec<GpsPoint>>, String> {
+    let samples = state.db.get_samples(id, start_seconds, end_seconds).map_err(|e| e.to_string())?;
+    let points = gps_points(&samples);
+    if points.is_empty() {
+        return Ok(None);
     }
+    Ok(Some(points))
+}
+
+#[tauri::command]
+fn export_workout(state: State<AppState>, id: i64, format: String, file_path: String) -> Result<(), String> {
+    let format = ExportFormat::parse(&format)?;
+    let workout = state.db.get_workout(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Workout not found".to_string())?;
+    let laps = state.db.get_workout_laps(id).map_err(|e| e.to_string())?;
+    let samples = state.db.get_samples(id, None, None).map_err(|e| e.to_string())?;
+
+    export::export_workout(&workout, &laps, &samples, format, &PathBuf::from(file_path))
+}
+
+#[tauri::command]
+fn get_workout_laps(state: State<AppState>, id: i64) -> Result<Vec<Lap>, String> {
+    state.db.get_workout_laps(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_swim_analysis(state: State<AppState>, id: i64) -> Result<Option<SwimAnalysis>, String> {
+    let workout = match state.db.get_workout(id).map_err(|e| e.to_string())? {
+        Some(w) => w,
+        None => return Ok(None),
+    };
+    let laps = state.db.get_workout_laps(id).map_err(|e| e.to_string())?;
+    let lengths = state.db.get_swim_lengths(id).map_err(|e| e.to_string())?;
+    Ok(analyze_swim(workout.pool_length_meters, &laps, &lengths))
 }
 
 #[tauri::command]
@@ -129,8 +440,7 @@ fn rename_workout(state: State<AppState>, id: i64, name: String) -> Result<bool,
 
 #[tauri::command]
 fn update_workout_tags(state: State<AppState>, id: i64, tags: Vec<String>) -> Result<bool, String> {
-    let tags_json = serde_json::to_string(&tags).map_err(|e| e.to_string())?;
-    state.db.update_tags(id, &tags_json).map_err(|e| e.to_string())
+    state.db.update_tags(id, &tags).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -154,8 +464,8 @@ fn get_streak_info(state: State<AppState>) -> Result<StreakInfo, String> {
 }
 
 #[tauri::command]
-fn get_personal_records(state: State<AppState>) -> Result<PersonalRecords, String> {
-    state.db.get_personal_records().map_err(|e| e.to_string())
+fn get_personal_records(state: State<AppState>, sport: Option<String>) -> Result<Vec<PersonalRecord>, String> {
+    state.db.get_personal_records(sport.as_deref()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -169,105 +479,239 @@ fn get_weekly_summary(state: State<AppState>, weeks: Option<i64>) -> Result<Vec<
 }
 
 #[tauri::command]
-fn get_activity_breakdown(state: State<AppState>) -> Result<Vec<ActivityBreakdown>, String> {
-    let breakdown = state.db.get_activity_breakdown().map_err(|e| e.to_string())?;
-    Ok(breakdown.into_iter().map(|(name, count)| ActivityBreakdown { name, count }).collect())
+fn get_activity_breakdown(state: State<AppState>) -> Result<Vec<CategoryBreakdown>, String> {
+    state.db.get_activity_breakdown().map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn get_all_tags(state: State<AppState>) -> Result<Vec<String>, String> {
+fn get_sport_categories(state: State<AppState>) -> Result<Vec<SportCategory>, String> {
+    state.db.get_sport_categories().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_default_sport_categories() -> Vec<SportCategory> {
+    default_sport_categories()
+}
+
+#[tauri::command]
+fn update_sport_categories(state: State<AppState>, categories: Vec<SportCategory>) -> Result<bool, String> {
+    state.db.update_sport_categories(&categories).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+#[tauri::command]
+fn get_all_tags(state: State<AppState>) -> Result<Vec<Tag>, String> {
     state.db.get_all_tags().map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn get_workout_by_date(state: State<AppState>, date: String) -> Result<Option<Workout>, String> {
-    state.db.get_workout_by_date(&date).map_err(|e| e.to_string())
+fn set_tag_color(state: State<AppState>, name: String, color: Option<String>) -> Result<bool, String> {
+    state.db.set_tag_color(&name, color.as_deref()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn upload_fit_file(state: State<AppState>, file_path: String) -> Result<UploadResult, String> {
-    let path = PathBuf::from(&file_path);
-    
-    if !path.exists() {
-        return Ok(UploadResult {
-            success: false,
-            message: "File not found".to_string(),
-            workout_id: None,
-            duplicate: false,
-        });
+fn rename_tag(state: State<AppState>, old_name: String, new_name: String) -> Result<bool, String> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        return Err("Tag name cannot be empty".to_string());
     }
+    state.db.rename_tag(&old_name, new_name).map_err(|e| e.to_string())
+}
 
-    // Parse the FIT file
-    let parsed = match parse_fit_file(&path) {
-        Ok(data) => data,
-        Err(e) => {
-            return Ok(UploadResult {
-                success: false,
-                message: format!("Failed to parse FIT file: {}", e),
-                workout_id: None,
-                duplicate: false,
-            });
-        }
-    };
+#[tauri::command]
+fn merge_tags(state: State<AppState>, sources: Vec<String>, target: String) -> Result<bool, String> {
+    let target = target.trim();
+    if target.is_empty() {
+        return Err("Tag name cannot be empty".to_string());
+    }
+    state.db.merge_tags(&sources, target).map_err(|e| e.to_string())
+}
 
-    // Check for duplicate
-    if state.db.workout_exists(&parsed.file_hash).map_err(|e| e.to_string())? {
-        return Ok(UploadResult {
-            success: false,
-            message: "This workout has already been uploaded".to_string(),
-            workout_id: None,
-            duplicate: true,
-        });
+#[tauri::command]
+fn delete_tag(state: State<AppState>, name: String) -> Result<bool, String> {
+    state.db.delete_tag(&name).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_athlete_settings(state: State<AppState>, date: Option<String>) -> Result<AthleteSettings, String> {
+    state.db.get_athlete_settings(date.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_athlete_profile(state: State<AppState>, date: Option<String>) -> Result<Option<AthleteProfile>, String> {
+    state.db.get_athlete_profile(date.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_athlete_profile_history(state: State<AppState>) -> Result<Vec<AthleteProfile>, String> {
+    state.db.get_athlete_profile_history().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_athlete_profile(state: State<AppState>, profile: AthleteProfile) -> Result<bool, String> {
+    state.db.update_athlete_profile(&profile).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_athlete_profile(state: State<AppState>, effective_date: String) -> Result<bool, String> {
+    state.db.delete_athlete_profile(&effective_date).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_training_load(state: State<AppState>, range: Option<DateRange>) -> Result<Vec<TrainingLoadDay>, String> {
+    state.db.get_training_load(&range.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_hr_zones(state: State<AppState>) -> Result<Option<HrZoneSettings>, String> {
+    state.db.get_hr_zones().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_hr_zones(state: State<AppState>, zones: HrZoneSettings) -> Result<bool, String> {
+    state.db.update_hr_zones(&zones).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_power_zones(state: State<AppState>) -> Result<Vec<Zone>, String> {
+    state.db.get_power_zones().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_default_power_zones() -> Vec<Zone> {
+    default_power_zones()
+}
+
+#[tauri::command]
+fn update_power_zones(state: State<AppState>, zones: Vec<Zone>) -> Result<bool, String> {
+    state.db.update_power_zones(&zones).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_pace_zones(state: State<AppState>) -> Result<Vec<Zone>, String> {
+    state.db.get_pace_zones().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_default_pace_zones() -> Vec<Zone> {
+    default_pace_zones()
+}
+
+#[tauri::command]
+fn update_pace_zones(state: State<AppState>, zones: Vec<Zone>) -> Result<bool, String> {
+    state.db.update_pace_zones(&zones).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_workout_zones(state: State<AppState>, id: i64, zone_type: String) -> Result<Vec<ZoneTime>, String> {
+    state.db.get_workout_zones(id, &zone_type).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_zone_distribution(
+    state: State<AppState>,
+    zone_type: String,
+    period: Period,
+    range: Option<DateRange>,
+) -> Result<Vec<ZoneDistribution>, String> {
+    state.db.get_zone_distribution(&zone_type, period, &range.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_best_efforts(state: State<AppState>, sport: Option<String>) -> Result<Vec<BestEffortRecord>, String> {
+    state.db.get_best_efforts(sport.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_power_curve(state: State<AppState>, sport: Option<String>, range: Option<DateRange>) -> Result<PowerCurves, String> {
+    state.db.get_power_curve(sport.as_deref(), &range.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+/// RMSSD, SDNN and DFA alpha1 over time of a workout's R-R intervals, with
+/// alpha1 over windows of `window_seconds` every `step_seconds` (2 min
+/// every 30 s by default). `None` when the workout has no intervals.
+#[tauri::command]
+fn get_workout_hrv(
+    state: State<AppState>,
+    id: i64,
+    window_seconds: Option<f64>,
+    step_seconds: Option<f64>,
+) -> Result<Option<HrvAnalysis>, String> {
+    let intervals = state.db.get_rr_intervals(id).map_err(|e| e.to_string())?;
+    if intervals.is_empty() {
+        return Ok(None);
     }
+    Ok(Some(analyze_hrv(
+        &intervals,
+        window_seconds.unwrap_or(DEFAULT_DFA_WINDOW_SECONDS),
+        step_seconds.unwrap_or(DEFAULT_DFA_STEP_SECONDS),
+    )))
+}
 
-    // Insert into database
-    let insert_workout = InsertWorkout {
-        file_hash: parsed.file_hash,
-        filename: parsed.filename,
-        name: None,
-        workout_type: parsed.workout_type,
-        start_time: parsed.start_time,
-        end_time: parsed.end_time,
-        duration_seconds: parsed.duration_seconds,
-        distance_meters: parsed.distance_meters,
-        total_calories: parsed.total_calories,
-        avg_heart_rate: parsed.avg_heart_rate,
-        max_heart_rate: parsed.max_heart_rate,
-        avg_power_watts: parsed.avg_power_watts,
-        max_power_watts: parsed.max_power_watts,
-        avg_cadence: parsed.avg_cadence,
-        max_cadence: parsed.max_cadence,
-        avg_speed_mps: parsed.avg_speed_mps,
-        max_speed_mps: parsed.max_speed_mps,
-        elevation_gain_meters: parsed.elevation_gain_meters,
-        elevation_loss_meters: parsed.elevation_loss_meters,
-        gps_data: Some(serde_json::to_string(&parsed.gps_data).unwrap_or_default()),
-        sensor_data: Some(serde_json::to_string(&parsed.sensor_data).unwrap_or_default()),
-        chart_data: Some(serde_json::to_string(&parsed.chart_data).unwrap_or_default()),
-    };
+#[tauri::command]
+fn get_hrv_history(state: State<AppState>, range: Option<DateRange>) -> Result<Vec<HrvSummary>, String> {
+    state.db.get_hrv_history(&range.unwrap_or_default()).map_err(|e| e.to_string())
+}
 
-    let workout_id = state.db.insert_workout(&insert_workout).map_err(|e| e.to_string())?;
+/// The recording device and connected sensors of a workout
+#[tauri::command]
+fn get_workout_devices(state: State<AppState>, id: i64) -> Result<Vec<DeviceInfo>, String> {
+    state.db.get_workout_devices(id).map_err(|e| e.to_string())
+}
 
-    Ok(UploadResult {
-        success: true,
-        message: "Workout uploaded successfully".to_string(),
-        workout_id: Some(workout_id),
-        duplicate: false,
-    })
+/// Devices to filter the workout list by
+#[tauri::command]
+fn get_devices(state: State<AppState>) -> Result<Vec<DeviceSummary>, String> {
+    state.db.get_devices().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_workout_by_date(state: State<AppState>, date: String) -> Result<Option<Workout>, String> {
+    state.db.get_workout_by_date(&date).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn upload_fit_file(state: State<AppState>, app: AppHandle, file_path: String) -> Result<UploadResult, String> {
+    let mut results = upload_fit_files(state, app, vec![file_path])?;
+    Ok(results.remove(0))
 }
 
 #[tauri::command]
-fn upload_fit_files(state: State<AppState>, file_paths: Vec<String>) -> Result<Vec<UploadResult>, String> {
-    let mut results = Vec::new();
-    for path in file_paths {
-        let result = upload_fit_file(state.clone(), path)?;
-        results.push(result);
+fn upload_fit_files(state: State<AppState>, app: AppHandle, file_paths: Vec<String>) -> Result<Vec<UploadResult>, String> {
+    let mut new_records = Vec::new();
+    let mut refresh = TrainingLoadRefresh::default();
+    // Each file is stored in a transaction of its own, after it is read and
+    // parsed, so other commands aren't held up by a large import
+    let results = file_paths.iter()
+        .map(|path| {
+            let file = match read_activity_file(path) {
+                Ok(file) => file,
+                Err(result) => return result,
+            };
+            state.db
+                .import(&mut refresh, |import| import_file(import, &file, &mut new_records))
+                .unwrap_or_else(|e| UploadResult {
+                    success: false,
+                    message: format!("Failed to store workout: {}", e),
+                    workout_id: None,
+                    duplicate: false,
+                })
+        })
+        .collect();
+    if let Err(e) = state.db.refresh_training_load(refresh) {
+        log::warn!("Failed to refresh training load: {}", e);
+    }
+
+    if !new_records.is_empty() {
+        if let Err(e) = app.emit("personal-record", &new_records) {
+            log::warn!("Failed to emit personal-record event: {}", e);
+        }
     }
     Ok(results)
 }
 
 #[tauri::command]
-fn upload_fit_folder(state: State<AppState>, folder_path: String) -> Result<Vec<UploadResult>, String> {
+fn upload_fit_folder(state: State<AppState>, app: AppHandle, folder_path: String) -> Result<Vec<UploadResult>, String> {
     let path = PathBuf::from(&folder_path);
     
     if !path.exists() || !path.is_dir() {
@@ -281,18 +725,16 @@ fn upload_fit_folder(state: State<AppState>, folder_path: String) -> Result<Vec<
 
     let mut file_paths = Vec::new();
     
-    // Recursively find all .fit files
+    // Recursively find all .fit, .gpx and .tcx files
     fn find_fit_files(dir: &PathBuf, files: &mut Vec<String>) {
         if let Ok(entries) = std::fs::read_dir(dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
                 if path.is_dir() {
                     find_fit_files(&path, files);
-                } else if let Some(ext) = path.extension() {
-                    if ext.to_ascii_lowercase() == "fit" {
-                        if let Some(path_str) = path.to_str() {
-                            files.push(path_str.to_string());
-                        }
+                } else if activity_format(&path).is_some() {
+                    if let Some(path_str) = path.to_str() {
+                        files.push(path_str.to_string());
                     }
                 }
             }
@@ -304,14 +746,42 @@ fn upload_fit_folder(state: State<AppState>, folder_path: String) -> Result<Vec<
     if file_paths.is_empty() {
         return Ok(vec![UploadResult {
             success: false,
-            message: "No FIT files found in folder".to_string(),
+            message: "No FIT, GPX or TCX files found in folder".to_string(),
             workout_id: None,
             duplicate: false,
         }]);
     }
     
     // Upload all found files
-    upload_fit_files(state, file_paths)
+    upload_fit_files(state, app, file_paths)
+}
+
+#[tauri::command]
+fn reprocess_workout(state: State<AppState>, id: i64) -> Result<bool, String> {
+    let mut refresh = TrainingLoadRefresh::default();
+    reprocess_stored_workout(&state.db, id, &mut refresh)?;
+    state.db.refresh_training_load(refresh).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+#[tauri::command]
+fn reprocess_all(state: State<AppState>) -> Result<ReprocessSummary, String> {
+    let ids = state.db.get_reprocessable_workout_ids().map_err(|e| e.to_string())?;
+    let mut summary = ReprocessSummary { reprocessed: 0, failed: 0, errors: Vec::new() };
+    let mut refresh = TrainingLoadRefresh::default();
+
+    for id in ids {
+        match reprocess_stored_workout(&state.db, id, &mut refresh) {
+            Ok(()) => summary.reprocessed += 1,
+            Err(e) => {
+                summary.failed += 1;
+                summary.errors.push(format!("Workout {}: {}", id, e));
+            }
+        }
+    }
+
+    state.db.refresh_training_load(refresh).map_err(|e| e.to_string())?;
+    Ok(summary)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -345,6 +815,9 @@ pub fn run() {
             get_workout_by_date,
             get_workout_chart_data,
             get_workout_gps_data,
+            get_workout_laps,
+            export_workout,
+            get_swim_analysis,
             delete_workout,
             rename_workout,
             update_workout_tags,
@@ -356,10 +829,41 @@ pub fn run() {
             get_contribution_calendar,
             get_weekly_summary,
             get_activity_breakdown,
+            get_sport_categories,
+            get_default_sport_categories,
+            update_sport_categories,
             get_all_tags,
+            set_tag_color,
+            rename_tag,
+            merge_tags,
+            delete_tag,
+            get_athlete_settings,
+            get_athlete_profile,
+            get_athlete_profile_history,
+            update_athlete_profile,
+            delete_athlete_profile,
+            get_training_load,
+            get_power_curve,
+            get_workout_hrv,
+            get_hrv_history,
+            get_workout_devices,
+            get_devices,
+            get_best_efforts,
+            get_hr_zones,
+            update_hr_zones,
+            get_power_zones,
+            get_default_power_zones,
+            update_power_zones,
+            get_pace_zones,
+            get_default_pace_zones,
+            update_pace_zones,
+            get_workout_zones,
+            get_zone_distribution,
             upload_fit_file,
             upload_fit_files,
             upload_fit_folder,
+            reprocess_workout,
+            reprocess_all,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
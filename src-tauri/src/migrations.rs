@@ -0,0 +1,1231 @@
+use crate::analytics::{moving_seconds, power_metrics};
+use crate::database::{
+    load_samples, recalculate_categories, recalculate_tss, recalculate_zones, refresh_training_load,
+    store_best_efforts, store_power_curve,
+};
+use crate::fit_parser::{GpsPoint, SensorPoint};
+use crate::samples::{merge_samples, sensor_points, Sample};
+use chrono::{Datelike, Local};
+use log::info;
+use rusqlite::{params, Connection, Result};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A schema change, applied once in order of `version`. The database's
+/// `PRAGMA user_version` records the last version that was applied.
+/// Shipped migrations never change: `apply` only runs SQL against the
+/// schema of its own version, and data derived from workouts is rebuilt
+/// through `backfills`. Data that needs rebuilding later gets a new version.
+struct Migration {
+    version: i64,
+    description: &'static str,
+    apply: fn(&Connection) -> Result<()>,
+    backfills: &'static [Backfill],
+}
+
+/// Data derived from the stored workouts that a migration needs rebuilt.
+/// Backfills run once every pending migration is applied, so they use the
+/// current schema and calculations. They run in the order listed here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Backfill {
+    /// NP, VI and work of workouts with power samples
+    PowerMetrics,
+    /// Mean-maximal power of workouts with power samples
+    PowerCurves,
+    /// Best efforts of workouts with distance samples
+    BestEfforts,
+    /// Moving time as the samples show it
+    MovingTime,
+    /// TSS against the athlete profile, from the power metrics above
+    Tss,
+    /// The CTL/ATL/TSB series, from the TSS above
+    TrainingLoad,
+    /// Time in zones of every workout with samples
+    Zones,
+    /// Display categories under the sport category mapping
+    Categories,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, description: "initial workouts table", apply: initial_schema, backfills: &[] },
+    Migration { version: 2, description: "workout notes", apply: workout_notes, backfills: &[] },
+    Migration { version: 3, description: "multisport legs", apply: multisport_legs, backfills: &[] },
+    Migration { version: 4, description: "laps table", apply: laps_table, backfills: &[] },
+    Migration { version: 5, description: "pool swim lengths", apply: pool_swimming, backfills: &[] },
+    Migration { version: 6, description: "stored original files", apply: source_files, backfills: &[] },
+    Migration { version: 7, description: "typed samples table", apply: samples_table, backfills: &[] },
+    Migration { version: 8, description: "tags tables", apply: tags_tables, backfills: &[] },
+    Migration { version: 9, description: "full-text search", apply: full_text_search, backfills: &[] },
+    Migration { version: 10, description: "training load", apply: training_load, backfills: &[Backfill::Tss, Backfill::TrainingLoad] },
+    Migration { version: 11, description: "power metrics", apply: power_metrics_columns, backfills: &[Backfill::PowerMetrics, Backfill::Tss, Backfill::TrainingLoad] },
+    Migration { version: 12, description: "power curves", apply: power_curves, backfills: &[Backfill::PowerCurves] },
+    Migration { version: 13, description: "best efforts", apply: best_efforts_table, backfills: &[Backfill::BestEfforts] },
+    Migration { version: 14, description: "time in zones", apply: workout_zones, backfills: &[Backfill::Zones] },
+    Migration { version: 15, description: "athlete profile", apply: athlete_profile, backfills: &[Backfill::Tss, Backfill::TrainingLoad, Backfill::Zones] },
+    Migration { version: 16, description: "moving time", apply: moving_time, backfills: &[Backfill::MovingTime] },
+    Migration { version: 17, description: "sensor dynamics channels", apply: sensor_dynamics, backfills: &[] },
+    Migration { version: 18, description: "developer fields", apply: developer_fields_tables, backfills: &[] },
+    Migration { version: 19, description: "heart rate variability", apply: rr_intervals_table, backfills: &[] },
+    Migration { version: 20, description: "recording devices and sensors", apply: workout_devices, backfills: &[] },
+    Migration { version: 21, description: "sub-sports and sport categories", apply: sport_taxonomy, backfills: &[Backfill::Categories] },
+    Migration { version: 22, description: "legs without a file hash", apply: leg_file_hashes, backfills: &[] },
+    Migration { version: 23, description: "other recordings of a workout", apply: workout_recordings, backfills: &[] },
+    Migration { version: 24, description: "flying sport", apply: flying_sport, backfills: &[Backfill::Categories] },
+    Migration { version: 25, description: "power and pace zones", apply: power_and_pace_zones, backfills: &[Backfill::Zones] },
+    Migration { version: 26, description: "TSS over timer time", apply: tss_over_timer_time, backfills: &[Backfill::Tss, Backfill::TrainingLoad] },
+];
+
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+pub fn current_version(conn: &Connection) -> Result<i64> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?",
+        params![table],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for name in names {
+        if name? == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Databases created before migrations were tracked may already have some of
+/// the columns a migration adds, so additions are skipped when present.
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    if !column_exists(conn, table, column)? {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+/// The sample columns as v7 created them, which its conversion writes
+const V7_SAMPLE_COLUMNS: &str =
+    "timestamp, elapsed_s, lat, lon, altitude, heart_rate, power, cadence, speed, distance, temperature";
+
+fn insert_v7_samples(conn: &Connection, workout_id: i64, samples: &[Sample]) -> Result<()> {
+    let mut stmt = conn.prepare(&format!(
+        "INSERT INTO samples (workout_id, sample_index, {}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        V7_SAMPLE_COLUMNS
+    ))?;
+    for (i, sample) in samples.iter().enumerate() {
+        stmt.execute(params![
+            workout_id,
+            i as i64,
+            sample.timestamp,
+            sample.elapsed_s,
+            sample.lat,
+            sample.lon,
+            sample.altitude,
+            sample.heart_rate,
+            sample.power,
+            sample.cadence,
+            sample.speed,
+            sample.distance,
+            sample.temperature,
+        ])?;
+    }
+    Ok(())
+}
+
+/// Ids of the workouts with at least one sample matching `condition`
+fn workouts_with_samples(conn: &Connection, condition: &str) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare(&format!("SELECT DISTINCT workout_id FROM samples WHERE {}", condition))?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+    rows.collect()
+}
+
+fn run_backfill(conn: &Connection, backfill: Backfill) -> Result<()> {
+    match backfill {
+        Backfill::PowerMetrics => {
+            let mut stmt = conn.prepare(
+                "UPDATE workouts SET normalized_power_watts = ?, variability_index = ?, work_kj = ? WHERE id = ?",
+            )?;
+            for id in workouts_with_samples(conn, "power IS NOT NULL")? {
+                let samples = load_samples(conn, id, None, None)?;
+                if let Some(power) = power_metrics(&sensor_points(&samples)) {
+                    stmt.execute(params![power.normalized_power, power.variability_index, power.work_kj, id])?;
+                }
+            }
+            Ok(())
+        }
+        Backfill::PowerCurves => {
+            for id in workouts_with_samples(conn, "power IS NOT NULL")? {
+                store_power_curve(conn, id, &load_samples(conn, id, None, None)?)?;
+            }
+            Ok(())
+        }
+        Backfill::BestEfforts => {
+            for id in workouts_with_samples(conn, "distance IS NOT NULL")? {
+                store_best_efforts(conn, id, &load_samples(conn, id, None, None)?)?;
+            }
+            Ok(())
+        }
+        Backfill::MovingTime => {
+            // Timer pauses weren't kept, so existing workouts get the moving
+            // time their samples show. Re-processing the original file also
+            // fills in the timer time.
+            let mut stmt = conn.prepare(
+                "UPDATE workouts SET moving_seconds = ?, avg_moving_speed_mps = ? WHERE id = ?",
+            )?;
+            for id in workouts_with_samples(conn, "speed IS NOT NULL OR distance IS NOT NULL")? {
+                let (duration_seconds, distance_meters): (Option<i64>, Option<f64>) = conn.query_row(
+                    "SELECT duration_seconds, distance_meters FROM workouts WHERE id = ?",
+                    params![id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )?;
+                let Some(moving) = moving_seconds(&load_samples(conn, id, None, None)?, &[]) else { continue };
+                let moving = (moving.round() as i64).min(duration_seconds.unwrap_or(i64::MAX));
+                let speed = distance_meters.filter(|_| moving > 0).map(|d| d / moving as f64);
+                stmt.execute(params![moving, speed, id])?;
+            }
+            Ok(())
+        }
+        Backfill::Tss => recalculate_tss(conn),
+        Backfill::TrainingLoad => refresh_training_load(conn, None),
+        Backfill::Zones => recalculate_zones(conn),
+        Backfill::Categories => recalculate_categories(conn),
+    }
+}
+
+/// Copies the database next to itself before it is upgraded, e.g.
+/// `workouts.db.backup-v0`. An older backup for the same version is replaced.
+fn backup(conn: &Connection, db_path: &Path, from_version: i64) -> Result<PathBuf> {
+    let file_name = db_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("workouts.db");
+    let backup_path = db_path.with_file_name(format!("{}.backup-v{}", file_name, from_version));
+    let _ = fs::remove_file(&backup_path);
+    conn.execute("VACUUM INTO ?", params![backup_path.to_string_lossy()])?;
+    Ok(backup_path)
+}
+
+/// Brings the schema up to date in one transaction, then rebuilds the data
+/// the applied migrations call for in the same transaction. Existing
+/// databases are backed up first. A database from a newer build is refused.
+pub fn migrate(conn: &mut Connection, db_path: &Path) -> Result<()> {
+    let version = current_version(conn)?;
+    let latest = latest_version();
+
+    // Writing to it could lose what the newer version stores
+    if version > latest {
+        return Err(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
+            Some(format!(
+                "Database schema version {} is newer than this build supports ({})",
+                version, latest
+            )),
+        ));
+    }
+    if version == latest {
+        return Ok(());
+    }
+
+    if table_exists(conn, "workouts")? {
+        let backup_path = backup(conn, db_path, version)?;
+        info!("Backed up database to {:?} before migrating", backup_path);
+    }
+
+    let tx = conn.transaction()?;
+    let mut backfills = BTreeSet::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.version > version) {
+        info!("Applying migration {}: {}", migration.version, migration.description);
+        (migration.apply)(&tx)?;
+        backfills.extend(migration.backfills.iter().copied());
+    }
+    for backfill in backfills {
+        info!("Backfilling {:?}", backfill);
+        run_backfill(&tx, backfill)?;
+    }
+    tx.pragma_update(None, "user_version", latest)?;
+    tx.commit()
+}
+
+fn initial_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS workouts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_hash TEXT UNIQUE NOT NULL,
+            filename TEXT NOT NULL,
+            name TEXT,
+            tags TEXT,
+            workout_type TEXT,
+            start_time DATETIME,
+            end_time DATETIME,
+            duration_seconds INTEGER,
+            distance_meters REAL,
+            total_calories INTEGER,
+            avg_heart_rate INTEGER,
+            max_heart_rate INTEGER,
+            avg_power_watts INTEGER,
+            max_power_watts INTEGER,
+            avg_cadence INTEGER,
+            max_cadence INTEGER,
+            avg_speed_mps REAL,
+            max_speed_mps REAL,
+            elevation_gain_meters REAL,
+            elevation_loss_meters REAL,
+            gps_data TEXT,
+            sensor_data TEXT,
+            chart_data TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_workouts_start_time ON workouts(start_time DESC)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_workouts_type ON workouts(workout_type)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn workout_notes(conn: &Connection) -> Result<()> {
+    add_column(conn, "workouts", "notes", "TEXT")
+}
+
+fn multisport_legs(conn: &Connection) -> Result<()> {
+    add_column(conn, "workouts", "parent_id", "INTEGER REFERENCES workouts(id) ON DELETE CASCADE")?;
+    add_column(conn, "workouts", "session_index", "INTEGER")?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_workouts_parent ON workouts(parent_id)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn laps_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS laps (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            workout_id INTEGER NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+            lap_index INTEGER NOT NULL,
+            start_time DATETIME,
+            end_time DATETIME,
+            duration_seconds REAL,
+            distance_meters REAL,
+            total_calories INTEGER,
+            avg_heart_rate INTEGER,
+            max_heart_rate INTEGER,
+            avg_power_watts INTEGER,
+            max_power_watts INTEGER,
+            avg_cadence INTEGER,
+            max_cadence INTEGER,
+            avg_speed_mps REAL,
+            max_speed_mps REAL,
+            elevation_gain_meters REAL,
+            elevation_loss_meters REAL,
+            lap_trigger TEXT
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_laps_workout ON laps(workout_id, lap_index)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn pool_swimming(conn: &Connection) -> Result<()> {
+    add_column(conn, "workouts", "pool_length_meters", "REAL")?;
+    add_column(conn, "laps", "swim_stroke", "TEXT")?;
+    add_column(conn, "laps", "total_strokes", "INTEGER")?;
+    add_column(conn, "laps", "num_lengths", "INTEGER")?;
+    add_column(conn, "laps", "num_active_lengths", "INTEGER")?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS swim_lengths (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            workout_id INTEGER NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+            length_index INTEGER NOT NULL,
+            start_time DATETIME,
+            duration_seconds REAL,
+            length_type TEXT,
+            swim_stroke TEXT,
+            total_strokes INTEGER,
+            avg_speed_mps REAL,
+            avg_swimming_cadence INTEGER
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_swim_lengths_workout ON swim_lengths(workout_id, length_index)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn source_files(conn: &Connection) -> Result<()> {
+    // Original uploaded files, gzip-compressed, so workouts can be re-parsed later
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS source_files (
+            file_hash TEXT PRIMARY KEY,
+            filename TEXT NOT NULL,
+            format TEXT NOT NULL,
+            original_size INTEGER NOT NULL,
+            data BLOB NOT NULL,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn samples_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS samples (
+            workout_id INTEGER NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+            sample_index INTEGER NOT NULL,
+            timestamp TEXT,
+            elapsed_s REAL,
+            lat REAL,
+            lon REAL,
+            altitude REAL,
+            heart_rate INTEGER,
+            power INTEGER,
+            cadence INTEGER,
+            speed REAL,
+            distance REAL,
+            temperature REAL,
+            PRIMARY KEY (workout_id, sample_index)
+        ) WITHOUT ROWID",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_samples_elapsed ON samples(workout_id, elapsed_s)",
+        [],
+    )?;
+
+    if !column_exists(conn, "workouts", "sensor_data")? {
+        return Ok(());
+    }
+
+    // Move the JSON blobs into rows, then drop them along with the
+    // precomputed chart, which is now built from the samples on request
+    let rows: Vec<(i64, Option<String>, Option<String>)> = {
+        let mut stmt = conn.prepare("SELECT id, gps_data, sensor_data FROM workouts")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<Result<_>>()?
+    };
+    for (id, gps_json, sensor_json) in rows {
+        let gps: Vec<GpsPoint> = gps_json
+            .and_then(|j| serde_json::from_str(&j).ok())
+            .unwrap_or_default();
+        let sensor: Vec<SensorPoint> = sensor_json
+            .and_then(|j| serde_json::from_str(&j).ok())
+            .unwrap_or_default();
+        insert_v7_samples(conn, id, &merge_samples(&gps, &sensor))?;
+    }
+
+    for column in ["gps_data", "sensor_data", "chart_data"] {
+        if column_exists(conn, "workouts", column)? {
+            conn.execute(&format!("ALTER TABLE workouts DROP COLUMN {}", column), [])?;
+        }
+    }
+    Ok(())
+}
+
+fn tags_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT UNIQUE NOT NULL,
+            color TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS workout_tags (
+            workout_id INTEGER NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+            tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+            PRIMARY KEY (workout_id, tag_id)
+        ) WITHOUT ROWID",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_workout_tags_tag ON workout_tags(tag_id)",
+        [],
+    )?;
+
+    if !column_exists(conn, "workouts", "tags")? {
+        return Ok(());
+    }
+
+    // Tags used to be a JSON array on each workout
+    let rows: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT id, tags FROM workouts WHERE tags IS NOT NULL")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_>>()?
+    };
+    for (id, tags_json) in rows {
+        let tags: Vec<String> = serde_json::from_str(&tags_json).unwrap_or_default();
+        for name in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?)", params![name])?;
+            conn.execute(
+                "INSERT OR IGNORE INTO workout_tags (workout_id, tag_id) SELECT ?, id FROM tags WHERE name = ?",
+                params![id, name],
+            )?;
+        }
+    }
+
+    conn.execute("ALTER TABLE workouts DROP COLUMN tags", [])?;
+    Ok(())
+}
+
+fn full_text_search(conn: &Connection) -> Result<()> {
+    // Name, notes and tag names of every workout, keyed by workout id and
+    // kept in sync by the triggers below
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS workouts_fts USING fts5(
+            name, notes, tags,
+            tokenize = 'unicode61 remove_diacritics 2',
+            prefix = '2 3'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS workouts_fts_insert AFTER INSERT ON workouts BEGIN
+            INSERT INTO workouts_fts (rowid, name, notes, tags) VALUES (new.id, new.name, new.notes, '');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS workouts_fts_update AFTER UPDATE OF name, notes ON workouts BEGIN
+            UPDATE workouts_fts SET name = new.name, notes = new.notes WHERE rowid = new.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS workouts_fts_delete AFTER DELETE ON workouts BEGIN
+            DELETE FROM workouts_fts WHERE rowid = old.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS workout_tags_fts_insert AFTER INSERT ON workout_tags BEGIN
+            UPDATE workouts_fts SET tags = (
+                SELECT group_concat(t.name, ' ') FROM workout_tags wt JOIN tags t ON t.id = wt.tag_id
+                WHERE wt.workout_id = new.workout_id
+            ) WHERE rowid = new.workout_id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS workout_tags_fts_delete AFTER DELETE ON workout_tags BEGIN
+            UPDATE workouts_fts SET tags = coalesce((
+                SELECT group_concat(t.name, ' ') FROM workout_tags wt JOIN tags t ON t.id = wt.tag_id
+                WHERE wt.workout_id = old.workout_id
+            ), '') WHERE rowid = old.workout_id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS tags_fts_rename AFTER UPDATE OF name ON tags BEGIN
+            UPDATE workouts_fts SET tags = (
+                SELECT group_concat(t.name, ' ') FROM workout_tags wt JOIN tags t ON t.id = wt.tag_id
+                WHERE wt.workout_id = workouts_fts.rowid
+            ) WHERE rowid IN (SELECT workout_id FROM workout_tags WHERE tag_id = new.id);
+        END;
+
+        DELETE FROM workouts_fts;
+        INSERT INTO workouts_fts (rowid, name, notes, tags)
+        SELECT w.id, w.name, w.notes, coalesce((
+            SELECT group_concat(t.name, ' ') FROM workout_tags wt JOIN tags t ON t.id = wt.tag_id
+            WHERE wt.workout_id = w.id
+        ), '')
+        FROM workouts w;",
+    )
+}
+
+fn training_load(conn: &Connection) -> Result<()> {
+    add_column(conn, "workouts", "tss", "REAL")?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS training_load (
+            date TEXT PRIMARY KEY,
+            tss REAL NOT NULL,
+            ctl REAL NOT NULL,
+            atl REAL NOT NULL,
+            tsb REAL NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn power_metrics_columns(conn: &Connection) -> Result<()> {
+    add_column(conn, "workouts", "normalized_power_watts", "REAL")?;
+    add_column(conn, "workouts", "intensity_factor", "REAL")?;
+    add_column(conn, "workouts", "variability_index", "REAL")?;
+    add_column(conn, "workouts", "work_kj", "REAL")?;
+    add_column(conn, "workouts", "tss_source", "TEXT")
+}
+
+fn power_curves(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS power_curves (
+            workout_id INTEGER NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+            duration_seconds INTEGER NOT NULL,
+            watts REAL NOT NULL,
+            PRIMARY KEY (workout_id, duration_seconds)
+        ) WITHOUT ROWID",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_power_curves_duration ON power_curves(duration_seconds, watts)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn best_efforts_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS best_efforts (
+            workout_id INTEGER NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+            distance TEXT NOT NULL,
+            distance_meters REAL NOT NULL,
+            elapsed_seconds REAL NOT NULL,
+            start_offset_s REAL NOT NULL,
+            end_offset_s REAL NOT NULL,
+            PRIMARY KEY (workout_id, distance)
+        ) WITHOUT ROWID",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_best_efforts_distance ON best_efforts(distance, elapsed_seconds)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn workout_zones(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS workout_zones (
+            workout_id INTEGER NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+            zone_type TEXT NOT NULL,
+            zone_index INTEGER NOT NULL,
+            seconds REAL NOT NULL,
+            PRIMARY KEY (workout_id, zone_type, zone_index)
+        ) WITHOUT ROWID",
+        [],
+    )?;
+    Ok(())
+}
+
+/// The athlete settings the frontend synced into `settings` before v15
+#[derive(Deserialize, Default, PartialEq)]
+struct SyncedAthleteSettings {
+    max_heart_rate: Option<i64>,
+    resting_heart_rate: Option<i64>,
+    age: Option<i64>,
+    ftp_watts: Option<i64>,
+    threshold_heart_rate: Option<i64>,
+    threshold_pace_seconds_per_km: Option<i64>,
+}
+
+fn athlete_profile(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS athlete_profile (
+            effective_date TEXT PRIMARY KEY,
+            weight_kg REAL,
+            height_cm REAL,
+            sex TEXT,
+            birth_date TEXT,
+            max_heart_rate INTEGER,
+            resting_heart_rate INTEGER,
+            threshold_heart_rate INTEGER,
+            ftp_watts INTEGER,
+            threshold_pace_seconds_per_km INTEGER
+        )",
+        [],
+    )?;
+
+    // The settings the frontend synced become the first profile entry, in
+    // effect since the first workout. Only an age was kept, so the birth
+    // date is approximated by the start of the birth year.
+    let legacy: Option<SyncedAthleteSettings> = conn
+        .query_row("SELECT value FROM settings WHERE key = 'athlete'", [], |row| row.get::<_, String>(0))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok());
+    if let Some(settings) = legacy.filter(|s| *s != SyncedAthleteSettings::default()) {
+        let effective_date: String = conn.query_row(
+            "SELECT COALESCE(MIN(DATE(start_time)), DATE('now', 'localtime')) FROM workouts",
+            [],
+            |row| row.get(0),
+        )?;
+        let birth_date = settings.age.map(|age| format!("{}-01-01", Local::now().year() as i64 - age));
+        conn.execute(
+            "INSERT OR REPLACE INTO athlete_profile (effective_date, birth_date, max_heart_rate,
+                resting_heart_rate, threshold_heart_rate, ftp_watts, threshold_pace_seconds_per_km)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            params![
+                effective_date,
+                birth_date,
+                settings.max_heart_rate,
+                settings.resting_heart_rate,
+                settings.threshold_heart_rate,
+                settings.ftp_watts,
+                settings.threshold_pace_seconds_per_km,
+            ],
+        )?;
+    }
+    conn.execute("DELETE FROM settings WHERE key = 'athlete'", [])?;
+    Ok(())
+}
+
+fn moving_time(conn: &Connection) -> Result<()> {
+    add_column(conn, "workouts", "timer_seconds", "INTEGER")?;
+    add_column(conn, "workouts", "moving_seconds", "INTEGER")?;
+    add_column(conn, "workouts", "avg_moving_speed_mps", "REAL")
+}
+
+fn sensor_dynamics(conn: &Connection) -> Result<()> {
+    for column in [
+        "vertical_oscillation", "ground_contact_time", "ground_contact_balance", "stride_length",
+        "vertical_ratio", "fractional_cadence", "left_right_balance", "left_torque_effectiveness",
+        "right_torque_effectiveness", "left_pedal_smoothness", "right_pedal_smoothness",
+        "respiration_rate", "core_temperature", "spo2",
+    ] {
+        add_column(conn, "samples", column, "REAL")?;
+    }
+    for column in [
+        "avg_temperature_c", "avg_vertical_oscillation_mm", "avg_ground_contact_time_ms",
+        "avg_ground_contact_balance", "avg_stride_length_meters", "avg_vertical_ratio",
+        "avg_fractional_cadence", "avg_left_right_balance", "avg_left_torque_effectiveness",
+        "avg_right_torque_effectiveness", "avg_left_pedal_smoothness", "avg_right_pedal_smoothness",
+        "avg_respiration_rate", "avg_core_temperature_c", "avg_spo2",
+    ] {
+        add_column(conn, "workouts", column, "REAL")?;
+    }
+
+    // Temperature is the only one of these channels stored so far
+    conn.execute(
+        "UPDATE workouts SET avg_temperature_c = (SELECT AVG(temperature) FROM samples WHERE workout_id = workouts.id)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Developer fields weren't read before, so there is nothing to backfill;
+/// reprocessing a workout from its original file picks them up.
+fn developer_fields_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS developer_fields (
+            workout_id INTEGER NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+            key TEXT NOT NULL,
+            field_index INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            units TEXT,
+            app_id TEXT,
+            PRIMARY KEY (workout_id, key)
+        ) WITHOUT ROWID",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS developer_samples (
+            workout_id INTEGER NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+            sample_index INTEGER NOT NULL,
+            key TEXT NOT NULL,
+            value REAL NOT NULL,
+            PRIMARY KEY (workout_id, sample_index, key)
+        ) WITHOUT ROWID",
+        [],
+    )?;
+    Ok(())
+}
+
+/// R-R intervals weren't kept before, so there is nothing to backfill;
+/// reprocessing a workout from its original file picks them up.
+fn rr_intervals_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS rr_intervals (
+            workout_id INTEGER NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+            beat_index INTEGER NOT NULL,
+            elapsed_s REAL NOT NULL,
+            rr_ms REAL NOT NULL,
+            corrected_ms REAL NOT NULL,
+            artifact INTEGER NOT NULL,
+            PRIMARY KEY (workout_id, beat_index)
+        ) WITHOUT ROWID",
+        [],
+    )?;
+    for column in ["hrv_rmssd_ms", "hrv_sdnn_ms", "dfa_aerobic_threshold_hr", "dfa_anaerobic_threshold_hr"] {
+        add_column(conn, "workouts", column, "REAL")?;
+    }
+    Ok(())
+}
+
+/// The recording device from FIT `file_id` on the workout, and every device
+/// from `device_info` in a table of its own. Existing workouts get theirs
+/// when reprocessed.
+fn workout_devices(conn: &Connection) -> Result<()> {
+    for (column, ty) in [
+        ("device_manufacturer", "TEXT"),
+        ("device_product", "TEXT"),
+        ("device_serial_number", "INTEGER"),
+        ("file_created_at", "TEXT"),
+    ] {
+        add_column(conn, "workouts", column, ty)?;
+    }
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS workout_devices (
+            workout_id INTEGER NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+            device_index TEXT NOT NULL,
+            device_type TEXT,
+            manufacturer TEXT,
+            product TEXT,
+            product_name TEXT,
+            serial_number INTEGER,
+            software_version REAL,
+            battery_status TEXT,
+            battery_voltage REAL,
+            source_type TEXT,
+            PRIMARY KEY (workout_id, device_index)
+        ) WITHOUT ROWID",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_workout_devices_product ON workout_devices(product)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_workout_devices_serial ON workout_devices(serial_number)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Sub-sports weren't kept before, so existing workouts are categorised
+/// by sport alone until reprocessed
+fn sport_taxonomy(conn: &Connection) -> Result<()> {
+    add_column(conn, "workouts", "sub_sport", "TEXT")?;
+    add_column(conn, "workouts", "category", "TEXT")?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_workouts_category ON workouts(category)", [])?;
+    Ok(())
+}
+
+/// Legs share their parent's file, so they lose the made-up `hash:index`
+/// they were given. ALTER TABLE can't drop NOT NULL, so the constraint is
+/// removed from the stored definition the way SQLite documents for it,
+/// which leaves the rows, indexes and triggers as they are.
+fn leg_file_hashes(conn: &Connection) -> Result<()> {
+    let schema_version: i64 = conn.query_row("PRAGMA schema_version", [], |row| row.get(0))?;
+    conn.pragma_update(None, "writable_schema", true)?;
+    conn.execute(
+        "UPDATE sqlite_master SET sql = replace(sql, 'file_hash TEXT UNIQUE NOT NULL', 'file_hash TEXT UNIQUE')
+         WHERE type = 'table' AND name = 'workouts'",
+        [],
+    )?;
+    conn.pragma_update(None, "schema_version", schema_version + 1)?;
+    conn.pragma_update(None, "writable_schema", false)?;
+    conn.execute("UPDATE workouts SET file_hash = NULL WHERE parent_id IS NOT NULL", [])?;
+    Ok(())
+}
+
+/// Files of an activity already stored from another device, e.g. a watch
+/// recording of a ride the bike computer also recorded. Their devices are
+/// added to the workout's own.
+fn workout_recordings(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS workout_recordings (
+            file_hash TEXT PRIMARY KEY,
+            workout_id INTEGER NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+            filename TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_workout_recordings_workout ON workout_recordings(workout_id)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// FIT sport 20 is flying, which earlier builds read as strength
+/// training. GPX and TCX files name their sport, so only FIT workouts
+/// are corrected.
+fn flying_sport(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "UPDATE workouts SET workout_type = 'flying'
+         WHERE workout_type = 'strength_training' AND lower(filename) LIKE '%.fit'",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Time in power and pace zones is stored with heart rate's, so the schema
+/// stays; workouts stored before only have their heart rate zones
+fn power_and_pace_zones(_conn: &Connection) -> Result<()> {
+    Ok(())
+}
+
+/// TSS is scored over the time the timer ran rather than the elapsed time,
+/// which counted stops; stored workouts are scored again
+fn tss_over_timer_time(_conn: &Connection) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::{workout_tss, AthleteSettings, DateRange};
+    use crate::database::Database;
+
+    /// A fresh directory per test so databases and backups don't collide
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "occ-migrations-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// The schema exactly as v0.1.0 created it, with one workout
+    fn create_v0_1_0_database(path: &Path) {
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;
+            CREATE TABLE IF NOT EXISTS workouts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                file_hash TEXT UNIQUE NOT NULL,
+                filename TEXT NOT NULL,
+                name TEXT,
+                tags TEXT,
+                workout_type TEXT,
+                start_time DATETIME,
+                end_time DATETIME,
+                duration_seconds INTEGER,
+                distance_meters REAL,
+                total_calories INTEGER,
+                avg_heart_rate INTEGER,
+                max_heart_rate INTEGER,
+                avg_power_watts INTEGER,
+                max_power_watts INTEGER,
+                avg_cadence INTEGER,
+                max_cadence INTEGER,
+                avg_speed_mps REAL,
+                max_speed_mps REAL,
+                elevation_gain_meters REAL,
+                elevation_loss_meters REAL,
+                gps_data TEXT,
+                sensor_data TEXT,
+                chart_data TEXT,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            );
+            CREATE INDEX IF NOT EXISTS idx_workouts_start_time ON workouts(start_time DESC);
+            CREATE INDEX IF NOT EXISTS idx_workouts_type ON workouts(workout_type);
+            INSERT INTO workouts (file_hash, filename, name, tags, workout_type, start_time, duration_seconds, distance_meters, gps_data, sensor_data, chart_data)
+            VALUES ('abc123', 'morning.fit', 'Morning Run', '[\"easy\"]', 'running', '2025-01-10T07:00:00+00:00', 1800, 5000.0,
+                '[{\"timestamp\":\"2025-01-10T07:00:00+00:00\",\"lat\":52.5,\"lon\":13.4,\"altitude\":34.0},
+                  {\"timestamp\":\"2025-01-10T07:00:01+00:00\",\"lat\":52.50001,\"lon\":13.40001,\"altitude\":34.5}]',
+                '[{\"timestamp\":\"2025-01-10T07:00:00+00:00\",\"heart_rate\":120,\"power\":null,\"cadence\":80,\"speed\":2.8,\"distance\":0.0,\"altitude\":34.0},
+                  {\"timestamp\":\"2025-01-10T07:00:01+00:00\",\"heart_rate\":122,\"power\":null,\"cadence\":82,\"speed\":2.9,\"distance\":2.9,\"altitude\":34.5}]',
+                '{\"timestamps\":[],\"heart_rate\":[],\"power\":[],\"cadence\":[],\"speed\":[],\"altitude\":[]}');",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn upgrades_v0_1_0_database() {
+        let dir = temp_dir("v010");
+        let path = dir.join("workouts.db");
+        create_v0_1_0_database(&path);
+
+        let db = Database::new(&path).unwrap();
+
+        let workout = db.get_workout(1).unwrap().expect("workout survives the upgrade");
+        assert_eq!(workout.name.as_deref(), Some("Morning Run"));
+        assert_eq!(workout.tags.as_deref(), Some("[\"easy\"]"));
+        let tags = db.get_all_tags().unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].name, "easy");
+        assert_eq!(tags[0].usage_count, 1);
+        let found = db.get_workouts(10, 0, None, None, Some("morn"), None, None, None, None, None, None, None, None, None, None, None, None).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(db.get_total_workout_count(None, None, Some("easy"), None, None, None, None, None, None, None, None, None, None).unwrap(), 1);
+        let load = db.get_training_load(&DateRange::default()).unwrap();
+        assert_eq!(load.first().map(|d| d.date.as_str()), Some("2025-01-10"));
+        assert_eq!(workout.notes, None);
+        assert_eq!(workout.parent_id, None);
+        assert!(db.get_workout_laps(1).unwrap().is_empty());
+        assert!(db.update_notes(1, "felt good").unwrap());
+
+        let samples = db.get_samples(1, None, None).unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[1].elapsed_s, Some(1.0));
+        assert_eq!(samples[1].heart_rate, Some(122));
+        assert_eq!(samples[1].lat, Some(52.50001));
+        assert_eq!(db.get_samples(1, Some(0.5), None).unwrap().len(), 1);
+        assert_eq!(workout.moving_seconds, Some(1));
+        assert_eq!(found[0].moving_seconds, Some(1));
+        let short = |basis| db.get_total_workout_count(None, None, None, None, None, None, None, None, Some(60), basis, None, None, None).unwrap();
+        assert_eq!(short(None), 0);
+        assert_eq!(short(Some("moving")), 1);
+        let zones = db.get_workout_zones(1, "heart_rate").unwrap();
+        assert_eq!(zones.len(), 5);
+        assert_eq!(zones[1].seconds, 1.0);
+        assert_eq!(samples[1].vertical_oscillation, None);
+        assert_eq!(workout.avg_ground_contact_time_ms, None);
+        assert_eq!(workout.device_product, None);
+        assert!(db.get_workout_devices(1).unwrap().is_empty());
+        assert_eq!(workout.sub_sport, None);
+        assert_eq!(workout.category.as_deref(), Some("running"));
+        let breakdown = db.get_activity_breakdown().unwrap();
+        assert_eq!(breakdown[0].name, "running");
+        assert_eq!(breakdown[0].sports[0].sport, "running");
+
+        let conn = Connection::open(&path).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        assert_eq!(workout.normalized_power_watts, None);
+        for column in ["notes", "parent_id", "session_index", "pool_length_meters", "normalized_power_watts", "timer_seconds", "moving_seconds", "avg_spo2", "device_product", "sub_sport", "category"] {
+            assert!(column_exists(&conn, "workouts", column).unwrap(), "missing {}", column);
+        }
+        assert!(!column_exists(&conn, "workouts", "sensor_data").unwrap());
+        assert!(!column_exists(&conn, "workouts", "tags").unwrap());
+        for table in ["laps", "swim_lengths", "source_files", "samples", "tags", "workout_tags", "workouts_fts", "training_load", "power_curves", "best_efforts", "workout_zones", "athlete_profile", "developer_fields", "developer_samples", "rr_intervals", "workout_devices"] {
+            assert!(table_exists(&conn, table).unwrap(), "missing {}", table);
+        }
+
+        let backup = Connection::open(dir.join("workouts.db.backup-v0")).unwrap();
+        assert_eq!(current_version(&backup).unwrap(), 0);
+        assert!(!column_exists(&backup, "workouts", "notes").unwrap());
+        let count: i64 = backup
+            .query_row("SELECT COUNT(*) FROM workouts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn upgrades_database_that_already_has_notes() {
+        let dir = temp_dir("v020");
+        let path = dir.join("workouts.db");
+        create_v0_1_0_database(&path);
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute("ALTER TABLE workouts ADD COLUMN notes TEXT", []).unwrap();
+            conn.execute("UPDATE workouts SET notes = 'kept'", []).unwrap();
+        }
+
+        let db = Database::new(&path).unwrap();
+        let workout = db.get_workout(1).unwrap().unwrap();
+        assert_eq!(workout.notes.as_deref(), Some("kept"));
+    }
+
+    #[test]
+    fn moves_synced_settings_into_athlete_profile() {
+        let dir = temp_dir("profile");
+        let path = dir.join("workouts.db");
+        create_v0_1_0_database(&path);
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+                 INSERT INTO settings VALUES ('athlete', '{\"max_heart_rate\":180,\"resting_heart_rate\":50,\"age\":40}');",
+            )
+            .unwrap();
+        }
+
+        let db = Database::new(&path).unwrap();
+        let history = db.get_athlete_profile_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].effective_date, "2025-01-10");
+        assert_eq!(history[0].max_heart_rate, Some(180));
+        assert!(history[0].birth_date.is_some());
+        let settings = db.get_athlete_settings(Some("2025-01-10T07:00:00+00:00")).unwrap();
+        assert_eq!(settings.resting_heart_rate, Some(50));
+    }
+
+    #[test]
+    fn backfills_power_data_of_existing_workouts() {
+        let dir = temp_dir("power");
+        let path = dir.join("workouts.db");
+        create_v0_1_0_database(&path);
+        {
+            let points: Vec<String> = (0..120)
+                .map(|i| format!(
+                    "{{\"timestamp\":\"2025-01-11T07:{:02}:{:02}+00:00\",\"heart_rate\":140,\"power\":200,\"cadence\":90,\"speed\":8.0,\"distance\":{}.0,\"altitude\":null}}",
+                    i / 60, i % 60, i * 8
+                ))
+                .collect();
+            let conn = Connection::open(&path).unwrap();
+            conn.execute(
+                "INSERT INTO workouts (file_hash, filename, workout_type, start_time, duration_seconds, avg_heart_rate, sensor_data)
+                 VALUES ('def456', 'ride.fit', 'cycling', '2025-01-11T07:00:00+00:00', 119, 140, ?)",
+                params![format!("[{}]", points.join(","))],
+            )
+            .unwrap();
+        }
+
+        let db = Database::new(&path).unwrap();
+        let workout = db.get_workout(2).unwrap().unwrap();
+        assert_eq!(workout.normalized_power_watts.map(f64::round), Some(200.0));
+        assert_eq!(workout.tss_source.as_deref(), Some("heart_rate"));
+        let curve = db.get_power_curve(None, &DateRange::default()).unwrap();
+        assert_eq!(curve.all_time.first().map(|p| p.workout_id), Some(2));
+        let zones = db.get_workout_zones(2, "heart_rate").unwrap();
+        assert!(zones.iter().any(|z| z.seconds > 0.0));
+    }
+
+    #[test]
+    fn clears_made_up_file_hashes_of_legs() {
+        let dir = temp_dir("legs");
+        let path = dir.join("workouts.db");
+        create_v0_1_0_database(&path);
+        {
+            let mut conn = Connection::open(&path).unwrap();
+            let tx = conn.transaction().unwrap();
+            for migration in MIGRATIONS.iter().filter(|m| m.version < 22) {
+                (migration.apply)(&tx).unwrap();
+            }
+            tx.execute_batch(
+                "INSERT INTO workouts (file_hash, filename, name, parent_id, session_index)
+                 VALUES ('abc123:0', 'morning.fit', 'Warm-up', 1, 0);
+                 INSERT INTO workouts (file_hash, filename) VALUES ('deleted', 'deleted.fit');
+                 DELETE FROM workouts WHERE file_hash = 'deleted';
+                 INSERT INTO laps (workout_id, lap_index) VALUES (2, 0);
+                 PRAGMA user_version = 21;",
+            )
+            .unwrap();
+            tx.commit().unwrap();
+        }
+
+        let db = Database::new(&path).unwrap();
+        let parent = db.get_workout(1).unwrap().unwrap();
+        assert_eq!(parent.file_hash.as_deref(), Some("abc123"));
+        assert_eq!(parent.legs.len(), 1);
+        let leg = db.get_workout(2).unwrap().unwrap();
+        assert_eq!(leg.file_hash, None);
+        assert_eq!(leg.parent_id, Some(1));
+        assert_eq!(db.get_workout_laps(2).unwrap().len(), 1);
+        assert!(db.rename_workout(2, "Swim").unwrap());
+
+        let conn = Connection::open(&path).unwrap();
+        let renamed: i64 = conn
+            .query_row("SELECT rowid FROM workouts_fts WHERE workouts_fts MATCH 'swim'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(renamed, 2);
+        conn.execute("INSERT INTO workouts (filename) VALUES ('new.fit')", []).unwrap();
+        assert_eq!(conn.last_insert_rowid(), 4);
+        let integrity: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0)).unwrap();
+        assert_eq!(integrity, "ok");
+    }
+
+    #[test]
+    fn reads_fit_sport_20_as_flying() {
+        let dir = temp_dir("flying");
+        let path = dir.join("workouts.db");
+        create_v0_1_0_database(&path);
+        {
+            let mut conn = Connection::open(&path).unwrap();
+            let tx = conn.transaction().unwrap();
+            for migration in MIGRATIONS.iter().filter(|m| m.version < 24) {
+                (migration.apply)(&tx).unwrap();
+            }
+            tx.execute_batch(
+                "UPDATE workouts SET workout_type = 'strength_training', category = 'strength_training';
+                 INSERT INTO workouts (file_hash, filename, workout_type, category)
+                 VALUES ('def456', 'lifting.gpx', 'strength_training', 'strength_training');
+                 PRAGMA user_version = 23;",
+            )
+            .unwrap();
+            tx.commit().unwrap();
+        }
+
+        let db = Database::new(&path).unwrap();
+        let flight = db.get_workout(1).unwrap().unwrap();
+        assert_eq!(flight.workout_type.as_deref(), Some("flying"));
+        assert_eq!(flight.category.as_deref(), Some("flying"));
+        let lifting = db.get_workout(2).unwrap().unwrap();
+        assert_eq!(lifting.workout_type.as_deref(), Some("strength_training"));
+        assert_eq!(lifting.category.as_deref(), Some("strength_training"));
+    }
+
+    #[test]
+    fn scores_stored_workouts_over_timer_time() {
+        let dir = temp_dir("timer-tss");
+        let path = dir.join("workouts.db");
+        create_v0_1_0_database(&path);
+        {
+            let mut conn = Connection::open(&path).unwrap();
+            let tx = conn.transaction().unwrap();
+            for migration in MIGRATIONS.iter().filter(|m| m.version < 26) {
+                (migration.apply)(&tx).unwrap();
+            }
+            // Two hours out, one of them stopped with the timer paused
+            tx.execute_batch(
+                "UPDATE workouts SET duration_seconds = 7200, timer_seconds = 3600, avg_heart_rate = 150;
+                 PRAGMA user_version = 25;",
+            )
+            .unwrap();
+            tx.commit().unwrap();
+        }
+
+        let db = Database::new(&path).unwrap();
+        let workout = db.get_workout(1).unwrap().unwrap();
+        let expected = workout_tss(Some(3600), Some(150), None, &AthleteSettings::default()).tss;
+        assert!(expected.is_some());
+        assert_eq!(workout.tss, expected);
+    }
+
+    #[test]
+    fn fresh_database_needs_no_backup() {
+        let dir = temp_dir("fresh");
+        let path = dir.join("workouts.db");
+        Database::new(&path).unwrap();
+
+        let conn = Connection::open(&path).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        assert!(!dir.join("workouts.db.backup-v0").exists());
+    }
+
+    #[test]
+    fn refuses_newer_database() {
+        let dir = temp_dir("newer");
+        let path = dir.join("workouts.db");
+        drop(Database::new(&path).unwrap());
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.pragma_update(None, "user_version", latest_version() + 1).unwrap();
+        }
+
+        assert!(Database::new(&path).is_err());
+        let conn = Connection::open(&path).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version() + 1);
+    }
+
+    #[test]
+    fn reopening_is_a_no_op() {
+        let dir = temp_dir("reopen");
+        let path = dir.join("workouts.db");
+        create_v0_1_0_database(&path);
+        drop(Database::new(&path).unwrap());
+        fs::remove_file(dir.join("workouts.db.backup-v0")).unwrap();
+
+        drop(Database::new(&path).unwrap());
+        let backups = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().contains(".backup-"))
+            .count();
+        assert_eq!(backups, 0);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::developer_fields::DeveloperValue;
+use crate::fit_parser::{GpsPoint, SensorPoint};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// One row of the `samples` table: a recorded point with its position and
+/// sensor values. `elapsed_s` counts from the first sample of the workout.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Sample {
+    pub timestamp: Option<String>,
+    pub elapsed_s: Option<f64>,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    pub altitude: Option<f64>,
+    pub heart_rate: Option<i64>,
+    pub power: Option<i64>,
+    pub cadence: Option<i64>,
+    pub speed: Option<f64>,
+    pub distance: Option<f64>,
+    pub temperature: Option<f64>,
+    /// Running and cycling dynamics and physiological channels, in the
+    /// units of the matching `SensorPoint` fields
+    pub vertical_oscillation: Option<f64>,
+    pub ground_contact_time: Option<f64>,
+    pub ground_contact_balance: Option<f64>,
+    pub stride_length: Option<f64>,
+    pub vertical_ratio: Option<f64>,
+    pub fractional_cadence: Option<f64>,
+    pub left_right_balance: Option<f64>,
+    pub left_torque_effectiveness: Option<f64>,
+    pub right_torque_effectiveness: Option<f64>,
+    pub left_pedal_smoothness: Option<f64>,
+    pub right_pedal_smoothness: Option<f64>,
+    pub respiration_rate: Option<f64>,
+    pub core_temperature: Option<f64>,
+    pub spo2: Option<f64>,
+    /// Developer field values, kept in `developer_samples` rather than in
+    /// the row itself
+    #[serde(default)]
+    pub developer: Vec<DeveloperValue>,
+}
+
+fn parse_time(ts: Option<&str>) -> Option<DateTime<Utc>> {
+    ts.and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Joins GPS positions onto sensor points by timestamp. Positions without a
+/// matching sensor point become samples of their own, in time order.
+pub fn merge_samples(gps_data: &[GpsPoint], sensor_data: &[SensorPoint]) -> Vec<Sample> {
+    let positions: HashMap<&str, &GpsPoint> = gps_data.iter()
+        .filter_map(|g| Some((g.timestamp.as_deref()?, g)))
+        .collect();
+
+    let mut samples: Vec<Sample> = sensor_data.iter()
+        .map(|s| {
+            let gps = s.timestamp.as_deref().and_then(|t| positions.get(t));
+            Sample {
+                timestamp: s.timestamp.clone(),
+                elapsed_s: None,
+                lat: gps.map(|g| g.lat),
+                lon: gps.map(|g| g.lon),
+                altitude: s.altitude.or_else(|| gps.and_then(|g| g.altitude)),
+                heart_rate: s.heart_rate,
+                power: s.power,
+                cadence: s.cadence,
+                speed: s.speed,
+                distance: s.distance,
+                temperature: s.temperature,
+                vertical_oscillation: s.vertical_oscillation,
+                ground_contact_time: s.ground_contact_time,
+                ground_contact_balance: s.ground_contact_balance,
+                stride_length: s.stride_length,
+                vertical_ratio: s.vertical_ratio,
+                fractional_cadence: s.fractional_cadence,
+                left_right_balance: s.left_right_balance,
+                left_torque_effectiveness: s.left_torque_effectiveness,
+                right_torque_effectiveness: s.right_torque_effectiveness,
+                left_pedal_smoothness: s.left_pedal_smoothness,
+                right_pedal_smoothness: s.right_pedal_smoothness,
+                respiration_rate: s.respiration_rate,
+                core_temperature: s.core_temperature,
+                spo2: s.spo2,
+                developer: s.developer.clone(),
+            }
+        })
+        .collect();
+
+    let sensor_times: HashSet<&str> = sensor_data.iter()
+        .filter_map(|s| s.timestamp.as_deref())
+        .collect();
+    let unmatched: Vec<Sample> = gps_data.iter()
+        .filter(|g| g.timestamp.as_deref().map(|t| !sensor_times.contains(t)).unwrap_or(true))
+        .map(|g| Sample {
+            timestamp: g.timestamp.clone(),
+            lat: Some(g.lat),
+            lon: Some(g.lon),
+            altitude: g.altitude,
+            ..Default::default()
+        })
+        .collect();
+
+    if !unmatched.is_empty() {
+        samples.extend(unmatched);
+        samples.sort_by_key(|s| parse_time(s.timestamp.as_deref()));
+    }
+
+    let start = samples.iter().filter_map(|s| parse_time(s.timestamp.as_deref())).min();
+    if let Some(start) = start {
+        for sample in &mut samples {
+            sample.elapsed_s = parse_time(sample.timestamp.as_deref())
+                .map(|t| (t - start).num_milliseconds() as f64 / 1000.0);
+        }
+    }
+
+    samples
+}
+
+pub fn gps_points(samples: &[Sample]) -> Vec<GpsPoint> {
+    samples.iter()
+        .filter_map(|s| {
+            Some(GpsPoint {
+                timestamp: s.timestamp.clone(),
+                lat: s.lat?,
+                lon: s.lon?,
+                altitude: s.altitude,
+            })
+        })
+        .collect()
+}
+
+pub fn sensor_points(samples: &[Sample]) -> Vec<SensorPoint> {
+    samples.iter()
+        .map(|s| SensorPoint {
+            timestamp: s.timestamp.clone(),
+            heart_rate: s.heart_rate,
+            power: s.power,
+            cadence: s.cadence,
+            speed: s.speed,
+            distance: s.distance,
+            altitude: s.altitude,
+            temperature: s.temperature,
+            vertical_oscillation: s.vertical_oscillation,
+            ground_contact_time: s.ground_contact_time,
+            ground_contact_balance: s.ground_contact_balance,
+            stride_length: s.stride_length,
+            vertical_ratio: s.vertical_ratio,
+            fractional_cadence: s.fractional_cadence,
+            left_right_balance: s.left_right_balance,
+            left_torque_effectiveness: s.left_torque_effectiveness,
+            right_torque_effectiveness: s.right_torque_effectiveness,
+            left_pedal_smoothness: s.left_pedal_smoothness,
+            right_pedal_smoothness: s.right_pedal_smoothness,
+            respiration_rate: s.respiration_rate,
+            core_temperature: s.core_temperature,
+            spo2: s.spo2,
+            developer: s.developer.clone(),
+        })
+        .collect()
+}
@@ -0,0 +1,282 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Markers wrapped around matched terms in search snippets
+pub const HIGHLIGHT_START: &str = "<mark>";
+pub const HIGHLIGHT_END: &str = "</mark>";
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// A word typed by the user, matched as a prefix
+fn word_term(word: &str) -> Option<String> {
+    let word = word.trim_end_matches('*');
+    word.chars().any(char::is_alphanumeric).then(|| quote(word) + "*")
+}
+
+/// A quoted phrase, matched as a prefix when followed by `*`
+fn read_phrase(chars: &mut Peekable<Chars>) -> Option<String> {
+    let phrase: String = chars.by_ref().take_while(|&c| c != '"').collect();
+    let prefix = chars.next_if_eq(&'*').is_some();
+    phrase.chars().any(char::is_alphanumeric).then(|| {
+        let term = quote(phrase.trim());
+        if prefix { term + "*" } else { term }
+    })
+}
+
+fn read_word(chars: &mut Peekable<Chars>) -> String {
+    let mut word = String::new();
+    while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !matches!(c, '"' | '(' | ')' | ',')) {
+        word.push(c);
+    }
+    word
+}
+
+/// The inside of `NEAR(...)`: its words and phrases and an optional
+/// `, distance`, as one FTS5 NEAR group
+fn read_near(chars: &mut Peekable<Chars>) -> Option<String> {
+    let mut terms = Vec::new();
+    let mut distance = None;
+    while let Some(c) = chars.next() {
+        match c {
+            ')' => break,
+            '"' => terms.extend(read_phrase(chars)),
+            ',' => {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                distance = read_word(chars).parse::<u32>().ok();
+            }
+            c if c.is_whitespace() || c == '(' => {}
+            c => {
+                let word = c.to_string() + &read_word(chars);
+                terms.extend(word_term(&word));
+            }
+        }
+    }
+    if terms.is_empty() {
+        return None;
+    }
+    Some(match distance {
+        Some(n) => format!("NEAR({}, {})", terms.join(" "), n),
+        None => format!("NEAR({})", terms.join(" ")),
+    })
+}
+
+/// Writes out tokens so that operators and groups are only kept where FTS5
+/// accepts them
+#[derive(Default)]
+struct QueryBuilder {
+    query: Vec<String>,
+    pending_op: Option<&'static str>,
+    pending_opens: usize,
+    depth: usize,
+}
+
+impl QueryBuilder {
+    fn term(&mut self, term: String) {
+        // FTS5 only joins bare terms implicitly, groups need an AND
+        if let Some(last) = self.query.last().filter(|last| *last != "(") {
+            match self.pending_op.take() {
+                Some(op) => self.query.push(op.to_string()),
+                None if self.pending_opens > 0 || last == ")" => self.query.push("AND".to_string()),
+                None => {}
+            }
+        }
+        self.pending_op = None;
+        for _ in 0..self.pending_opens {
+            self.query.push("(".to_string());
+        }
+        self.depth += self.pending_opens;
+        self.pending_opens = 0;
+        self.query.push(term);
+    }
+
+    /// An operator without a term or group on its left is searched for as
+    /// a word, so a leading `NOT` never turns into a match of what follows.
+    /// Of two operators in a row (`AND NOT`) the later one applies, unless
+    /// the earlier is `NOT`, which FTS5 can't combine with another.
+    fn operator(&mut self, op: &'static str) {
+        if let Some(pending) = self.pending_op {
+            if pending != "NOT" {
+                self.pending_op = Some(op);
+            }
+        } else if self.pending_opens == 0 && self.query.last().is_some_and(|last| last != "(") {
+            self.pending_op = Some(op);
+        } else {
+            self.term(quote(op));
+        }
+    }
+
+    /// Likewise an operator with nothing on its right
+    fn dangling_operator(&mut self) {
+        if let Some(op) = self.pending_op.take() {
+            self.term(quote(op));
+        }
+    }
+
+    fn open(&mut self) {
+        self.pending_opens += 1;
+    }
+
+    /// Groups can't be empty, so they are only written out once a term
+    /// follows the opening parenthesis
+    fn close(&mut self) {
+        self.dangling_operator();
+        if self.pending_opens > 0 {
+            self.pending_opens -= 1;
+        } else if self.depth > 0 {
+            self.query.push(")".to_string());
+            self.depth -= 1;
+        }
+    }
+
+    fn finish(mut self) -> Option<String> {
+        self.dangling_operator();
+        for _ in 0..self.depth {
+            self.query.push(")".to_string());
+        }
+        (!self.query.is_empty()).then(|| self.query.join(" "))
+    }
+}
+
+/// Turns what the user typed into an FTS5 query that is always valid.
+///
+/// - words match as prefixes, so results update while typing (`ru` finds "Run")
+/// - `"easy run"` matches the phrase, `"easy run"*` its prefix
+/// - `AND`, `OR` and `NOT` (upper case) combine terms and parentheses group
+///   them; words next to each other must all match. An operator missing a
+///   term on either side is searched for as a word.
+/// - `NEAR(tempo hills, 5)` matches terms within 5 words of each other
+///
+/// Returns `None` when there is nothing to search for.
+pub fn fts_query(input: &str) -> Option<String> {
+    let mut builder = QueryBuilder::default();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() || c == ',' {
+            chars.next();
+        } else if c == '(' {
+            chars.next();
+            builder.open();
+        } else if c == ')' {
+            chars.next();
+            builder.close();
+        } else if c == '"' {
+            chars.next();
+            if let Some(term) = read_phrase(&mut chars) {
+                builder.term(term);
+            }
+        } else {
+            let word = read_word(&mut chars);
+            match word.as_str() {
+                "AND" => builder.operator("AND"),
+                "OR" => builder.operator("OR"),
+                "NOT" => builder.operator("NOT"),
+                "NEAR" if chars.next_if_eq(&'(').is_some() => {
+                    if let Some(term) = read_near(&mut chars) {
+                        builder.term(term);
+                    }
+                }
+                _ => {
+                    if let Some(term) = word_term(&word) {
+                        builder.term(term);
+                    }
+                }
+            }
+        }
+    }
+
+    builder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    const CASES: &[(&str, Option<&str>)] = &[
+        // Nothing to search for
+        ("", None),
+        ("   ", None),
+        ("*", None),
+        ("\"\"", None),
+        ("()", None),
+        ("NEAR()", None),
+        // Words are prefixes, phrases are exact unless followed by *
+        ("ru", Some(r#""ru"*"#)),
+        ("run*", Some(r#""run"*"#)),
+        ("easy run", Some(r#""easy"* "run"*"#)),
+        ("easy,run", Some(r#""easy"* "run"*"#)),
+        ("o'brien", Some(r#""o'brien"*"#)),
+        (r#""easy run""#, Some(r#""easy run""#)),
+        (r#""easy run"*"#, Some(r#""easy run"*"#)),
+        (r#""unclosed phrase"#, Some(r#""unclosed phrase""#)),
+        (r#"say "" "hi""#, Some(r#""say"* "hi""#)),
+        // Operators between terms
+        ("run OR ride", Some(r#""run"* OR "ride"*"#)),
+        ("run AND ride", Some(r#""run"* AND "ride"*"#)),
+        ("run NOT ride", Some(r#""run"* NOT "ride"*"#)),
+        ("run AND NOT ride", Some(r#""run"* NOT "ride"*"#)),
+        ("a NOT OR b", Some(r#""a"* NOT "b"*"#)),
+        ("a OR OR b", Some(r#""a"* OR "b"*"#)),
+        ("run or ride", Some(r#""run"* "or"* "ride"*"#)),
+        // Operators missing an operand are words
+        ("NOT run", Some(r#""NOT" "run"*"#)),
+        ("run NOT", Some(r#""run"* "NOT""#)),
+        ("a OR", Some(r#""a"* "OR""#)),
+        ("OR a", Some(r#""OR" "a"*"#)),
+        ("(NOT run)", Some(r#"( "NOT" "run"* )"#)),
+        ("(a OR) b", Some(r#"( "a"* "OR" ) AND "b"*"#)),
+        // Parentheses
+        ("(run OR ride) hills", Some(r#"( "run"* OR "ride"* ) AND "hills"*"#)),
+        ("hills (run OR ride)", Some(r#""hills"* AND ( "run"* OR "ride"* )"#)),
+        ("((a))", Some(r#"( ( "a"* ) )"#)),
+        ("a ()", Some(r#""a"*"#)),
+        (")a(", Some(r#""a"*"#)),
+        ("a (b", Some(r#""a"* AND ( "b"* )"#)),
+        // NEAR groups
+        ("NEAR(tempo hills)", Some(r#"NEAR("tempo"* "hills"*)"#)),
+        (r#"NEAR(tempo "hill repeats", 5)"#, Some(r#"NEAR("tempo"* "hill repeats", 5)"#)),
+        ("run NEAR(a b)", Some(r#""run"* NEAR("a"* "b"*)"#)),
+        ("NEAR (a b)", Some(r#""NEAR"* AND ( "a"* "b"* )"#)),
+    ];
+
+    #[test]
+    fn builds_fts_queries() {
+        for (input, expected) in CASES {
+            assert_eq!(fts_query(input).as_deref(), *expected, "input {:?}", input);
+        }
+    }
+
+    fn matching(conn: &Connection, input: &str) -> Vec<i64> {
+        let query = fts_query(input).unwrap();
+        let mut stmt = conn.prepare("SELECT rowid FROM docs WHERE docs MATCH ? ORDER BY rowid").unwrap();
+        let rows = stmt.query_map([&query], |row| row.get(0)).unwrap();
+        rows.collect::<rusqlite::Result<_>>().unwrap_or_else(|e| panic!("{:?} as {:?}: {}", input, query, e))
+    }
+
+    #[test]
+    fn queries_are_valid_fts5() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE docs USING fts5(body);
+             INSERT INTO docs (rowid, body) VALUES (1, 'easy run'), (2, 'hill ride'), (3, 'notes on a run');",
+        )
+        .unwrap();
+        for (input, expected) in CASES {
+            if expected.is_some() {
+                matching(&conn, input);
+            }
+        }
+
+        assert_eq!(matching(&conn, "run"), vec![1, 3]);
+        assert_eq!(matching(&conn, "run NOT easy"), vec![3]);
+        // A leading NOT is the word "not", not everything but what follows
+        // and not a search for "run" either
+        assert!(matching(&conn, "NOT ride").is_empty());
+        assert!(matching(&conn, "NOT run").is_empty());
+        assert_eq!(matching(&conn, "run OR ride"), vec![1, 2, 3]);
+        assert_eq!(matching(&conn, "NEAR(easy run, 1)"), vec![1]);
+    }
+}
@@ -0,0 +1,140 @@
+//! Display categories of FIT sports and sub-sports. Workouts keep the sport
+//! and sub-sport their file gives; the category they are listed, filtered
+//! and counted under comes from a user-editable mapping.
+
+use serde::{Deserialize, Serialize};
+
+/// The sub-sport filter of workouts without a sub-sport, which are stored
+/// with none rather than FIT's `generic`
+pub const GENERIC_SUB_SPORT: &str = "generic";
+
+/// Maps a sport, or one of its sub-sports when `sub_sport` is set, onto a
+/// display category
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SportCategory {
+    pub sport: String,
+    pub sub_sport: Option<String>,
+    pub category: String,
+}
+
+impl SportCategory {
+    fn new(sport: &str, sub_sport: Option<&str>, category: &str) -> Self {
+        Self {
+            sport: sport.to_string(),
+            sub_sport: sub_sport.map(str::to_string),
+            category: category.to_string(),
+        }
+    }
+}
+
+/// Groups sports the way most training logs do. Sports left out are their
+/// own category.
+pub fn default_sport_categories() -> Vec<SportCategory> {
+    vec![
+        SportCategory::new("running", None, "running"),
+        SportCategory::new("wheelchair_push_run", None, "running"),
+        SportCategory::new("cycling", None, "cycling"),
+        SportCategory::new("e_biking", None, "cycling"),
+        SportCategory::new("fitness_equipment", Some("indoor_cycling"), "cycling"),
+        SportCategory::new("fitness_equipment", Some("spin"), "cycling"),
+        SportCategory::new("fitness_equipment", Some("treadmill"), "running"),
+        SportCategory::new("fitness_equipment", Some("indoor_running"), "running"),
+        SportCategory::new("fitness_equipment", Some("indoor_rowing"), "rowing"),
+        SportCategory::new("fitness_equipment", None, "cardio"),
+        SportCategory::new("swimming", None, "swimming"),
+        SportCategory::new("walking", None, "walking"),
+        SportCategory::new("wheelchair_push_walk", None, "walking"),
+        SportCategory::new("hiking", None, "hiking"),
+        SportCategory::new("mountaineering", None, "hiking"),
+        SportCategory::new("snowshoeing", None, "hiking"),
+        SportCategory::new("training", Some("strength_training"), "strength_training"),
+        SportCategory::new("training", Some("yoga"), "mind_and_body"),
+        SportCategory::new("training", Some("pilates"), "mind_and_body"),
+        SportCategory::new("training", Some("flexibility_training"), "mind_and_body"),
+        SportCategory::new("training", Some("breathing"), "mind_and_body"),
+        SportCategory::new("training", Some("cardio_training"), "cardio"),
+        SportCategory::new("training", Some("hiit"), "cardio"),
+        SportCategory::new("training", None, "strength_training"),
+        SportCategory::new("strength_training", None, "strength_training"),
+        SportCategory::new("meditation", None, "mind_and_body"),
+        SportCategory::new("hiit", None, "cardio"),
+        SportCategory::new("jump_rope", None, "cardio"),
+        SportCategory::new("boxing", None, "cardio"),
+        SportCategory::new("floor_climbing", None, "cardio"),
+        SportCategory::new("dance", None, "cardio"),
+        SportCategory::new("rowing", None, "rowing"),
+        SportCategory::new("paddling", None, "water_sports"),
+        SportCategory::new("kayaking", None, "water_sports"),
+        SportCategory::new("rafting", None, "water_sports"),
+        SportCategory::new("stand_up_paddleboarding", None, "water_sports"),
+        SportCategory::new("surfing", None, "water_sports"),
+        SportCategory::new("windsurfing", None, "water_sports"),
+        SportCategory::new("kitesurfing", None, "water_sports"),
+        SportCategory::new("sailing", None, "water_sports"),
+        SportCategory::new("cross_country_skiing", None, "winter_sports"),
+        SportCategory::new("alpine_skiing", None, "winter_sports"),
+        SportCategory::new("snowboarding", None, "winter_sports"),
+        SportCategory::new("ice_skating", None, "winter_sports"),
+        SportCategory::new("tennis", None, "racket_sports"),
+        SportCategory::new("racket", None, "racket_sports"),
+        SportCategory::new("soccer", None, "team_sports"),
+        SportCategory::new("basketball", None, "team_sports"),
+        SportCategory::new("american_football", None, "team_sports"),
+        SportCategory::new("baseball", None, "team_sports"),
+        SportCategory::new("cricket", None, "team_sports"),
+        SportCategory::new("rugby", None, "team_sports"),
+        SportCategory::new("hockey", None, "team_sports"),
+        SportCategory::new("lacrosse", None, "team_sports"),
+        SportCategory::new("volleyball", None, "team_sports"),
+        SportCategory::new("rock_climbing", None, "climbing"),
+    ]
+}
+
+/// The category of a sport and sub-sport: the mapping for both, else the
+/// one for the sport alone, else the sport itself
+pub fn categorize(categories: &[SportCategory], sport: Option<&str>, sub_sport: Option<&str>) -> String {
+    let sport = sport.unwrap_or("generic");
+    let matching = |sub: Option<&str>| {
+        categories.iter().find(|c| c.sport == sport && c.sub_sport.as_deref() == sub)
+    };
+    sub_sport
+        .and_then(|sub| matching(Some(sub)))
+        .or_else(|| matching(None))
+        .map(|c| c.category.clone())
+        .unwrap_or_else(|| sport.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categorizes_by_sub_sport_then_sport() {
+        let categories = default_sport_categories();
+        let category = |sport, sub_sport| categorize(&categories, sport, sub_sport);
+
+        // The mapping for the sub-sport
+        assert_eq!(category(Some("fitness_equipment"), Some("treadmill")), "running");
+        assert_eq!(category(Some("training"), Some("yoga")), "mind_and_body");
+        // The sport's own when its sub-sport has none
+        assert_eq!(category(Some("fitness_equipment"), Some("elliptical")), "cardio");
+        assert_eq!(category(Some("running"), Some("trail")), "running");
+        assert_eq!(category(Some("training"), None), "strength_training");
+        // Sports without a mapping are their own category
+        assert_eq!(category(Some("flying"), None), "flying");
+        assert_eq!(category(Some("sport_99"), Some("treadmill")), "sport_99");
+        assert_eq!(category(None, None), "generic");
+    }
+
+    #[test]
+    fn edited_mappings_replace_defaults() {
+        let categories = vec![
+            SportCategory::new("cycling", Some("gravel_cycling"), "gravel"),
+            SportCategory::new("cycling", None, "riding"),
+        ];
+
+        assert_eq!(categorize(&categories, Some("cycling"), Some("gravel_cycling")), "gravel");
+        assert_eq!(categorize(&categories, Some("cycling"), Some("road")), "riding");
+        assert_eq!(categorize(&categories, Some("running"), None), "running");
+    }
+}
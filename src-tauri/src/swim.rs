@@ -0,0 +1,380 @@
+use crate::database::{Lap, SwimLength};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SwimLengthStats {
+    pub length_index: i64,
+    pub start_time: Option<String>,
+    pub duration_seconds: Option<f64>,
+    pub length_type: Option<String>,
+    pub swim_stroke: Option<String>,
+    pub total_strokes: Option<i64>,
+    pub pace_per_100m_seconds: Option<f64>,
+    pub swolf: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SwimInterval {
+    pub interval_index: i64,
+    pub swim_stroke: Option<String>,
+    pub lengths: i64,
+    pub distance_meters: f64,
+    pub duration_seconds: f64,
+    pub rest_after_seconds: f64,
+    pub pace_per_100m_seconds: Option<f64>,
+    pub avg_swolf: Option<f64>,
+    pub avg_strokes_per_length: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StrokeBreakdown {
+    pub swim_stroke: String,
+    pub lengths: i64,
+    pub distance_meters: f64,
+    pub duration_seconds: f64,
+    pub pace_per_100m_seconds: Option<f64>,
+    pub avg_swolf: Option<f64>,
+    pub avg_strokes_per_length: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SwimAnalysis {
+    pub pool_length_meters: f64,
+    pub active_lengths: i64,
+    pub distance_meters: f64,
+    pub swim_time_seconds: f64,
+    pub rest_time_seconds: f64,
+    pub pace_per_100m_seconds: Option<f64>,
+    pub avg_swolf: Option<f64>,
+    pub avg_strokes_per_length: Option<f64>,
+    pub strokes: Vec<StrokeBreakdown>,
+    pub intervals: Vec<SwimInterval>,
+    pub lengths: Vec<SwimLengthStats>,
+}
+
+fn is_active(length: &SwimLength) -> bool {
+    length.length_type.as_deref() != Some("idle")
+}
+
+fn parse_time(ts: Option<&str>) -> Option<DateTime<Utc>> {
+    ts.and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// SWOLF is the time for one length in seconds plus the strokes taken
+fn swolf(length: &SwimLength) -> Option<f64> {
+    Some(length.duration_seconds?.round() + length.total_strokes? as f64)
+}
+
+fn pace_per_100m(duration_seconds: f64, distance_meters: f64) -> Option<f64> {
+    if distance_meters > 0.0 && duration_seconds > 0.0 {
+        Some(duration_seconds / distance_meters * 100.0)
+    } else {
+        None
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0usize), |(s, c), v| (s + v, c + 1));
+    if count > 0 { Some(sum / count as f64) } else { None }
+}
+
+/// Groups lengths into intervals. Laps mark intervals when the device recorded
+/// them, otherwise a run of active lengths ends at the next idle length.
+fn group_lengths<'a>(laps: &[Lap], lengths: &'a [SwimLength]) -> Vec<Vec<&'a SwimLength>> {
+    let lap_ranges: Vec<(DateTime<Utc>, DateTime<Utc>)> = laps.iter()
+        .filter_map(|lap| Some((parse_time(lap.start_time.as_deref())?, parse_time(lap.end_time.as_deref())?)))
+        .collect();
+
+    let mut groups: Vec<Vec<&SwimLength>> = Vec::new();
+    if !lap_ranges.is_empty() {
+        for (start, end) in &lap_ranges {
+            let group: Vec<&SwimLength> = lengths.iter()
+                .filter(|l| {
+                    parse_time(l.start_time.as_deref())
+                        .map(|t| t >= *start && t < *end)
+                        .unwrap_or(false)
+                })
+                .collect();
+            if !group.is_empty() {
+                groups.push(group);
+            }
+        }
+        return groups;
+    }
+
+    let mut current: Vec<&SwimLength> = Vec::new();
+    for length in lengths {
+        if is_active(length) && current.last().map(|l| !is_active(l)).unwrap_or(false) {
+            groups.push(std::mem::take(&mut current));
+        }
+        current.push(length);
+    }
+    if !current.is_empty() {
+        groups.push(current);
+    }
+    groups
+}
+
+/// Builds per-length, per-interval and per-stroke statistics for a pool swim.
+/// Returns None when the workout has no recorded lengths or the pool length is unknown.
+pub fn analyze_swim(
+    pool_length_meters: Option<f64>,
+    laps: &[Lap],
+    lengths: &[SwimLength],
+) -> Option<SwimAnalysis> {
+    let pool_length = pool_length_meters.filter(|l| *l > 0.0)?;
+    if lengths.is_empty() {
+        return None;
+    }
+
+    let length_stats: Vec<SwimLengthStats> = lengths.iter()
+        .map(|l| {
+            let active = is_active(l);
+            SwimLengthStats {
+                length_index: l.length_index,
+                start_time: l.start_time.clone(),
+                duration_seconds: l.duration_seconds,
+                length_type: l.length_type.clone(),
+                swim_stroke: l.swim_stroke.clone(),
+                total_strokes: l.total_strokes,
+                pace_per_100m_seconds: if active {
+                    l.duration_seconds.and_then(|d| pace_per_100m(d, pool_length))
+                } else {
+                    None
+                },
+                swolf: if active { swolf(l) } else { None },
+            }
+        })
+        .collect();
+
+    let active: Vec<&SwimLength> = lengths.iter().filter(|l| is_active(l)).collect();
+    let swim_time: f64 = active.iter().filter_map(|l| l.duration_seconds).sum();
+    let rest_time: f64 = lengths.iter()
+        .filter(|l| !is_active(l))
+        .filter_map(|l| l.duration_seconds)
+        .sum();
+    let distance = active.len() as f64 * pool_length;
+
+    // Stroke breakdown over active lengths
+    let mut by_stroke: BTreeMap<String, Vec<&SwimLength>> = BTreeMap::new();
+    for length in &active {
+        let stroke = length.swim_stroke.clone().unwrap_or_else(|| "unknown".to_string());
+        by_stroke.entry(stroke).or_default().push(length);
+    }
+    let strokes: Vec<StrokeBreakdown> = by_stroke.into_iter()
+        .map(|(stroke, ls)| {
+            let duration: f64 = ls.iter().filter_map(|l| l.duration_seconds).sum();
+            let stroke_distance = ls.len() as f64 * pool_length;
+            StrokeBreakdown {
+                swim_stroke: stroke,
+                lengths: ls.len() as i64,
+                distance_meters: stroke_distance,
+                duration_seconds: duration,
+                pace_per_100m_seconds: pace_per_100m(duration, stroke_distance),
+                avg_swolf: mean(ls.iter().filter_map(|l| swolf(l))),
+                avg_strokes_per_length: mean(ls.iter().filter_map(|l| l.total_strokes.map(|s| s as f64))),
+            }
+        })
+        .collect();
+
+    // Intervals, with idle-only groups counted as rest after the previous interval
+    let mut intervals: Vec<SwimInterval> = Vec::new();
+    for group in group_lengths(laps, lengths) {
+        let group_active: Vec<&SwimLength> = group.iter().copied().filter(|l| is_active(l)).collect();
+        let rest: f64 = group.iter()
+            .filter(|l| !is_active(l))
+            .filter_map(|l| l.duration_seconds)
+            .sum();
+
+        if group_active.is_empty() {
+            if let Some(last) = intervals.last_mut() {
+                last.rest_after_seconds += rest;
+            }
+            continue;
+        }
+
+        let duration: f64 = group_active.iter().filter_map(|l| l.duration_seconds).sum();
+        let interval_distance = group_active.len() as f64 * pool_length;
+        let first_stroke = group_active[0].swim_stroke.clone();
+        let swim_stroke = if group_active.iter().all(|l| l.swim_stroke == first_stroke) {
+            first_stroke
+        } else {
+            Some("mixed".to_string())
+        };
+
+        intervals.push(SwimInterval {
+            interval_index: intervals.len() as i64,
+            swim_stroke,
+            lengths: group_active.len() as i64,
+            distance_meters: interval_distance,
+            duration_seconds: duration,
+            rest_after_seconds: rest,
+            pace_per_100m_seconds: pace_per_100m(duration, interval_distance),
+            avg_swolf: mean(group_active.iter().filter_map(|l| swolf(l))),
+            avg_strokes_per_length: mean(group_active.iter().filter_map(|l| l.total_strokes.map(|s| s as f64))),
+        });
+    }
+
+    Some(SwimAnalysis {
+        pool_length_meters: pool_length,
+        active_lengths: active.len() as i64,
+        distance_meters: distance,
+        swim_time_seconds: swim_time,
+        rest_time_seconds: rest_time,
+        pace_per_100m_seconds: pace_per_100m(swim_time, distance),
+        avg_swolf: mean(active.iter().filter_map(|l| swolf(l))),
+        avg_strokes_per_length: mean(active.iter().filter_map(|l| l.total_strokes.map(|s| s as f64))),
+        strokes,
+        intervals,
+        lengths: length_stats,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: &str = "2025-06-01T08:00:00+00:00";
+
+    fn at(offset_seconds: i64) -> Option<String> {
+        let start = DateTime::parse_from_rfc3339(START).unwrap();
+        Some((start + chrono::Duration::seconds(offset_seconds)).to_rfc3339())
+    }
+
+    fn length(index: i64, offset: i64, seconds: f64, stroke: &str, strokes: i64) -> SwimLength {
+        SwimLength {
+            length_index: index,
+            start_time: at(offset),
+            duration_seconds: Some(seconds),
+            length_type: Some("active".to_string()),
+            swim_stroke: Some(stroke.to_string()),
+            total_strokes: Some(strokes),
+            avg_speed_mps: None,
+            avg_swimming_cadence: None,
+        }
+    }
+
+    fn rest(index: i64, offset: i64, seconds: f64) -> SwimLength {
+        SwimLength {
+            length_type: Some("idle".to_string()),
+            swim_stroke: None,
+            total_strokes: None,
+            ..length(index, offset, seconds, "", 0)
+        }
+    }
+
+    fn lap(index: i64, start: i64, end: i64) -> Lap {
+        Lap {
+            id: index,
+            workout_id: 1,
+            lap_index: index,
+            start_time: at(start),
+            end_time: at(end),
+            duration_seconds: Some((end - start) as f64),
+            distance_meters: None,
+            total_calories: None,
+            avg_heart_rate: None,
+            max_heart_rate: None,
+            avg_power_watts: None,
+            max_power_watts: None,
+            avg_cadence: None,
+            max_cadence: None,
+            avg_speed_mps: None,
+            max_speed_mps: None,
+            elevation_gain_meters: None,
+            elevation_loss_meters: None,
+            lap_trigger: None,
+            swim_stroke: None,
+            total_strokes: None,
+            num_lengths: None,
+            num_active_lengths: None,
+        }
+    }
+
+    /// Two lengths of freestyle, a rest at the wall, then a length of
+    /// breaststroke and one of freestyle
+    fn lengths() -> Vec<SwimLength> {
+        vec![
+            length(0, 0, 20.0, "freestyle", 15),
+            length(1, 20, 22.0, "freestyle", 17),
+            rest(2, 42, 30.0),
+            length(3, 72, 30.0, "breaststroke", 10),
+            length(4, 102, 25.0, "freestyle", 15),
+        ]
+    }
+
+    #[test]
+    fn totals_pace_and_swolf() {
+        let analysis = analyze_swim(Some(25.0), &[], &lengths()).unwrap();
+
+        assert_eq!(analysis.active_lengths, 4);
+        assert_eq!(analysis.distance_meters, 100.0);
+        assert_eq!(analysis.swim_time_seconds, 97.0);
+        assert_eq!(analysis.rest_time_seconds, 30.0);
+        assert_eq!(analysis.pace_per_100m_seconds, Some(97.0));
+        assert_eq!(analysis.avg_swolf, Some(38.5));
+        assert_eq!(analysis.avg_strokes_per_length, Some(14.25));
+
+        let first = &analysis.lengths[0];
+        assert_eq!(first.pace_per_100m_seconds, Some(80.0));
+        assert_eq!(first.swolf, Some(35.0));
+        // Rest at the wall has neither
+        assert_eq!(analysis.lengths[2].pace_per_100m_seconds, None);
+        assert_eq!(analysis.lengths[2].swolf, None);
+    }
+
+    #[test]
+    fn swolf_rounds_the_length_time() {
+        assert_eq!(swolf(&length(0, 0, 21.6, "freestyle", 17)), Some(39.0));
+        assert_eq!(swolf(&SwimLength { total_strokes: None, ..length(0, 0, 21.6, "freestyle", 17) }), None);
+    }
+
+    #[test]
+    fn breaks_down_active_lengths_by_stroke() {
+        let analysis = analyze_swim(Some(25.0), &[], &lengths()).unwrap();
+        let strokes: Vec<_> = analysis.strokes.iter()
+            .map(|s| (s.swim_stroke.as_str(), s.lengths, s.distance_meters, s.duration_seconds))
+            .collect();
+
+        assert_eq!(strokes, [("breaststroke", 1, 25.0, 30.0), ("freestyle", 3, 75.0, 67.0)]);
+        assert_eq!(analysis.strokes[0].pace_per_100m_seconds, Some(120.0));
+        assert_eq!(analysis.strokes[1].avg_swolf, Some(38.0));
+    }
+
+    #[test]
+    fn without_laps_an_idle_length_ends_an_interval() {
+        let analysis = analyze_swim(Some(25.0), &[], &lengths()).unwrap();
+        let intervals: Vec<_> = analysis.intervals.iter()
+            .map(|i| (i.swim_stroke.as_deref(), i.lengths, i.duration_seconds, i.rest_after_seconds))
+            .collect();
+
+        assert_eq!(intervals, [(Some("freestyle"), 2, 42.0, 30.0), (Some("mixed"), 2, 55.0, 0.0)]);
+        assert_eq!(analysis.intervals[0].pace_per_100m_seconds, Some(84.0));
+        assert_eq!(analysis.intervals[0].avg_swolf, Some(37.0));
+    }
+
+    #[test]
+    fn laps_mark_intervals_and_rest_laps_add_to_the_one_before() {
+        // The rest has a lap of its own, and the last two lengths one each
+        let laps = [lap(0, 0, 42), lap(1, 42, 72), lap(2, 72, 102), lap(3, 102, 127)];
+        let analysis = analyze_swim(Some(25.0), &laps, &lengths()).unwrap();
+        let intervals: Vec<_> = analysis.intervals.iter()
+            .map(|i| (i.interval_index, i.swim_stroke.as_deref(), i.lengths, i.rest_after_seconds))
+            .collect();
+
+        assert_eq!(
+            intervals,
+            [(0, Some("freestyle"), 2, 30.0), (1, Some("breaststroke"), 1, 0.0), (2, Some("freestyle"), 1, 0.0)]
+        );
+    }
+
+    #[test]
+    fn needs_a_pool_length_and_lengths() {
+        assert!(analyze_swim(None, &[], &lengths()).is_none());
+        assert!(analyze_swim(Some(0.0), &[], &lengths()).is_none());
+        assert!(analyze_swim(Some(25.0), &[], &[]).is_none());
+    }
+}
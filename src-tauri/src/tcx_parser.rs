@@ -0,0 +1,319 @@
+use crate::fit_parser::{calculate_elevation_changes, hash_bytes, FileCreator, LapData, ParsedFitData};
+use crate::track::{build_track, normalize_sport, normalize_sub_sport, parse_xml, TrackPoint};
+use chrono::{DateTime, Duration, Utc};
+use log::info;
+
+fn child<'a, 'input>(node: roxmltree::Node<'a, 'input>, name: &str) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|c| c.is_element() && c.tag_name().name() == name)
+}
+
+fn child_text<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name).and_then(|c| c.text()).map(|t| t.trim())
+}
+
+fn child_f64(node: roxmltree::Node, name: &str) -> Option<f64> {
+    child_text(node, name).and_then(|t| t.parse().ok())
+}
+
+/// Heart rate values are wrapped in a `<Value>` element
+fn child_bpm(node: roxmltree::Node, name: &str) -> Option<i64> {
+    child(node, name)
+        .and_then(|c| child_f64(c, "Value"))
+        .map(|v| v.round() as i64)
+}
+
+/// Reads a field from the ActivityExtension v2 block (`TPX` on trackpoints,
+/// `LX` on laps), matched by local name regardless of namespace prefix.
+fn extension_f64(node: roxmltree::Node, name: &str) -> Option<f64> {
+    child(node, "Extensions")?
+        .descendants()
+        .find(|d| d.is_element() && d.tag_name().name() == name)
+        .and_then(|d| d.text())
+        .and_then(|t| t.trim().parse().ok())
+}
+
+fn parse_time(text: Option<&str>) -> Option<DateTime<Utc>> {
+    text.and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn trigger_method_to_string(method: &str) -> String {
+    match method {
+        "Manual" => "manual".to_string(),
+        "Distance" => "distance".to_string(),
+        "Time" => "time".to_string(),
+        "Location" => "position_lap".to_string(),
+        "HeartRate" => "heart_rate".to_string(),
+        other => other.to_lowercase(),
+    }
+}
+
+fn parse_trackpoint(node: roxmltree::Node) -> TrackPoint {
+    let position = child(node, "Position");
+    TrackPoint {
+        time: parse_time(child_text(node, "Time")),
+        lat: position.and_then(|p| child_f64(p, "LatitudeDegrees")),
+        lon: position.and_then(|p| child_f64(p, "LongitudeDegrees")),
+        altitude: child_f64(node, "AltitudeMeters"),
+        heart_rate: child_bpm(node, "HeartRateBpm"),
+        cadence: child_f64(node, "Cadence")
+            .or_else(|| extension_f64(node, "RunCadence"))
+            .map(|v| v.round() as i64),
+        power: extension_f64(node, "Watts").map(|v| v.round() as i64),
+        speed: extension_f64(node, "Speed"),
+        distance: child_f64(node, "DistanceMeters"),
+        temperature: None,
+    }
+}
+
+fn parse_lap(node: roxmltree::Node, points: &[TrackPoint]) -> LapData {
+    let start = node.attribute("StartTime").and_then(|t| parse_time(Some(t)));
+    let duration = child_f64(node, "TotalTimeSeconds");
+    let end = match (start, duration) {
+        (Some(s), Some(d)) => Some(s + Duration::milliseconds((d * 1000.0) as i64)),
+        _ => points.iter().filter_map(|p| p.time).max(),
+    };
+
+    let powers: Vec<i64> = points.iter().filter_map(|p| p.power).collect();
+    let cadences: Vec<i64> = points.iter().filter_map(|p| p.cadence).collect();
+    let altitudes: Vec<f64> = points.iter().filter_map(|p| p.altitude).collect();
+    let (elevation_gain, elevation_loss) = calculate_elevation_changes(&altitudes);
+
+    let avg = |values: &[i64]| {
+        if values.is_empty() {
+            None
+        } else {
+            Some((values.iter().sum::<i64>() as f64 / values.len() as f64).round() as i64)
+        }
+    };
+
+    let distance = child_f64(node, "DistanceMeters");
+    let avg_speed = extension_f64(node, "AvgSpeed").or(match (distance, duration) {
+        (Some(d), Some(t)) if t > 0.0 => Some(d / t),
+        _ => None,
+    });
+
+    LapData {
+        start_time: start.map(|t| t.to_rfc3339()),
+        end_time: end.map(|t| t.to_rfc3339()),
+        duration_seconds: duration,
+        distance_meters: distance,
+        total_calories: child_f64(node, "Calories").map(|v| v.round() as i64),
+        avg_heart_rate: child_bpm(node, "AverageHeartRateBpm"),
+        max_heart_rate: child_bpm(node, "MaximumHeartRateBpm"),
+        avg_power_watts: extension_f64(node, "AvgWatts").map(|v| v.round() as i64).or_else(|| avg(&powers)),
+        max_power_watts: extension_f64(node, "MaxWatts")
+            .map(|v| v.round() as i64)
+            .or_else(|| powers.iter().copied().max()),
+        avg_cadence: child_f64(node, "Cadence")
+            .or_else(|| extension_f64(node, "AvgRunCadence"))
+            .map(|v| v.round() as i64)
+            .or_else(|| avg(&cadences)),
+        max_cadence: extension_f64(node, "MaxBikeCadence")
+            .or_else(|| extension_f64(node, "MaxRunCadence"))
+            .map(|v| v.round() as i64)
+            .or_else(|| cadences.iter().copied().max()),
+        avg_speed_mps: avg_speed,
+        max_speed_mps: child_f64(node, "MaximumSpeed"),
+        elevation_gain_meters: elevation_gain,
+        elevation_loss_meters: elevation_loss,
+        lap_trigger: child_text(node, "TriggerMethod").map(trigger_method_to_string),
+        swim_stroke: None,
+        total_strokes: None,
+        num_lengths: None,
+        num_active_lengths: None,
+    }
+}
+
+pub fn parse_tcx_bytes(file_data: &[u8], filename: &str) -> Result<ParsedFitData, String> {
+    info!("Parsing TCX file: {}", filename);
+
+    let file_hash = hash_bytes(file_data);
+    let filename = filename.to_string();
+
+    let text = String::from_utf8_lossy(file_data);
+    let doc = parse_xml(&text)
+        .map_err(|e| format!("Failed to parse TCX file: {}", e))?;
+
+    let activity = doc.descendants()
+        .find(|n| n.is_element() && n.tag_name().name() == "Activity")
+        .ok_or_else(|| "TCX file contains no activity".to_string())?;
+
+    let workout_type = activity.attribute("Sport").map(normalize_sport);
+    let sub_sport = activity.attribute("Sport").and_then(normalize_sub_sport);
+
+    let mut points: Vec<TrackPoint> = Vec::new();
+    let mut laps: Vec<LapData> = Vec::new();
+
+    for lap_node in activity.children().filter(|c| c.is_element() && c.tag_name().name() == "Lap") {
+        let lap_points: Vec<TrackPoint> = lap_node.descendants()
+            .filter(|n| n.is_element() && n.tag_name().name() == "Trackpoint")
+            .map(parse_trackpoint)
+            .collect();
+        laps.push(parse_lap(lap_node, &lap_points));
+        points.extend(lap_points);
+    }
+
+    if laps.is_empty() {
+        return Err("TCX activity contains no laps".to_string());
+    }
+
+    let track = build_track(&points);
+    let summary = track.summary;
+
+    // Lap totals are what the device recorded; fall back to the samples when absent
+    let lap_sum = |f: fn(&LapData) -> Option<f64>| {
+        let values: Vec<f64> = laps.iter().filter_map(f).collect();
+        if values.is_empty() { None } else { Some(values.iter().sum::<f64>()) }
+    };
+
+    let start_time = summary.start_time.clone()
+        .or_else(|| laps.first().and_then(|l| l.start_time.clone()));
+    let end_time = summary.end_time.clone()
+        .or_else(|| laps.last().and_then(|l| l.end_time.clone()));
+    let duration_seconds = summary.duration_seconds
+        .or_else(|| lap_sum(|l| l.duration_seconds).map(|d| d.round() as i64));
+    // Lap TotalTimeSeconds is timer time, so paused laps add up to less
+    let timer_seconds = lap_sum(|l| l.duration_seconds).map(|d| d.round() as i64);
+    let distance_meters = lap_sum(|l| l.distance_meters)
+        .filter(|d| *d > 0.0)
+        .or(summary.distance_meters);
+    let total_calories = lap_sum(|l| l.total_calories.map(|c| c as f64)).map(|c| c.round() as i64);
+
+    let avg_speed_mps = match (distance_meters, duration_seconds) {
+        (Some(d), Some(t)) if t > 0 => Some(d / t as f64),
+        _ => summary.avg_speed,
+    };
+    let max_speed_mps = laps.iter()
+        .filter_map(|l| l.max_speed_mps)
+        .chain(summary.max_speed)
+        .reduce(f64::max);
+
+    info!(
+        "Parsed TCX workout: type={:?}, duration={:?}s, distance={:?}m, laps={}, gps_points={}, sensor_points={}",
+        workout_type, duration_seconds, distance_meters, laps.len(),
+        track.gps_data.len(), track.sensor_data.len()
+    );
+
+    Ok(ParsedFitData {
+        file_hash,
+        filename,
+        workout_type,
+        sub_sport,
+        start_time,
+        end_time,
+        duration_seconds,
+        timer_seconds,
+        distance_meters,
+        total_calories,
+        avg_heart_rate: summary.avg_heart_rate,
+        max_heart_rate: summary.max_heart_rate
+            .or_else(|| laps.iter().filter_map(|l| l.max_heart_rate).max()),
+        avg_power_watts: summary.avg_power,
+        max_power_watts: summary.max_power,
+        avg_cadence: summary.avg_cadence,
+        max_cadence: summary.max_cadence,
+        avg_speed_mps,
+        max_speed_mps,
+        elevation_gain_meters: summary.elevation_gain,
+        elevation_loss_meters: summary.elevation_loss,
+        pool_length_meters: None,
+        gps_data: track.gps_data,
+        sensor_data: track.sensor_data,
+        laps,
+        lengths: Vec::new(),
+        pauses: Vec::new(),
+        developer_fields: Vec::new(),
+        rr_intervals_ms: Vec::new(),
+        creator: FileCreator::default(),
+        devices: Vec::new(),
+        legs: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trackpoint(time: &str, lat: f64, extensions: &str) -> String {
+        format!(
+            "<Trackpoint><Time>{}</Time>
+               <Position><LatitudeDegrees>{}</LatitudeDegrees><LongitudeDegrees>0</LongitudeDegrees></Position>
+               <HeartRateBpm><Value>140</Value></HeartRateBpm>
+               <Extensions><ns3:TPX>{}</ns3:TPX></Extensions>
+             </Trackpoint>",
+            time, lat, extensions
+        )
+    }
+
+    /// Two one-minute laps of a run with a minute's pause between them.
+    /// Neither laps nor trackpoints record distance.
+    fn run() -> String {
+        let lap = |start: &str, points: [String; 2]| {
+            format!(
+                "<Lap StartTime=\"{}\"><TotalTimeSeconds>60</TotalTimeSeconds><Calories>12</Calories>
+                   <TriggerMethod>Manual</TriggerMethod><Track>{}{}</Track></Lap>",
+                start, points[0], points[1]
+            )
+        };
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+             <TrainingCenterDatabase xmlns=\"http://www.garmin.com/xmlschemas/TrainingCenterDatabase/v2\"
+                 xmlns:ns3=\"http://www.garmin.com/xmlschemas/ActivityExtension/v2\">
+               <Activities><Activity Sport=\"Running\"><Id>2025-06-01T08:00:00Z</Id>{}{}</Activity></Activities>
+             </TrainingCenterDatabase>",
+            lap("2025-06-01T08:00:00Z", [
+                trackpoint("2025-06-01T08:00:00Z", 0.0, "<ns3:Speed>3.0</ns3:Speed><ns3:RunCadence>85</ns3:RunCadence><ns3:Watts>250</ns3:Watts>"),
+                trackpoint("2025-06-01T08:01:00Z", 0.001, "<ns3:Speed>3.5</ns3:Speed><ns3:RunCadence>87</ns3:RunCadence><ns3:Watts>270</ns3:Watts>"),
+            ]),
+            lap("2025-06-01T08:02:00Z", [
+                trackpoint("2025-06-01T08:02:00Z", 0.001, ""),
+                trackpoint("2025-06-01T08:03:00Z", 0.002, ""),
+            ]),
+        )
+    }
+
+    #[test]
+    fn reads_activity_extension_values() {
+        let parsed = parse_tcx_bytes(run().as_bytes(), "run.tcx").unwrap();
+
+        let first = &parsed.sensor_data[0];
+        assert_eq!(first.speed, Some(3.0));
+        assert_eq!(first.cadence, Some(85));
+        assert_eq!(first.power, Some(250));
+        assert_eq!(first.heart_rate, Some(140));
+
+        let lap = &parsed.laps[0];
+        assert_eq!(lap.avg_power_watts, Some(260));
+        assert_eq!(lap.max_power_watts, Some(270));
+        assert_eq!(lap.avg_cadence, Some(86));
+        assert_eq!(lap.lap_trigger.as_deref(), Some("manual"));
+    }
+
+    #[test]
+    fn timer_time_sums_laps_and_elapsed_time_spans_trackpoints() {
+        let parsed = parse_tcx_bytes(run().as_bytes(), "run.tcx").unwrap();
+
+        assert_eq!(parsed.workout_type.as_deref(), Some("running"));
+        assert_eq!(parsed.timer_seconds, Some(120));
+        assert_eq!(parsed.duration_seconds, Some(180));
+        assert_eq!(parsed.total_calories, Some(24));
+        assert_eq!(parsed.start_time.as_deref(), Some("2025-06-01T08:00:00+00:00"));
+    }
+
+    #[test]
+    fn fills_in_distance_from_positions() {
+        let parsed = parse_tcx_bytes(run().as_bytes(), "run.tcx").unwrap();
+
+        let distance = parsed.distance_meters.unwrap();
+        assert!((distance - 222.39).abs() < 0.01, "distance {}", distance);
+        assert_eq!(parsed.avg_speed_mps, Some(distance / 180.0));
+    }
+
+    #[test]
+    fn reads_exports_with_a_byte_order_mark() {
+        let data = format!("\u{feff}\r\n{}", run());
+        assert!(parse_tcx_bytes(data.as_bytes(), "run.tcx").is_ok());
+        assert!(parse_tcx_bytes(b"<TrainingCenterDatabase/>", "empty.tcx").is_err());
+    }
+}
@@ -0,0 +1,257 @@
+use crate::fit_parser::{calculate_elevation_changes, GpsPoint, SensorPoint};
+use chrono::{DateTime, Utc};
+
+const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+
+/// A single trackpoint from an XML export (GPX, TCX) before derived values are filled in
+#[derive(Debug, Default, Clone)]
+pub struct TrackPoint {
+    pub time: Option<DateTime<Utc>>,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    pub altitude: Option<f64>,
+    pub heart_rate: Option<i64>,
+    pub cadence: Option<i64>,
+    pub power: Option<i64>,
+    pub speed: Option<f64>,
+    pub distance: Option<f64>,
+    pub temperature: Option<f64>,
+}
+
+/// Session-level totals derived from the samples themselves
+#[derive(Debug, Default, Clone)]
+pub struct TrackSummary {
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub duration_seconds: Option<i64>,
+    pub distance_meters: Option<f64>,
+    pub avg_heart_rate: Option<i64>,
+    pub max_heart_rate: Option<i64>,
+    pub avg_power: Option<i64>,
+    pub max_power: Option<i64>,
+    pub avg_cadence: Option<i64>,
+    pub max_cadence: Option<i64>,
+    pub avg_speed: Option<f64>,
+    pub max_speed: Option<f64>,
+    pub elevation_gain: Option<f64>,
+    pub elevation_loss: Option<f64>,
+}
+
+pub struct Track {
+    pub gps_data: Vec<GpsPoint>,
+    pub sensor_data: Vec<SensorPoint>,
+    pub summary: TrackSummary,
+}
+
+pub fn haversine_meters(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let d_lat = (lat2 - lat1).to_radians();
+    let d_lon = (lon2 - lon1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_METERS * a.sqrt().asin()
+}
+
+fn avg_i64(values: &[i64]) -> Option<i64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some((values.iter().sum::<i64>() as f64 / values.len() as f64).round() as i64)
+    }
+}
+
+/// Parses an XML export. Some apps save it with a byte order mark or blank
+/// lines before the XML declaration, which the parser would reject.
+pub fn parse_xml(text: &str) -> Result<roxmltree::Document<'_>, roxmltree::Error> {
+    roxmltree::Document::parse(text.trim_start_matches('\u{feff}').trim_start())
+}
+
+/// Maps the free-form activity names used by other apps onto our sport names
+pub fn normalize_sport(name: &str) -> String {
+    let lower = name.trim().to_lowercase();
+    match lower.as_str() {
+        "run" | "running" | "trail_running" | "treadmill_running" => "running".to_string(),
+        "ride" | "bike" | "biking" | "cycling" | "road_biking" | "mountain_biking" | "virtualride" => "cycling".to_string(),
+        "walk" | "walking" => "walking".to_string(),
+        "hike" | "hiking" => "hiking".to_string(),
+        "swim" | "swimming" | "open_water_swimming" => "swimming".to_string(),
+        "row" | "rowing" => "rowing".to_string(),
+        "other" | "" => "generic".to_string(),
+        _ => lower.replace([' ', '-'], "_"),
+    }
+}
+
+/// The FIT sub-sport the activity names of other apps imply, if any
+pub fn normalize_sub_sport(name: &str) -> Option<String> {
+    let sub_sport = match name.trim().to_lowercase().as_str() {
+        "trail_running" => "trail",
+        "treadmill_running" => "treadmill",
+        "road_biking" => "road",
+        "mountain_biking" => "mountain",
+        "virtualride" | "virtualrun" => "virtual_activity",
+        "open_water_swimming" => "open_water",
+        _ => return None,
+    };
+    Some(sub_sport.to_string())
+}
+
+/// Builds GPS and sensor arrays from trackpoints. Cumulative distance is taken
+/// from the file when present and otherwise integrated from the GPS positions;
+/// speed falls back to distance over time between consecutive points.
+pub fn build_track(points: &[TrackPoint]) -> Track {
+    let mut gps_data = Vec::new();
+    let mut sensor_data = Vec::with_capacity(points.len());
+
+    let mut cumulative = 0.0;
+    let mut last_position: Option<(f64, f64)> = None;
+    let mut last_sample: Option<(DateTime<Utc>, f64)> = None;
+
+    for point in points {
+        let timestamp = point.time.map(|t| t.to_rfc3339());
+
+        let position = match (point.lat, point.lon) {
+            (Some(lat), Some(lon)) if lat.abs() <= 90.0 && lon.abs() <= 180.0 => Some((lat, lon)),
+            _ => None,
+        };
+
+        if let Some((lat, lon)) = position {
+            if let Some((prev_lat, prev_lon)) = last_position {
+                cumulative += haversine_meters(prev_lat, prev_lon, lat, lon);
+            }
+            last_position = Some((lat, lon));
+            gps_data.push(GpsPoint {
+                timestamp: timestamp.clone(),
+                lat,
+                lon,
+                altitude: point.altitude,
+            });
+        }
+
+        let distance = point.distance.or(if last_position.is_some() { Some(cumulative) } else { None });
+        if let Some(d) = point.distance {
+            cumulative = d;
+        }
+
+        let speed = point.speed.or_else(|| {
+            let (time, dist) = (point.time?, distance?);
+            let (prev_time, prev_dist) = last_sample?;
+            let dt = (time - prev_time).num_milliseconds() as f64 / 1000.0;
+            if dt > 0.0 { Some(((dist - prev_dist) / dt).max(0.0)) } else { None }
+        });
+
+        if let (Some(time), Some(dist)) = (point.time, distance) {
+            last_sample = Some((time, dist));
+        }
+
+        sensor_data.push(SensorPoint {
+            timestamp,
+            heart_rate: point.heart_rate,
+            power: point.power,
+            cadence: point.cadence,
+            speed,
+            distance,
+            altitude: point.altitude,
+            temperature: point.temperature,
+            ..Default::default()
+        });
+    }
+
+    let summary = summarize(points, &sensor_data);
+    Track { gps_data, sensor_data, summary }
+}
+
+fn summarize(points: &[TrackPoint], sensor_data: &[SensorPoint]) -> TrackSummary {
+    let start = points.iter().filter_map(|p| p.time).min();
+    let end = points.iter().filter_map(|p| p.time).max();
+    let duration_seconds = match (start, end) {
+        (Some(s), Some(e)) => Some((e - s).num_seconds()),
+        _ => None,
+    };
+
+    let distance_meters = sensor_data.iter().filter_map(|p| p.distance).reduce(f64::max);
+
+    let heart_rates: Vec<i64> = sensor_data.iter().filter_map(|p| p.heart_rate).collect();
+    let powers: Vec<i64> = sensor_data.iter().filter_map(|p| p.power).collect();
+    let cadences: Vec<i64> = sensor_data.iter().filter_map(|p| p.cadence).collect();
+    let altitudes: Vec<f64> = sensor_data.iter().filter_map(|p| p.altitude).collect();
+    let (elevation_gain, elevation_loss) = calculate_elevation_changes(&altitudes);
+
+    let avg_speed = match (distance_meters, duration_seconds) {
+        (Some(d), Some(t)) if t > 0 => Some(d / t as f64),
+        _ => None,
+    };
+
+    TrackSummary {
+        start_time: start.map(|t| t.to_rfc3339()),
+        end_time: end.map(|t| t.to_rfc3339()),
+        duration_seconds,
+        distance_meters,
+        avg_heart_rate: avg_i64(&heart_rates),
+        max_heart_rate: heart_rates.iter().copied().max(),
+        avg_power: avg_i64(&powers),
+        max_power: powers.iter().copied().max(),
+        avg_cadence: avg_i64(&cadences),
+        max_cadence: cadences.iter().copied().max(),
+        avg_speed,
+        max_speed: sensor_data.iter().filter_map(|p| p.speed).reduce(f64::max),
+        elevation_gain,
+        elevation_loss,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(seconds: i64, lat: f64) -> TrackPoint {
+        let start = DateTime::parse_from_rfc3339("2025-06-01T08:00:00Z").unwrap().with_timezone(&Utc);
+        TrackPoint {
+            time: Some(start + chrono::Duration::seconds(seconds)),
+            lat: Some(lat),
+            lon: Some(0.0),
+            ..TrackPoint::default()
+        }
+    }
+
+    /// A thousandth of a degree of latitude
+    const MILLIDEGREE_METERS: f64 = 111.195;
+
+    #[test]
+    fn fills_in_distance_and_speed_from_positions() {
+        let track = build_track(&[point(0, 0.0), point(10, 0.001), point(20, 0.002)]);
+
+        let distances: Vec<f64> = track.sensor_data.iter().map(|p| p.distance.unwrap()).collect();
+        assert_eq!(distances[0], 0.0);
+        assert!((distances[2] - 2.0 * MILLIDEGREE_METERS).abs() < 0.01);
+        let speed = track.sensor_data[1].speed.unwrap();
+        assert!((speed - MILLIDEGREE_METERS / 10.0).abs() < 0.01);
+        assert_eq!(track.gps_data.len(), 3);
+        assert_eq!(track.summary.duration_seconds, Some(20));
+    }
+
+    #[test]
+    fn prefers_recorded_distance_and_speed() {
+        let track = build_track(&[
+            TrackPoint { distance: Some(0.0), speed: Some(2.5), ..point(0, 0.0) },
+            TrackPoint { distance: Some(120.0), ..point(10, 0.001) },
+        ]);
+
+        assert_eq!(track.sensor_data[0].speed, Some(2.5));
+        assert_eq!(track.sensor_data[1].distance, Some(120.0));
+        assert_eq!(track.sensor_data[1].speed, Some(12.0));
+        assert_eq!(track.summary.distance_meters, Some(120.0));
+    }
+
+    #[test]
+    fn skips_positions_out_of_range() {
+        let track = build_track(&[point(0, 0.0), TrackPoint { lat: Some(91.0), ..point(10, 0.0) }]);
+
+        assert_eq!(track.gps_data.len(), 1);
+        assert_eq!(track.sensor_data.len(), 2);
+    }
+
+    #[test]
+    fn parses_xml_after_a_byte_order_mark() {
+        let doc = parse_xml("\u{feff}\n  <?xml version=\"1.0\"?><gpx/>").unwrap();
+        assert_eq!(doc.root_element().tag_name().name(), "gpx");
+    }
+}
@@ -1,5 +1,10 @@
 import { useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
 import { useWorkoutStore } from './stores/workoutStore';
+import { useSettingsStore } from './stores/settingsStore';
+import { useToastStore } from './stores/toastStore';
+import { recordLabel, formatRecordValue } from './components/PersonalRecordsCard';
+import type { NewRecord } from './types';
 import Header from './components/Header';
 import Dashboard from './components/Dashboard';
 import WorkoutModal from './components/WorkoutModal';
@@ -35,9 +40,29 @@ function App() {
   }, []);
 
   useEffect(() => {
-    fetchDashboardData();
+    // The athlete profile and zones live in the database; older versions
+    // kept them in local storage, which only seeds an empty database
+    const { loadAthleteProfile, loadHrZones } = useSettingsStore.getState();
+    loadAthleteProfile().then(loadHrZones).then(fetchDashboardData);
   }, [fetchDashboardData]);
 
+  useEffect(() => {
+    // Imports report the records they broke
+    const unlisten = listen<NewRecord[]>('personal-record', (event) => {
+      const { addToast } = useToastStore.getState();
+      for (const record of event.payload) {
+        const sport = record.sport ? `${record.sport.replace(/_/g, ' ')} ` : '';
+        addToast(
+          `New ${sport}record: ${recordLabel(record.metric)} ${formatRecordValue(record.metric, record.value)}`,
+          'success'
+        );
+      }
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
   return (
     <div className="min-h-screen bg-[var(--color-bg-primary)] flex flex-col">
       <Header />
@@ -6,6 +6,9 @@ import PersonalRecordsCard from './PersonalRecordsCard';
 import WorkoutList from './WorkoutList';
 import TrainingLoadCard from './TrainingLoadCard';
 import RecoveryScoreCard from './RecoveryScoreCard';
+import PowerCurveCard from './PowerCurveCard';
+import ZoneDistributionCard from './ZoneDistributionCard';
+import HrvHistoryCard from './HrvHistoryCard';
 
 export default function Dashboard() {
   return (
@@ -35,7 +38,20 @@ export default function Dashboard() {
         <PersonalRecordsCard />
       </div>
 
-      {/* Row 5: Recent Workouts (full width) */}
+      {/* Row 5: Power Curve + Intensity Distribution */}
+      <div className="col-span-12 lg:col-span-7">
+        <PowerCurveCard />
+      </div>
+      <div className="col-span-12 lg:col-span-5">
+        <ZoneDistributionCard />
+      </div>
+
+      {/* Row 6: HRV trend (full width, only with chest strap recordings) */}
+      <div className="col-span-12 empty:hidden">
+        <HrvHistoryCard />
+      </div>
+
+      {/* Row 7: Recent Workouts (full width) */}
       <div className="col-span-12">
         <WorkoutList />
       </div>
@@ -0,0 +1,84 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Watch, BatteryLow } from 'lucide-react';
+import { useWorkoutStore } from '../stores/workoutStore';
+import type { DeviceInfo } from '../types';
+import { capitalizeWorkoutType, formatDeviceName } from '../types';
+
+const LOW_BATTERY = ['low', 'critical'];
+
+export default function DevicesCard() {
+  const selectedWorkout = useWorkoutStore((state) => state.selectedWorkout);
+  const [devices, setDevices] = useState<DeviceInfo[]>([]);
+
+  // Legs have no devices of their own; they are kept on the parent
+  const workoutId = selectedWorkout ? selectedWorkout.parent_id ?? selectedWorkout.id : null;
+
+  useEffect(() => {
+    if (workoutId === null) {
+      setDevices([]);
+      return;
+    }
+    let cancelled = false;
+    invoke<DeviceInfo[]>('get_workout_devices', { id: workoutId })
+      .then((result) => {
+        if (!cancelled) setDevices(result);
+      })
+      .catch((error) => {
+        console.error('Failed to fetch devices:', error);
+        if (!cancelled) setDevices([]);
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [workoutId]);
+
+  // Sensors built into the recording device say little on their own
+  const shown = devices.filter((d) => d.device_index.endsWith('creator') || d.source_type !== 'local');
+  if (shown.length === 0) return null;
+
+  return (
+    <div className="card p-4">
+      <div className="flex items-center gap-2 mb-3">
+        <div className="w-8 h-8 rounded-lg bg-slate-700/30 flex items-center justify-center">
+          <Watch className="w-4 h-4 text-slate-300" />
+        </div>
+        <h3 className="text-sm font-medium text-[var(--color-text-primary)]">Devices</h3>
+      </div>
+
+      <div className="space-y-1.5">
+        {shown.map((device) => (
+          <div key={device.device_index} className="flex items-center gap-2 text-xs">
+            <span className="text-[var(--color-text-primary)] font-medium flex-1 truncate">
+              {formatDeviceName(device)}
+              <span className="ml-2 font-normal text-[var(--color-text-secondary)]">
+                {device.device_index === 'creator'
+                  ? 'Recording device'
+                  : device.device_index.endsWith(':creator')
+                    ? 'Also recorded'
+                    : capitalizeWorkoutType(device.device_type)}
+              </span>
+            </span>
+            {device.serial_number !== null && (
+              <span className="text-[var(--color-text-secondary)]">#{device.serial_number}</span>
+            )}
+            {device.software_version !== null && (
+              <span className="text-[var(--color-text-secondary)]">v{device.software_version}</span>
+            )}
+            {device.battery_status && (
+              <span
+                className={`flex items-center gap-1 ${
+                  LOW_BATTERY.includes(device.battery_status) ? 'text-amber-400' : 'text-[var(--color-text-secondary)]'
+                }`}
+              >
+                {LOW_BATTERY.includes(device.battery_status) && <BatteryLow className="w-3 h-3" />}
+                {capitalizeWorkoutType(device.battery_status)}
+                {device.battery_voltage !== null && ` (${device.battery_voltage.toFixed(2)} V)`}
+              </span>
+            )}
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+}
@@ -15,20 +15,25 @@ export default function Header() {
     try {
       const files = await open({
         multiple: true,
-        filters: [{ name: 'FIT Files', extensions: ['fit', 'FIT'] }],
+        filters: [{ name: 'Activity Files', extensions: ['fit', 'FIT', 'gpx', 'GPX', 'tcx', 'TCX'] }],
       });
       
       if (files && files.length > 0) {
         const paths = Array.isArray(files) ? files : [files];
         const results = await uploadFiles(paths);
         
-        const successful = results.filter((r) => r.success).length;
-        const duplicates = results.filter((r) => r.duplicate).length;
+        const successful = results.filter((r) => r.success && !r.duplicate).length;
+        // Other recordings of an activity already stored are kept with it
+        const linked = results.filter((r) => r.success && r.duplicate).length;
+        const duplicates = results.filter((r) => !r.success && r.duplicate).length;
         const failed = results.filter((r) => !r.success && !r.duplicate).length;
         
         if (successful > 0) {
           addToast(`Successfully uploaded ${successful} workout${successful > 1 ? 's' : ''}`, 'success');
         }
+        if (linked > 0) {
+          addToast(`${linked} recording${linked > 1 ? 's' : ''} added to stored activities`, 'info');
+        }
         if (duplicates > 0) {
           addToast(`${duplicates} duplicate${duplicates > 1 ? 's' : ''} skipped`, 'warning');
         }
@@ -49,25 +54,30 @@ export default function Header() {
       });
       
       if (folder) {
-        addToast('Scanning folder for FIT files...', 'info');
+        addToast('Scanning folder for activity files...', 'info');
         const results = await invoke<UploadResult[]>('upload_fit_folder', { folderPath: folder });
         
-        const successful = results.filter((r) => r.success).length;
-        const duplicates = results.filter((r) => r.duplicate).length;
+        const successful = results.filter((r) => r.success && !r.duplicate).length;
+        // Other recordings of an activity already stored are kept with it
+        const linked = results.filter((r) => r.success && r.duplicate).length;
+        const duplicates = results.filter((r) => !r.success && r.duplicate).length;
         const failed = results.filter((r) => !r.success && !r.duplicate).length;
         
         if (successful > 0) {
           addToast(`Successfully uploaded ${successful} workout${successful > 1 ? 's' : ''}`, 'success');
           fetchDashboardData();
         }
+        if (linked > 0) {
+          addToast(`${linked} recording${linked > 1 ? 's' : ''} added to stored activities`, 'info');
+        }
         if (duplicates > 0) {
           addToast(`${duplicates} duplicate${duplicates > 1 ? 's' : ''} skipped`, 'warning');
         }
         if (failed > 0) {
           addToast(`Failed to upload ${failed} file${failed > 1 ? 's' : ''}`, 'error');
         }
-        if (successful === 0 && duplicates === 0 && failed === 0) {
-          addToast('No FIT files found in folder', 'warning');
+        if (successful === 0 && linked === 0 && duplicates === 0 && failed === 0) {
+          addToast('No FIT, GPX or TCX files found in folder', 'warning');
         }
       }
     } catch (error) {
@@ -0,0 +1,131 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Activity } from 'lucide-react';
+import {
+  ResponsiveContainer,
+  LineChart,
+  Line,
+  XAxis,
+  YAxis,
+  Tooltip,
+  ReferenceLine,
+} from 'recharts';
+import { useWorkoutStore } from '../stores/workoutStore';
+import type { HrvAnalysis } from '../types';
+
+function formatElapsed(seconds: number): string {
+  const minutes = Math.floor(seconds / 60);
+  const hours = Math.floor(minutes / 60);
+  if (hours > 0) return `${hours}:${(minutes % 60).toString().padStart(2, '0')}`;
+  return `${minutes}m`;
+}
+
+function formatValue(value: number | null, unit: string): string {
+  return value !== null ? `${Math.round(value)} ${unit}` : '--';
+}
+
+export default function HrvCard() {
+  const selectedWorkout = useWorkoutStore((state) => state.selectedWorkout);
+  const [analysis, setAnalysis] = useState<HrvAnalysis | null>(null);
+
+  // DFA alpha1 is computed on request from the stored R-R intervals
+  useEffect(() => {
+    if (!selectedWorkout) {
+      setAnalysis(null);
+      return;
+    }
+    let cancelled = false;
+    invoke<HrvAnalysis | null>('get_workout_hrv', { id: selectedWorkout.id })
+      .then((result) => {
+        if (!cancelled) setAnalysis(result);
+      })
+      .catch((error) => {
+        console.error('Failed to fetch HRV:', error);
+        if (!cancelled) setAnalysis(null);
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [selectedWorkout]);
+
+  if (!analysis) return null;
+
+  const stats = [
+    { label: 'RMSSD', value: formatValue(analysis.rmssd_ms, 'ms') },
+    { label: 'SDNN', value: formatValue(analysis.sdnn_ms, 'ms') },
+    { label: 'AeT HR', value: formatValue(analysis.aerobic_threshold_hr, 'bpm') },
+    { label: 'AnT HR', value: formatValue(analysis.anaerobic_threshold_hr, 'bpm') },
+  ];
+
+  return (
+    <div className="card p-4">
+      <div className="flex items-center justify-between mb-3">
+        <div className="flex items-center gap-2">
+          <div className="w-8 h-8 rounded-lg bg-pink-900/30 flex items-center justify-center">
+            <Activity className="w-4 h-4 text-pink-400" />
+          </div>
+          <h3 className="text-sm font-medium text-[var(--color-text-primary)]">Heart Rate Variability</h3>
+        </div>
+        <span className="text-xs text-[var(--color-text-secondary)]">
+          {analysis.beats} beats, {analysis.artifact_percent.toFixed(1)}% corrected
+        </span>
+      </div>
+
+      <div className="grid grid-cols-4 gap-2 mb-3">
+        {stats.map((stat) => (
+          <div key={stat.label} className="text-center">
+            <p className="text-xs text-[var(--color-text-secondary)]">{stat.label}</p>
+            <p className="text-sm font-medium text-[var(--color-text-primary)]">{stat.value}</p>
+          </div>
+        ))}
+      </div>
+
+      {analysis.dfa_alpha1.length > 1 ? (
+        <div className="h-40">
+          <ResponsiveContainer width="100%" height="100%">
+            <LineChart data={analysis.dfa_alpha1} margin={{ top: 5, right: 10, left: -20, bottom: 0 }}>
+              <XAxis
+                dataKey="elapsed_s"
+                type="number"
+                domain={['dataMin', 'dataMax']}
+                tickFormatter={formatElapsed}
+                tick={{ fontSize: 10, fill: 'var(--color-text-secondary)' }}
+              />
+              <YAxis
+                domain={[0, 'auto']}
+                tickFormatter={(v) => Number(v).toFixed(1)}
+                tick={{ fontSize: 10, fill: 'var(--color-text-secondary)' }}
+              />
+              <Tooltip
+                contentStyle={{
+                  backgroundColor: 'var(--color-bg-secondary)',
+                  border: '1px solid var(--color-border)',
+                  borderRadius: '8px',
+                  fontSize: '12px',
+                }}
+                labelFormatter={(v) => formatElapsed(Number(v))}
+                formatter={(value, name) => [Number(value).toFixed(2), name]}
+              />
+              {/* Alpha1 crosses 0.75 near the aerobic and 0.5 near the anaerobic threshold */}
+              <ReferenceLine y={0.75} stroke="#22c55e" strokeDasharray="4 4" />
+              <ReferenceLine y={0.5} stroke="#ef4444" strokeDasharray="4 4" />
+              <Line
+                type="monotone"
+                dataKey="alpha1"
+                name="DFA α1"
+                stroke="#ec4899"
+                strokeWidth={2}
+                dot={false}
+                isAnimationActive={false}
+              />
+            </LineChart>
+          </ResponsiveContainer>
+        </div>
+      ) : (
+        <p className="text-xs text-[var(--color-text-secondary)]">
+          Not enough beats for DFA alpha1 over time.
+        </p>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,114 @@
+import { useMemo } from 'react';
+import { HeartPulse } from 'lucide-react';
+import {
+  ResponsiveContainer,
+  LineChart,
+  Line,
+  XAxis,
+  YAxis,
+  Tooltip,
+} from 'recharts';
+import { useWorkoutStore } from '../stores/workoutStore';
+
+function formatDate(value: string): string {
+  return new Date(value).toLocaleDateString(undefined, { month: 'short', day: 'numeric' });
+}
+
+function formatLatest(value: number | null | undefined, unit: string): string {
+  return value !== null && value !== undefined ? `${Math.round(value)} ${unit}` : '--';
+}
+
+export default function HrvHistoryCard() {
+  const hrvHistory = useWorkoutStore((state) => state.hrvHistory);
+
+  const data = useMemo(
+    () => hrvHistory.filter((summary) => summary.start_time !== null),
+    [hrvHistory],
+  );
+
+  if (data.length === 0) return null;
+
+  const latest = data[data.length - 1];
+  const latestThreshold = [...data].reverse().find((summary) => summary.aerobic_threshold_hr !== null);
+
+  return (
+    <div className="card p-4 h-full flex flex-col">
+      <div className="flex items-center justify-between mb-3">
+        <div className="flex items-center gap-2">
+          <div className="w-8 h-8 rounded-lg bg-pink-900/30 flex items-center justify-center">
+            <HeartPulse className="w-4 h-4 text-pink-400" />
+          </div>
+          <h3 className="text-sm font-medium text-[var(--color-text-primary)]">HRV Trend</h3>
+        </div>
+        <div className="flex items-center gap-3 text-xs text-[var(--color-text-secondary)]">
+          <span>
+            RMSSD <span className="text-[var(--color-text-primary)] font-medium">{formatLatest(latest.rmssd_ms, 'ms')}</span>
+          </span>
+          <span>
+            AeT <span className="text-[var(--color-text-primary)] font-medium">{formatLatest(latestThreshold?.aerobic_threshold_hr, 'bpm')}</span>
+          </span>
+        </div>
+      </div>
+
+      <div className="w-full h-[180px]">
+        <ResponsiveContainer width="100%" height="100%">
+          <LineChart data={data} margin={{ top: 5, right: 0, left: -20, bottom: 0 }}>
+            <XAxis
+              dataKey="start_time"
+              tickFormatter={formatDate}
+              tick={{ fontSize: 10, fill: 'var(--color-text-secondary)' }}
+              axisLine={false}
+              tickLine={false}
+            />
+            <YAxis
+              yAxisId="rmssd"
+              tick={{ fontSize: 10, fill: 'var(--color-text-secondary)' }}
+              axisLine={false}
+              tickLine={false}
+            />
+            <YAxis
+              yAxisId="hr"
+              orientation="right"
+              domain={['auto', 'auto']}
+              tick={{ fontSize: 10, fill: 'var(--color-text-secondary)' }}
+              axisLine={false}
+              tickLine={false}
+            />
+            <Tooltip
+              contentStyle={{
+                backgroundColor: 'var(--color-bg-card)',
+                border: '1px solid var(--color-border)',
+                borderRadius: '8px',
+                fontSize: '12px',
+              }}
+              labelFormatter={(label) => new Date(label).toLocaleDateString()}
+              formatter={(value, name) => [Math.round(Number(value)), name]}
+            />
+            <Line
+              yAxisId="rmssd"
+              type="monotone"
+              dataKey="rmssd_ms"
+              name="RMSSD (ms)"
+              stroke="#ec4899"
+              strokeWidth={2}
+              dot={false}
+              isAnimationActive={false}
+            />
+            {/* Workouts that never reached the threshold have none; the line bridges them */}
+            <Line
+              yAxisId="hr"
+              type="monotone"
+              dataKey="aerobic_threshold_hr"
+              name="Aerobic threshold (bpm)"
+              stroke="#22c55e"
+              strokeWidth={2}
+              dot={{ r: 2 }}
+              connectNulls
+              isAnimationActive={false}
+            />
+          </LineChart>
+        </ResponsiveContainer>
+      </div>
+    </div>
+  );
+}
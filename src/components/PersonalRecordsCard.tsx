@@ -1,85 +1,180 @@
+import { useMemo, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { useWorkoutStore } from '../stores/workoutStore';
+import type { Workout } from '../types';
+
+const RECORD_STYLES: Record<string, { label: string; bgColor: string; borderColor: string; textColor: string }> = {
+  distance: { label: 'Distance', bgColor: 'bg-amber-900/20', borderColor: 'border-amber-400', textColor: 'text-amber-300' },
+  duration: { label: 'Duration', bgColor: 'bg-purple-900/20', borderColor: 'border-purple-400', textColor: 'text-purple-300' },
+  max_heart_rate: { label: 'Max HR', bgColor: 'bg-red-900/20', borderColor: 'border-red-400', textColor: 'text-red-300' },
+  max_speed: { label: 'Speed', bgColor: 'bg-emerald-900/20', borderColor: 'border-emerald-400', textColor: 'text-emerald-300' },
+  elevation_gain: { label: 'Elevation', bgColor: 'bg-sky-900/20', borderColor: 'border-sky-400', textColor: 'text-sky-300' },
+  calories: { label: 'Calories', bgColor: 'bg-orange-900/20', borderColor: 'border-orange-400', textColor: 'text-orange-300' },
+  max_power: { label: 'Max Power', bgColor: 'bg-yellow-900/20', borderColor: 'border-yellow-400', textColor: 'text-yellow-300' },
+  normalized_power: { label: 'NP', bgColor: 'bg-yellow-900/20', borderColor: 'border-yellow-500', textColor: 'text-yellow-400' },
+};
+
+const BEST_EFFORT_LABELS: Record<string, string> = {
+  '400m': '400 m',
+  '1k': '1 km',
+  '1mi': '1 mile',
+  '5k': '5 km',
+  '10k': '10 km',
+  half_marathon: 'Half',
+  marathon: 'Marathon',
+};
+
+const formatEffortTime = (seconds: number) => {
+  const total = Math.round(seconds);
+  const h = Math.floor(total / 3600);
+  const m = Math.floor((total % 3600) / 60);
+  const s = total % 60;
+  const mmss = `${h > 0 ? String(m).padStart(2, '0') : m}:${String(s).padStart(2, '0')}`;
+  return h > 0 ? `${h}:${mmss}` : mmss;
+};
+
+/** Display name of a record metric or best effort distance */
+export function recordLabel(metric: string): string {
+  return RECORD_STYLES[metric]?.label ?? BEST_EFFORT_LABELS[metric] ?? metric;
+}
+
+/** Formats a record value; best efforts are times in seconds */
+export function formatRecordValue(metric: string, value: number): string {
+  switch (metric) {
+    case 'distance':
+      return `${(value / 1000).toFixed(1)} km`;
+    case 'duration':
+      return `${(value / 3600).toFixed(1)} hrs`;
+    case 'max_heart_rate':
+      return `${Math.round(value)} bpm`;
+    case 'max_speed':
+      return `${(value * 3.6).toFixed(1)} km/h`;
+    case 'elevation_gain':
+      return `${value.toFixed(0)} m`;
+    case 'calories':
+      return `${Math.round(value)}`;
+    case 'max_power':
+    case 'normalized_power':
+      return `${Math.round(value)} W`;
+    default:
+      return formatEffortTime(value);
+  }
+}
+
+const formatSport = (sport: string | null) =>
+  sport ? sport.replace(/_/g, ' ').replace(/\b\w/g, (c) => c.toUpperCase()) : 'Other';
 
 export default function PersonalRecordsCard() {
   const personalRecords = useWorkoutStore((state) => state.personalRecords);
+  const bestEfforts = useWorkoutStore((state) => state.bestEfforts);
+  const openModal = useWorkoutStore((state) => state.openModal);
+  const [selectedSport, setSelectedSport] = useState<string | null>(null);
 
-  const records = [
-    {
-      label: 'Distance',
-      value: personalRecords?.max_distance_km 
-        ? `${(personalRecords.max_distance_km).toFixed(1)} km`
-        : '--',
-      bgColor: 'bg-amber-900/20',
-      borderColor: 'border-amber-400',
-      textColor: 'text-amber-300',
-    },
-    {
-      label: 'Duration',
-      value: personalRecords?.max_duration_hours 
-        ? `${(personalRecords.max_duration_hours).toFixed(1)} hrs`
-        : '--',
-      bgColor: 'bg-purple-900/20',
-      borderColor: 'border-purple-400',
-      textColor: 'text-purple-300',
-    },
-    {
-      label: 'Max HR',
-      value: personalRecords?.max_heart_rate 
-        ? `${personalRecords.max_heart_rate} bpm`
-        : '--',
-      bgColor: 'bg-red-900/20',
-      borderColor: 'border-red-400',
-      textColor: 'text-red-300',
-    },
-    {
-      label: 'Speed',
-      value: personalRecords?.max_speed_kmh 
-        ? `${(personalRecords.max_speed_kmh).toFixed(1)} km/h`
-        : '--',
-      bgColor: 'bg-emerald-900/20',
-      borderColor: 'border-emerald-400',
-      textColor: 'text-emerald-300',
-    },
-    {
-      label: 'Elevation',
-      value: personalRecords?.max_elevation_gain 
-        ? `${personalRecords.max_elevation_gain.toFixed(0)} m`
-        : '--',
-      bgColor: 'bg-sky-900/20',
-      borderColor: 'border-sky-400',
-      textColor: 'text-sky-300',
-    },
-    {
-      label: 'Calories',
-      value: personalRecords?.max_calories 
-        ? `${personalRecords.max_calories}`
-        : '--',
-      bgColor: 'bg-orange-900/20',
-      borderColor: 'border-orange-400',
-      textColor: 'text-orange-300',
-    },
-  ];
+  // Sports with the most records first, so the main sport is the default
+  const sports = useMemo(() => {
+    const counts = new Map<string, number>();
+    for (const record of personalRecords) {
+      const key = record.sport ?? '';
+      counts.set(key, (counts.get(key) ?? 0) + record.history.length);
+    }
+    return [...counts.entries()].sort((a, b) => b[1] - a[1]).map(([sport]) => sport);
+  }, [personalRecords]);
+
+  const sport = selectedSport !== null && sports.includes(selectedSport) ? selectedSport : sports[0] ?? '';
+  const records = personalRecords.filter((r) => (r.sport ?? '') === sport);
+  const efforts = bestEfforts.filter((r) => (r.sport ?? '') === sport);
+
+  const openWorkout = async (id: number) => {
+    try {
+      const workout = await invoke<Workout | null>('get_workout', { id });
+      if (workout) {
+        openModal(workout);
+      }
+    } catch (error) {
+      console.error('Failed to load workout:', error);
+    }
+  };
+
+  const recordTitle = (name: string | null, startTime: string | null, previousRecords: number) =>
+    [
+      name ?? 'Workout',
+      startTime ? new Date(startTime).toLocaleDateString() : '',
+      previousRecords > 0 ? `${previousRecords} earlier record${previousRecords !== 1 ? 's' : ''}` : '',
+    ].filter(Boolean).join(' · ');
 
   return (
     <div className="card hover-lift p-4 h-full flex flex-col">
-      <h3 className="text-sm font-semibold text-[var(--color-text-primary)] mb-3">
-        Personal Records
-      </h3>
-      <div className="grid grid-cols-2 sm:grid-cols-3 gap-3 flex-1">
-        {records.map((record) => (
-          <div
-            key={record.label}
-            className={`flex flex-col justify-center p-3 rounded-lg ${record.bgColor} border-l-4 ${record.borderColor}`}
+      <div className="flex items-center justify-between mb-3">
+        <h3 className="text-sm font-semibold text-[var(--color-text-primary)]">
+          Personal Records
+        </h3>
+        {sports.length > 1 && (
+          <select
+            value={sport}
+            onChange={(e) => setSelectedSport(e.target.value)}
+            className="px-2 py-1 rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-xs text-[var(--color-text-primary)]"
           >
-            <p className={`text-[10px] font-medium ${record.textColor} uppercase tracking-wider`}>
-              {record.label}
-            </p>
-            <p className="text-lg font-bold text-[var(--color-text-primary)] mt-0.5">
-              {record.value}
-            </p>
-          </div>
-        ))}
+            {sports.map((s) => (
+              <option key={s} value={s}>{formatSport(s || null)}</option>
+            ))}
+          </select>
+        )}
       </div>
+
+      {records.length === 0 ? (
+        <p className="text-xs text-[var(--color-text-secondary)] py-6 text-center">
+          Records appear once you import workouts
+        </p>
+      ) : (
+        <div className="grid grid-cols-2 sm:grid-cols-4 gap-3 flex-1">
+          {records.map((record) => {
+            const style = RECORD_STYLES[record.metric];
+            return (
+              <button
+                key={record.metric}
+                onClick={() => openWorkout(record.best.workout_id)}
+                title={recordTitle(record.best.workout_name, record.best.start_time, record.history.length - 1)}
+                className={`flex flex-col justify-center items-start text-left p-3 rounded-lg ${style?.bgColor ?? ''} border-l-4 ${style?.borderColor ?? ''}`}
+              >
+                <p className={`text-[10px] font-medium ${style?.textColor ?? ''} uppercase tracking-wider`}>
+                  {recordLabel(record.metric)}
+                </p>
+                <p className="text-lg font-bold text-[var(--color-text-primary)] mt-0.5">
+                  {formatRecordValue(record.metric, record.best.value)}
+                </p>
+                <p className="text-[10px] text-[var(--color-text-secondary)]">
+                  {record.best.start_time ? new Date(record.best.start_time).toLocaleDateString() : ''}
+                </p>
+              </button>
+            );
+          })}
+        </div>
+      )}
+
+      {efforts.length > 0 && (
+        <>
+          <h4 className="text-xs font-medium text-[var(--color-text-secondary)] mt-4 mb-2">
+            Best Efforts
+          </h4>
+          <div className="grid grid-cols-3 sm:grid-cols-4 lg:grid-cols-7 gap-2">
+            {efforts.map((record) => (
+              <button
+                key={record.distance}
+                onClick={() => openWorkout(record.best.workout_id)}
+                title={recordTitle(record.best.workout_name, record.best.start_time, record.history.length - 1)}
+                className="flex flex-col items-start p-2 rounded-lg bg-[var(--color-bg-secondary)] hover:bg-[var(--color-border)] transition-colors text-left"
+              >
+                <span className="text-[10px] font-medium text-[var(--color-text-secondary)] uppercase tracking-wider">
+                  {recordLabel(record.distance)}
+                </span>
+                <span className="text-sm font-bold text-[var(--color-text-primary)]">
+                  {formatEffortTime(record.best.elapsed_seconds)}
+                </span>
+              </button>
+            ))}
+          </div>
+        </>
+      )}
     </div>
   );
 }
@@ -0,0 +1,154 @@
+import { useMemo, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Zap } from 'lucide-react';
+import { useWorkoutStore } from '../stores/workoutStore';
+import type { PowerCurvePoint, Workout } from '../types';
+import {
+  ResponsiveContainer,
+  LineChart,
+  Line,
+  XAxis,
+  YAxis,
+  Tooltip,
+  Legend,
+} from 'recharts';
+
+const formatDurationLabel = (seconds: number) => {
+  if (seconds < 60) return `${seconds}s`;
+  if (seconds < 3600) return `${Math.round(seconds / 60)}m`;
+  return `${+(seconds / 3600).toFixed(1)}h`;
+};
+
+export default function PowerCurveCard() {
+  const powerCurve = useWorkoutStore((state) => state.powerCurve);
+  const openModal = useWorkoutStore((state) => state.openModal);
+  const [season, setSeason] = useState<string>('');
+
+  const seasons = powerCurve?.seasons ?? [];
+
+  // One row per duration; each curve keeps its source point for the tooltip
+  // and for opening the workout that set it
+  const data = useMemo(() => {
+    const byDuration = new Map<number, Record<string, number | PowerCurvePoint>>();
+    const add = (key: string, points: PowerCurvePoint[]) => {
+      for (const point of points) {
+        const row = byDuration.get(point.duration_seconds) ?? { duration: point.duration_seconds };
+        row[key] = Math.round(point.watts);
+        row[`${key}Point`] = point;
+        byDuration.set(point.duration_seconds, row);
+      }
+    };
+    add('allTime', powerCurve?.all_time ?? []);
+    add('last90', powerCurve?.last_90_days ?? []);
+    add('season', powerCurve?.seasons.find((s) => s.season === season)?.points ?? []);
+    return [...byDuration.values()].sort((a, b) => (a.duration as number) - (b.duration as number));
+  }, [powerCurve, season]);
+
+  const openWorkout = async (point: PowerCurvePoint | undefined) => {
+    if (!point) return;
+    try {
+      const workout = await invoke<Workout | null>('get_workout', { id: point.workout_id });
+      if (workout) {
+        openModal(workout);
+      }
+    } catch (error) {
+      console.error('Failed to load workout:', error);
+    }
+  };
+
+  return (
+    <div className="card p-4 h-full flex flex-col">
+      <div className="flex items-center justify-between mb-3">
+        <div className="flex items-center gap-2">
+          <div className="w-8 h-8 rounded-lg bg-amber-900/30 flex items-center justify-center">
+            <Zap className="w-4 h-4 text-amber-400" />
+          </div>
+          <h3 className="text-sm font-medium text-[var(--color-text-primary)]">Power Curve</h3>
+        </div>
+        {seasons.length > 0 && (
+          <select
+            value={season}
+            onChange={(e) => setSeason(e.target.value)}
+            className="px-2 py-1 rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-xs text-[var(--color-text-primary)]"
+          >
+            <option value="">Compare season…</option>
+            {seasons.map((s) => (
+              <option key={s.season} value={s.season}>{s.season}</option>
+            ))}
+          </select>
+        )}
+      </div>
+
+      {data.length === 0 ? (
+        <p className="text-xs text-[var(--color-text-secondary)] py-6 text-center">
+          Import workouts with power data to see your best efforts by duration
+        </p>
+      ) : (
+        <div className="w-full h-[220px]">
+          <ResponsiveContainer width="100%" height="100%">
+            <LineChart data={data}>
+              <XAxis
+                dataKey="duration"
+                tickFormatter={formatDurationLabel}
+                tick={{ fontSize: 10, fill: 'var(--color-text-secondary)' }}
+                axisLine={false}
+                tickLine={false}
+              />
+              <YAxis
+                unit=" W"
+                width={50}
+                tick={{ fontSize: 10, fill: 'var(--color-text-secondary)' }}
+                axisLine={false}
+                tickLine={false}
+              />
+              <Tooltip
+                contentStyle={{
+                  backgroundColor: 'var(--color-bg-card)',
+                  border: '1px solid var(--color-border)',
+                  borderRadius: '8px',
+                  fontSize: '12px',
+                }}
+                labelFormatter={(label) => formatDurationLabel(Number(label))}
+                formatter={(value, name, item) => {
+                  const point = item.payload[`${item.dataKey}Point`] as PowerCurvePoint | undefined;
+                  const date = point?.start_time ? new Date(point.start_time).toLocaleDateString() : '';
+                  return [`${value} W ${date}`, name];
+                }}
+              />
+              <Legend wrapperStyle={{ fontSize: '11px' }} />
+              <Line
+                type="monotone"
+                dataKey="allTime"
+                name="All time"
+                stroke="#f59e0b"
+                strokeWidth={2}
+                dot={false}
+                activeDot={{ r: 4, cursor: 'pointer', onClick: (_, e) => openWorkout((e as { payload: Record<string, PowerCurvePoint> }).payload.allTimePoint) }}
+              />
+              <Line
+                type="monotone"
+                dataKey="last90"
+                name="Last 90 days"
+                stroke="#6366f1"
+                strokeWidth={2}
+                dot={false}
+                activeDot={{ r: 4, cursor: 'pointer', onClick: (_, e) => openWorkout((e as { payload: Record<string, PowerCurvePoint> }).payload.last90Point) }}
+              />
+              {season && (
+                <Line
+                  type="monotone"
+                  dataKey="season"
+                  name={season}
+                  stroke="#22c55e"
+                  strokeWidth={2}
+                  dot={false}
+                  activeDot={{ r: 4, cursor: 'pointer', onClick: (_, e) => openWorkout((e as { payload: Record<string, PowerCurvePoint> }).payload.seasonPoint) }}
+                />
+              )}
+            </LineChart>
+          </ResponsiveContainer>
+        </div>
+      )}
+    </div>
+  );
+}
@@ -1,7 +1,8 @@
 import { useMemo, useEffect, useState } from 'react';
 import { Battery, BatteryLow, BatteryMedium, BatteryFull, BatteryWarning, Zap } from 'lucide-react';
 import { invoke } from '@tauri-apps/api/core';
-import { useSettingsStore, calculateTSS, getRecoveryStatus } from '../stores/settingsStore';
+import { useSettingsStore, getRecoveryStatus } from '../stores/settingsStore';
+import { useWorkoutStore } from '../stores/workoutStore';
 import type { WorkoutSummary } from '../types';
 
 interface RecoveryInfo {
@@ -16,8 +17,7 @@ export default function RecoveryScoreCard() {
   const [recentWorkouts, setRecentWorkouts] = useState<WorkoutSummary[]>([]);
   const [isLoading, setIsLoading] = useState(true);
   
-  const getEstimatedMaxHR = useSettingsStore((state) => state.getEstimatedMaxHR);
-  const restingHeartRate = useSettingsStore((state) => state.restingHeartRate);
+  const trainingLoad = useWorkoutStore((state) => state.trainingLoad);
   const openSettings = useSettingsStore((state) => state.openSettings);
 
   useEffect(() => {
@@ -25,7 +25,7 @@ export default function RecoveryScoreCard() {
       try {
         const response = await invoke<{ workouts: WorkoutSummary[] }>('get_workouts', {
           page: 1,
-          perPage: 100,
+          perPage: 1,
         });
         setRecentWorkouts(response.workouts);
       } catch (error) {
@@ -39,42 +39,8 @@ export default function RecoveryScoreCard() {
   }, []);
 
   const recoveryInfo = useMemo((): RecoveryInfo => {
-    const maxHR = getEstimatedMaxHR();
-    const restHR = restingHeartRate ?? 60;
     const now = new Date();
-
-    // Calculate daily TSS
-    const dailyTSS = new Map<string, number>();
-    
-    recentWorkouts
-      .filter((w) => w.duration_seconds && w.avg_heart_rate && w.start_time)
-      .forEach((w) => {
-        const date = new Date(w.start_time!).toISOString().split('T')[0];
-        const durationMinutes = (w.duration_seconds ?? 0) / 60;
-        const avgHR = w.avg_heart_rate ?? 0;
-        const tss = calculateTSS(durationMinutes, avgHR, maxHR, restHR);
-        
-        dailyTSS.set(date, (dailyTSS.get(date) ?? 0) + tss);
-      });
-
-    // Calculate ATL and CTL
-    let atlSum = 0;
-    let ctlSum = 0;
-    const today = new Date();
-    
-    for (let i = 0; i < 42; i++) {
-      const date = new Date(today);
-      date.setDate(date.getDate() - i);
-      const dateStr = date.toISOString().split('T')[0];
-      const tss = dailyTSS.get(dateStr) ?? 0;
-      
-      if (i < 7) atlSum += tss;
-      ctlSum += tss;
-    }
-
-    const atl = Math.round(atlSum / 7);
-    const ctl = Math.round(ctlSum / Math.max(42, dailyTSS.size || 1));
-    const tsb = ctl - atl;
+    const tsb = Math.round(trainingLoad[trainingLoad.length - 1]?.tsb ?? 0);
 
     // Get recovery status
     const status = getRecoveryStatus(tsb);
@@ -116,7 +82,7 @@ export default function RecoveryScoreCard() {
       lastWorkoutHoursAgo,
       suggestedIntensity,
     };
-  }, [recentWorkouts, getEstimatedMaxHR, restingHeartRate]);
+  }, [recentWorkouts, trainingLoad]);
 
   const getBatteryIcon = (score: number) => {
     if (score >= 80) return <BatteryFull className="w-5 h-5" />;
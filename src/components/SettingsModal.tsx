@@ -1,17 +1,40 @@
-import { useState } from 'react';
-import { X, Heart, RefreshCw } from 'lucide-react';
-import { useSettingsStore, DEFAULT_HR_ZONES, type HRZone } from '../stores/settingsStore';
+import { useEffect, useState } from 'react';
+import { X, Heart, RefreshCw, RotateCcw, Trash2 } from 'lucide-react';
+import { useSettingsStore, DEFAULT_HR_ZONES, todayString, type HRZone } from '../stores/settingsStore';
+import type { AthleteProfile, HrZoneMethod, SportCategory, Zone } from '../types';
+import { capitalizeWorkoutType, formatSport } from '../types';
+import { invoke } from '@tauri-apps/api/core';
+import { useWorkoutStore } from '../stores/workoutStore';
+import { useToastStore } from '../stores/toastStore';
 
+function formatPace(secondsPerKm: number): string {
+  return `${Math.floor(secondsPerKm / 60)}:${(secondsPerKm % 60).toString().padStart(2, '0')}`;
+}
+
+// The form is mounted when the modal opens, so it starts from the profile
+// as loaded from the database
 export default function SettingsModal() {
+  const isSettingsOpen = useSettingsStore((state) => state.isSettingsOpen);
+  if (!isSettingsOpen) return null;
+  return <SettingsForm />;
+}
+
+function SettingsForm() {
   const {
-    isSettingsOpen,
     closeSettings,
+    weightKg,
+    heightCm,
+    sex,
+    birthDate,
     maxHeartRate,
-    setMaxHeartRate,
     restingHeartRate,
-    setRestingHeartRate,
-    age,
-    setAge,
+    ftpWatts,
+    thresholdHeartRate,
+    thresholdPace,
+    profileHistory,
+    setProfile,
+    hrZoneMethod,
+    setHrZoneMethod,
     hrZones,
     setHRZones,
     useCustomZones,
@@ -19,32 +42,91 @@ export default function SettingsModal() {
     resetZonesToDefault,
     getEstimatedMaxHR,
     getZoneBoundaries,
+    saveAthleteProfile,
+    deleteProfileEntry,
+    syncHrZones,
+    powerZones,
+    paceZones,
+    loadPercentZones,
+    savePercentZones,
   } = useSettingsStore();
 
+  const [localWeight, setLocalWeight] = useState<string>(weightKg?.toString() ?? '');
+  const [localHeight, setLocalHeight] = useState<string>(heightCm?.toString() ?? '');
+  const [localSex, setLocalSex] = useState<string>(sex ?? '');
+  const [localBirthDate, setLocalBirthDate] = useState<string>(birthDate ?? '');
   const [localMaxHR, setLocalMaxHR] = useState<string>(maxHeartRate?.toString() ?? '');
   const [localRestHR, setLocalRestHR] = useState<string>(restingHeartRate?.toString() ?? '');
-  const [localAge, setLocalAge] = useState<string>(age?.toString() ?? '');
+  const [localFtp, setLocalFtp] = useState<string>(ftpWatts?.toString() ?? '');
+  const [localLthr, setLocalLthr] = useState<string>(thresholdHeartRate?.toString() ?? '');
+  const [localPace, setLocalPace] = useState<string>(thresholdPace ? formatPace(thresholdPace) : '');
+  const [effectiveDate, setEffectiveDate] = useState<string>(todayString());
   const [localZones, setLocalZones] = useState<HRZone[]>(hrZones);
+  const [localPowerZones, setLocalPowerZones] = useState<Zone[]>(powerZones);
+  const [localPaceZones, setLocalPaceZones] = useState<Zone[]>(paceZones);
+  const [isReprocessing, setIsReprocessing] = useState(false);
+  const reprocessAll = useWorkoutStore((state) => state.reprocessAll);
+  const fetchTrainingLoad = useWorkoutStore((state) => state.fetchTrainingLoad);
+  const allTags = useWorkoutStore((state) => state.allTags);
+  const setTagColor = useWorkoutStore((state) => state.setTagColor);
+  const renameTag = useWorkoutStore((state) => state.renameTag);
+  const deleteTag = useWorkoutStore((state) => state.deleteTag);
+  const [tagEdits, setTagEdits] = useState<Record<string, string>>({});
+  const activityBreakdown = useWorkoutStore((state) => state.activityBreakdown);
+  const sportCategories = useWorkoutStore((state) => state.sportCategories);
+  const fetchSportCategories = useWorkoutStore((state) => state.fetchSportCategories);
+  const updateSportCategories = useWorkoutStore((state) => state.updateSportCategories);
+  const [categoryEdits, setCategoryEdits] = useState<Record<string, string>>({});
+  const addToast = useToastStore((state) => state.addToast);
 
-  if (!isSettingsOpen) return null;
+  const handleSave = async () => {
+    const profile: Partial<AthleteProfile> = {};
+
+    // Save weight and height
+    const weight = localWeight ? parseFloat(localWeight) : null;
+    if (weight === null || (weight >= 20 && weight <= 300)) {
+      profile.weight_kg = weight;
+    }
+    const height = localHeight ? parseFloat(localHeight) : null;
+    if (height === null || (height >= 100 && height <= 250)) {
+      profile.height_cm = height;
+    }
+    profile.sex = localSex === 'male' || localSex === 'female' ? localSex : null;
+    profile.birth_date = localBirthDate || null;
 
-  const handleSave = () => {
     // Save max HR
     const maxHR = localMaxHR ? parseInt(localMaxHR, 10) : null;
     if (maxHR === null || (maxHR >= 100 && maxHR <= 250)) {
-      setMaxHeartRate(maxHR);
+      profile.max_heart_rate = maxHR;
     }
 
     // Save resting HR
     const restHR = localRestHR ? parseInt(localRestHR, 10) : null;
     if (restHR === null || (restHR >= 30 && restHR <= 120)) {
-      setRestingHeartRate(restHR);
+      profile.resting_heart_rate = restHR;
     }
 
-    // Save age
-    const ageVal = localAge ? parseInt(localAge, 10) : null;
-    if (ageVal === null || (ageVal >= 10 && ageVal <= 100)) {
-      setAge(ageVal);
+    // Save FTP
+    const ftp = localFtp ? parseInt(localFtp, 10) : null;
+    if (ftp === null || (ftp >= 50 && ftp <= 600)) {
+      profile.ftp_watts = ftp;
+    }
+
+    // Save LTHR
+    const lthr = localLthr ? parseInt(localLthr, 10) : null;
+    if (lthr === null || (lthr >= 80 && lthr <= 230)) {
+      profile.threshold_heart_rate = lthr;
+    }
+
+    // Save threshold pace, entered as m:ss per km
+    const paceMatch = localPace.trim().match(/^(\d{1,2}):([0-5]\d)$/);
+    if (!localPace.trim()) {
+      profile.threshold_pace_seconds_per_km = null;
+    } else if (paceMatch) {
+      const pace = parseInt(paceMatch[1], 10) * 60 + parseInt(paceMatch[2], 10);
+      if (pace >= 120 && pace <= 720) {
+        profile.threshold_pace_seconds_per_km = pace;
+      }
     }
 
     // Save zones if custom
@@ -52,13 +134,33 @@ export default function SettingsModal() {
       setHRZones(localZones);
     }
 
+    setProfile(profile);
     closeSettings();
+    await saveAthleteProfile(effectiveDate || todayString());
+    await syncHrZones();
+    if (JSON.stringify(localPowerZones) !== JSON.stringify(powerZones)) {
+      await savePercentZones('power', localPowerZones);
+    }
+    if (JSON.stringify(localPaceZones) !== JSON.stringify(paceZones)) {
+      await savePercentZones('pace', localPaceZones);
+    }
+    fetchTrainingLoad();
+  };
+
+  const handleDeleteProfileEntry = async (date: string) => {
+    if (!confirm(`Remove the profile entry from ${date}? Workouts after it use the previous entry again.`)) {
+      return;
+    }
+    await deleteProfileEntry(date);
+    fetchTrainingLoad();
   };
 
   const handleZoneChange = (index: number, field: 'min' | 'max', value: string) => {
     const newZones = [...localZones];
     const numValue = parseInt(value, 10);
-    if (!isNaN(numValue) && numValue >= 0 && numValue <= 100) {
+    // Zones above threshold go past 100% of LTHR
+    const limit = hrZoneMethod === 'lactate_threshold' ? 150 : 100;
+    if (!isNaN(numValue) && numValue >= 0 && numValue <= limit) {
       newZones[index] = { ...newZones[index], [field]: numValue };
       setLocalZones(newZones);
     }
@@ -69,6 +171,103 @@ export default function SettingsModal() {
     resetZonesToDefault();
   };
 
+  const handleReprocessAll = async () => {
+    setIsReprocessing(true);
+    const summary = await reprocessAll();
+    setIsReprocessing(false);
+    if (!summary) {
+      addToast('Failed to reprocess workouts', 'error');
+      return;
+    }
+    addToast(`Reprocessed ${summary.reprocessed} workout${summary.reprocessed !== 1 ? 's' : ''}`, 'success');
+    if (summary.failed > 0) {
+      addToast(`${summary.failed} workout${summary.failed > 1 ? 's' : ''} could not be reprocessed`, 'warning');
+    }
+  };
+
+  // Renaming onto an existing tag merges the two
+  const handleRenameTag = async (oldName: string) => {
+    const newName = (tagEdits[oldName] ?? oldName).trim();
+    setTagEdits((edits) => {
+      const next = { ...edits };
+      delete next[oldName];
+      return next;
+    });
+    if (!newName || newName === oldName) return;
+    const merging = allTags.some((t) => t.name === newName);
+    const success = await renameTag(oldName, newName);
+    if (!success) {
+      addToast('Failed to rename tag', 'error');
+    } else if (merging) {
+      addToast(`Merged #${oldName} into #${newName}`, 'success');
+    }
+  };
+
+  const handleDeleteTag = async (name: string, usageCount: number) => {
+    if (usageCount > 0 && !confirm(`Remove #${name} from ${usageCount} workout${usageCount !== 1 ? 's' : ''}?`)) {
+      return;
+    }
+    if (!(await deleteTag(name))) {
+      addToast('Failed to delete tag', 'error');
+    }
+  };
+
+  useEffect(() => {
+    fetchSportCategories();
+  }, [fetchSportCategories]);
+
+  useEffect(() => {
+    loadPercentZones();
+  }, [loadPercentZones]);
+
+  useEffect(() => setLocalPowerZones(powerZones), [powerZones]);
+  useEffect(() => setLocalPaceZones(paceZones), [paceZones]);
+
+  const handleResetPercentZones = async (command: string, setZones: (zones: Zone[]) => void) => {
+    try {
+      setZones(await invoke<Zone[]>(command));
+    } catch (error) {
+      console.error('Failed to load default zones:', error);
+    }
+  };
+
+  // Every sport and sub-sport the workouts have, under its current category
+  const sportRows = activityBreakdown.flatMap((category) =>
+    category.sports.map((sport) => ({ ...sport, category: category.name, key: `${sport.sport}:${sport.sub_sport ?? ''}` }))
+  );
+  const categoryNames = [...new Set([...sportCategories.map((c) => c.category), ...activityBreakdown.map((c) => c.name)])];
+
+  // Categories are stored like sport names, e.g. "Mind and Body" as mind_and_body
+  const handleCategoryChange = async (sport: string, subSport: string | null, key: string, current: string) => {
+    const entered = (categoryEdits[key] ?? '').trim();
+    setCategoryEdits((edits) => {
+      const next = { ...edits };
+      delete next[key];
+      return next;
+    });
+    const category = entered.toLowerCase().replace(/\s+/g, '_');
+    if (!category || category === current) return;
+    const mapping: SportCategory[] = [
+      ...sportCategories.filter((c) => !(c.sport === sport && c.sub_sport === subSport)),
+      { sport, sub_sport: subSport, category },
+    ];
+    if (!(await updateSportCategories(mapping))) {
+      addToast('Failed to update sport category', 'error');
+    }
+  };
+
+  const handleResetCategories = async () => {
+    if (!confirm('Reset every sport to its default category?')) return;
+    try {
+      const defaults = await invoke<SportCategory[]>('get_default_sport_categories');
+      if (!(await updateSportCategories(defaults))) {
+        addToast('Failed to reset sport categories', 'error');
+      }
+    } catch (error) {
+      console.error('Failed to load default sport categories:', error);
+    }
+  };
+
   const estimatedMaxHR = getEstimatedMaxHR();
   const zoneBoundaries = getZoneBoundaries();
 
@@ -101,21 +300,61 @@ export default function SettingsModal() {
             <div className="grid grid-cols-2 gap-3">
               <div>
                 <label className="block text-xs text-[var(--color-text-secondary)] mb-1">
-                  Age (years)
+                  Birth Date
                 </label>
                 <input
-                  type="number"
-                  value={localAge}
-                  onChange={(e) => setLocalAge(e.target.value)}
-                  placeholder="e.g., 30"
-                  min="10"
-                  max="100"
+                  type="date"
+                  value={localBirthDate}
+                  onChange={(e) => setLocalBirthDate(e.target.value)}
                   className="w-full px-3 py-2 rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-[var(--color-text-primary)] text-sm focus:outline-none focus:ring-2 focus:ring-[var(--color-accent)]"
                 />
                 <p className="text-[10px] text-[var(--color-text-secondary)] mt-1">
                   Used to estimate max HR if not set
                 </p>
               </div>
+              <div>
+                <label className="block text-xs text-[var(--color-text-secondary)] mb-1">
+                  Sex
+                </label>
+                <select
+                  value={localSex}
+                  onChange={(e) => setLocalSex(e.target.value)}
+                  className="w-full px-3 py-2 rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-[var(--color-text-primary)] text-sm focus:outline-none focus:ring-2 focus:ring-[var(--color-accent)]"
+                >
+                  <option value="">Not set</option>
+                  <option value="female">Female</option>
+                  <option value="male">Male</option>
+                </select>
+              </div>
+              <div>
+                <label className="block text-xs text-[var(--color-text-secondary)] mb-1">
+                  Weight (kg)
+                </label>
+                <input
+                  type="number"
+                  value={localWeight}
+                  onChange={(e) => setLocalWeight(e.target.value)}
+                  placeholder="e.g., 70"
+                  min="20"
+                  max="300"
+                  step="0.1"
+                  className="w-full px-3 py-2 rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-[var(--color-text-primary)] text-sm focus:outline-none focus:ring-2 focus:ring-[var(--color-accent)]"
+                />
+              </div>
+              <div>
+                <label className="block text-xs text-[var(--color-text-secondary)] mb-1">
+                  Height (cm)
+                </label>
+                <input
+                  type="number"
+                  value={localHeight}
+                  onChange={(e) => setLocalHeight(e.target.value)}
+                  placeholder="e.g., 175"
+                  min="100"
+                  max="250"
+                  className="w-full px-3 py-2 rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-[var(--color-text-primary)] text-sm focus:outline-none focus:ring-2 focus:ring-[var(--color-accent)]"
+                />
+              </div>
               <div>
                 <label className="block text-xs text-[var(--color-text-secondary)] mb-1">
                   Max Heart Rate (bpm)
@@ -133,7 +372,7 @@ export default function SettingsModal() {
                   Leave blank to auto-calculate
                 </p>
               </div>
-              <div className="col-span-2">
+              <div>
                 <label className="block text-xs text-[var(--color-text-secondary)] mb-1">
                   Resting Heart Rate (bpm)
                 </label>
@@ -150,7 +389,101 @@ export default function SettingsModal() {
                   Used for accurate training load calculations
                 </p>
               </div>
+              <div>
+                <label className="block text-xs text-[var(--color-text-secondary)] mb-1">
+                  FTP (watts)
+                </label>
+                <input
+                  type="number"
+                  value={localFtp}
+                  onChange={(e) => setLocalFtp(e.target.value)}
+                  placeholder="e.g., 250"
+                  min="50"
+                  max="600"
+                  className="w-full px-3 py-2 rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-[var(--color-text-primary)] text-sm focus:outline-none focus:ring-2 focus:ring-[var(--color-accent)]"
+                />
+                <p className="text-[10px] text-[var(--color-text-secondary)] mt-1">
+                  Power-based TSS for workouts with power
+                </p>
+              </div>
+              <div>
+                <label className="block text-xs text-[var(--color-text-secondary)] mb-1">
+                  Threshold Heart Rate (bpm)
+                </label>
+                <input
+                  type="number"
+                  value={localLthr}
+                  onChange={(e) => setLocalLthr(e.target.value)}
+                  placeholder="e.g., 170"
+                  min="80"
+                  max="230"
+                  className="w-full px-3 py-2 rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-[var(--color-text-primary)] text-sm focus:outline-none focus:ring-2 focus:ring-[var(--color-accent)]"
+                />
+                <p className="text-[10px] text-[var(--color-text-secondary)] mt-1">
+                  Needed for zones based on LTHR
+                </p>
+              </div>
+              <div>
+                <label className="block text-xs text-[var(--color-text-secondary)] mb-1">
+                  Threshold Pace (min/km)
+                </label>
+                <input
+                  type="text"
+                  value={localPace}
+                  onChange={(e) => setLocalPace(e.target.value)}
+                  placeholder="e.g., 4:30"
+                  className="w-full px-3 py-2 rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-[var(--color-text-primary)] text-sm focus:outline-none focus:ring-2 focus:ring-[var(--color-accent)]"
+                />
+                <p className="text-[10px] text-[var(--color-text-secondary)] mt-1">
+                  Running pace zones
+                </p>
+              </div>
+            </div>
+
+            <div className="mt-3">
+              <label className="block text-xs text-[var(--color-text-secondary)] mb-1">
+                Effective From
+              </label>
+              <input
+                type="date"
+                value={effectiveDate}
+                onChange={(e) => setEffectiveDate(e.target.value)}
+                className="w-full px-3 py-2 rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-[var(--color-text-primary)] text-sm focus:outline-none focus:ring-2 focus:ring-[var(--color-accent)]"
+              />
+              <p className="text-[10px] text-[var(--color-text-secondary)] mt-1">
+                These values apply to workouts from this date on, e.g. the day of an FTP test
+              </p>
             </div>
+
+            {profileHistory.length > 1 && (
+              <div className="mt-3 space-y-1">
+                <p className="text-xs text-[var(--color-text-secondary)]">History</p>
+                {[...profileHistory].reverse().map((entry) => (
+                  <div
+                    key={entry.effective_date}
+                    className="flex items-center gap-2 px-2 py-1 rounded-lg bg-[var(--color-bg-secondary)] text-xs"
+                  >
+                    <span className="text-[var(--color-text-primary)] w-24">{entry.effective_date}</span>
+                    <span className="text-[var(--color-text-secondary)] flex-1 truncate">
+                      {[
+                        entry.ftp_watts && `FTP ${entry.ftp_watts} W`,
+                        entry.max_heart_rate && `Max ${entry.max_heart_rate} bpm`,
+                        entry.threshold_heart_rate && `LTHR ${entry.threshold_heart_rate} bpm`,
+                        entry.threshold_pace_seconds_per_km && `${formatPace(entry.threshold_pace_seconds_per_km)}/km`,
+                        entry.weight_kg && `${entry.weight_kg} kg`,
+                      ].filter(Boolean).join(' · ')}
+                    </span>
+                    <button
+                      onClick={() => handleDeleteProfileEntry(entry.effective_date)}
+                      className="p-1 text-[var(--color-text-secondary)] hover:text-red-400 transition-colors"
+                      title="Remove entry"
+                    >
+                      <Trash2 className="w-3 h-3" />
+                    </button>
+                  </div>
+                ))}
+              </div>
+            )}
           </section>
 
           {/* HR Zones Section */}
@@ -179,6 +512,16 @@ export default function SettingsModal() {
               </div>
             </div>
 
+            <select
+              value={hrZoneMethod}
+              onChange={(e) => setHrZoneMethod(e.target.value as HrZoneMethod)}
+              className="w-full mb-2 px-3 py-2 rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-[var(--color-text-primary)] text-sm focus:outline-none focus:ring-2 focus:ring-[var(--color-accent)]"
+            >
+              <option value="max_heart_rate">% of max HR</option>
+              <option value="heart_rate_reserve">% of heart rate reserve (Karvonen)</option>
+              <option value="lactate_threshold">% of threshold HR (LTHR)</option>
+            </select>
+
             <div className="space-y-2">
               {localZones.map((zone, index) => (
                 <div 
@@ -200,7 +543,7 @@ export default function SettingsModal() {
                         onChange={(e) => handleZoneChange(index, 'min', e.target.value)}
                         className="w-14 px-2 py-1 text-xs rounded bg-[var(--color-bg-primary)] border border-[var(--color-border)] text-center text-[var(--color-text-primary)]"
                         min="0"
-                        max="100"
+                        max={hrZoneMethod === 'lactate_threshold' ? 150 : 100}
                       />
                       <span className="text-xs text-[var(--color-text-secondary)]">-</span>
                       <input
@@ -209,7 +552,7 @@ export default function SettingsModal() {
                         onChange={(e) => handleZoneChange(index, 'max', e.target.value)}
                         className="w-14 px-2 py-1 text-xs rounded bg-[var(--color-bg-primary)] border border-[var(--color-border)] text-center text-[var(--color-text-primary)]"
                         min="0"
-                        max="100"
+                        max={hrZoneMethod === 'lactate_threshold' ? 150 : 100}
                       />
                       <span className="text-xs text-[var(--color-text-secondary)]">%</span>
                     </>
@@ -224,9 +567,153 @@ export default function SettingsModal() {
 
             <p className="text-[10px] text-[var(--color-text-secondary)] mt-2">
               Current max HR: {estimatedMaxHR} bpm {!maxHeartRate && '(estimated)'}
+              {hrZoneMethod === 'lactate_threshold' && !thresholdHeartRate && ' · Set a threshold HR to use LTHR zones'}
             </p>
           </section>
 
+          <PercentZones
+            title="Power Zones"
+            unit="% of FTP"
+            zones={localPowerZones}
+            onChange={setLocalPowerZones}
+            onReset={() => handleResetPercentZones('get_default_power_zones', setLocalPowerZones)}
+            limits={(zone) =>
+              ftpWatts ? `${Math.round((ftpWatts * zone.min) / 100)}-${Math.round((ftpWatts * zone.max) / 100)} W` : null
+            }
+            hint={!ftpWatts ? 'Set an FTP to use power zones' : null}
+          />
+
+          {/* Faster paces are higher percentages of threshold speed */}
+          <PercentZones
+            title="Running Pace Zones"
+            unit="% of threshold speed"
+            zones={localPaceZones}
+            onChange={setLocalPaceZones}
+            onReset={() => handleResetPercentZones('get_default_pace_zones', setLocalPaceZones)}
+            limits={(zone) =>
+              thresholdPace
+                ? `${zone.min > 0 ? formatPace(Math.round((thresholdPace * 100) / zone.min)) : '∞'}-${formatPace(
+                    Math.round((thresholdPace * 100) / zone.max)
+                  )} /km`
+                : null
+            }
+            hint={!thresholdPace ? 'Set a threshold pace to use pace zones' : null}
+          />
+
+          {/* Data Section */}
+          <section>
+            <div className="flex items-center justify-between">
+              <div>
+                <h3 className="text-sm font-medium text-[var(--color-text-primary)]">
+                  Data
+                </h3>
+                <p className="text-[10px] text-[var(--color-text-secondary)] mt-1">
+                  Re-read every workout from its original file with the current parser
+                </p>
+              </div>
+              <button
+                onClick={handleReprocessAll}
+                disabled={isReprocessing}
+                className="flex items-center gap-1 px-3 py-1.5 text-xs rounded-lg bg-[var(--color-bg-secondary)] text-[var(--color-text-primary)] hover:opacity-90 transition-opacity disabled:opacity-50"
+              >
+                <RefreshCw className={`w-3 h-3 ${isReprocessing ? 'animate-spin' : ''}`} />
+                Reprocess all
+              </button>
+            </div>
+          </section>
+
+          {/* Tags Section */}
+          {allTags.length > 0 && (
+            <section>
+              <h3 className="text-sm font-medium text-[var(--color-text-primary)]">
+                Tags
+              </h3>
+              <p className="text-[10px] text-[var(--color-text-secondary)] mt-1 mb-3">
+                Changes apply to every workout. Rename a tag to an existing name to merge them.
+              </p>
+              <div className="space-y-2">
+                {allTags.map((tag) => (
+                  <div key={tag.id} className="flex items-center gap-2">
+                    <input
+                      type="color"
+                      value={tag.color ?? '#6b7280'}
+                      onChange={(e) => setTagColor(tag.name, e.target.value)}
+                      className="w-6 h-6 rounded cursor-pointer bg-transparent border-0 p-0"
+                      title="Tag color"
+                    />
+                    <input
+                      type="text"
+                      value={tagEdits[tag.name] ?? tag.name}
+                      onChange={(e) => setTagEdits({ ...tagEdits, [tag.name]: e.target.value })}
+                      onBlur={() => handleRenameTag(tag.name)}
+                      onKeyDown={(e) => e.key === 'Enter' && e.currentTarget.blur()}
+                      className="flex-1 px-2 py-1 text-xs rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-[var(--color-text-primary)] focus:outline-none focus:border-[var(--color-accent)]"
+                    />
+                    <span className="text-[10px] text-[var(--color-text-secondary)] w-16 text-right">
+                      {tag.usage_count} workout{tag.usage_count !== 1 ? 's' : ''}
+                    </span>
+                    <button
+                      onClick={() => handleDeleteTag(tag.name, tag.usage_count)}
+                      className="p-1 text-[var(--color-text-secondary)] hover:text-red-400 transition-colors"
+                      title="Delete tag"
+                    >
+                      <Trash2 className="w-3.5 h-3.5" />
+                    </button>
+                  </div>
+                ))}
+              </div>
+            </section>
+          )}
+
+          {/* Sport Categories Section */}
+          {sportRows.length > 0 && (
+            <section>
+              <div className="flex items-center justify-between">
+                <div>
+                  <h3 className="text-sm font-medium text-[var(--color-text-primary)]">
+                    Sport Categories
+                  </h3>
+                  <p className="text-[10px] text-[var(--color-text-secondary)] mt-1 mb-3">
+                    The category each sport is listed, filtered and counted under. A sub-sport can have its own.
+                  </p>
+                </div>
+                <button
+                  onClick={handleResetCategories}
+                  className="flex items-center gap-1 px-3 py-1.5 text-xs rounded-lg bg-[var(--color-bg-secondary)] text-[var(--color-text-primary)] hover:opacity-90 transition-opacity"
+                >
+                  <RotateCcw className="w-3 h-3" />
+                  Reset
+                </button>
+              </div>
+              <datalist id="sport-category-names">
+                {categoryNames.map((name) => (
+                  <option key={name} value={capitalizeWorkoutType(name)} />
+                ))}
+              </datalist>
+              <div className="space-y-2">
+                {sportRows.map((row) => (
+                  <div key={row.key} className="flex items-center gap-2">
+                    <span className="flex-1 text-xs text-[var(--color-text-primary)] truncate">
+                      {formatSport(row.sport, row.sub_sport)}
+                    </span>
+                    <input
+                      type="text"
+                      list="sport-category-names"
+                      value={categoryEdits[row.key] ?? capitalizeWorkoutType(row.category)}
+                      onChange={(e) => setCategoryEdits({ ...categoryEdits, [row.key]: e.target.value })}
+                      onBlur={() => handleCategoryChange(row.sport, row.sub_sport, row.key, row.category)}
+                      onKeyDown={(e) => e.key === 'Enter' && e.currentTarget.blur()}
+                      className="w-40 px-2 py-1 text-xs rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-[var(--color-text-primary)] focus:outline-none focus:border-[var(--color-accent)]"
+                    />
+                    <span className="text-[10px] text-[var(--color-text-secondary)] w-16 text-right">
+                      {row.count} workout{row.count !== 1 ? 's' : ''}
+                    </span>
+                  </div>
+                ))}
+              </div>
+            </section>
+          )}
+
           {/* Info Section */}
           <section className="bg-[var(--color-bg-secondary)] rounded-lg p-3">
             <h4 className="text-xs font-medium text-[var(--color-text-primary)] mb-2">
@@ -261,3 +748,74 @@ export default function SettingsModal() {
     </div>
   );
 }
+
+interface PercentZonesProps {
+  title: string;
+  unit: string;
+  zones: Zone[];
+  onChange: (zones: Zone[]) => void;
+  onReset: () => void;
+  // The zone's limits under the current profile, when it has the reference value
+  limits: (zone: Zone) => string | null;
+  hint: string | null;
+}
+
+// Power or pace zones, whose limits are percentages of a threshold
+function PercentZones({ title, unit, zones, onChange, onReset, limits, hint }: PercentZonesProps) {
+  const handleChange = (index: number, field: 'min' | 'max', value: string) => {
+    const numValue = parseFloat(value);
+    if (!isNaN(numValue) && numValue >= 0 && numValue <= 300) {
+      onChange(zones.map((zone, i) => (i === index ? { ...zone, [field]: numValue } : zone)));
+    }
+  };
+
+  return (
+    <section>
+      <div className="flex items-center justify-between mb-3">
+        <h3 className="text-sm font-medium text-[var(--color-text-primary)]">
+          {title}
+          <span className="ml-2 text-[10px] font-normal text-[var(--color-text-secondary)]">{unit}</span>
+        </h3>
+        <button
+          onClick={onReset}
+          className="flex items-center gap-1 px-2 py-1 text-xs text-[var(--color-text-secondary)] hover:text-[var(--color-text-primary)] transition-colors"
+        >
+          <RefreshCw className="w-3 h-3" />
+          Reset
+        </button>
+      </div>
+
+      <div className="space-y-2">
+        {zones.map((zone, index) => (
+          <div key={index} className="flex items-center gap-2 p-2 rounded-lg bg-[var(--color-bg-secondary)]">
+            <div className="w-3 h-3 rounded-sm flex-shrink-0" style={{ backgroundColor: zone.color }} />
+            <span className="text-xs text-[var(--color-text-primary)] flex-1 truncate">{zone.name}</span>
+            {limits(zone) && (
+              <span className="text-[10px] text-[var(--color-text-secondary)]">{limits(zone)}</span>
+            )}
+            <input
+              type="number"
+              value={zone.min}
+              onChange={(e) => handleChange(index, 'min', e.target.value)}
+              className="w-14 px-2 py-1 text-xs rounded bg-[var(--color-bg-primary)] border border-[var(--color-border)] text-center text-[var(--color-text-primary)]"
+              min="0"
+              max="300"
+            />
+            <span className="text-xs text-[var(--color-text-secondary)]">-</span>
+            <input
+              type="number"
+              value={zone.max}
+              onChange={(e) => handleChange(index, 'max', e.target.value)}
+              className="w-14 px-2 py-1 text-xs rounded bg-[var(--color-bg-primary)] border border-[var(--color-border)] text-center text-[var(--color-text-primary)]"
+              min="0"
+              max="300"
+            />
+            <span className="text-xs text-[var(--color-text-secondary)]">%</span>
+          </div>
+        ))}
+      </div>
+
+      {hint && <p className="text-[10px] text-[var(--color-text-secondary)] mt-2">{hint}</p>}
+    </section>
+  );
+}
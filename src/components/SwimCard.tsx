@@ -0,0 +1,126 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Waves } from 'lucide-react';
+import { useWorkoutStore } from '../stores/workoutStore';
+import type { SwimAnalysis } from '../types';
+import { capitalizeWorkoutType, formatDuration } from '../types';
+
+function formatPace100(seconds: number | null): string {
+  return seconds !== null ? `${formatDuration(seconds)} /100m` : '--';
+}
+
+function formatAverage(value: number | null): string {
+  return value !== null ? value.toFixed(1) : '--';
+}
+
+export default function SwimCard() {
+  const selectedWorkout = useWorkoutStore((state) => state.selectedWorkout);
+  const [analysis, setAnalysis] = useState<SwimAnalysis | null>(null);
+
+  // Worked out on request from the stored laps and lengths
+  useEffect(() => {
+    if (!selectedWorkout) {
+      setAnalysis(null);
+      return;
+    }
+    let cancelled = false;
+    invoke<SwimAnalysis | null>('get_swim_analysis', { id: selectedWorkout.id })
+      .then((result) => {
+        if (!cancelled) setAnalysis(result);
+      })
+      .catch((error) => {
+        console.error('Failed to fetch swim analysis:', error);
+        if (!cancelled) setAnalysis(null);
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [selectedWorkout]);
+
+  if (!analysis || analysis.active_lengths === 0) return null;
+
+  const stats = [
+    { label: 'Distance', value: `${Math.round(analysis.distance_meters)} m` },
+    { label: 'Swim Time', value: formatDuration(analysis.swim_time_seconds) },
+    { label: 'Rest', value: formatDuration(analysis.rest_time_seconds) },
+    { label: 'Pace', value: formatPace100(analysis.pace_per_100m_seconds) },
+    { label: 'SWOLF', value: formatAverage(analysis.avg_swolf) },
+    { label: 'Strokes/Length', value: formatAverage(analysis.avg_strokes_per_length) },
+  ];
+
+  return (
+    <div className="card p-4">
+      <div className="flex items-center justify-between mb-3">
+        <div className="flex items-center gap-2">
+          <div className="w-8 h-8 rounded-lg bg-cyan-900/30 flex items-center justify-center">
+            <Waves className="w-4 h-4 text-cyan-400" />
+          </div>
+          <h3 className="text-sm font-medium text-[var(--color-text-primary)]">Swim</h3>
+        </div>
+        <span className="text-xs text-[var(--color-text-secondary)]">
+          {analysis.active_lengths} × {analysis.pool_length_meters} m
+        </span>
+      </div>
+
+      <div className="grid grid-cols-3 sm:grid-cols-6 gap-2 mb-3">
+        {stats.map((stat) => (
+          <div key={stat.label} className="text-center">
+            <p className="text-xs text-[var(--color-text-secondary)]">{stat.label}</p>
+            <p className="text-sm font-medium text-[var(--color-text-primary)]">{stat.value}</p>
+          </div>
+        ))}
+      </div>
+
+      {/* One stroke says nothing a breakdown would add */}
+      {analysis.strokes.length > 1 && (
+        <div className="space-y-1 mb-3">
+          {analysis.strokes.map((stroke) => (
+            <div key={stroke.swim_stroke} className="flex items-center gap-2 text-xs">
+              <span className="flex-1 font-medium text-[var(--color-text-primary)]">
+                {capitalizeWorkoutType(stroke.swim_stroke)}
+              </span>
+              <span className="text-[var(--color-text-secondary)]">{Math.round(stroke.distance_meters)} m</span>
+              <span className="w-24 text-right text-[var(--color-text-secondary)]">
+                {formatPace100(stroke.pace_per_100m_seconds)}
+              </span>
+              <span className="w-16 text-right text-[var(--color-text-secondary)]">
+                SWOLF {formatAverage(stroke.avg_swolf)}
+              </span>
+            </div>
+          ))}
+        </div>
+      )}
+
+      {analysis.intervals.length > 1 && (
+        <table className="w-full text-xs">
+          <thead>
+            <tr className="text-[var(--color-text-secondary)]">
+              <th className="text-left font-normal pb-1">#</th>
+              <th className="text-left font-normal pb-1">Stroke</th>
+              <th className="text-right font-normal pb-1">Distance</th>
+              <th className="text-right font-normal pb-1">Time</th>
+              <th className="text-right font-normal pb-1">Pace</th>
+              <th className="text-right font-normal pb-1">SWOLF</th>
+              <th className="text-right font-normal pb-1">Rest</th>
+            </tr>
+          </thead>
+          <tbody className="text-[var(--color-text-primary)]">
+            {analysis.intervals.map((interval) => (
+              <tr key={interval.interval_index}>
+                <td className="py-0.5">{interval.interval_index + 1}</td>
+                <td className="py-0.5">{capitalizeWorkoutType(interval.swim_stroke)}</td>
+                <td className="py-0.5 text-right">{Math.round(interval.distance_meters)} m</td>
+                <td className="py-0.5 text-right">{formatDuration(interval.duration_seconds)}</td>
+                <td className="py-0.5 text-right">{formatPace100(interval.pace_per_100m_seconds)}</td>
+                <td className="py-0.5 text-right">{formatAverage(interval.avg_swolf)}</td>
+                <td className="py-0.5 text-right text-[var(--color-text-secondary)]">
+                  {interval.rest_after_seconds > 0 ? formatDuration(interval.rest_after_seconds) : ''}
+                </td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      )}
+    </div>
+  );
+}
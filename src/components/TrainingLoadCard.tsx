@@ -1,8 +1,7 @@
-import { useMemo, useEffect, useState } from 'react';
+import { useMemo } from 'react';
 import { Activity, TrendingUp, TrendingDown, Minus } from 'lucide-react';
-import { invoke } from '@tauri-apps/api/core';
-import { useSettingsStore, calculateTSS } from '../stores/settingsStore';
-import type { WorkoutSummary } from '../types';
+import { useSettingsStore } from '../stores/settingsStore';
+import { useWorkoutStore } from '../stores/workoutStore';
 import {
   ResponsiveContainer,
   BarChart,
@@ -12,118 +11,20 @@ import {
   Tooltip,
 } from 'recharts';
 
-interface DailyTSS {
-  date: string;
-  tss: number;
-  workouts: number;
-}
-
 export default function TrainingLoadCard() {
-  const [recentWorkouts, setRecentWorkouts] = useState<WorkoutSummary[]>([]);
-  const [isLoading, setIsLoading] = useState(true);
-  
-  const getEstimatedMaxHR = useSettingsStore((state) => state.getEstimatedMaxHR);
-  const restingHeartRate = useSettingsStore((state) => state.restingHeartRate);
+  const trainingLoad = useWorkoutStore((state) => state.trainingLoad);
+  const isLoading = useWorkoutStore((state) => state.isLoading) && trainingLoad.length === 0;
   const openSettings = useSettingsStore((state) => state.openSettings);
 
-  // Fetch recent workouts for training load calculation
-  useEffect(() => {
-    const fetchRecentWorkouts = async () => {
-      try {
-        const response = await invoke<{ workouts: WorkoutSummary[] }>('get_workouts', {
-          page: 1,
-          perPage: 100, // Get last 100 workouts for calculation
-        });
-        setRecentWorkouts(response.workouts);
-      } catch (error) {
-        console.error('Failed to fetch workouts for training load:', error);
-      } finally {
-        setIsLoading(false);
-      }
-    };
-
-    fetchRecentWorkouts();
-  }, []);
-
+  // The backend series runs day by day through today
   const trainingData = useMemo(() => {
-    const maxHR = getEstimatedMaxHR();
-    const restHR = restingHeartRate ?? 60;
-
-    // Calculate TSS for each workout
-    const workoutTSS = recentWorkouts
-      .filter((w) => w.duration_seconds && w.avg_heart_rate)
-      .map((w) => {
-        const durationMinutes = (w.duration_seconds ?? 0) / 60;
-        const avgHR = w.avg_heart_rate ?? 0;
-        const tss = calculateTSS(durationMinutes, avgHR, maxHR, restHR);
-        
-        return {
-          date: w.start_time ? new Date(w.start_time).toISOString().split('T')[0] : '',
-          tss,
-          durationMinutes,
-          avgHR,
-        };
-      })
-      .filter((w) => w.date);
-
-    // Aggregate TSS by day
-    const dailyMap = new Map<string, DailyTSS>();
-    workoutTSS.forEach((w) => {
-      const existing = dailyMap.get(w.date);
-      if (existing) {
-        existing.tss += w.tss;
-        existing.workouts += 1;
-      } else {
-        dailyMap.set(w.date, { date: w.date, tss: w.tss, workouts: 1 });
-      }
-    });
-
-    // Get last 7 days for chart
-    const today = new Date();
-    const last7Days: DailyTSS[] = [];
-    for (let i = 6; i >= 0; i--) {
-      const date = new Date(today);
-      date.setDate(date.getDate() - i);
-      const dateStr = date.toISOString().split('T')[0];
-      const data = dailyMap.get(dateStr);
-      last7Days.push({
-        date: dateStr,
-        tss: data?.tss ?? 0,
-        workouts: data?.workouts ?? 0,
-      });
-    }
-
-    // Calculate ATL (7-day average)
-    const last7DaysTSS = last7Days.reduce((sum, d) => sum + d.tss, 0);
-    const atl = Math.round(last7DaysTSS / 7);
-
-    // Calculate CTL (42-day average) - we need more data
-    const sortedDates = Array.from(dailyMap.keys()).sort();
-    let ctlSum = 0;
-    let ctlDays = 0;
-    const cutoffDate = new Date();
-    cutoffDate.setDate(cutoffDate.getDate() - 42);
-    
-    sortedDates.forEach((date) => {
-      if (new Date(date) >= cutoffDate) {
-        ctlSum += dailyMap.get(date)?.tss ?? 0;
-        ctlDays++;
-      }
-    });
-    const ctl = ctlDays > 0 ? Math.round(ctlSum / Math.min(42, ctlDays)) : 0;
-
-    // TSB = CTL - ATL
-    const tsb = ctl - atl;
+    const last14Days = trainingLoad.slice(-14);
+    const last7Days = last14Days.slice(-7).map((d) => ({ date: d.date, tss: Math.round(d.tss) }));
+    const previous7Days = last14Days.slice(0, -7);
+    const today = trainingLoad[trainingLoad.length - 1];
 
-    // Calculate week-over-week trend
-    const thisWeekTSS = last7DaysTSS;
-    let lastWeekTSS = 0;
-    for (let i = 13; i >= 7; i--) {
-      const date = new Date(today);
-      date.setDate(date.getDate() - i);
-      const dateStr = date.toISOString().split('T')[0];
-      lastWeekTSS += dailyMap.get(dateStr)?.tss ?? 0;
-    }
+    const thisWeekTSS = last7Days.reduce((sum, d) => sum + d.tss, 0);
+    const lastWeekTSS = Math.round(previous7Days.reduce((sum, d) => sum + d.tss, 0));
 
     const weeklyChange = lastWeekTSS > 0 
       ? Math.round(((thisWeekTSS - lastWeekTSS) / lastWeekTSS) * 100) 
@@ -131,13 +32,13 @@ export default function TrainingLoadCard() {
 
     return {
       dailyData: last7Days,
-      atl,
-      ctl,
-      tsb,
+      atl: Math.round(today?.atl ?? 0),
+      ctl: Math.round(today?.ctl ?? 0),
+      tsb: Math.round(today?.tsb ?? 0),
       weeklyTSS: thisWeekTSS,
       weeklyChange,
     };
-  }, [recentWorkouts, getEstimatedMaxHR, restingHeartRate]);
+  }, [trainingLoad]);
 
   const getTrendIcon = (change: number) => {
     if (change > 5) return <TrendingUp className="w-3 h-3 text-emerald-400" />;
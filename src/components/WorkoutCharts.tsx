@@ -19,6 +19,12 @@ interface WorkoutChartsProps {
 // Maximum number of data points to display in charts
 const MAX_CHART_POINTS = 500;
 
+// Developer fields (Connect IQ apps) take their colors in turn
+const DEVELOPER_COLORS = ['#ec4899', '#14b8a6', '#f43f5e', '#0891b2', '#7c3aed'];
+
+// Data key of a developer field; its key ("0.3") also ends up in SVG ids
+const developerKey = (key: string) => `dev_${key.replace(/\W/g, '_')}`;
+
 // Downsample data using LTTB (Largest Triangle Three Buckets) algorithm
 function downsampleData<T extends Record<string, unknown>>(
   data: T[],
@@ -112,8 +118,25 @@ export default function WorkoutCharts({ chartData, onHover, workoutType }: Worko
       heartRate: chartData.heart_rate[i],
       speed: chartData.speed[i] ? chartData.speed[i]! * 3.6 : null, // Convert to km/h
       power: chartData.power[i],
-      cadence: chartData.cadence[i],
+      cadence: chartData.cadence[i] !== null
+        ? chartData.cadence[i]! + (chartData.fractional_cadence?.[i] ?? 0)
+        : null,
       altitude: chartData.altitude[i],
+      temperature: chartData.temperature?.[i] ?? null,
+      verticalOscillation: chartData.vertical_oscillation?.[i] ?? null,
+      groundContactTime: chartData.ground_contact_time?.[i] ?? null,
+      groundContactBalance: chartData.ground_contact_balance?.[i] ?? null,
+      strideLength: chartData.stride_length?.[i] ?? null,
+      verticalRatio: chartData.vertical_ratio?.[i] ?? null,
+      leftRightBalance: chartData.left_right_balance?.[i] ?? null,
+      leftTorqueEffectiveness: chartData.left_torque_effectiveness?.[i] ?? null,
+      rightTorqueEffectiveness: chartData.right_torque_effectiveness?.[i] ?? null,
+      leftPedalSmoothness: chartData.left_pedal_smoothness?.[i] ?? null,
+      rightPedalSmoothness: chartData.right_pedal_smoothness?.[i] ?? null,
+      respirationRate: chartData.respiration_rate?.[i] ?? null,
+      coreTemperature: chartData.core_temperature?.[i] ?? null,
+      spo2: chartData.spo2?.[i] ?? null,
+      ...Object.fromEntries(chartData.developer.map((series) => [developerKey(series.key), series.values[i]])),
     };
   });
   
@@ -130,14 +153,19 @@ export default function WorkoutCharts({ chartData, onHover, workoutType }: Worko
   const hasSpeed = chartData.speed.some((v) => v !== null);
   const hasPower = chartData.power.some((v) => v !== null);
   const hasCadence = chartData.cadence.some((v) => v !== null);
+  const has = (key: keyof typeof fullData[0]) => fullData.some((d) => d[key] !== null && d[key] !== undefined);
 
   // Calculate min/max for each data type from FULL data (not downsampled)
-  const getMinMax = (key: keyof typeof fullData[0]) => {
+  // Channels with small ranges (stride length in m, core temperature) pass
+  // a smaller minimum padding and keep fractional limits below 1
+  const getMinMax = (key: keyof typeof fullData[0], minPadding = 5) => {
     const values = fullData.map(d => d[key]).filter((v): v is number => v !== null && typeof v === 'number');
     if (values.length === 0) return { min: 0, max: 100 };
-    const min = Math.floor(Math.min(...values));
-    const max = Math.ceil(Math.max(...values));
-    const padding = Math.max(5, Math.round((max - min) * 0.1));
+    const whole = minPadding >= 1;
+    const min = whole ? Math.floor(Math.min(...values)) : Math.min(...values);
+    const max = whole ? Math.ceil(Math.max(...values)) : Math.max(...values);
+    const spread = (max - min) * 0.1;
+    const padding = Math.max(minPadding, whole ? Math.round(spread) : spread);
     return { min: Math.max(0, min - padding), max: max + padding };
   };
 
@@ -178,6 +206,146 @@ export default function WorkoutCharts({ chartData, onHover, workoutType }: Worko
       show: hasCadence,
       domain: getMinMax('cadence'),
     },
+    {
+      key: 'temperature',
+      title: 'Temperature',
+      color: '#f97316',
+      unit: '°C',
+      yLabel: '°C',
+      show: has('temperature'),
+      domain: getMinMax('temperature'),
+    },
+    {
+      key: 'verticalOscillation',
+      title: 'Vertical Oscillation',
+      color: '#8b5cf6',
+      unit: 'mm',
+      yLabel: 'mm',
+      show: has('verticalOscillation'),
+      domain: getMinMax('verticalOscillation'),
+    },
+    {
+      key: 'groundContactTime',
+      title: 'Ground Contact Time',
+      color: '#a855f7',
+      unit: 'ms',
+      yLabel: 'ms',
+      show: has('groundContactTime'),
+      domain: getMinMax('groundContactTime'),
+    },
+    {
+      key: 'groundContactBalance',
+      title: 'GCT Balance (L)',
+      color: '#c084fc',
+      unit: '%',
+      yLabel: '%',
+      show: has('groundContactBalance'),
+      domain: getMinMax('groundContactBalance', 1),
+      decimals: 1,
+    },
+    {
+      key: 'strideLength',
+      title: 'Stride Length',
+      color: '#6366f1',
+      unit: 'm',
+      yLabel: 'm',
+      show: has('strideLength'),
+      domain: getMinMax('strideLength', 0.1),
+      decimals: 2,
+    },
+    {
+      key: 'verticalRatio',
+      title: 'Vertical Ratio',
+      color: '#818cf8',
+      unit: '%',
+      yLabel: '%',
+      show: has('verticalRatio'),
+      domain: getMinMax('verticalRatio', 1),
+      decimals: 1,
+    },
+    {
+      key: 'leftRightBalance',
+      title: 'Power Balance (L)',
+      color: '#eab308',
+      unit: '%',
+      yLabel: '%',
+      show: has('leftRightBalance'),
+      domain: getMinMax('leftRightBalance', 1),
+    },
+    {
+      key: 'leftTorqueEffectiveness',
+      title: 'Torque Effectiveness (L)',
+      color: '#d97706',
+      unit: '%',
+      yLabel: '%',
+      show: has('leftTorqueEffectiveness'),
+      domain: getMinMax('leftTorqueEffectiveness'),
+    },
+    {
+      key: 'rightTorqueEffectiveness',
+      title: 'Torque Effectiveness (R)',
+      color: '#b45309',
+      unit: '%',
+      yLabel: '%',
+      show: has('rightTorqueEffectiveness'),
+      domain: getMinMax('rightTorqueEffectiveness'),
+    },
+    {
+      key: 'leftPedalSmoothness',
+      title: 'Pedal Smoothness (L)',
+      color: '#84cc16',
+      unit: '%',
+      yLabel: '%',
+      show: has('leftPedalSmoothness'),
+      domain: getMinMax('leftPedalSmoothness'),
+    },
+    {
+      key: 'rightPedalSmoothness',
+      title: 'Pedal Smoothness (R)',
+      color: '#65a30d',
+      unit: '%',
+      yLabel: '%',
+      show: has('rightPedalSmoothness'),
+      domain: getMinMax('rightPedalSmoothness'),
+    },
+    {
+      key: 'respirationRate',
+      title: 'Respiration Rate',
+      color: '#06b6d4',
+      unit: 'brpm',
+      yLabel: 'brpm',
+      show: has('respirationRate'),
+      domain: getMinMax('respirationRate'),
+    },
+    {
+      key: 'coreTemperature',
+      title: 'Core Temperature',
+      color: '#dc2626',
+      unit: '°C',
+      yLabel: '°C',
+      show: has('coreTemperature'),
+      domain: getMinMax('coreTemperature', 0.5),
+      decimals: 1,
+    },
+    {
+      key: 'spo2',
+      title: 'SpO2',
+      color: '#0ea5e9',
+      unit: '%',
+      yLabel: '%',
+      show: has('spo2'),
+      domain: getMinMax('spo2', 1),
+    },
+    ...chartData.developer.map((series, i) => ({
+      key: developerKey(series.key),
+      title: series.name,
+      color: DEVELOPER_COLORS[i % DEVELOPER_COLORS.length],
+      unit: series.units ?? '',
+      yLabel: series.units ?? '',
+      show: series.values.some((v) => v !== null),
+      domain: getMinMax(developerKey(series.key), 1),
+      decimals: 1,
+    })),
   ].filter((c) => c.show);
 
   if (chartConfig.length === 0) {
@@ -247,8 +415,9 @@ export default function WorkoutCharts({ chartData, onHover, workoutType }: Worko
           <div className={statsGridClass}>
             {chartConfig.map((config) => {
               const values = fullData.map(d => d[config.key as keyof typeof fullData[0]]).filter((v): v is number => v !== null && typeof v === 'number');
-              const avg = values.length > 0 ? Math.round(values.reduce((a, b) => a + b, 0) / values.length) : 0;
-              const max = values.length > 0 ? Math.round(Math.max(...values)) : 0;
+              const decimals = config.decimals ?? 0;
+              const avg = values.length > 0 ? (values.reduce((a, b) => a + b, 0) / values.length).toFixed(decimals) : 0;
+              const max = values.length > 0 ? Math.max(...values).toFixed(decimals) : 0;
               return (
                 <div key={config.key} className="bg-[var(--color-bg-secondary)] rounded-lg p-2 text-center">
                   <p className="text-xs text-[var(--color-text-secondary)]">{config.title}</p>
@@ -303,7 +472,7 @@ export default function WorkoutCharts({ chartData, onHover, workoutType }: Worko
                         tickLine={false}
                         width={40}
                         tickCount={5}
-                        tickFormatter={(v) => `${Math.round(Number(v))}${config.unit === 'bpm' ? '' : config.unit === 'W' ? '' : ''}`}
+                        tickFormatter={(v) => Number(v).toFixed(config.decimals ? 1 : 0)}
                       />
                       <Tooltip
                         contentStyle={{
@@ -1,24 +1,50 @@
 import { Filter, X, Search, Calendar, Clock, MapPin } from 'lucide-react';
 import { useWorkoutStore } from '../stores/workoutStore';
 import { invoke } from '@tauri-apps/api/core';
-import type { Workout } from '../types';
+import type { DurationBasis, SortOrder, Workout, WorkoutSortKey } from '../types';
 import {
+  deviceFilterValue,
+  formatDeviceName,
   formatDuration,
   formatDistance,
   formatDate,
+  formatSport,
   formatTime,
   capitalizeWorkoutType,
   getActivityColor,
 } from '../types';
 import { useState, useEffect, useRef } from 'react';
 
+const SORT_OPTIONS: { value: `${WorkoutSortKey}:${SortOrder}`; label: string }[] = [
+  { value: 'date:desc', label: 'Newest first' },
+  { value: 'date:asc', label: 'Oldest first' },
+  { value: 'duration:desc', label: 'Longest elapsed time' },
+  { value: 'moving_time:desc', label: 'Longest moving time' },
+  { value: 'distance:desc', label: 'Longest distance' },
+];
+
+// Search snippets wrap matched terms in <mark>; render them without innerHTML
+function renderSnippet(snippet: string) {
+  return snippet.split(/<mark>(.*?)<\/mark>/g).map((part, i) =>
+    i % 2 === 1 ? (
+      <mark key={i} className="bg-[var(--color-accent)]/30 text-[var(--color-text-primary)] rounded-sm">
+        {part}
+      </mark>
+    ) : (
+      part
+    )
+  );
+}
+
 export default function WorkoutList() {
   const {
     workouts,
     currentPage,
     totalWorkouts,
     perPage,
+    categoryFilter,
     workoutTypeFilter,
+    subSportFilter,
     tagFilter,
     searchQuery,
     dateRangeStart,
@@ -27,16 +53,24 @@ export default function WorkoutList() {
     maxDistance,
     minDuration,
     maxDuration,
+    durationBasis,
+    deviceFilter,
+    sortBy,
+    sortOrder,
     activityBreakdown,
     allTags,
+    devices,
     isLoading,
     setPage,
-    setWorkoutTypeFilter,
+    setCategoryFilter,
+    setSportFilter,
     setTagFilter,
     setSearchQuery,
     setDateRange,
     setDistanceFilter,
     setDurationFilter,
+    setDeviceFilter,
+    setSort,
     clearAllFilters,
     openModal,
   } = useWorkoutStore();
@@ -49,8 +83,12 @@ export default function WorkoutList() {
   const [localMaxDistance, setLocalMaxDistance] = useState(maxDistance?.toString() || '');
   const [localMinDuration, setLocalMinDuration] = useState(minDuration?.toString() || '');
   const [localMaxDuration, setLocalMaxDuration] = useState(maxDuration?.toString() || '');
+  const [localDurationBasis, setLocalDurationBasis] = useState<DurationBasis>(durationBasis);
   const searchTimeout = useRef<ReturnType<typeof setTimeout> | null>(null);
   const totalPages = Math.ceil(totalWorkouts / perPage);
+  const usedTags = allTags.filter((tag) => tag.usage_count > 0);
+  const selectedDevice = devices.find((device) => deviceFilterValue(device) === deviceFilter);
+  const selectedCategory = activityBreakdown.find((activity) => activity.name === categoryFilter);
 
   // Debounced search
   useEffect(() => {
@@ -86,7 +124,8 @@ export default function WorkoutList() {
     );
     setDurationFilter(
       localMinDuration ? parseFloat(localMinDuration) : null,
-      localMaxDuration ? parseFloat(localMaxDuration) : null
+      localMaxDuration ? parseFloat(localMaxDuration) : null,
+      localDurationBasis
     );
   };
 
@@ -98,12 +137,16 @@ export default function WorkoutList() {
     setLocalMaxDistance('');
     setLocalMinDuration('');
     setLocalMaxDuration('');
+    setLocalDurationBasis('elapsed');
     clearAllFilters();
   };
 
-  const hasActiveFilters = workoutTypeFilter !== null || tagFilter !== null || searchQuery !== '' || 
+  const hasActiveFilters = categoryFilter !== null || workoutTypeFilter !== null || tagFilter !== null || searchQuery !== '' || 
     dateRangeStart !== null || dateRangeEnd !== null || minDistance !== null || maxDistance !== null ||
-    minDuration !== null || maxDuration !== null;
+    minDuration !== null || maxDuration !== null || deviceFilter !== null;
+
+  // Rows show moving time when the list is sorted or filtered by it
+  const showMovingTime = sortBy === 'moving_time' || durationBasis === 'moving';
 
   return (
     <div className="card p-4">
@@ -118,6 +161,18 @@ export default function WorkoutList() {
           )}
         </h3>
         <div className="flex items-center gap-2">
+          <select
+            value={`${sortBy}:${sortOrder}`}
+            onChange={(e) => {
+              const [key, order] = e.target.value.split(':');
+              setSort(key as WorkoutSortKey, order as SortOrder);
+            }}
+            className="cursor-pointer px-2 py-1.5 text-xs bg-[var(--color-bg-secondary)] border border-[var(--color-border)] rounded-lg text-[var(--color-text-primary)] focus:outline-none focus:ring-2 focus:ring-[var(--color-accent)]"
+          >
+            {SORT_OPTIONS.map((option) => (
+              <option key={option.value} value={option.value}>{option.label}</option>
+            ))}
+          </select>
           {hasActiveFilters && (
             <button
               onClick={clearFilters}
@@ -148,7 +203,7 @@ export default function WorkoutList() {
             type="text"
             value={localSearch}
             onChange={(e) => setLocalSearch(e.target.value)}
-            placeholder="Search workouts by name, notes, or tags..."
+            placeholder='Search name, notes or tags (e.g. tempo OR "long run")...'
             className="w-full pl-9 pr-3 py-2 text-sm bg-[var(--color-bg-secondary)] border border-[var(--color-border)] rounded-lg text-[var(--color-text-primary)] placeholder:text-[var(--color-text-secondary)] focus:outline-none focus:ring-2 focus:ring-[var(--color-accent)]"
           />
           {localSearch && (
@@ -222,6 +277,14 @@ export default function WorkoutList() {
             <div className="flex items-center gap-1 mb-2">
               <Clock className="w-3 h-3 text-[var(--color-text-secondary)]" />
               <span className="text-xs text-[var(--color-text-secondary)]">Duration (minutes):</span>
+              <select
+                value={localDurationBasis}
+                onChange={(e) => setLocalDurationBasis(e.target.value as DurationBasis)}
+                className="ml-auto cursor-pointer px-1.5 py-0.5 text-xs bg-[var(--color-bg-card)] border border-[var(--color-border)] rounded-md text-[var(--color-text-primary)] focus:outline-none focus:ring-2 focus:ring-[var(--color-accent)]"
+              >
+                <option value="elapsed">Elapsed time</option>
+                <option value="moving">Moving time</option>
+              </select>
             </div>
             <div className="flex items-center gap-2">
               <input
@@ -258,9 +321,9 @@ export default function WorkoutList() {
             <span className="text-xs text-[var(--color-text-secondary)] block mb-2">Activity Type:</span>
             <div className="flex flex-wrap gap-1.5">
               <button
-                onClick={() => setWorkoutTypeFilter(null)}
+                onClick={() => setCategoryFilter(null)}
                 className={`cursor-pointer px-2.5 py-1 text-xs font-medium rounded-md transition-colors ${
-                  workoutTypeFilter === null
+                  categoryFilter === null
                     ? 'bg-[var(--color-accent)] text-white'
                     : 'bg-[var(--color-bg-card)] text-[var(--color-text-secondary)] hover:bg-[var(--color-border)]'
                 }`}
@@ -270,11 +333,11 @@ export default function WorkoutList() {
               {activityBreakdown.map((activity) => (
                 <button
                   key={activity.name}
-                  onClick={() => setWorkoutTypeFilter(
-                    workoutTypeFilter === activity.name ? null : activity.name
+                  onClick={() => setCategoryFilter(
+                    categoryFilter === activity.name ? null : activity.name
                   )}
                   className={`cursor-pointer px-2.5 py-1 text-xs font-medium rounded-md transition-colors flex items-center gap-1.5 ${
-                    workoutTypeFilter === activity.name
+                    categoryFilter === activity.name
                       ? 'bg-[var(--color-accent)] text-white'
                       : 'bg-[var(--color-bg-card)] text-[var(--color-text-secondary)] hover:bg-[var(--color-border)]'
                   }`}
@@ -288,10 +351,32 @@ export default function WorkoutList() {
                 </button>
               ))}
             </div>
+            {/* Sports and sub-sports within the selected category */}
+            {selectedCategory && selectedCategory.sports.length > 1 && (
+              <div className="flex flex-wrap gap-1.5 mt-1.5 pl-3 border-l-2 border-[var(--color-border)]">
+                {selectedCategory.sports.map((sport) => {
+                  const active = workoutTypeFilter === sport.sport && subSportFilter === sport.sub_sport;
+                  return (
+                    <button
+                      key={`${sport.sport}:${sport.sub_sport ?? ''}`}
+                      onClick={() => active ? setSportFilter(null, null) : setSportFilter(sport.sport, sport.sub_sport)}
+                      className={`cursor-pointer px-2.5 py-1 text-xs font-medium rounded-md transition-colors ${
+                        active
+                          ? 'bg-[var(--color-accent)] text-white'
+                          : 'bg-[var(--color-bg-card)] text-[var(--color-text-secondary)] hover:bg-[var(--color-border)]'
+                      }`}
+                    >
+                      {formatSport(sport.sport, sport.sub_sport)}
+                      <span className="opacity-70"> ({sport.count})</span>
+                    </button>
+                  );
+                })}
+              </div>
+            )}
           </div>
 
           {/* Tag Filter */}
-          {allTags.length > 0 && (
+          {usedTags.length > 0 && (
             <div>
               <span className="text-xs text-[var(--color-text-secondary)] block mb-2">Tags:</span>
               <div className="flex flex-wrap gap-1.5">
@@ -305,22 +390,66 @@ export default function WorkoutList() {
                 >
                   All
                 </button>
-                {allTags.map((tag) => (
+                {usedTags.map((tag) => (
                   <button
-                    key={tag}
-                    onClick={() => setTagFilter(tagFilter === tag ? null : tag)}
-                    className={`cursor-pointer px-2.5 py-1 text-xs font-medium rounded-md transition-colors ${
-                      tagFilter === tag
+                    key={tag.id}
+                    onClick={() => setTagFilter(tagFilter === tag.name ? null : tag.name)}
+                    className={`cursor-pointer flex items-center gap-1.5 px-2.5 py-1 text-xs font-medium rounded-md transition-colors ${
+                      tagFilter === tag.name
                         ? 'bg-[var(--color-accent)] text-white'
                         : 'bg-[var(--color-bg-card)] text-[var(--color-text-secondary)] hover:bg-[var(--color-border)]'
                     }`}
                   >
-                    #{tag}
+                    {tag.color && (
+                      <span className="w-2 h-2 rounded-full" style={{ backgroundColor: tag.color }} />
+                    )}
+                    #{tag.name}
+                    <span className="opacity-70">({tag.usage_count})</span>
                   </button>
                 ))}
               </div>
             </div>
           )}
+
+          {/* Device Filter */}
+          {devices.length > 1 && (
+            <div>
+              <span className="text-xs text-[var(--color-text-secondary)] block mb-2">Device:</span>
+              <div className="flex flex-wrap gap-1.5">
+                <button
+                  onClick={() => setDeviceFilter(null)}
+                  className={`cursor-pointer px-2.5 py-1 text-xs font-medium rounded-md transition-colors ${
+                    deviceFilter === null
+                      ? 'bg-[var(--color-accent)] text-white'
+                      : 'bg-[var(--color-bg-card)] text-[var(--color-text-secondary)] hover:bg-[var(--color-border)]'
+                  }`}
+                >
+                  All
+                </button>
+                {devices.map((device) => {
+                  const value = deviceFilterValue(device);
+                  return (
+                    <button
+                      key={`${device.product}-${device.serial_number}`}
+                      onClick={() => setDeviceFilter(deviceFilter === value ? null : value)}
+                      title={device.serial_number !== null ? `Serial ${device.serial_number}` : undefined}
+                      className={`cursor-pointer px-2.5 py-1 text-xs font-medium rounded-md transition-colors ${
+                        deviceFilter === value
+                          ? 'bg-[var(--color-accent)] text-white'
+                          : 'bg-[var(--color-bg-card)] text-[var(--color-text-secondary)] hover:bg-[var(--color-border)]'
+                      }`}
+                    >
+                      {formatDeviceName(device)}
+                      {device.device_type && !device.recorder && (
+                        <span className="opacity-70"> · {capitalizeWorkoutType(device.device_type)}</span>
+                      )}
+                      <span className="opacity-70"> ({device.workout_count})</span>
+                    </button>
+                  );
+                })}
+              </div>
+            </div>
+          )}
           </div>
         </div>
       )}
@@ -329,14 +458,22 @@ export default function WorkoutList() {
       {!showFilters && hasActiveFilters && (
         <div className="mb-3 flex flex-wrap items-center gap-2">
           <span className="text-xs text-[var(--color-text-secondary)]">Active:</span>
-          {workoutTypeFilter && (
+          {categoryFilter && (
             <span className="inline-flex items-center gap-1 px-2 py-1 text-xs bg-[var(--color-accent)]/20 text-[var(--color-accent)] rounded-md">
               <span
                 className="w-2 h-2 rounded-full"
-                style={{ backgroundColor: getActivityColor(workoutTypeFilter) }}
+                style={{ backgroundColor: getActivityColor(categoryFilter) }}
               />
-              {capitalizeWorkoutType(workoutTypeFilter)}
-              <button onClick={() => setWorkoutTypeFilter(null)} className="cursor-pointer ml-1 hover:opacity-70">
+              {capitalizeWorkoutType(categoryFilter)}
+              <button onClick={() => setCategoryFilter(null)} className="cursor-pointer ml-1 hover:opacity-70">
+                <X className="w-3 h-3" />
+              </button>
+            </span>
+          )}
+          {workoutTypeFilter && (
+            <span className="inline-flex items-center gap-1 px-2 py-1 text-xs bg-[var(--color-accent)]/20 text-[var(--color-accent)] rounded-md">
+              {formatSport(workoutTypeFilter, subSportFilter)}
+              <button onClick={() => setSportFilter(null, null)} className="cursor-pointer ml-1 hover:opacity-70">
                 <X className="w-3 h-3" />
               </button>
             </span>
@@ -349,6 +486,14 @@ export default function WorkoutList() {
               </button>
             </span>
           )}
+          {deviceFilter && (
+            <span className="inline-flex items-center gap-1 px-2 py-1 text-xs bg-[var(--color-accent)]/20 text-[var(--color-accent)] rounded-md">
+              {selectedDevice ? formatDeviceName(selectedDevice) : deviceFilter}
+              <button onClick={() => setDeviceFilter(null)} className="cursor-pointer ml-1 hover:opacity-70">
+                <X className="w-3 h-3" />
+              </button>
+            </span>
+          )}
         </div>
       )}
 
@@ -375,14 +520,14 @@ export default function WorkoutList() {
               {/* Activity type color bar */}
               <div
                 className="w-1 h-10 rounded-full flex-shrink-0"
-                style={{ backgroundColor: getActivityColor(workout.workout_type) }}
+                style={{ backgroundColor: getActivityColor(workout.category ?? workout.workout_type) }}
               />
               
               {/* Main info */}
               <div className="flex-1 min-w-0">
                 <div className="flex items-center gap-2">
                   <span className="text-sm font-medium text-[var(--color-text-primary)] truncate">
-                    {capitalizeWorkoutType(workout.workout_type)}
+                    {formatSport(workout.workout_type, workout.sub_sport)}
                   </span>
                   {workout.name && workout.name !== workout.workout_type && (
                     <span className="text-xs text-[var(--color-text-secondary)] truncate hidden sm:block">
@@ -393,6 +538,11 @@ export default function WorkoutList() {
                 <p className="text-xs text-[var(--color-text-secondary)]">
                   {formatDate(workout.start_time)} at {formatTime(workout.start_time)}
                 </p>
+                {workout.search_snippet && (
+                  <p className="text-xs text-[var(--color-text-secondary)] truncate">
+                    {renderSnippet(workout.search_snippet)}
+                  </p>
+                )}
               </div>
               
               {/* Stats */}
@@ -407,9 +557,9 @@ export default function WorkoutList() {
                 )}
                 <div>
                   <p className="text-xs font-medium text-[var(--color-text-primary)]">
-                    {formatDuration(workout.duration_seconds)}
+                    {formatDuration(showMovingTime ? workout.moving_seconds ?? workout.duration_seconds : workout.duration_seconds)}
                   </p>
-                  <p className="text-[10px] text-[var(--color-text-secondary)]">Duration</p>
+                  <p className="text-[10px] text-[var(--color-text-secondary)]">{showMovingTime ? 'Moving' : 'Duration'}</p>
                 </div>
                 {workout.avg_heart_rate && workout.avg_heart_rate > 0 && (
                   <div className="hidden md:block">
@@ -20,20 +20,31 @@ import {
   Map,
   BarChart3,
   FileText,
+  Download,
+  RefreshCw,
+  Thermometer,
+  Footprints,
+  Wind,
 } from 'lucide-react';
+import { save } from '@tauri-apps/plugin-dialog';
 import { useWorkoutStore } from '../stores/workoutStore';
 import { useToastStore } from '../stores/toastStore';
 import {
   formatDuration,
   formatDistance,
   formatSpeed,
+  formatPace,
   formatDateTime,
   capitalizeWorkoutType,
+  formatSport,
   getActivityColor,
 } from '../types';
 import WorkoutMap from './WorkoutMap';
 import WorkoutCharts from './WorkoutCharts';
-import HRZonesCard from './HRZonesCard';
+import ZonesCard from './ZonesCard';
+import HrvCard from './HrvCard';
+import SwimCard from './SwimCard';
+import DevicesCard from './DevicesCard';
 import SegmentAnalysis from './SegmentAnalysis';
 import ElevationProfile from './ElevationProfile';
 
@@ -47,6 +58,8 @@ export default function WorkoutModal() {
     renameWorkout,
     updateTags,
     updateNotes,
+    exportWorkout,
+    reprocessWorkout,
   } = useWorkoutStore();
   const addToast = useToastStore((state) => state.addToast);
 
@@ -97,6 +110,42 @@ export default function WorkoutModal() {
     }
   };
 
+  const handleExport = async () => {
+    try {
+      const baseName = (selectedWorkout.name || selectedWorkout.filename.replace(/\.[^.]+$/, ''))
+        .replace(/[\\/:*?"<>|]/g, '_');
+      const filePath = await save({
+        defaultPath: `${baseName}.gpx`,
+        filters: [
+          { name: 'GPX', extensions: ['gpx'] },
+          { name: 'TCX', extensions: ['tcx'] },
+          { name: 'FIT', extensions: ['fit'] },
+        ],
+      });
+      if (!filePath) return;
+
+      const format = filePath.split('.').pop()?.toLowerCase() || 'gpx';
+      const success = await exportWorkout(selectedWorkout.id, format, filePath);
+      if (success) {
+        addToast('Workout exported successfully', 'success');
+      } else {
+        addToast('Failed to export workout', 'error');
+      }
+    } catch (error) {
+      console.error('Failed to open save dialog:', error);
+      addToast('Failed to open save dialog', 'error');
+    }
+  };
+
+  const handleReprocess = async () => {
+    const success = await reprocessWorkout(selectedWorkout.id);
+    if (success) {
+      addToast('Workout reprocessed from the original file', 'success');
+    } else {
+      addToast('Failed to reprocess workout', 'error');
+    }
+  };
+
   const handleAddTag = async () => {
     if (newTag.trim() && !tags.includes(newTag.trim())) {
       const success = await updateTags(selectedWorkout.id, [...tags, newTag.trim()]);
@@ -139,7 +188,7 @@ export default function WorkoutModal() {
           <div className="flex items-center gap-3">
             <div
               className="w-3 h-8 rounded-full"
-              style={{ backgroundColor: getActivityColor(selectedWorkout.workout_type) }}
+              style={{ backgroundColor: getActivityColor(selectedWorkout.category ?? selectedWorkout.workout_type) }}
             />
             {isEditing ? (
               <div className="flex items-center gap-2">
@@ -171,6 +220,9 @@ export default function WorkoutModal() {
                 </h2>
                 <p className="text-sm text-[var(--color-text-secondary)]">
                   {formatDateTime(selectedWorkout.start_time)}
+                  {(selectedWorkout.name || selectedWorkout.sub_sport) && (
+                    <> · {formatSport(selectedWorkout.workout_type, selectedWorkout.sub_sport)}</>
+                  )}
                 </p>
               </div>
             )}
@@ -186,6 +238,20 @@ export default function WorkoutModal() {
             >
               <Edit2 className="w-4 h-4" />
             </button>
+            <button
+              onClick={handleReprocess}
+              className="cursor-pointer p-2 rounded-lg hover:bg-[var(--color-bg-secondary)] text-[var(--color-text-secondary)]"
+              title="Reprocess"
+            >
+              <RefreshCw className="w-4 h-4" />
+            </button>
+            <button
+              onClick={handleExport}
+              className="cursor-pointer p-2 rounded-lg hover:bg-[var(--color-bg-secondary)] text-[var(--color-text-secondary)]"
+              title="Export"
+            >
+              <Download className="w-4 h-4" />
+            </button>
             <button
               onClick={handleDelete}
               className="cursor-pointer p-2 rounded-lg hover:bg-red-500/10 text-red-500"
@@ -295,16 +361,30 @@ export default function WorkoutModal() {
               const hasMeaningfulDistance = !isStrengthBased && 
                 selectedWorkout.distance_meters && 
                 selectedWorkout.distance_meters > 100;
+
+              // Pace reads better than speed on foot
+              const isPaceBased = ['running', 'walking', 'hiking'].includes(workoutType);
+              const movingSpeed = selectedWorkout.avg_moving_speed_mps;
+              const w = selectedWorkout;
+              const leftRight = (left: number | null, right: number | null) =>
+                left !== null && right !== null ? `${Math.round(left)}% / ${Math.round(right)}%` : null;
               
               // Unified stats list with calculated values
               const displayMetrics = [
                 {
                   icon: Clock,
-                  label: 'Duration',
+                  label: 'Elapsed Time',
                   value: formatDuration(selectedWorkout.duration_seconds),
                   show: true,
                   color: 'text-[var(--color-text-primary)]',
                 },
+                {
+                  icon: Clock,
+                  label: 'Moving Time',
+                  value: selectedWorkout.moving_seconds ? formatDuration(selectedWorkout.moving_seconds) : null,
+                  show: !!selectedWorkout.moving_seconds && selectedWorkout.moving_seconds !== selectedWorkout.duration_seconds,
+                  color: 'text-[var(--color-text-primary)]',
+                },
                 {
                   icon: MapPin,
                   label: 'Distance',
@@ -326,6 +406,41 @@ export default function WorkoutModal() {
                   show: !!selectedWorkout.max_power_watts,
                   color: 'text-amber-600',
                 },
+                {
+                  icon: Zap,
+                  label: 'Normalized Power',
+                  value: selectedWorkout.normalized_power_watts ? `${Math.round(selectedWorkout.normalized_power_watts)} W` : null,
+                  show: !!selectedWorkout.normalized_power_watts,
+                  color: 'text-amber-500',
+                },
+                {
+                  icon: Zap,
+                  label: 'Intensity Factor',
+                  value: selectedWorkout.intensity_factor ? selectedWorkout.intensity_factor.toFixed(2) : null,
+                  show: !!selectedWorkout.intensity_factor,
+                  color: 'text-amber-500',
+                },
+                {
+                  icon: Activity,
+                  label: selectedWorkout.tss_source === 'heart_rate' ? 'TSS (HR)' : 'TSS',
+                  value: selectedWorkout.tss ? `${Math.round(selectedWorkout.tss)}` : null,
+                  show: !!selectedWorkout.tss,
+                  color: 'text-purple-500',
+                },
+                {
+                  icon: Zap,
+                  label: 'Variability Index',
+                  value: selectedWorkout.variability_index ? selectedWorkout.variability_index.toFixed(2) : null,
+                  show: !!selectedWorkout.variability_index,
+                  color: 'text-amber-600',
+                },
+                {
+                  icon: Flame,
+                  label: 'Work',
+                  value: selectedWorkout.work_kj ? `${Math.round(selectedWorkout.work_kj)} kJ` : null,
+                  show: !!selectedWorkout.work_kj,
+                  color: 'text-amber-600',
+                },
                 {
                   icon: Activity,
                   label: 'Avg Cadence',
@@ -354,6 +469,13 @@ export default function WorkoutModal() {
                   show: hasMeaningfulDistance && !!selectedWorkout.avg_speed_mps,
                   color: 'text-emerald-500',
                 },
+                {
+                  icon: MapPin,
+                  label: isPaceBased ? 'Moving Pace' : 'Moving Speed',
+                  value: movingSpeed ? (isPaceBased ? formatPace(movingSpeed) : formatSpeed(movingSpeed)) : null,
+                  show: hasMeaningfulDistance && !!movingSpeed,
+                  color: 'text-emerald-500',
+                },
                 {
                   icon: MapPin,
                   label: 'Max Speed',
@@ -375,6 +497,94 @@ export default function WorkoutModal() {
                   show: !!selectedWorkout.elevation_gain_meters,
                   color: 'text-indigo-500',
                 },
+                {
+                  icon: Thermometer,
+                  label: 'Avg Temperature',
+                  value: w.avg_temperature_c !== null ? `${w.avg_temperature_c.toFixed(1)} °C` : null,
+                  show: w.avg_temperature_c !== null,
+                  color: 'text-orange-400',
+                },
+                {
+                  icon: Footprints,
+                  label: 'Vertical Oscillation',
+                  value: w.avg_vertical_oscillation_mm !== null ? `${(w.avg_vertical_oscillation_mm / 10).toFixed(1)} cm` : null,
+                  show: w.avg_vertical_oscillation_mm !== null,
+                  color: 'text-violet-500',
+                },
+                {
+                  icon: Footprints,
+                  label: 'Ground Contact Time',
+                  value: w.avg_ground_contact_time_ms !== null ? `${Math.round(w.avg_ground_contact_time_ms)} ms` : null,
+                  show: w.avg_ground_contact_time_ms !== null,
+                  color: 'text-violet-500',
+                },
+                {
+                  icon: Footprints,
+                  label: 'GCT Balance',
+                  value: w.avg_ground_contact_balance !== null
+                    ? `${w.avg_ground_contact_balance.toFixed(1)}% L / ${(100 - w.avg_ground_contact_balance).toFixed(1)}% R`
+                    : null,
+                  show: w.avg_ground_contact_balance !== null,
+                  color: 'text-violet-500',
+                },
+                {
+                  icon: Footprints,
+                  label: 'Stride Length',
+                  value: w.avg_stride_length_meters !== null ? `${w.avg_stride_length_meters.toFixed(2)} m` : null,
+                  show: w.avg_stride_length_meters !== null,
+                  color: 'text-indigo-500',
+                },
+                {
+                  icon: Footprints,
+                  label: 'Vertical Ratio',
+                  value: w.avg_vertical_ratio !== null ? `${w.avg_vertical_ratio.toFixed(1)}%` : null,
+                  show: w.avg_vertical_ratio !== null,
+                  color: 'text-indigo-500',
+                },
+                {
+                  icon: Zap,
+                  label: 'L/R Balance',
+                  value: w.avg_left_right_balance !== null
+                    ? `${Math.round(w.avg_left_right_balance)}% / ${Math.round(100 - w.avg_left_right_balance)}%`
+                    : null,
+                  show: w.avg_left_right_balance !== null,
+                  color: 'text-amber-500',
+                },
+                {
+                  icon: Zap,
+                  label: 'Torque Effectiveness L/R',
+                  value: leftRight(w.avg_left_torque_effectiveness, w.avg_right_torque_effectiveness),
+                  show: true,
+                  color: 'text-amber-600',
+                },
+                {
+                  icon: Zap,
+                  label: 'Pedal Smoothness L/R',
+                  value: leftRight(w.avg_left_pedal_smoothness, w.avg_right_pedal_smoothness),
+                  show: true,
+                  color: 'text-lime-500',
+                },
+                {
+                  icon: Wind,
+                  label: 'Respiration Rate',
+                  value: w.avg_respiration_rate !== null ? `${w.avg_respiration_rate.toFixed(1)} brpm` : null,
+                  show: w.avg_respiration_rate !== null,
+                  color: 'text-cyan-500',
+                },
+                {
+                  icon: Thermometer,
+                  label: 'Core Temperature',
+                  value: w.avg_core_temperature_c !== null ? `${w.avg_core_temperature_c.toFixed(1)} °C` : null,
+                  show: w.avg_core_temperature_c !== null,
+                  color: 'text-red-500',
+                },
+                {
+                  icon: Heart,
+                  label: 'Avg SpO2',
+                  value: w.avg_spo2 !== null ? `${Math.round(w.avg_spo2)}%` : null,
+                  show: w.avg_spo2 !== null,
+                  color: 'text-sky-500',
+                },
               ].filter(m => m.show && m.value);
 
               return (
@@ -431,13 +641,19 @@ export default function WorkoutModal() {
                 workoutType={selectedWorkout.workout_type || 'generic'}
               />
               
-              {/* HR Zones for this workout */}
-              {chartData.heart_rate.some(hr => hr !== null) && (
-                <HRZonesCard />
+              {/* Time in HR, power and pace zones for this workout */}
+              {(chartData.heart_rate.some(hr => hr !== null) || chartData.power.some(p => p !== null) || chartData.speed.some(v => v !== null)) && (
+                <ZonesCard />
               )}
+
+              {/* R-R interval analysis, for straps that record beat-to-beat intervals */}
+              {selectedWorkout.hrv_rmssd_ms !== null && <HrvCard />}
             </div>
           )}
 
+          {/* Lengths, intervals and strokes of a pool swim */}
+          {selectedWorkout.pool_length_meters !== null && <SwimCard />}
+
           {/* Elevation Profile */}
           {gpsData && gpsData.length > 0 && gpsData.some(p => p.altitude !== null) && (
             <ElevationProfile 
@@ -455,6 +671,9 @@ export default function WorkoutModal() {
             />
           )}
 
+          {/* Recording device and connected sensors */}
+          <DevicesCard />
+
           {/* Tags */}
           <div className="card p-4">
             <button
@@ -587,7 +806,9 @@ export default function WorkoutModal() {
           {/* File Info */}
           <div className="text-xs text-[var(--color-text-secondary)] pt-4 border-t border-[var(--color-border)]">
             <p>File: {selectedWorkout.filename}</p>
-            <p>Hash: {selectedWorkout.file_hash.substring(0, 16)}...</p>
+            {selectedWorkout.file_hash && (
+              <p>Hash: {selectedWorkout.file_hash.substring(0, 16)}...</p>
+            )}
           </div>
         </div>
       </div>
@@ -0,0 +1,153 @@
+import { useEffect, useMemo, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { BarChart3 } from 'lucide-react';
+import { useWorkoutStore } from '../stores/workoutStore';
+import { useSettingsStore } from '../stores/settingsStore';
+import type { Period, Zone, ZoneType } from '../types';
+import {
+  ResponsiveContainer,
+  BarChart,
+  Bar,
+  XAxis,
+  YAxis,
+  Tooltip,
+} from 'recharts';
+
+const ZONE_TYPE_LABELS: Record<ZoneType, string> = {
+  heart_rate: 'Heart rate',
+  power: 'Power',
+  pace: 'Pace',
+};
+
+function formatHours(seconds: number): string {
+  return `${(seconds / 3600).toFixed(1)}h`;
+}
+
+export default function ZoneDistributionCard() {
+  const zoneDistribution = useWorkoutStore((state) => state.zoneDistribution);
+  const zoneType = useWorkoutStore((state) => state.zoneDistributionType);
+  const period = useWorkoutStore((state) => state.zoneDistributionPeriod);
+  const setView = useWorkoutStore((state) => state.setZoneDistributionView);
+  const fetchZoneDistribution = useWorkoutStore((state) => state.fetchZoneDistribution);
+  const hrZones = useSettingsStore((state) => state.hrZones);
+  const zonesVersion = useSettingsStore((state) => state.zonesVersion);
+  const [zones, setZones] = useState<Zone[]>([]);
+
+  // Changed zones or athlete settings recompute every workout's time in zone
+  useEffect(() => {
+    if (zonesVersion > 0) fetchZoneDistribution();
+  }, [zonesVersion, fetchZoneDistribution]);
+
+  useEffect(() => {
+    if (zoneType === 'heart_rate') {
+      setZones(hrZones);
+      return;
+    }
+    invoke<Zone[]>(zoneType === 'power' ? 'get_power_zones' : 'get_pace_zones')
+      .then(setZones)
+      .catch((error) => console.error('Failed to fetch zones:', error));
+  }, [zoneType, hrZones]);
+
+  const data = useMemo(() => zoneDistribution.map((d) => {
+    const row: Record<string, string | number> = { period: d.period };
+    d.seconds.forEach((seconds, i) => {
+      row[`z${i}`] = seconds;
+    });
+    return row;
+  }), [zoneDistribution]);
+
+  // Polarized view: the two lowest zones are easy, the third moderate and
+  // everything above it hard
+  const intensity = useMemo(() => {
+    const totals = [0, 0, 0];
+    for (const d of zoneDistribution) {
+      d.seconds.forEach((seconds, i) => {
+        totals[Math.min(i < 2 ? 0 : i - 1, 2)] += seconds;
+      });
+    }
+    const total = totals.reduce((sum, s) => sum + s, 0);
+    return total > 0 ? totals.map((s) => Math.round((s / total) * 100)) : null;
+  }, [zoneDistribution]);
+
+  const formatPeriod = (value: string) => (period === 'week' ? value.split('-')[1] : value.slice(2));
+
+  return (
+    <div className="card p-4 h-full flex flex-col">
+      <div className="flex items-center justify-between mb-3">
+        <div className="flex items-center gap-2">
+          <div className="w-8 h-8 rounded-lg bg-emerald-900/30 flex items-center justify-center">
+            <BarChart3 className="w-4 h-4 text-emerald-400" />
+          </div>
+          <h3 className="text-sm font-medium text-[var(--color-text-primary)]">Intensity Distribution</h3>
+        </div>
+        <div className="flex items-center gap-2">
+          <select
+            value={zoneType}
+            onChange={(e) => setView(e.target.value as ZoneType, period)}
+            className="px-2 py-1 rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-xs text-[var(--color-text-primary)]"
+          >
+            {(Object.keys(ZONE_TYPE_LABELS) as ZoneType[]).map((type) => (
+              <option key={type} value={type}>{ZONE_TYPE_LABELS[type]}</option>
+            ))}
+          </select>
+          <select
+            value={period}
+            onChange={(e) => setView(zoneType, e.target.value as Period)}
+            className="px-2 py-1 rounded-lg bg-[var(--color-bg-secondary)] border border-[var(--color-border)] text-xs text-[var(--color-text-primary)]"
+          >
+            <option value="week">Weekly</option>
+            <option value="month">Monthly</option>
+          </select>
+        </div>
+      </div>
+
+      {data.length === 0 ? (
+        <p className="text-xs text-[var(--color-text-secondary)] py-6 text-center">
+          No time in {ZONE_TYPE_LABELS[zoneType].toLowerCase()} zones yet
+        </p>
+      ) : (
+        <>
+          <div className="w-full h-[180px]">
+            <ResponsiveContainer width="100%" height="100%">
+              <BarChart data={data}>
+                <XAxis
+                  dataKey="period"
+                  tickFormatter={formatPeriod}
+                  tick={{ fontSize: 10, fill: 'var(--color-text-secondary)' }}
+                  axisLine={false}
+                  tickLine={false}
+                />
+                <YAxis
+                  tickFormatter={formatHours}
+                  width={40}
+                  tick={{ fontSize: 10, fill: 'var(--color-text-secondary)' }}
+                  axisLine={false}
+                  tickLine={false}
+                />
+                <Tooltip
+                  contentStyle={{
+                    backgroundColor: 'var(--color-bg-card)',
+                    border: '1px solid var(--color-border)',
+                    borderRadius: '8px',
+                    fontSize: '12px',
+                  }}
+                  formatter={(value) => formatHours(Number(value))}
+                />
+                {zones.map((zone, i) => (
+                  <Bar key={i} dataKey={`z${i}`} name={zone.name} stackId="zones" fill={zone.color} />
+                ))}
+              </BarChart>
+            </ResponsiveContainer>
+          </div>
+          {intensity && (
+            <div className="flex justify-between text-xs text-[var(--color-text-secondary)] pt-2 border-t border-[var(--color-border)] mt-2">
+              <span>Easy <span className="text-[var(--color-text-primary)] font-medium">{intensity[0]}%</span></span>
+              <span>Moderate <span className="text-[var(--color-text-primary)] font-medium">{intensity[1]}%</span></span>
+              <span>Hard <span className="text-[var(--color-text-primary)] font-medium">{intensity[2]}%</span></span>
+            </div>
+          )}
+        </>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,184 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Heart, Zap, Footprints } from 'lucide-react';
+import { useWorkoutStore } from '../stores/workoutStore';
+import { useSettingsStore } from '../stores/settingsStore';
+import type { HRZoneTime, ZoneTime, ZoneType } from '../types';
+
+function formatTime(seconds: number): string {
+  if (seconds < 60) return `${seconds}s`;
+  const hours = Math.floor(seconds / 3600);
+  const minutes = Math.floor((seconds % 3600) / 60);
+  if (hours > 0) {
+    return `${hours}h ${minutes}m`;
+  }
+  return `${minutes}m`;
+}
+
+function formatPace(mps: number): string {
+  if (!mps || !isFinite(mps)) return '--:--';
+  const secondsPerKm = 1000 / mps;
+  const minutes = Math.floor(secondsPerKm / 60);
+  const seconds = Math.round(secondsPerKm % 60);
+  return `${minutes}:${seconds.toString().padStart(2, '0')}`;
+}
+
+const ZONE_TYPES: { type: ZoneType; label: string }[] = [
+  { type: 'heart_rate', label: 'HR' },
+  { type: 'power', label: 'Power' },
+  { type: 'pace', label: 'Pace' },
+];
+
+// Pace limits are speeds; the faster end of a zone is its upper limit
+function formatLimits(type: ZoneType, zone: ZoneTime): string {
+  if (type === 'pace') return `${formatPace(zone.high)}-${formatPace(zone.low)} /km`;
+  const unit = type === 'power' ? 'W' : 'bpm';
+  return `${Math.round(zone.low)}-${Math.round(zone.high)} ${unit}`;
+}
+
+export default function ZonesCard() {
+  const selectedWorkout = useWorkoutStore((state) => state.selectedWorkout);
+  const zonesVersion = useSettingsStore((state) => state.zonesVersion);
+  const openSettings = useSettingsStore((state) => state.openSettings);
+  const [zonesByType, setZonesByType] = useState<Partial<Record<ZoneType, ZoneTime[]>>>({});
+  const [zoneType, setZoneType] = useState<ZoneType>('heart_rate');
+
+  // Time in zone is computed from the samples when a workout is stored
+  useEffect(() => {
+    if (!selectedWorkout) {
+      setZonesByType({});
+      return;
+    }
+    let cancelled = false;
+    Promise.all(ZONE_TYPES.map(({ type }) =>
+      invoke<ZoneTime[]>('get_workout_zones', { id: selectedWorkout.id, zoneType: type })
+        .then((zones) => [type, zones] as const)
+    ))
+      .then((results) => {
+        if (cancelled) return;
+        const recorded = results.filter(([, zones]) => zones.some((z) => z.seconds > 0));
+        setZonesByType(Object.fromEntries(recorded));
+        if (recorded.length > 0) {
+          setZoneType((current) => (recorded.some(([type]) => type === current) ? current : recorded[0][0]));
+        }
+      })
+      .catch((error) => {
+        console.error('Failed to fetch zones:', error);
+        if (!cancelled) setZonesByType({});
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [selectedWorkout, zonesVersion]);
+
+  const zones = zonesByType[zoneType] ?? [];
+  const total = zones.reduce((sum, z) => sum + z.seconds, 0);
+  const zoneData: HRZoneTime[] = zones.map((zone) => ({
+    zoneName: zone.name,
+    zoneIndex: zone.zone_index + 1,
+    timeSeconds: Math.round(zone.seconds),
+    percentage: total > 0 ? (zone.seconds / total) * 100 : 0,
+    color: zone.color,
+  }));
+  const availableTypes = ZONE_TYPES.filter(({ type }) => zonesByType[type]);
+  const totalTime = zoneData.reduce((sum, z) => sum + z.timeSeconds, 0);
+  const Icon = zoneType === 'power' ? Zap : zoneType === 'pace' ? Footprints : Heart;
+
+  const header = (
+    <div className="flex items-center justify-between mb-3">
+      <div className="flex items-center gap-2">
+        <div className="w-8 h-8 rounded-lg bg-red-900/30 flex items-center justify-center">
+          <Icon className="w-4 h-4 text-red-400" />
+        </div>
+        <h3 className="text-sm font-medium text-[var(--color-text-primary)]">Zones</h3>
+        {availableTypes.length > 1 && (
+          <div className="flex gap-1 ml-2">
+            {availableTypes.map(({ type, label }) => (
+              <button
+                key={type}
+                onClick={() => setZoneType(type)}
+                className={`px-2 py-0.5 rounded text-xs transition-colors ${
+                  zoneType === type
+                    ? 'bg-[var(--color-accent)] text-white'
+                    : 'text-[var(--color-text-secondary)] hover:text-[var(--color-text-primary)]'
+                }`}
+              >
+                {label}
+              </button>
+            ))}
+          </div>
+        )}
+      </div>
+      <button
+        onClick={openSettings}
+        className="text-xs text-[var(--color-accent)] hover:underline"
+      >
+        Configure
+      </button>
+    </div>
+  );
+
+  if (!selectedWorkout || zoneData.length === 0 || totalTime === 0) {
+    return (
+      <div className="card p-4">
+        {header}
+        <p className="text-xs text-[var(--color-text-secondary)]">
+          Set your max HR, FTP or threshold pace to see time in zones for this workout.
+        </p>
+      </div>
+    );
+  }
+
+  return (
+    <div className="card p-4">
+      {header}
+
+      <div className="space-y-2">
+        {/* Stacked bar visualization */}
+        <div className="h-4 rounded-full overflow-hidden flex bg-[var(--color-bg-secondary)]">
+          {zoneData.map((zone, idx) => (
+            zone.percentage > 0 && (
+              <div
+                key={idx}
+                className="h-full transition-all"
+                style={{
+                  width: `${zone.percentage}%`,
+                  backgroundColor: zone.color,
+                }}
+                title={`${zone.zoneName}: ${formatTime(zone.timeSeconds)} (${zone.percentage.toFixed(1)}%)`}
+              />
+            )
+          ))}
+        </div>
+
+        {/* Zone breakdown */}
+        <div className="space-y-1.5 mt-3">
+          {zoneData.map((zone, idx) => (
+            <div key={idx} className="flex items-center gap-2 text-xs">
+              <div
+                className="w-3 h-3 rounded-sm flex-shrink-0"
+                style={{ backgroundColor: zone.color }}
+              />
+              <span className="text-[var(--color-text-secondary)] flex-1 truncate">
+                Z{zone.zoneIndex}
+                <span className="ml-2 opacity-70">{formatLimits(zoneType, zones[idx])}</span>
+              </span>
+              <span className="text-[var(--color-text-primary)] font-medium">
+                {formatTime(zone.timeSeconds)}
+              </span>
+              <span className="text-[var(--color-text-secondary)] w-10 text-right">
+                {zone.percentage.toFixed(0)}%
+              </span>
+            </div>
+          ))}
+        </div>
+
+        <div className="pt-2 border-t border-[var(--color-border)] mt-2">
+          <p className="text-xs text-[var(--color-text-secondary)]">
+            Total: {formatTime(totalTime)}
+          </p>
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -1,10 +1,12 @@
 import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
+import { invoke } from '@tauri-apps/api/core';
+import type { AthleteProfile, HrZoneMethod, HrZoneSettings, Zone, ZoneType } from '../types';
 
 export interface HRZone {
   name: string;
-  min: number; // percentage of max HR
-  max: number; // percentage of max HR
+  min: number; // percentage of max HR, heart rate reserve or LTHR
+  max: number; // percentage of max HR, heart rate reserve or LTHR
   color: string;
 }
 
@@ -18,57 +20,233 @@ export const DEFAULT_HR_ZONES: HRZone[] = [
 ];
 
 interface SettingsState {
-  // User profile
+  // User profile, as in effect today. The database keeps its history.
+  weightKg: number | null;
+  heightCm: number | null;
+  sex: 'male' | 'female' | null;
+  birthDate: string | null; // YYYY-MM-DD
   maxHeartRate: number | null;
   restingHeartRate: number | null;
-  age: number | null;
+  ftpWatts: number | null;
+  thresholdHeartRate: number | null;
+  thresholdPace: number | null; // seconds per km
+  profileHistory: AthleteProfile[];
   
   // HR Zones
+  hrZoneMethod: HrZoneMethod;
   hrZones: HRZone[];
   useCustomZones: boolean;
+  // Power and pace zones, in percent of FTP and threshold speed. Only the
+  // database keeps them.
+  powerZones: Zone[];
+  paceZones: Zone[];
+  // Bumped whenever the backend has recomputed time in zone
+  zonesVersion: number;
   
   // Settings modal
   isSettingsOpen: boolean;
   
   // Actions
-  setMaxHeartRate: (hr: number | null) => void;
-  setRestingHeartRate: (hr: number | null) => void;
-  setAge: (age: number | null) => void;
+  setProfile: (profile: Partial<Omit<AthleteProfile, 'effective_date'>>) => void;
+  setHrZoneMethod: (method: HrZoneMethod) => void;
   setHRZones: (zones: HRZone[]) => void;
   setUseCustomZones: (use: boolean) => void;
   resetZonesToDefault: () => void;
   openSettings: () => void;
   closeSettings: () => void;
+  loadAthleteProfile: () => Promise<void>;
+  saveAthleteProfile: (effectiveDate: string) => Promise<void>;
+  deleteProfileEntry: (effectiveDate: string) => Promise<void>;
+  syncHrZones: () => Promise<void>;
+  loadHrZones: () => Promise<void>;
+  loadPercentZones: () => Promise<void>;
+  savePercentZones: (zoneType: Exclude<ZoneType, 'heart_rate'>, zones: Zone[]) => Promise<void>;
   
   // Computed
+  getAge: () => number | null;
   getEstimatedMaxHR: () => number;
   getZoneBoundaries: () => { zone: HRZone; minBpm: number; maxBpm: number }[];
 }
 
+export function todayString(): string {
+  const now = new Date();
+  return `${now.getFullYear()}-${String(now.getMonth() + 1).padStart(2, '0')}-${String(now.getDate()).padStart(2, '0')}`;
+}
+
 export const useSettingsStore = create<SettingsState>()(
   persist(
     (set, get) => ({
       // Initial state
+      weightKg: null,
+      heightCm: null,
+      sex: null,
+      birthDate: null,
       maxHeartRate: null,
       restingHeartRate: null,
-      age: null,
+      ftpWatts: null,
+      thresholdHeartRate: null,
+      thresholdPace: null,
+      profileHistory: [],
+      hrZoneMethod: 'max_heart_rate',
       hrZones: DEFAULT_HR_ZONES,
       useCustomZones: false,
+      powerZones: [],
+      paceZones: [],
+      zonesVersion: 0,
       isSettingsOpen: false,
 
       // Actions
-      setMaxHeartRate: (hr) => set({ maxHeartRate: hr }),
-      setRestingHeartRate: (hr) => set({ restingHeartRate: hr }),
-      setAge: (age) => set({ age }),
+      setProfile: (profile) => set({
+        ...('weight_kg' in profile && { weightKg: profile.weight_kg ?? null }),
+        ...('height_cm' in profile && { heightCm: profile.height_cm ?? null }),
+        ...('sex' in profile && { sex: profile.sex ?? null }),
+        ...('birth_date' in profile && { birthDate: profile.birth_date ?? null }),
+        ...('max_heart_rate' in profile && { maxHeartRate: profile.max_heart_rate ?? null }),
+        ...('resting_heart_rate' in profile && { restingHeartRate: profile.resting_heart_rate ?? null }),
+        ...('ftp_watts' in profile && { ftpWatts: profile.ftp_watts ?? null }),
+        ...('threshold_heart_rate' in profile && { thresholdHeartRate: profile.threshold_heart_rate ?? null }),
+        ...('threshold_pace_seconds_per_km' in profile && { thresholdPace: profile.threshold_pace_seconds_per_km ?? null }),
+      }),
+      setHrZoneMethod: (method) => set({ hrZoneMethod: method }),
       setHRZones: (zones) => set({ hrZones: zones }),
       setUseCustomZones: (use) => set({ useCustomZones: use }),
       resetZonesToDefault: () => set({ hrZones: DEFAULT_HR_ZONES, useCustomZones: false }),
       openSettings: () => set({ isSettingsOpen: true }),
       closeSettings: () => set({ isSettingsOpen: false }),
 
+      // The profile lives in the database, which computes TSS, training load
+      // and time in zone against the entry in effect on each workout's day
+      loadAthleteProfile: async () => {
+        try {
+          const profileHistory = await invoke<AthleteProfile[]>('get_athlete_profile_history');
+          set({ profileHistory });
+          if (profileHistory.length === 0) {
+            // Values only older versions kept in local storage start the history
+            const { birthDate, maxHeartRate, restingHeartRate, ftpWatts, thresholdHeartRate, thresholdPace } = get();
+            if (birthDate || maxHeartRate || restingHeartRate || ftpWatts || thresholdHeartRate || thresholdPace) {
+              await get().saveAthleteProfile(todayString());
+            }
+            return;
+          }
+          const current = await invoke<AthleteProfile | null>('get_athlete_profile', { date: null });
+          if (current) {
+            get().setProfile(current);
+          }
+        } catch (error) {
+          console.error('Failed to load athlete profile:', error);
+        }
+      },
+
+      // Stores the current values as the entry starting on `effectiveDate`;
+      // the backend then recomputes every workout's TSS and zones
+      saveAthleteProfile: async (effectiveDate) => {
+        const state = get();
+        const profile: AthleteProfile = {
+          effective_date: effectiveDate,
+          weight_kg: state.weightKg,
+          height_cm: state.heightCm,
+          sex: state.sex,
+          birth_date: state.birthDate,
+          max_heart_rate: state.maxHeartRate,
+          resting_heart_rate: state.restingHeartRate,
+          threshold_heart_rate: state.thresholdHeartRate,
+          ftp_watts: state.ftpWatts,
+          threshold_pace_seconds_per_km: state.thresholdPace,
+        };
+        try {
+          const saved = await invoke<boolean>('update_athlete_profile', { profile });
+          if (saved) set({ zonesVersion: get().zonesVersion + 1 });
+          await get().loadAthleteProfile();
+        } catch (error) {
+          console.error('Failed to save athlete profile:', error);
+        }
+      },
+
+      deleteProfileEntry: async (effectiveDate) => {
+        try {
+          const deleted = await invoke<boolean>('delete_athlete_profile', { effectiveDate });
+          if (deleted) set({ zonesVersion: get().zonesVersion + 1 });
+          await get().loadAthleteProfile();
+        } catch (error) {
+          console.error('Failed to delete athlete profile entry:', error);
+        }
+      },
+
+      // Time in zone is stored per workout by the backend, which recomputes
+      // it when the zone definitions change
+      syncHrZones: async () => {
+        const { hrZoneMethod, hrZones, useCustomZones } = get();
+        const zones: HrZoneSettings = {
+          method: hrZoneMethod,
+          zones: useCustomZones ? hrZones : DEFAULT_HR_ZONES,
+        };
+        try {
+          const saved = await invoke<boolean>('update_hr_zones', { zones });
+          if (saved) set({ zonesVersion: get().zonesVersion + 1 });
+        } catch (error) {
+          console.error('Failed to save HR zones:', error);
+        }
+      },
+
+      // The database holds the zones once they have been saved; before that
+      // the ones kept in local storage are written to it
+      loadHrZones: async () => {
+        try {
+          const stored = await invoke<HrZoneSettings | null>('get_hr_zones');
+          if (!stored) {
+            await get().syncHrZones();
+            return;
+          }
+          set({
+            hrZoneMethod: stored.method,
+            hrZones: stored.zones,
+            useCustomZones: JSON.stringify(stored.zones) !== JSON.stringify(DEFAULT_HR_ZONES),
+          });
+        } catch (error) {
+          console.error('Failed to load HR zones:', error);
+        }
+      },
+
+      loadPercentZones: async () => {
+        try {
+          const [powerZones, paceZones] = await Promise.all([
+            invoke<Zone[]>('get_power_zones'),
+            invoke<Zone[]>('get_pace_zones'),
+          ]);
+          set({ powerZones, paceZones });
+        } catch (error) {
+          console.error('Failed to load power and pace zones:', error);
+        }
+      },
+
+      savePercentZones: async (zoneType, zones) => {
+        try {
+          const saved = await invoke<boolean>(zoneType === 'power' ? 'update_power_zones' : 'update_pace_zones', { zones });
+          if (saved) {
+            set({
+              ...(zoneType === 'power' ? { powerZones: zones } : { paceZones: zones }),
+              zonesVersion: get().zonesVersion + 1,
+            });
+          }
+        } catch (error) {
+          console.error(`Failed to save ${zoneType} zones:`, error);
+        }
+      },
+
       // Computed functions
+      getAge: () => {
+        const { birthDate } = get();
+        if (!birthDate) return null;
+        const birth = new Date(birthDate);
+        const now = new Date();
+        const hadBirthday = now.getMonth() > birth.getMonth()
+          || (now.getMonth() === birth.getMonth() && now.getDate() >= birth.getDate());
+        return now.getFullYear() - birth.getFullYear() - (hadBirthday ? 0 : 1);
+      },
+
       getEstimatedMaxHR: () => {
-        const { maxHeartRate, age } = get();
+        const { maxHeartRate, getAge } = get();
+        const age = getAge();
         // If user has set max HR, use it
         if (maxHeartRate) return maxHeartRate;
         // If age is set, estimate using formula (220 - age)
@@ -78,22 +256,41 @@ export const useSettingsStore = create<SettingsState>()(
       },
 
       getZoneBoundaries: () => {
+        const { hrZones: zones, hrZoneMethod, restingHeartRate, thresholdHeartRate } = get();
         const maxHR = get().getEstimatedMaxHR();
-        const zones = get().hrZones;
-        
+
+        // Karvonen: resting HR plus a share of the heart rate reserve
+        let base = 0;
+        let range = maxHR;
+        if (hrZoneMethod === 'heart_rate_reserve') {
+          base = restingHeartRate ?? 60;
+          range = maxHR - base;
+        } else if (hrZoneMethod === 'lactate_threshold') {
+          range = thresholdHeartRate ?? maxHR;
+        }
+
         return zones.map((zone) => ({
           zone,
-          minBpm: Math.round((zone.min / 100) * maxHR),
-          maxBpm: Math.round((zone.max / 100) * maxHR),
+          minBpm: Math.round(base + (zone.min / 100) * range),
+          maxBpm: Math.round(base + (zone.max / 100) * range),
         }));
       },
     }),
     {
       name: 'settings-storage',
+      version: 1,
+      // Older versions kept an age rather than a birth date. As in the
+      // database migration, it's approximated by the start of the birth year.
+      migrate: (persisted, version) => {
+        const { age, ...state } = persisted as Partial<SettingsState> & { age?: number | null };
+        if (version === 0 && age && !state.birthDate) {
+          state.birthDate = `${new Date().getFullYear() - age}-01-01`;
+        }
+        return state as SettingsState;
+      },
+      // The athlete profile is loaded from the database instead
       partialize: (state) => ({
-        maxHeartRate: state.maxHeartRate,
-        restingHeartRate: state.restingHeartRate,
-        age: state.age,
+        hrZoneMethod: state.hrZoneMethod,
         hrZones: state.hrZones,
         useCustomZones: state.useCustomZones,
       }),
@@ -120,45 +317,6 @@ export function calculateTRIMP(
   return Math.round(trimp);
 }
 
-/**
- * Calculate Training Stress Score (TSS) approximation
- * Simplified version based on duration and intensity
- */
-export function calculateTSS(
-  durationMinutes: number,
-  avgHR: number,
-  maxHR: number,
-  restingHR: number = 60
-): number {
-  // Intensity Factor (IF) based on heart rate
-  const hrReserve = (avgHR - restingHR) / (maxHR - restingHR);
-  const intensityFactor = Math.min(hrReserve, 1);
-  
-  // TSS = (duration in hours * IF^2) * 100
-  const durationHours = durationMinutes / 60;
-  const tss = durationHours * Math.pow(intensityFactor, 2) * 100;
-  
-  return Math.round(tss);
-}
-
-/**
- * Calculate Acute Training Load (ATL) - 7 day rolling average
- */
-export function calculateATL(recentTSSValues: number[]): number {
-  if (recentTSSValues.length === 0) return 0;
-  const last7Days = recentTSSValues.slice(-7);
-  return Math.round(last7Days.reduce((sum, tss) => sum + tss, 0) / 7);
-}
-
-/**
- * Calculate Chronic Training Load (CTL) - 42 day rolling average
- */
-export function calculateCTL(tssValues: number[]): number {
-  if (tssValues.length === 0) return 0;
-  const last42Days = tssValues.slice(-42);
-  return Math.round(last42Days.reduce((sum, tss) => sum + tss, 0) / Math.min(42, last42Days.length));
-}
-
 /**
  * Calculate Training Status Balance (TSB) = CTL - ATL
  * Positive = Fresh, Negative = Fatigued
@@ -7,14 +7,29 @@ import type {
   Stats,
   MonthlyStats,
   StreakInfo,
-  PersonalRecords,
+  PersonalRecord,
   ContributionDay,
   WeeklySummary,
   ActivityBreakdown,
   ChartData,
   GpsPoint,
   UploadResult,
+  ReprocessSummary,
+  Tag,
+  TrainingLoadDay,
+  PowerCurves,
+  BestEffortRecord,
+  Period,
+  ZoneDistribution,
+  ZoneType,
+  DurationBasis,
+  WorkoutSortKey,
+  SortOrder,
+  DeviceSummary,
+  SportCategory,
+  HrvSummary,
 } from '../types';
+import { GENERIC_SUB_SPORT } from '../types';
 
 interface WorkoutState {
   // Data
@@ -25,19 +40,30 @@ interface WorkoutState {
   stats: Stats | null;
   monthlyStats: MonthlyStats | null;
   streakInfo: StreakInfo | null;
-  personalRecords: PersonalRecords | null;
+  personalRecords: PersonalRecord[];
   contributionCalendar: ContributionDay[];
   weeklySummary: WeeklySummary[];
   activityBreakdown: ActivityBreakdown[];
-  allTags: string[];
+  allTags: Tag[];
+  devices: DeviceSummary[];
+  sportCategories: SportCategory[];
+  trainingLoad: TrainingLoadDay[];
+  powerCurve: PowerCurves | null;
+  bestEfforts: BestEffortRecord[];
+  zoneDistribution: ZoneDistribution[];
+  zoneDistributionType: ZoneType;
+  zoneDistributionPeriod: Period;
+  hrvHistory: HrvSummary[];
   
   // Pagination
   currentPage: number;
   totalWorkouts: number;
   perPage: number;
   
-  // Filters
+  // Filters. A sport (workout type) and sub-sport narrow down a category.
+  categoryFilter: string | null;
   workoutTypeFilter: string | null;
+  subSportFilter: string | null;
   tagFilter: string | null;
   searchQuery: string;
   dateRangeStart: string | null;
@@ -46,6 +72,12 @@ interface WorkoutState {
   maxDistance: number | null;
   minDuration: number | null;
   maxDuration: number | null;
+  durationBasis: DurationBasis;
+  deviceFilter: string | null;
+  
+  // Sorting
+  sortBy: WorkoutSortKey;
+  sortOrder: SortOrder;
   
   // Loading states
   isLoading: boolean;
@@ -67,21 +99,40 @@ interface WorkoutState {
   fetchWeeklySummary: () => Promise<void>;
   fetchActivityBreakdown: () => Promise<void>;
   fetchAllTags: () => Promise<void>;
+  fetchDevices: () => Promise<void>;
+  fetchSportCategories: () => Promise<void>;
+  updateSportCategories: (categories: SportCategory[]) => Promise<boolean>;
+  fetchTrainingLoad: () => Promise<void>;
+  fetchPowerCurve: () => Promise<void>;
+  fetchBestEfforts: () => Promise<void>;
+  fetchZoneDistribution: () => Promise<void>;
+  setZoneDistributionView: (zoneType: ZoneType, period: Period) => void;
+  fetchHrvHistory: () => Promise<void>;
   fetchDashboardData: () => Promise<void>;
   
   uploadFiles: (paths: string[]) => Promise<UploadResult[]>;
   deleteWorkout: (id: number) => Promise<boolean>;
   renameWorkout: (id: number, name: string) => Promise<boolean>;
   updateTags: (id: number, tags: string[]) => Promise<boolean>;
+  setTagColor: (name: string, color: string | null) => Promise<boolean>;
+  renameTag: (oldName: string, newName: string) => Promise<boolean>;
+  mergeTags: (sources: string[], target: string) => Promise<boolean>;
+  deleteTag: (name: string) => Promise<boolean>;
   updateNotes: (id: number, notes: string) => Promise<boolean>;
+  exportWorkout: (id: number, format: string, filePath: string) => Promise<boolean>;
+  reprocessWorkout: (id: number) => Promise<boolean>;
+  reprocessAll: () => Promise<ReprocessSummary | null>;
   
   setPage: (page: number) => void;
-  setWorkoutTypeFilter: (type: string | null) => void;
+  setCategoryFilter: (category: string | null) => void;
+  setSportFilter: (sport: string | null, subSport: string | null) => void;
   setTagFilter: (tag: string | null) => void;
   setSearchQuery: (query: string) => void;
   setDateRange: (start: string | null, end: string | null) => void;
   setDistanceFilter: (min: number | null, max: number | null) => void;
-  setDurationFilter: (min: number | null, max: number | null) => void;
+  setDurationFilter: (min: number | null, max: number | null, basis: DurationBasis) => void;
+  setDeviceFilter: (device: string | null) => void;
+  setSort: (sortBy: WorkoutSortKey, sortOrder: SortOrder) => void;
   clearAllFilters: () => void;
   openModal: (workout: Workout) => void;
   closeModal: () => void;
@@ -96,15 +147,26 @@ export const useWorkoutStore = create<WorkoutState>((set, get) => ({
   stats: null,
   monthlyStats: null,
   streakInfo: null,
-  personalRecords: null,
+  personalRecords: [],
   contributionCalendar: [],
   weeklySummary: [],
   activityBreakdown: [],
   allTags: [],
+  devices: [],
+  sportCategories: [],
+  trainingLoad: [],
+  powerCurve: null,
+  bestEfforts: [],
+  zoneDistribution: [],
+  zoneDistributionType: 'heart_rate',
+  zoneDistributionPeriod: 'week',
+  hrvHistory: [],
   currentPage: 1,
   totalWorkouts: 0,
   perPage: 15,
+  categoryFilter: null,
   workoutTypeFilter: null,
+  subSportFilter: null,
   tagFilter: null,
   searchQuery: '',
   dateRangeStart: null,
@@ -113,12 +175,18 @@ export const useWorkoutStore = create<WorkoutState>((set, get) => ({
   maxDistance: null,
   minDuration: null,
   maxDuration: null,
+  durationBasis: 'elapsed',
+  deviceFilter: null,
+  sortBy: 'date',
+  sortOrder: 'desc',
   isLoading: false,
   isUploading: false,
   isModalOpen: false,
 
   fetchWorkouts: async () => {
-    const { currentPage, perPage, workoutTypeFilter, tagFilter, searchQuery, dateRangeStart, dateRangeEnd, minDistance, maxDistance, minDuration, maxDuration } = get();
+    const { currentPage, perPage, categoryFilter, workoutTypeFilter, subSportFilter, tagFilter, searchQuery, dateRangeStart, dateRangeEnd, minDistance, maxDistance, minDuration, maxDuration, durationBasis, deviceFilter, sortBy, sortOrder } = get();
+    // Newest first is the default; search results are then ranked by relevance
+    const isDefaultSort = sortBy === 'date' && sortOrder === 'desc';
     set({ isLoading: true });
     try {
       const response = await invoke<WorkoutsResponse>('get_workouts', {
@@ -133,6 +201,13 @@ export const useWorkoutStore = create<WorkoutState>((set, get) => ({
         maxDistance: maxDistance ? maxDistance * 1000 : null,
         minDuration: minDuration ? minDuration * 60 : null, // Convert minutes to seconds
         maxDuration: maxDuration ? maxDuration * 60 : null,
+        durationBasis,
+        device: deviceFilter,
+        category: categoryFilter,
+        // A sport's chip without a sub-sport lists only its generic workouts
+        subSport: workoutTypeFilter !== null ? subSportFilter ?? GENERIC_SUB_SPORT : null,
+        sortBy: isDefaultSort ? null : sortBy,
+        sortOrder: isDefaultSort ? null : sortOrder,
       });
       set({
         workouts: response.workouts,
@@ -201,7 +276,7 @@ export const useWorkoutStore = create<WorkoutState>((set, get) => ({
 
   fetchPersonalRecords: async () => {
     try {
-      const records = await invoke<PersonalRecords>('get_personal_records');
+      const records = await invoke<PersonalRecord[]>('get_personal_records', { sport: null });
       set({ personalRecords: records });
     } catch (error) {
       console.error('Failed to fetch personal records:', error);
@@ -237,15 +312,126 @@ export const useWorkoutStore = create<WorkoutState>((set, get) => ({
 
   fetchAllTags: async () => {
     try {
-      const tags = await invoke<string[]>('get_all_tags');
+      const tags = await invoke<Tag[]>('get_all_tags');
       set({ allTags: tags });
     } catch (error) {
       console.error('Failed to fetch tags:', error);
     }
   },
 
+  fetchDevices: async () => {
+    try {
+      const devices = await invoke<DeviceSummary[]>('get_devices');
+      set({ devices });
+    } catch (error) {
+      console.error('Failed to fetch devices:', error);
+    }
+  },
+
+  fetchSportCategories: async () => {
+    try {
+      const sportCategories = await invoke<SportCategory[]>('get_sport_categories');
+      set({ sportCategories });
+    } catch (error) {
+      console.error('Failed to fetch sport categories:', error);
+    }
+  },
+
+  // The backend recategorises every workout when the mapping changes
+  updateSportCategories: async (categories: SportCategory[]) => {
+    try {
+      const success = await invoke<boolean>('update_sport_categories', { categories });
+      if (success) {
+        const { selectedWorkout } = get();
+        set({ sportCategories: categories, categoryFilter: null, workoutTypeFilter: null, subSportFilter: null });
+        if (selectedWorkout) {
+          get().fetchWorkout(selectedWorkout.id);
+        }
+        get().fetchActivityBreakdown();
+        get().fetchContributionCalendar();
+        get().fetchWorkouts();
+      }
+      return success;
+    } catch (error) {
+      console.error('Failed to update sport categories:', error);
+      return false;
+    }
+  },
+
+  fetchTrainingLoad: async () => {
+    try {
+      // Six weeks covers the dashboard's week-over-week comparison
+      const start = new Date();
+      start.setDate(start.getDate() - 41);
+      const trainingLoad = await invoke<TrainingLoadDay[]>('get_training_load', {
+        range: { start: start.toISOString().split('T')[0], end: null },
+      });
+      set({ trainingLoad });
+    } catch (error) {
+      console.error('Failed to fetch training load:', error);
+    }
+  },
+
+  fetchPowerCurve: async () => {
+    try {
+      const powerCurve = await invoke<PowerCurves>('get_power_curve', { sport: null, range: null });
+      set({ powerCurve });
+    } catch (error) {
+      console.error('Failed to fetch power curve:', error);
+    }
+  },
+
+  fetchBestEfforts: async () => {
+    try {
+      const bestEfforts = await invoke<BestEffortRecord[]>('get_best_efforts', { sport: null });
+      set({ bestEfforts });
+    } catch (error) {
+      console.error('Failed to fetch best efforts:', error);
+    }
+  },
+
+  fetchZoneDistribution: async () => {
+    const { zoneDistributionType, zoneDistributionPeriod } = get();
+    try {
+      // Twelve weeks or twelve months back
+      const start = new Date();
+      if (zoneDistributionPeriod === 'week') {
+        start.setDate(start.getDate() - 7 * 12);
+      } else {
+        start.setMonth(start.getMonth() - 12);
+      }
+      const zoneDistribution = await invoke<ZoneDistribution[]>('get_zone_distribution', {
+        zoneType: zoneDistributionType,
+        period: zoneDistributionPeriod,
+        range: { start: start.toISOString().split('T')[0], end: null },
+      });
+      set({ zoneDistribution });
+    } catch (error) {
+      console.error('Failed to fetch zone distribution:', error);
+    }
+  },
+
+  setZoneDistributionView: (zoneType, period) => {
+    set({ zoneDistributionType: zoneType, zoneDistributionPeriod: period });
+    get().fetchZoneDistribution();
+  },
+
+  fetchHrvHistory: async () => {
+    try {
+      // Six months of workouts recorded with a chest strap
+      const start = new Date();
+      start.setMonth(start.getMonth() - 6);
+      const hrvHistory = await invoke<HrvSummary[]>('get_hrv_history', {
+        range: { start: start.toISOString().split('T')[0], end: null },
+      });
+      set({ hrvHistory });
+    } catch (error) {
+      console.error('Failed to fetch HRV history:', error);
+    }
+  },
+
   fetchDashboardData: async () => {
-    const { fetchStats, fetchMonthlyStats, fetchStreakInfo, fetchPersonalRecords, fetchContributionCalendar, fetchWeeklySummary, fetchActivityBreakdown, fetchWorkouts, fetchAllTags } = get();
+    const { fetchStats, fetchMonthlyStats, fetchStreakInfo, fetchPersonalRecords, fetchContributionCalendar, fetchWeeklySummary, fetchActivityBreakdown, fetchWorkouts, fetchAllTags, fetchDevices, fetchTrainingLoad, fetchPowerCurve, fetchBestEfforts, fetchZoneDistribution, fetchHrvHistory } = get();
     await Promise.all([
       fetchStats(),
       fetchMonthlyStats(),
@@ -256,6 +442,12 @@ export const useWorkoutStore = create<WorkoutState>((set, get) => ({
       fetchActivityBreakdown(),
       fetchWorkouts(),
       fetchAllTags(),
+      fetchDevices(),
+      fetchTrainingLoad(),
+      fetchPowerCurve(),
+      fetchBestEfforts(),
+      fetchZoneDistribution(),
+      fetchHrvHistory(),
     ]);
   },
 
@@ -316,6 +508,82 @@ export const useWorkoutStore = create<WorkoutState>((set, get) => ({
     }
   },
 
+  setTagColor: async (name: string, color: string | null) => {
+    try {
+      const success = await invoke<boolean>('set_tag_color', { name, color });
+      if (success) {
+        get().fetchAllTags();
+      }
+      return success;
+    } catch (error) {
+      console.error('Failed to set tag color:', error);
+      return false;
+    }
+  },
+
+  renameTag: async (oldName: string, newName: string) => {
+    try {
+      const success = await invoke<boolean>('rename_tag', { oldName, newName });
+      if (success) {
+        const { tagFilter, selectedWorkout } = get();
+        if (tagFilter === oldName) {
+          set({ tagFilter: newName.trim() });
+        }
+        if (selectedWorkout) {
+          get().fetchWorkout(selectedWorkout.id);
+        }
+        get().fetchAllTags();
+        get().fetchWorkouts();
+      }
+      return success;
+    } catch (error) {
+      console.error('Failed to rename tag:', error);
+      return false;
+    }
+  },
+
+  mergeTags: async (sources: string[], target: string) => {
+    try {
+      const success = await invoke<boolean>('merge_tags', { sources, target });
+      if (success) {
+        const { tagFilter, selectedWorkout } = get();
+        if (tagFilter && sources.includes(tagFilter)) {
+          set({ tagFilter: target.trim() });
+        }
+        if (selectedWorkout) {
+          get().fetchWorkout(selectedWorkout.id);
+        }
+        get().fetchAllTags();
+        get().fetchWorkouts();
+      }
+      return success;
+    } catch (error) {
+      console.error('Failed to merge tags:', error);
+      return false;
+    }
+  },
+
+  deleteTag: async (name: string) => {
+    try {
+      const success = await invoke<boolean>('delete_tag', { name });
+      if (success) {
+        const { tagFilter, selectedWorkout } = get();
+        if (tagFilter === name) {
+          set({ tagFilter: null });
+        }
+        if (selectedWorkout) {
+          get().fetchWorkout(selectedWorkout.id);
+        }
+        get().fetchAllTags();
+        get().fetchWorkouts();
+      }
+      return success;
+    } catch (error) {
+      console.error('Failed to delete tag:', error);
+      return false;
+    }
+  },
+
   updateNotes: async (id: number, notes: string) => {
     try {
       const success = await invoke<boolean>('update_workout_notes', { id, notes });
@@ -329,13 +597,53 @@ export const useWorkoutStore = create<WorkoutState>((set, get) => ({
     }
   },
 
+  exportWorkout: async (id: number, format: string, filePath: string) => {
+    try {
+      await invoke('export_workout', { id, format, filePath });
+      return true;
+    } catch (error) {
+      console.error('Failed to export workout:', error);
+      return false;
+    }
+  },
+
+  reprocessWorkout: async (id: number) => {
+    try {
+      await invoke<boolean>('reprocess_workout', { id });
+      get().fetchWorkout(id);
+      get().fetchChartData(id);
+      get().fetchGpsData(id);
+      get().fetchDashboardData();
+      return true;
+    } catch (error) {
+      console.error('Failed to reprocess workout:', error);
+      return false;
+    }
+  },
+
+  reprocessAll: async () => {
+    try {
+      const summary = await invoke<ReprocessSummary>('reprocess_all');
+      get().fetchDashboardData();
+      return summary;
+    } catch (error) {
+      console.error('Failed to reprocess workouts:', error);
+      return null;
+    }
+  },
+
   setPage: (page: number) => {
     set({ currentPage: page });
     get().fetchWorkouts();
   },
 
-  setWorkoutTypeFilter: (type: string | null) => {
-    set({ workoutTypeFilter: type, currentPage: 1 });
+  setCategoryFilter: (category: string | null) => {
+    set({ categoryFilter: category, workoutTypeFilter: null, subSportFilter: null, currentPage: 1 });
+    get().fetchWorkouts();
+  },
+
+  setSportFilter: (sport: string | null, subSport: string | null) => {
+    set({ workoutTypeFilter: sport, subSportFilter: subSport, currentPage: 1 });
     get().fetchWorkouts();
   },
 
@@ -359,14 +667,26 @@ export const useWorkoutStore = create<WorkoutState>((set, get) => ({
     get().fetchWorkouts();
   },
 
-  setDurationFilter: (min: number | null, max: number | null) => {
-    set({ minDuration: min, maxDuration: max, currentPage: 1 });
+  setDurationFilter: (min: number | null, max: number | null, basis: DurationBasis) => {
+    set({ minDuration: min, maxDuration: max, durationBasis: basis, currentPage: 1 });
+    get().fetchWorkouts();
+  },
+
+  setDeviceFilter: (device: string | null) => {
+    set({ deviceFilter: device, currentPage: 1 });
+    get().fetchWorkouts();
+  },
+
+  setSort: (sortBy: WorkoutSortKey, sortOrder: SortOrder) => {
+    set({ sortBy, sortOrder, currentPage: 1 });
     get().fetchWorkouts();
   },
 
   clearAllFilters: () => {
     set({
+      categoryFilter: null,
       workoutTypeFilter: null,
+      subSportFilter: null,
       tagFilter: null,
       searchQuery: '',
       dateRangeStart: null,
@@ -375,6 +695,8 @@ export const useWorkoutStore = create<WorkoutState>((set, get) => ({
       maxDistance: null,
       minDuration: null,
       maxDuration: null,
+      durationBasis: 'elapsed',
+      deviceFilter: null,
       currentPage: 1,
     });
     get().fetchWorkouts();
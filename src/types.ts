@@ -1,14 +1,19 @@
 export interface Workout {
   id: number;
-  file_hash: string;
+  // Unset on multisport legs, which share their parent's file
+  file_hash: string | null;
   filename: string;
   name: string | null;
   notes: string | null;
   tags: string | null;
   workout_type: string | null;
+  sub_sport: string | null;
+  category: string | null;
   start_time: string | null;
   end_time: string | null;
   duration_seconds: number | null;
+  timer_seconds: number | null;
+  moving_seconds: number | null;
   distance_meters: number | null;
   total_calories: number | null;
   avg_heart_rate: number | null;
@@ -18,11 +23,46 @@ export interface Workout {
   avg_cadence: number | null;
   max_cadence: number | null;
   avg_speed_mps: number | null;
+  avg_moving_speed_mps: number | null;
   max_speed_mps: number | null;
   elevation_gain_meters: number | null;
   elevation_loss_meters: number | null;
+  pool_length_meters: number | null;
+  parent_id: number | null;
+  session_index: number | null;
   created_at: string;
   updated_at: string;
+  tss: number | null;
+  normalized_power_watts: number | null;
+  intensity_factor: number | null;
+  variability_index: number | null;
+  work_kj: number | null;
+  tss_source: 'power' | 'heart_rate' | null;
+  // Session averages of the sensor channels
+  avg_temperature_c: number | null;
+  avg_vertical_oscillation_mm: number | null;
+  avg_ground_contact_time_ms: number | null;
+  avg_ground_contact_balance: number | null;
+  avg_stride_length_meters: number | null;
+  avg_vertical_ratio: number | null;
+  avg_fractional_cadence: number | null;
+  avg_left_right_balance: number | null;
+  avg_left_torque_effectiveness: number | null;
+  avg_right_torque_effectiveness: number | null;
+  avg_left_pedal_smoothness: number | null;
+  avg_right_pedal_smoothness: number | null;
+  avg_respiration_rate: number | null;
+  avg_core_temperature_c: number | null;
+  avg_spo2: number | null;
+  hrv_rmssd_ms: number | null;
+  hrv_sdnn_ms: number | null;
+  dfa_aerobic_threshold_hr: number | null;
+  dfa_anaerobic_threshold_hr: number | null;
+  device_manufacturer: string | null;
+  device_product: string | null;
+  device_serial_number: number | null;
+  file_created_at: string | null;
+  legs: WorkoutSummary[];
 }
 
 export interface WorkoutSummary {
@@ -30,12 +70,31 @@ export interface WorkoutSummary {
   name: string | null;
   notes: string | null;
   workout_type: string | null;
+  sub_sport: string | null;
+  category: string | null;
   start_time: string | null;
   duration_seconds: number | null;
+  moving_seconds: number | null;
   distance_meters: number | null;
   total_calories: number | null;
   avg_heart_rate: number | null;
   tags: string | null;
+  tss: number | null;
+  search_snippet: string | null;
+}
+
+// Whether duration filters apply to elapsed or moving time
+export type DurationBasis = 'elapsed' | 'moving';
+
+export type WorkoutSortKey = 'date' | 'duration' | 'moving_time' | 'distance';
+
+export type SortOrder = 'asc' | 'desc';
+
+export interface Tag {
+  id: number;
+  name: string;
+  color: string | null;
+  usage_count: number;
 }
 
 export interface WorkoutsResponse {
@@ -64,13 +123,44 @@ export interface StreakInfo {
   active_days: number;
 }
 
-export interface PersonalRecords {
-  max_distance_km: number;
-  max_duration_hours: number;
-  max_heart_rate: number;
-  max_speed_kmh: number;
-  max_elevation_gain: number;
-  max_calories: number;
+export interface RecordEntry {
+  workout_id: number;
+  workout_name: string | null;
+  start_time: string | null;
+  value: number;
+}
+
+export interface PersonalRecord {
+  sport: string | null;
+  metric: string;
+  best: RecordEntry;
+  history: RecordEntry[];
+}
+
+/** Payload of the `personal-record` event sent when an import breaks records */
+export interface NewRecord {
+  workout_id: number;
+  sport: string | null;
+  metric: string;
+  value: number;
+  previous: RecordEntry;
+}
+
+export interface BestEffortEntry {
+  workout_id: number;
+  workout_name: string | null;
+  start_time: string | null;
+  elapsed_seconds: number;
+  start_offset_s: number;
+  end_offset_s: number;
+}
+
+export interface BestEffortRecord {
+  sport: string | null;
+  distance: string;
+  distance_meters: number;
+  best: BestEffortEntry;
+  history: BestEffortEntry[];
 }
 
 export interface ContributionDay {
@@ -84,9 +174,24 @@ export interface WeeklySummary {
   count: number;
 }
 
+// Workouts per display category, split by sport and sub-sport
 export interface ActivityBreakdown {
   name: string;
   count: number;
+  sports: SportBreakdown[];
+}
+
+export interface SportBreakdown {
+  sport: string;
+  sub_sport: string | null;
+  count: number;
+}
+
+// Maps a sport, or one of its sub-sports, onto a display category
+export interface SportCategory {
+  sport: string;
+  sub_sport: string | null;
+  category: string;
 }
 
 export interface GpsPoint {
@@ -103,6 +208,104 @@ export interface ChartData {
   cadence: (number | null)[];
   speed: (number | null)[];
   altitude: (number | null)[];
+  // Left out when the file doesn't record the channel
+  temperature?: (number | null)[];
+  vertical_oscillation?: (number | null)[];
+  ground_contact_time?: (number | null)[];
+  ground_contact_balance?: (number | null)[];
+  stride_length?: (number | null)[];
+  vertical_ratio?: (number | null)[];
+  fractional_cadence?: (number | null)[];
+  left_right_balance?: (number | null)[];
+  left_torque_effectiveness?: (number | null)[];
+  right_torque_effectiveness?: (number | null)[];
+  left_pedal_smoothness?: (number | null)[];
+  right_pedal_smoothness?: (number | null)[];
+  respiration_rate?: (number | null)[];
+  core_temperature?: (number | null)[];
+  spo2?: (number | null)[];
+  developer: DeveloperSeries[];
+}
+
+// A Connect IQ developer field recorded by an app or data field
+export interface DeveloperSeries {
+  key: string;
+  name: string;
+  units: string | null;
+  values: (number | null)[];
+}
+
+export interface Lap {
+  id: number;
+  workout_id: number;
+  lap_index: number;
+  start_time: string | null;
+  end_time: string | null;
+  duration_seconds: number | null;
+  distance_meters: number | null;
+  total_calories: number | null;
+  avg_heart_rate: number | null;
+  max_heart_rate: number | null;
+  avg_power_watts: number | null;
+  max_power_watts: number | null;
+  avg_cadence: number | null;
+  max_cadence: number | null;
+  avg_speed_mps: number | null;
+  max_speed_mps: number | null;
+  elevation_gain_meters: number | null;
+  elevation_loss_meters: number | null;
+  lap_trigger: string | null;
+  swim_stroke: string | null;
+  total_strokes: number | null;
+  num_lengths: number | null;
+  num_active_lengths: number | null;
+}
+
+export interface SwimLengthStats {
+  length_index: number;
+  start_time: string | null;
+  duration_seconds: number | null;
+  length_type: string | null;
+  swim_stroke: string | null;
+  total_strokes: number | null;
+  pace_per_100m_seconds: number | null;
+  swolf: number | null;
+}
+
+export interface SwimInterval {
+  interval_index: number;
+  swim_stroke: string | null;
+  lengths: number;
+  distance_meters: number;
+  duration_seconds: number;
+  rest_after_seconds: number;
+  pace_per_100m_seconds: number | null;
+  avg_swolf: number | null;
+  avg_strokes_per_length: number | null;
+}
+
+export interface StrokeBreakdown {
+  swim_stroke: string;
+  lengths: number;
+  distance_meters: number;
+  duration_seconds: number;
+  pace_per_100m_seconds: number | null;
+  avg_swolf: number | null;
+  avg_strokes_per_length: number | null;
+}
+
+export interface SwimAnalysis {
+  pool_length_meters: number;
+  active_lengths: number;
+  distance_meters: number;
+  swim_time_seconds: number;
+  rest_time_seconds: number;
+  pace_per_100m_seconds: number | null;
+  avg_swolf: number | null;
+  avg_strokes_per_length: number | null;
+  strokes: StrokeBreakdown[];
+  intervals: SwimInterval[];
+  lengths: SwimLengthStats[];
 }
 
 export interface UploadResult {
@@ -112,6 +315,12 @@ export interface UploadResult {
   duplicate: boolean;
 }
 
+export interface ReprocessSummary {
+  reprocessed: number;
+  failed: number;
+  errors: string[];
+}
+
 // Activity type colors for charts and UI
 export const ACTIVITY_COLORS: Record<string, string> = {
   cycling: '#6366f1',
@@ -130,6 +339,13 @@ export const ACTIVITY_COLORS: Record<string, string> = {
   alpine_skiing: '#2563eb',
   snowboarding: '#7c3aed',
   cross_country_skiing: '#4f46e5',
+  cardio: '#db2777',
+  mind_and_body: '#a855f7',
+  winter_sports: '#2563eb',
+  water_sports: '#0d9488',
+  racket_sports: '#ca8a04',
+  team_sports: '#65a30d',
+  climbing: '#b45309',
 };
 
 export function getActivityColor(type: string | null): string {
@@ -162,6 +378,14 @@ export function formatSpeed(mps: number | null): string {
   return `${kmh.toFixed(1)} km/h`;
 }
 
+export function formatPace(mps: number | null): string {
+  if (!mps || !isFinite(mps)) return '--:-- /km';
+  const secondsPerKm = Math.round(1000 / mps);
+  const minutes = Math.floor(secondsPerKm / 60);
+  const seconds = secondsPerKm % 60;
+  return `${minutes}:${seconds.toString().padStart(2, '0')} /km`;
+}
+
 export function formatDate(dateStr: string | null): string {
   if (!dateStr) return '';
   const date = new Date(dateStr);
@@ -197,6 +421,15 @@ export function capitalizeWorkoutType(type: string | null): string {
     .join(' ');
 }
 
+// "Running · Trail" for a sport with a sub-sport
+// Sub-sport filter of workouts without a sub-sport (GENERIC_SUB_SPORT in sports.rs)
+export const GENERIC_SUB_SPORT = 'generic';
+
+export function formatSport(sport: string | null, subSport: string | null): string {
+  const name = capitalizeWorkoutType(sport);
+  return subSport ? `${name} · ${capitalizeWorkoutType(subSport)}` : name;
+}
+
 // Training metrics types
 
 export interface HRZoneTime {
@@ -224,6 +457,81 @@ export interface TrainingLoadData {
   dailyTSS: WorkoutTSS[];
 }
 
+export interface TrainingLoadDay {
+  date: string;
+  tss: number;
+  ctl: number; // Chronic Training Load (42-day exponential average)
+  atl: number; // Acute Training Load (7-day exponential average)
+  tsb: number; // Training Stress Balance (CTL - ATL)
+}
+
+export interface PowerCurvePoint {
+  duration_seconds: number;
+  watts: number;
+  workout_id: number;
+  start_time: string | null;
+}
+
+export interface SeasonPowerCurve {
+  season: string;
+  points: PowerCurvePoint[];
+}
+
+export interface PowerCurves {
+  all_time: PowerCurvePoint[];
+  last_90_days: PowerCurvePoint[];
+  seasons: SeasonPowerCurve[];
+}
+
+// One dated entry of the athlete profile; it applies to workouts from
+// `effective_date` until the next entry
+export interface AthleteProfile {
+  effective_date: string;
+  weight_kg: number | null;
+  height_cm: number | null;
+  sex: 'male' | 'female' | null;
+  birth_date: string | null;
+  max_heart_rate: number | null;
+  resting_heart_rate: number | null;
+  threshold_heart_rate: number | null;
+  ftp_watts: number | null;
+  threshold_pace_seconds_per_km: number | null;
+}
+
+// Zone limits are percentages of max HR, heart rate reserve or LTHR
+export type HrZoneMethod = 'max_heart_rate' | 'heart_rate_reserve' | 'lactate_threshold';
+
+export interface Zone {
+  name: string;
+  min: number;
+  max: number;
+  color: string;
+}
+
+export interface HrZoneSettings {
+  method: HrZoneMethod;
+  zones: Zone[];
+}
+
+// `workout_zones.zone_type`: heart rate in bpm, power in watts, pace as m/s
+export type ZoneType = 'heart_rate' | 'power' | 'pace';
+
+export interface ZoneTime {
+  zone_index: number;
+  name: string;
+  color: string;
+  low: number;
+  high: number;
+  seconds: number;
+}
+
+export type Period = 'week' | 'month';
+
+export interface ZoneDistribution {
+  period: string;
+  seconds: number[];
+}
+
 export interface RecoveryData {
   status: 'recovered' | 'fresh' | 'optimal' | 'tired' | 'fatigued';
   label: string;
@@ -233,3 +541,69 @@ export interface RecoveryData {
   readinessScore: number; // 0-100
   suggestedIntensity: 'rest' | 'easy' | 'moderate' | 'hard' | 'max';
 }
+
+export interface DfaWindow {
+  elapsed_s: number;
+  alpha1: number;
+  heart_rate: number;
+  rmssd_ms: number | null;
+}
+
+export interface HrvAnalysis {
+  beats: number;
+  artifacts: number;
+  artifact_percent: number;
+  mean_rr_ms: number | null;
+  rmssd_ms: number | null;
+  sdnn_ms: number | null;
+  dfa_alpha1: DfaWindow[];
+  aerobic_threshold_hr: number | null;
+  anaerobic_threshold_hr: number | null;
+}
+
+export interface HrvSummary {
+  workout_id: number;
+  start_time: string | null;
+  workout_type: string | null;
+  rmssd_ms: number | null;
+  sdnn_ms: number | null;
+  aerobic_threshold_hr: number | null;
+  anaerobic_threshold_hr: number | null;
+}
+
+export interface DeviceInfo {
+  device_index: string; // "creator" for the recording device, "<file hash>:<index>" for another recording's
+  device_type: string | null;
+  manufacturer: string | null;
+  product: string | null;
+  product_name: string | null;
+  serial_number: number | null;
+  software_version: number | null;
+  battery_status: string | null;
+  battery_voltage: number | null;
+  source_type: string | null;
+}
+
+export interface DeviceSummary {
+  device_type: string | null;
+  manufacturer: string | null;
+  product: string | null;
+  product_name: string | null;
+  serial_number: number | null;
+  recorder: boolean;
+  workout_count: number;
+  last_used: string | null;
+}
+
+// Products fitparser has no name for come through as numbers
+export function formatDeviceName(device: Pick<DeviceInfo, 'manufacturer' | 'product' | 'product_name'>): string {
+  if (device.product_name) return device.product_name;
+  const product = device.product && !/^\d+$/.test(device.product) ? capitalizeWorkoutType(device.product) : null;
+  const manufacturer = device.manufacturer ? capitalizeWorkoutType(device.manufacturer) : null;
+  return [manufacturer, product].filter(Boolean).join(' ') || 'Unknown device';
+}
+
+// get_workouts matches a device by serial number or product
+export function deviceFilterValue(device: DeviceSummary): string {
+  return device.serial_number !== null ? device.serial_number.toString() : device.product ?? '';
+}